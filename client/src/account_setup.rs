@@ -0,0 +1,146 @@
+//! # Output Account Setup
+//!
+//! This module contains the helpers behind
+//! [`BatchSwapRouterClient::prepare_accounts`], which creates the output
+//! token accounts a batch needs before it runs. A batch into several new
+//! tokens would otherwise need one account lookup (and possibly one create
+//! transaction) per leg.
+//!
+//! ## Process
+//!
+//! 1. Collect the owner's associated token account (ATA) of every distinct
+//!    output mint ([`output_token_accounts`])
+//! 2. Check which of them exist with a single `getMultipleAccounts` request
+//!    (up to [`MAX_MULTIPLE_ACCOUNTS`] addresses, more than any batch has
+//!    output mints)
+//! 3. Build an idempotent create-ATA instruction for each missing one
+//!    ([`create_missing_accounts`])
+//!
+//! The instructions can be prepended to the batch transaction or sent on
+//! their own beforehand. Idempotent creates make both safe if another
+//! transaction creates an account in between. Output ATAs are SPL Token
+//! accounts, matching the token program the swap instructions take.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let mut instructions = swap_client.prepare_accounts(&swaps)?;
+//! instructions.extend(batch_instructions);
+//! ```
+//!
+//! [`BatchSwapRouterClient::prepare_accounts`]: crate::BatchSwapRouterClient::prepare_accounts
+
+use anchor_spl::associated_token::{
+    get_associated_token_address,
+    spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+};
+use anchor_spl::token::spl_token;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::types::SwapParams;
+
+/// Most addresses a single `getMultipleAccounts` request may ask for
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// An output token account a batch needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputAccount {
+    /// Output mint of one or more legs
+    pub mint: Pubkey,
+
+    /// The owner's associated token account for `mint`
+    pub address: Pubkey,
+}
+
+/// Collect the owner's output token accounts of a batch
+///
+/// # Arguments
+///
+/// * `owner` - The swap authority receiving the outputs
+/// * `swaps` - The batch's legs
+///
+/// # Returns
+///
+/// One [`OutputAccount`] per distinct output mint, in leg order
+#[must_use]
+pub fn output_token_accounts(owner: &Pubkey, swaps: &[SwapParams]) -> Vec<OutputAccount> {
+    let mut accounts: Vec<OutputAccount> = Vec::new();
+    for swap in swaps {
+        if accounts.iter().all(|account| account.mint != swap.output_mint) {
+            accounts.push(OutputAccount {
+                mint: swap.output_mint,
+                address: get_associated_token_address(owner, &swap.output_mint),
+            });
+        }
+    }
+    accounts
+}
+
+/// Build create-ATA instructions for the output accounts that do not exist
+///
+/// # Arguments
+///
+/// * `payer` - Pays the rent of the new accounts
+/// * `owner` - Owner of the new accounts
+/// * `accounts` - The batch's output accounts ([`output_token_accounts`])
+/// * `exists` - Whether each of `accounts` exists, in the same order
+///
+/// # Returns
+///
+/// One idempotent create-ATA instruction per missing account, in the order
+/// of `accounts`
+#[must_use]
+pub fn create_missing_accounts(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    accounts: &[OutputAccount],
+    exists: &[bool],
+) -> Vec<Instruction> {
+    accounts
+        .iter()
+        .zip(exists)
+        .filter(|(_, exists)| !**exists)
+        .map(|(account, _)| {
+            create_associated_token_account_idempotent(payer, owner, &account.mint, &spl_token::ID)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_accounts_are_deduplicated() {
+        let owner = Pubkey::new_unique();
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let swaps = vec![
+            SwapParams::new(a, b, 1_000, 1),
+            SwapParams::new(c, b, 1_000, 1),
+            SwapParams::new(b, a, 1_000, 1),
+        ];
+
+        let accounts = output_token_accounts(&owner, &swaps);
+        assert_eq!(
+            accounts.iter().map(|account| account.mint).collect::<Vec<_>>(),
+            vec![b, a]
+        );
+        assert_eq!(accounts[0].address, get_associated_token_address(&owner, &b));
+    }
+
+    #[test]
+    fn test_only_missing_accounts_are_created() {
+        let owner = Pubkey::new_unique();
+        let swaps = vec![
+            SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 1),
+            SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 1),
+        ];
+        let accounts = output_token_accounts(&owner, &swaps);
+
+        let instructions = create_missing_accounts(&owner, &owner, &accounts, &[true, false]);
+        assert_eq!(instructions.len(), 1);
+        // The new account is the second leg's ATA
+        assert_eq!(instructions[0].accounts[1].pubkey, accounts[1].address);
+        assert!(create_missing_accounts(&owner, &owner, &accounts, &[true, true]).is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+//! # Batch Builder
+//!
+//! This module collects swaps into a batch one at a time, for callers that
+//! decide per swap whether it belongs in the batch (price scans, rebalancing
+//! passes, ...). The builder can be inspected and iterated like the
+//! `Vec<SwapParams>` it wraps before [`BatchBuilder::build`] checks the batch
+//! against the program's limits.
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use xforce_terminal_contracts_client::{BatchBuilder, SwapParams, MAX_BATCH_SIZE};
+//! use solana_sdk::pubkey::Pubkey;
+//!
+//! let usdc = Pubkey::new_unique();
+//! // (mint, quoted output per 1 USDC, output wanted per 1 USDC)
+//! let scan = [
+//!     (Pubkey::new_unique(), 1_050, 1_000),
+//!     (Pubkey::new_unique(), 980, 1_000),
+//!     (Pubkey::new_unique(), 2_100, 2_000),
+//! ];
+//!
+//! let mut batch = BatchBuilder::new();
+//! for (mint, quoted, wanted) in scan {
+//!     // Only buy where the quote beats the target price
+//!     if quoted >= wanted && batch.len() < MAX_BATCH_SIZE {
+//!         batch.push(SwapParams::new(usdc, mint, 1_000_000, wanted * 1_000));
+//!     }
+//! }
+//!
+//! assert_eq!(batch.len(), 2);
+//! for swap in &batch {
+//!     println!("{} -> {}: {}", swap.input_mint, swap.output_mint, swap.amount);
+//! }
+//!
+//! let swaps = batch.build().unwrap();
+//! assert_eq!(swaps.len(), 2);
+//! ```
+
+use std::ops::Deref;
+
+use crate::error::ContractError;
+use crate::types::{SwapParams, MAX_BATCH_SIZE};
+
+/// Incrementally built batch of swaps
+///
+/// Derefs to the underlying `Vec<SwapParams>`, so slice methods such as
+/// `iter`, `first` or `contains` are available while building.
+///
+/// # Example
+///
+/// ```rust
+/// use xforce_terminal_contracts_client::{BatchBuilder, SwapParams};
+/// use solana_sdk::pubkey::Pubkey;
+///
+/// let mut batch = BatchBuilder::new()
+///     .with_swap(SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 900));
+/// assert!(!batch.is_empty());
+///
+/// // Start over, e.g. on the next scan
+/// batch.clear();
+/// assert!(batch.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchBuilder {
+    /// Swaps added so far, in order
+    swaps: Vec<SwapParams>,
+}
+
+impl BatchBuilder {
+    /// Create an empty batch
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a swap to the end of the batch
+    ///
+    /// Swaps are not checked until [`Self::build`].
+    pub fn push(&mut self, swap: SwapParams) {
+        self.swaps.push(swap);
+    }
+
+    /// Add a swap to the end of the batch, by value
+    ///
+    /// # Arguments
+    ///
+    /// * `swap` - The swap to add
+    #[must_use]
+    pub fn with_swap(mut self, swap: SwapParams) -> Self {
+        self.push(swap);
+        self
+    }
+
+    /// Number of swaps in the batch
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.swaps.len()
+    }
+
+    /// Whether the batch has no swaps
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.swaps.is_empty()
+    }
+
+    /// Remove every swap, keeping the allocation for reuse
+    pub fn clear(&mut self) {
+        self.swaps.clear();
+    }
+
+    /// Check the batch and return its swaps
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<SwapParams>)` - The swaps, in the order they were added
+    /// * `Err(ContractError)` - If the program would reject the batch
+    ///
+    /// # Errors
+    ///
+    /// Returns `ContractError::InvalidAccount` if the batch is empty, has more
+    /// than [`MAX_BATCH_SIZE`] swaps (split it with [`crate::chunked`]), or a
+    /// swap fails [`SwapParams::validate`].
+    pub fn build(self) -> Result<Vec<SwapParams>, ContractError> {
+        if self.swaps.is_empty() {
+            return Err(ContractError::InvalidAccount("Batch has no swaps".to_string()));
+        }
+
+        if self.swaps.len() > MAX_BATCH_SIZE {
+            return Err(ContractError::InvalidAccount(format!(
+                "Batch has {} swaps, maximum is {MAX_BATCH_SIZE}",
+                self.swaps.len()
+            )));
+        }
+
+        for (index, swap) in self.swaps.iter().enumerate() {
+            swap.validate()
+                .map_err(|e| ContractError::InvalidAccount(format!("Swap {index}: {e}")))?;
+        }
+
+        Ok(self.swaps)
+    }
+}
+
+impl Deref for BatchBuilder {
+    type Target = Vec<SwapParams>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.swaps
+    }
+}
+
+impl AsRef<Vec<SwapParams>> for BatchBuilder {
+    fn as_ref(&self) -> &Vec<SwapParams> {
+        &self.swaps
+    }
+}
+
+impl IntoIterator for BatchBuilder {
+    type Item = SwapParams;
+    type IntoIter = std::vec::IntoIter<SwapParams>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.swaps.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a BatchBuilder {
+    type Item = &'a SwapParams;
+    type IntoIter = std::slice::Iter<'a, SwapParams>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.swaps.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn swap(amount: u64) -> SwapParams {
+        SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), amount, 1)
+    }
+
+    #[test]
+    fn test_builder_keeps_order_and_iterates() {
+        let mut batch = BatchBuilder::new();
+        for amount in 1..=3 {
+            batch.push(swap(amount));
+        }
+
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch.iter().map(|swap| swap.amount).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!((&batch).into_iter().count(), 3);
+        assert_eq!(batch.as_ref().len(), 3);
+        assert_eq!(batch.into_iter().map(|swap| swap.amount).sum::<u64>(), 6);
+    }
+
+    #[test]
+    fn test_clear_empties_the_batch() {
+        let mut batch = BatchBuilder::new().with_swap(swap(1));
+        batch.clear();
+
+        assert!(batch.is_empty());
+        assert!(batch.build().is_err());
+    }
+
+    #[test]
+    fn test_build_enforces_batch_limits() {
+        let mut batch = BatchBuilder::new();
+        for _ in 0..=MAX_BATCH_SIZE {
+            batch.push(swap(1));
+        }
+        assert!(batch.build().is_err());
+
+        assert!(BatchBuilder::new().with_swap(swap(0)).build().is_err());
+        assert_eq!(BatchBuilder::new().with_swap(swap(5)).build().unwrap().len(), 1);
+    }
+}
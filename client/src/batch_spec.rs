@@ -0,0 +1,248 @@
+//! # Batch Spec Files
+//!
+//! This module defines [`BatchSpec`], the file format for scripted batches:
+//! ops teams keep a batch as a JSON file, review it, and submit it without
+//! writing code.
+//!
+//! ## Format
+//!
+//! Mints and accounts are base58 strings; `base_mint`, `compute_budget` and
+//! the optional swap fields may be omitted. Unknown fields are rejected so a
+//! misspelled field fails loudly instead of being ignored.
+//!
+//! ```json
+//! {
+//!   "swaps": [
+//!     {
+//!       "input_mint": "So11111111111111111111111111111111111111112",
+//!       "output_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+//!       "amount": 1000000000,
+//!       "min_output_amount": 90000000,
+//!       "max_slippage_bps": 100
+//!     }
+//!   ],
+//!   "fee_recipient": "<fee token account>",
+//!   "base_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+//!   "compute_budget": { "unit_limit": 400000, "unit_price_micro_lamports": 25000 }
+//! }
+//! ```
+//!
+//! ## Validation
+//!
+//! A spec is validated when it is loaded ([`BatchSpec::from_json`]) and
+//! again when it is submitted ([`BatchSpec::submit`]), since a spec built or
+//! edited in code skips the load check. Validation applies the
+//! [`BatchBuilder`] batch checks and checks the fee recipient and compute
+//! budget.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::batch_spec::BatchSpec;
+//!
+//! let spec = BatchSpec::from_json(&std::fs::read_to_string("batch.json")?)?;
+//! let signature = spec.submit(&swap_client)?;
+//! ```
+//!
+//! See `examples/batch_spec.rs` for a command-line runner.
+
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature, signature::Signer};
+
+use crate::batch_builder::BatchBuilder;
+use crate::batch_swap_router::BatchSwapRouterClient;
+use crate::error::ContractError;
+use crate::priority_fee::{compute_unit_limit_instruction, compute_unit_price_instruction, MAX_COMPUTE_UNIT_LIMIT};
+use crate::types::{pubkey_base58, SwapParams};
+
+/// Compute budget requested for a batch transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ComputeBudgetSpec {
+    /// Compute unit limit (`None` = the runtime default)
+    #[serde(default)]
+    pub unit_limit: Option<u32>,
+
+    /// Priority fee in micro-lamports per compute unit (`None` = no priority fee)
+    #[serde(default)]
+    pub unit_price_micro_lamports: Option<u64>,
+}
+
+/// A scripted batch, as stored in a batch file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchSpec {
+    /// Swaps of the batch, in execution order
+    pub swaps: Vec<SwapParams>,
+
+    /// Token account the batch's protocol fees are paid to
+    #[serde(with = "pubkey_base58")]
+    pub fee_recipient: Pubkey,
+
+    /// Settlement mint for the batch output totals (`None` = no totals)
+    #[serde(default, with = "pubkey_base58::option", skip_serializing_if = "Option::is_none")]
+    pub base_mint: Option<Pubkey>,
+
+    /// Compute budget of the transaction (`None` = runtime defaults)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_budget: Option<ComputeBudgetSpec>,
+}
+
+impl BatchSpec {
+    /// Parse and validate a batch spec from JSON
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The batch file contents
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BatchSpec)` - The validated spec
+    /// * `Err(ContractError)` - If the JSON is malformed or the batch invalid
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::SerializationError` - The JSON does not parse as a
+    ///   batch spec (syntax, unknown field, invalid pubkey)
+    /// - `ContractError::InvalidAccount` - The spec fails [`Self::validate`]
+    pub fn from_json(json: &str) -> Result<Self, ContractError> {
+        let spec: Self = serde_json::from_str(json).map_err(|e| ContractError::SerializationError(e.to_string()))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Serialize the spec as pretty-printed JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns `ContractError::SerializationError` if serialization fails.
+    pub fn to_json(&self) -> Result<String, ContractError> {
+        serde_json::to_string_pretty(self).map_err(|e| ContractError::SerializationError(e.to_string()))
+    }
+
+    /// Check the spec against the program's limits
+    ///
+    /// # Errors
+    ///
+    /// Returns `ContractError::InvalidAccount` if the swaps fail
+    /// [`BatchBuilder::build`], the fee recipient is unset, or the compute
+    /// unit limit is 0 or above [`MAX_COMPUTE_UNIT_LIMIT`].
+    pub fn validate(&self) -> Result<(), ContractError> {
+        self.swaps
+            .iter()
+            .cloned()
+            .fold(BatchBuilder::new(), BatchBuilder::with_swap)
+            .build()?;
+
+        if self.fee_recipient == Pubkey::default() {
+            return Err(ContractError::InvalidAccount("Batch spec has no fee recipient".to_string()));
+        }
+
+        if let Some(units) = self.compute_budget.and_then(|budget| budget.unit_limit) {
+            if units == 0 || units > MAX_COMPUTE_UNIT_LIMIT {
+                return Err(ContractError::InvalidAccount(format!(
+                    "Compute unit limit {units} must be between 1 and {MAX_COMPUTE_UNIT_LIMIT}"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compute Budget instructions to put before the batch instruction
+    ///
+    /// # Returns
+    ///
+    /// The `SetComputeUnitLimit` and `SetComputeUnitPrice` instructions the
+    /// spec asks for (empty without a compute budget)
+    #[must_use]
+    pub fn compute_budget_instructions(&self) -> Vec<Instruction> {
+        let budget = self.compute_budget.unwrap_or_default();
+        budget
+            .unit_limit
+            .map(compute_unit_limit_instruction)
+            .into_iter()
+            .chain(budget.unit_price_micro_lamports.map(compute_unit_price_instruction))
+            .collect()
+    }
+
+    /// Validate the spec and submit it as one batch swap
+    ///
+    /// Sends through [`BatchSwapRouterClient::batch_swap`], so like it this
+    /// requires the IDL-generated instruction types (see the crate notes).
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The batch swap router client to send with
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Signature)` - The batch transaction signature
+    /// * `Err(ContractError)` - If the spec is invalid or the batch fails
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::InvalidAccount` - The spec fails [`Self::validate`]
+    /// - Any error of [`BatchSwapRouterClient::batch_swap`]
+    pub fn submit<C, S>(&self, client: &BatchSwapRouterClient<C>) -> Result<Signature, ContractError>
+    where
+        C: Deref<Target = S> + Clone,
+        S: Signer,
+    {
+        self.validate()?;
+        client.batch_swap(self.swaps.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> BatchSpec {
+        BatchSpec {
+            swaps: vec![SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 900).with_tag(7)],
+            fee_recipient: Pubkey::new_unique(),
+            base_mint: None,
+            compute_budget: Some(ComputeBudgetSpec { unit_limit: Some(400_000), unit_price_micro_lamports: None }),
+        }
+    }
+
+    #[test]
+    fn test_spec_round_trips_through_json() {
+        let spec = spec();
+        let json = spec.to_json().unwrap();
+
+        // Mints are written as base58 strings
+        assert!(json.contains(&spec.swaps[0].input_mint.to_string()));
+        assert!(!json.contains("base_mint"));
+        assert_eq!(BatchSpec::from_json(&json).unwrap(), spec);
+        assert_eq!(spec.compute_budget_instructions().len(), 1);
+    }
+
+    #[test]
+    fn test_spec_is_validated_on_load() {
+        let mint = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let swap = |output: &Pubkey| {
+            format!(r#"{{"input_mint": "{mint}", "output_mint": "{output}", "amount": 1000, "min_output_amount": 900}}"#)
+        };
+
+        let valid = format!(r#"{{"swaps": [{}], "fee_recipient": "{recipient}"}}"#, swap(&Pubkey::new_unique()));
+        assert!(BatchSpec::from_json(&valid).is_ok());
+
+        let same_mints = format!(r#"{{"swaps": [{}], "fee_recipient": "{recipient}"}}"#, swap(&mint));
+        assert!(matches!(BatchSpec::from_json(&same_mints), Err(ContractError::InvalidAccount(_))));
+
+        let misspelled = valid.replace("fee_recipient", "fee_recipeint");
+        assert!(matches!(BatchSpec::from_json(&misspelled), Err(ContractError::SerializationError(_))));
+
+        let bad_pubkey = valid.replace(&recipient.to_string(), "not-a-pubkey");
+        assert!(matches!(BatchSpec::from_json(&bad_pubkey), Err(ContractError::SerializationError(_))));
+
+        let mut over_limit = spec();
+        over_limit.compute_budget = Some(ComputeBudgetSpec { unit_limit: Some(MAX_COMPUTE_UNIT_LIMIT + 1), ..Default::default() });
+        assert!(over_limit.validate().is_err());
+    }
+}
@@ -49,6 +49,9 @@
 //!         output_mint: usdc_mint,
 //!         amount: 1_000_000_000, // 1 SOL
 //!         min_output_amount: 90_000_000, // 90 USDC minimum
+//!         max_fee: None, // no absolute fee cap
+//!         max_slippage_bps: None, // global MAX_SLIPPAGE_BPS
+//!         tag: 0, // untagged
 //!     },
 //! ];
 //!
@@ -91,6 +94,29 @@
 //! }
 //! ```
 //!
+//! ## Read-After-Write Consistency
+//!
+//! Sends confirm at the RPC client's commitment (usually `confirmed`), and by
+//! default reads use it too. A `Stats` read made right after a swap can then
+//! be served by a node that has not seen the swap yet, or from a fork that is
+//! later dropped, so analytics built on it silently miss or double count
+//! swaps. Reads can use a stricter commitment than sends:
+//!
+//! - [`BatchSwapRouterClient::with_read_commitment`] sets the commitment for
+//!   every account read
+//! - [`BatchSwapRouterClient::fetch_stats_at`] reads `Stats` at an explicit
+//!   commitment
+//! - [`BatchSwapRouterClient::send_confirm_then_read`] sends, waits until the
+//!   transaction is finalized, and only then reads `Stats` at `finalized`
+//!
+//! ```rust,ignore
+//! use solana_sdk::commitment_config::CommitmentConfig;
+//!
+//! let swap_client = BatchSwapRouterClient::new(program)
+//!     .with_read_commitment(CommitmentConfig::finalized());
+//! let (signature, stats) = swap_client.send_confirm_then_read(instructions)?;
+//! ```
+//!
 //! ## Notes
 //!
 //! - After building the Anchor program with `anchor build`, the IDL will be
@@ -98,14 +124,42 @@
 //! - For now, the client methods require the IDL to be generated first.
 //! - All operations are synchronous and blocking.
 
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use solana_client::rpc_config::{RpcBlockConfig, RpcTransactionConfig};
 use anchor_client::Program;
+use solana_address_lookup_table_interface::instruction::{create_lookup_table, extend_lookup_table};
 use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    message::Message,
     pubkey::Pubkey,
     signature::{Signer, Signature},
+    transaction::Transaction,
 };
+use solana_transaction_status_client_types::{TransactionDetails, UiTransactionEncoding};
 
+use crate::account_setup;
+use crate::chunked::{self, ChunkOutcome};
+use crate::cluster;
+use crate::config::{self, FeeBpsCache, PausedCache};
+use crate::deadline::{self, ValidityWindow};
+use crate::durable_nonce::{self, DurableNonce};
 use crate::error::ContractError;
+use crate::event_scan::{self, ParsedEvent};
+use crate::events::{self, SwapExecutedEventData};
+use crate::lookup_table;
+use crate::mint_decimals::MintDecimalsCache;
+use crate::pda;
+use crate::stats::{self, PairStats, Stats};
 use crate::types::SwapParams;
+use crate::version::{self, ProgramVersion};
+use crate::wsol::{self, WsolOptions};
 
 /// Client for batch swap router contract
 ///
@@ -129,11 +183,30 @@ use crate::types::SwapParams;
 pub struct BatchSwapRouterClient<C> {
     /// The underlying Anchor program client
     program: Program<C>,
+
+    /// Commitment for account reads (`None` = the RPC client's commitment)
+    read_commitment: Option<CommitmentConfig>,
+
+    /// Decimals of every mint read by `fetch_mint_decimals`
+    mint_decimals: Mutex<MintDecimalsCache>,
+
+    /// Protocol fee rate read by `fetch_protocol_fee_bps`
+    fee_bps: Mutex<FeeBpsCache>,
+
+    /// Pause state read by `is_paused`
+    paused: Mutex<PausedCache>,
+
+    /// Nonce account durable transactions are built on (`None` = recent blockhashes)
+    durable_nonce: Option<DurableNonce>,
+
+    /// How swaps that spend or receive native SOL are wrapped and unwrapped
+    wsol: WsolOptions,
 }
 
-impl<C> BatchSwapRouterClient<C>
+impl<C, S> BatchSwapRouterClient<C>
 where
-    C: Signer + Clone,
+    C: Deref<Target = S> + Clone,
+    S: Signer,
 {
     /// Create a new batch swap router client
     ///
@@ -156,13 +229,110 @@ where
     /// ```
     #[must_use]
     pub fn new(program: Program<C>) -> Self {
-        Self { program }
+        Self {
+            program,
+            read_commitment: None,
+            mint_decimals: Mutex::new(MintDecimalsCache::default()),
+            fee_bps: Mutex::new(FeeBpsCache::default()),
+            paused: Mutex::new(PausedCache::default()),
+            durable_nonce: None,
+            wsol: WsolOptions::default(),
+        }
+    }
+
+    /// Read accounts at `commitment` instead of the send commitment
+    ///
+    /// Transactions still confirm at the RPC client's commitment; only
+    /// account reads (`fetch_stats`, `fetch_pair_stats`, ...) change. Use
+    /// `CommitmentConfig::finalized()` to never read state that could be
+    /// rolled back (see "Read-After-Write Consistency" in the module docs).
+    ///
+    /// # Arguments
+    ///
+    /// * `commitment` - The commitment account reads require
+    ///
+    /// # Returns
+    ///
+    /// The client, reading at `commitment`
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let client = BatchSwapRouterClient::new(program)
+    ///     .with_read_commitment(CommitmentConfig::finalized());
+    /// ```
+    #[must_use]
+    pub fn with_read_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.read_commitment = Some(commitment);
+        self
+    }
+
+    /// Build transactions on a durable nonce instead of a recent blockhash
+    ///
+    /// Transactions from [`Self::durable_transaction`] then stay valid until
+    /// the nonce is advanced, so they can be signed offline and sent later
+    /// (see [`crate::durable_nonce`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `nonce_account` - The initialized nonce account
+    /// * `nonce_authority` - The nonce account's authority (signs every
+    ///   durable transaction)
+    ///
+    /// # Returns
+    ///
+    /// The client, building durable transactions
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let client = BatchSwapRouterClient::new(program)
+    ///     .with_durable_nonce(nonce_account, relayer.pubkey());
+    /// ```
+    #[must_use]
+    pub fn with_durable_nonce(mut self, nonce_account: Pubkey, nonce_authority: Pubkey) -> Self {
+        self.durable_nonce = Some(DurableNonce {
+            nonce_account,
+            nonce_authority,
+        });
+        self
+    }
+
+    /// Choose which WSOL accounts are closed after a swap
+    ///
+    /// [`Self::batch_swap`] and [`Self::execute_swap`] wrap native SOL input
+    /// and unwrap native SOL output by default (see [`crate::wsol`]). The
+    /// owner's WSOL account is never closed if it existed beforehand.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Which WSOL accounts to close
+    ///
+    /// # Returns
+    ///
+    /// The client, handling WSOL with `options`
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Keep SOL output wrapped for the next swap
+    /// let client = BatchSwapRouterClient::new(program).with_wsol_options(WsolOptions {
+    ///     close_input: true,
+    ///     close_output: false,
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_wsol_options(mut self, options: WsolOptions) -> Self {
+        self.wsol = options;
+        self
     }
 
     /// Execute a batch swap
     ///
     /// This method executes a batch of token swaps in a single transaction.
-    /// All swaps are validated and executed atomically.
+    /// All swaps are validated and executed atomically. Legs that spend or
+    /// receive native SOL are wrapped and unwrapped around the swap (see
+    /// [`Self::with_wsol_options`]).
     ///
     /// # Arguments
     ///
@@ -183,6 +353,9 @@ where
     /// - The batch is empty
     /// - The batch exceeds the maximum size (10 swaps)
     /// - Any swap parameter is invalid
+    /// - The transaction exceeds the 1232-byte size limit
+    ///   (`ContractError::TransactionTooLarge`; split the batch)
+    /// - The program is paused (`ContractError::ProgramPaused`; nothing is sent)
     /// - The transaction fails
     /// - The IDL types are not available (program not built)
     ///
@@ -197,6 +370,9 @@ where
     ///         output_mint: usdc_mint,
     ///         amount: 1_000_000_000, // 1 SOL
     ///         min_output_amount: 90_000_000, // 90 USDC minimum
+    ///         max_fee: None, // no absolute fee cap
+    ///         max_slippage_bps: None, // global MAX_SLIPPAGE_BPS
+    ///         tag: 0, // untagged
     ///     },
     /// ];
     ///
@@ -217,20 +393,52 @@ where
     ///     output_mint: s.output_mint,
     ///     amount: s.amount,
     ///     min_output_amount: s.min_output_amount,
+    ///     max_fee: s.max_fee,
+    ///     max_slippage_bps: s.max_slippage_bps,
+    ///     tag: s.tag,
     /// }).collect();
     ///
     /// let payer = self.program.payer();
     /// let authority = payer.pubkey();
     ///
-    /// self.program
+    /// let request = self.program
     ///     .request()
     ///     .accounts(batch_swap_router::accounts::BatchSwap {
     ///         authority,
+    ///         config: config_pda,
+    ///         stats: pda::stats_pda(&self.program.id()).0,
     ///         fee_recipient: None,
     ///         token_program: anchor_spl::token::ID,
     ///         system_program: anchor_client::solana_sdk::system_program::ID,
+    ///         instructions_sysvar: anchor_client::solana_sdk::sysvar::instructions::ID,
     ///     })
-    ///     .args(batch_swap_router::instruction::BatchSwap { swaps: swap_args })
+    ///     // Events are on by default; pass `false` to save compute units
+    ///     .args(batch_swap_router::instruction::BatchSwap {
+    ///         version: INSTRUCTION_VERSION,
+    ///         swaps: swap_args,
+    ///         // Settlement mint for the event's total_output_amount; the
+    ///         // default pubkey matches no leg (totals in no mint)
+    ///         base_mint: Pubkey::default(),
+    ///         emit_events: true,
+    ///         memo_hash: memo::NO_MEMO_HASH,
+    ///         // No per-leg callback CPI
+    ///         callback_program: None,
+    ///         // No cap on the batch's summed protocol fees
+    ///         max_total_fee: 0,
+    ///         // No leg output accounts in the remaining accounts
+    ///         leg_output_accounts: false,
+    ///         // No batch-level slippage limit (needs leg output accounts)
+    ///         global_max_slippage_bps: 0,
+    ///         // No shared input account in the remaining accounts
+    ///         shared_input_account: false,
+    ///     });
+    ///
+    /// // Fail with TooManyAccounts or TransactionTooLarge instead of an RPC rejection
+    /// let instructions = request.instructions()?;
+    /// tx_size::check_legacy_accounts(&instructions, &authority)?;
+    /// tx_size::check_transaction_size(tx_size::legacy_transaction_size(&instructions, &authority))?;
+    ///
+    /// request
     ///     .send()
     ///     .map_err(|e| ContractError::TransactionFailed(e.to_string()))
     /// ```
@@ -244,19 +452,66 @@ where
                 .map_err(|e| ContractError::InvalidAccount(e))?;
         }
 
-        // Build the instruction request
-        // Note: After building with Anchor, use the generated IDL types
-        //
-        // For now, this requires the IDL to be generated by running `anchor build`
-        Err(ContractError::TransactionFailed(
-            "Batch swap requires Anchor IDL types. Build the program with 'anchor build' first, then use the generated IDL types with anchor-client.".to_string()
-        ))
+        // A paused program rejects every swap; don't pay for the transaction
+        self.ensure_not_paused()?;
+
+        let instructions = self.batch_swap_instructions(&swaps)?;
+        let instructions = self.with_wsol_instructions(&swaps, instructions)?;
+        self.send_instructions(instructions)
+    }
+
+    /// Execute a large set of swaps as several batch swaps
+    ///
+    /// Splits `swaps` into chunks of at most `chunk_size` swaps and submits
+    /// each chunk with [`Self::batch_swap`], in order. A failed chunk does not
+    /// stop the others; its outcome keeps its swaps for a retry (see
+    /// [`chunked::failed_swaps`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `swaps` - The swaps to execute, in order
+    /// * `chunk_size` - Maximum swaps per batch (1 to `MAX_BATCH_SIZE`)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<ChunkOutcome>)` - One outcome per chunk, in order
+    /// * `Err(ContractError)` - If `chunk_size` is out of range (nothing is sent)
+    ///
+    /// # Errors
+    ///
+    /// Returns `ContractError::InvalidAccount` if `chunk_size` is 0 or exceeds
+    /// `MAX_BATCH_SIZE`. Per-chunk failures are reported in the outcomes.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let outcomes = client.batch_swap_chunked(swaps, MAX_BATCH_SIZE)?;
+    /// let retry = chunked::failed_swaps(&outcomes);
+    /// ```
+    pub fn batch_swap_chunked(
+        &self,
+        swaps: Vec<SwapParams>,
+        chunk_size: usize,
+    ) -> Result<Vec<ChunkOutcome>, ContractError> {
+        let outcomes = chunked::chunk_swaps(swaps, chunk_size)?
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_index, swaps)| ChunkOutcome {
+                chunk_index,
+                result: self.batch_swap(swaps.clone()),
+                swaps,
+            })
+            .collect();
+
+        Ok(outcomes)
     }
 
     /// Execute a single swap
     ///
     /// This method executes a single token swap with slippage protection and
     /// fee calculation. It executes swaps between different token mints.
+    /// Native SOL input or output is wrapped and unwrapped around the swap
+    /// (see [`Self::with_wsol_options`]).
     ///
     /// # Arguments
     ///
@@ -264,7 +519,9 @@ where
     /// * `output_token_account` - Output token account (tokens received)
     /// * `input_mint` - Input token mint
     /// * `output_mint` - Output token mint
-    /// * `amount` - Amount of input tokens to swap
+    /// * `amount` - Amount of input tokens to swap, or
+    ///   [`USE_FULL_BALANCE`](crate::types::USE_FULL_BALANCE) for the input
+    ///   account's whole balance
     /// * `min_output_amount` - Minimum output amount (slippage protection)
     /// * `expected_output` - Expected output amount (from Jupiter quote)
     ///
@@ -280,6 +537,7 @@ where
     /// - The input and output accounts have the same mint
     /// - The authority doesn't own the input token account
     /// - Slippage tolerance is exceeded
+    /// - The program is paused (`ContractError::ProgramPaused`; nothing is sent)
     /// - The transaction fails
     /// - The IDL types are not available (program not built)
     ///
@@ -305,10 +563,10 @@ where
     /// generated and this method will use the generated types.
     pub fn execute_swap(
         &self,
-        _input_token_account: Pubkey,
-        _output_token_account: Pubkey,
-        _input_mint: Pubkey,
-        _output_mint: Pubkey,
+        input_token_account: Pubkey,
+        output_token_account: Pubkey,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
         amount: u64,
         min_output_amount: u64,
         expected_output: u64,
@@ -326,19 +584,24 @@ where
             ));
         }
 
-        if _input_mint == _output_mint {
+        if input_mint == output_mint {
             return Err(ContractError::InvalidAccount(
                 "Input and output mints must differ".to_string(),
             ));
         }
 
-        // Build the instruction request
-        // Note: After building with Anchor, use the generated IDL types
-        //
-        // For now, this requires the IDL to be generated by running `anchor build`
-        Err(ContractError::TransactionFailed(
-            "Execute swap requires Anchor IDL types. Build the program with 'anchor build' first, then use the generated IDL types with anchor-client.".to_string()
-        ))
+        // A paused program rejects every swap; don't pay for the transaction
+        self.ensure_not_paused()?;
+
+        let swap = SwapParams::new(input_mint, output_mint, amount, min_output_amount);
+        let instructions = self.execute_swap_instructions(
+            input_token_account,
+            output_token_account,
+            &swap,
+            expected_output,
+        )?;
+        let instructions = self.with_wsol_instructions(&[swap], instructions)?;
+        self.send_instructions(instructions)
     }
 
     /// Get the underlying program instance
@@ -365,6 +628,935 @@ where
     }
 }
 
+impl<C, S> BatchSwapRouterClient<C>
+where
+    C: Deref<Target = S> + Clone,
+    S: Signer,
+{
+    /// Check that the RPC node is on the cluster the program ID is deployed on
+    ///
+    /// Pointing the client at mainnet with the devnet program ID (or the
+    /// reverse) otherwise makes every call fail with an opaque missing-account
+    /// error. Call this once at startup, after [`Self::new`]. Program IDs that
+    /// are not a known deployment (local validators, custom deployments) pass
+    /// unchecked; see [`cluster::KNOWN_DEPLOYMENTS`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The cluster matches the program ID
+    /// * `Err(ContractError)` - If it does not, or the RPC request fails
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::ClientError` - The program ID is deployed on another cluster
+    /// - `ContractError::NetworkError` - The RPC request failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let client = BatchSwapRouterClient::new(program);
+    /// client.verify_cluster()?;
+    /// ```
+    pub fn verify_cluster(&self) -> Result<(), ContractError> {
+        let genesis_hash = self
+            .program
+            .rpc()
+            .get_genesis_hash()
+            .map_err(|e| ContractError::NetworkError(e.to_string()))?;
+        cluster::check_cluster(&self.program.id(), &genesis_hash)
+    }
+
+    /// Fetch the current nonce of the configured nonce account
+    ///
+    /// The nonce is the blockhash to sign durable transactions with. It
+    /// changes every time a durable transaction lands, so fetch it again for
+    /// each transaction.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Hash)` - The durable nonce
+    /// * `Err(ContractError)` - If no nonce is configured or it cannot be read
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::ClientError` - [`Self::with_durable_nonce`] was not called
+    /// - `ContractError::InvalidAccount` - The nonce account is missing, not an
+    ///   initialized nonce, or has another authority
+    /// - `ContractError::NetworkError` - The RPC request failed
+    pub fn fetch_durable_nonce(&self) -> Result<Hash, ContractError> {
+        let durable = self.durable_nonce()?;
+        let data = self.fetch_account_data(&durable.nonce_account, "Nonce", self.read_commitment())?;
+        let (authority, nonce) = durable_nonce::parse_nonce_account(&data)?;
+
+        if authority != durable.nonce_authority {
+            return Err(ContractError::InvalidAccount(format!(
+                "Nonce account {} is controlled by {authority}, not {}",
+                durable.nonce_account, durable.nonce_authority
+            )));
+        }
+        Ok(nonce)
+    }
+
+    /// Build an unsigned durable transaction paid by the payer
+    ///
+    /// Fetches the current nonce and prepends the advance-nonce instruction.
+    /// Sign it with the payer and the nonce authority, using the nonce as
+    /// the blockhash (`transaction.message.recent_blockhash`).
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - The transaction's instructions (route, router, memo, ...)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Transaction)` - The unsigned transaction
+    /// * `Err(ContractError)` - If the nonce cannot be fetched
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::fetch_durable_nonce`].
+    pub fn durable_transaction(&self, instructions: Vec<Instruction>) -> Result<Transaction, ContractError> {
+        let nonce = self.fetch_durable_nonce()?;
+        Ok(self
+            .durable_nonce()?
+            .unsigned_transaction(instructions, &self.program.payer(), nonce))
+    }
+
+    /// Fetch the deployed program's version and features
+    ///
+    /// Simulates the program's `get_version` instruction (nothing is sent or
+    /// paid) and decodes its return data. Use the result to gate client
+    /// behavior (see [`crate::version`]).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ProgramVersion)` - The program version, accepted instruction
+    ///   versions and feature bits
+    /// * `Err(ContractError)` - If the simulation fails or returns no version
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::NetworkError` - The RPC request failed
+    /// - `ContractError::TransactionFailed` - The simulation failed, e.g. the
+    ///   program predates `get_version`
+    /// - `ContractError::ProgramError` - The simulation returned no data
+    /// - `ContractError::SerializationError` - The return data is not a version
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let version = client.fetch_program_version()?;
+    /// println!("Program {} (features {:#x})", version.program_version, version.features);
+    /// ```
+    pub fn fetch_program_version(&self) -> Result<ProgramVersion, ContractError> {
+        let program_id = self.program.id();
+        let rpc = self.program.rpc();
+        let blockhash = rpc
+            .get_latest_blockhash()
+            .map_err(|e| ContractError::NetworkError(e.to_string()))?;
+
+        // Simulation does not check signatures, so the transaction stays unsigned
+        let message = Message::new_with_blockhash(
+            &[version::get_version_instruction(&program_id)],
+            Some(&self.program.payer()),
+            &blockhash,
+        );
+        let result = rpc
+            .simulate_transaction(&Transaction::new_unsigned(message))
+            .map_err(|e| ContractError::NetworkError(e.to_string()))?
+            .value;
+
+        if let Some(err) = result.err {
+            return Err(ContractError::TransactionFailed(format!("get_version simulation failed: {err}")));
+        }
+        let return_data = result
+            .return_data
+            .ok_or_else(|| ContractError::ProgramError("get_version returned no data".to_string()))?;
+
+        version::decode_version_return_data(&program_id, &return_data.program_id, &return_data.data.0)
+    }
+
+    /// Fetch the program-wide swap stats
+    ///
+    /// Derives the `Stats` PDA, reads the account and decodes it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Stats)` - The decoded stats
+    /// * `Err(ContractError)` - If the account is missing or cannot be read
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::InvalidAccount` - The stats account does not exist
+    ///   (config not initialized), is not a `Stats` account, or its stored
+    ///   bump does not derive its address
+    /// - `ContractError::NetworkError` - The RPC request failed
+    /// - `ContractError::SerializationError` - The account data cannot be decoded
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let stats = client.fetch_stats()?;
+    /// println!("{} swaps, {} fees", stats.total_swaps, stats.total_fees);
+    /// ```
+    pub fn fetch_stats(&self) -> Result<Stats, ContractError> {
+        self.fetch_stats_at(self.read_commitment())
+    }
+
+    /// Fetch the program-wide swap stats at an explicit commitment
+    ///
+    /// Same as [`Self::fetch_stats`], ignoring the configured read
+    /// commitment. Pass `CommitmentConfig::finalized()` after a write to
+    /// avoid reading stats that do not include it yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `commitment` - The commitment the read requires
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Stats)` - The decoded stats
+    /// * `Err(ContractError)` - If the account is missing or cannot be read
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::fetch_stats`].
+    pub fn fetch_stats_at(&self, commitment: CommitmentConfig) -> Result<Stats, ContractError> {
+        let (address, _) = pda::stats_pda(&self.program.id());
+        let data = self.fetch_account_data(&address, "Stats", commitment)?;
+        let stats = stats::decode_stats(&data)?;
+        pda::verify_stored_bump(&address, &self.program.id(), &[pda::STATS_SEED], stats.bump)?;
+        Ok(stats)
+    }
+
+    /// Send instructions, wait until they are finalized, then read the stats
+    ///
+    /// Closes the read-after-write race described in the module docs: the
+    /// stats are read at `finalized` only once the transaction itself is
+    /// finalized, so they always include it.
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - The instructions to send (e.g. a batch swap)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((Signature, Stats))` - The transaction signature and the stats
+    ///   after it
+    /// * `Err(ContractError)` - If sending, confirming or reading fails
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::TransactionFailed` - The transaction failed
+    /// - `ContractError::NetworkError` - The transaction was not finalized
+    ///   (e.g. its blockhash expired) or the RPC request failed
+    /// - Any error of [`Self::fetch_stats`]
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let (signature, stats) = client.send_confirm_then_read(instructions)?;
+    /// println!("{signature}: {} swaps", stats.total_swaps);
+    /// ```
+    pub fn send_confirm_then_read(
+        &self,
+        instructions: Vec<Instruction>,
+    ) -> Result<(Signature, Stats), ContractError> {
+        let mut request = self.program.request();
+        for instruction in instructions {
+            request = request.instruction(instruction);
+        }
+        let signature = request
+            .send()
+            .map_err(|e| ContractError::TransactionFailed(e.to_string()))?;
+
+        self.program
+            .rpc()
+            .poll_for_signature_with_commitment(&signature, CommitmentConfig::finalized())
+            .map_err(|e| ContractError::NetworkError(format!("{signature} not finalized: {e}")))?;
+
+        Ok((signature, self.fetch_stats_at(CommitmentConfig::finalized())?))
+    }
+
+    /// Send an `execute_swap` and return the `SwapExecutedEvent` it emitted
+    ///
+    /// Sends the instructions, waits until the transaction is confirmed,
+    /// fetches it and decodes the `SwapExecutedEvent` from its logs, so
+    /// callers get the swap's actual output, fees and slippage without
+    /// running an indexer.
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - The instructions to send, including one
+    ///   `execute_swap` (e.g. after compute budget or ATA setup)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((Signature, Some(SwapExecutedEventData)))` - The transaction
+    ///   signature and the swap's event
+    /// * `Ok((Signature, None))` - The swap landed but emitted no event
+    ///   (`emit_events = false`), or was a simulated swap (`simulate = true`)
+    ///   that moved no tokens
+    /// * `Err(ContractError)` - If sending, confirming or fetching fails
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::TransactionFailed` - The transaction failed
+    /// - `ContractError::NetworkError` - The transaction was not confirmed
+    ///   (e.g. its blockhash expired) or could not be fetched
+    /// - `ContractError::SerializationError` - The event could not be decoded
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let (signature, event) = client.execute_swap_and_get_event(instructions)?;
+    /// if let Some(event) = event {
+    ///     println!("{signature}: received {} ({} bps slippage)", event.net_output_amount, event.slippage_bps);
+    /// }
+    /// ```
+    pub fn execute_swap_and_get_event(
+        &self,
+        instructions: Vec<Instruction>,
+    ) -> Result<(Signature, Option<SwapExecutedEventData>), ContractError> {
+        let mut request = self.program.request();
+        for instruction in instructions {
+            request = request.instruction(instruction);
+        }
+        let signature = request
+            .send()
+            .map_err(|e| ContractError::TransactionFailed(e.to_string()))?;
+
+        let rpc = self.program.rpc();
+        rpc.poll_for_signature_with_commitment(&signature, CommitmentConfig::confirmed())
+            .map_err(|e| ContractError::NetworkError(format!("{signature} not confirmed: {e}")))?;
+
+        let transaction = rpc
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .map_err(|e| ContractError::NetworkError(format!("Failed to fetch {signature}: {e}")))?;
+        let logs: Option<Vec<String>> = transaction
+            .transaction
+            .meta
+            .and_then(|meta| meta.log_messages.into());
+
+        // A simulated swap still logs its event, but no swap happened
+        let event = events::swap_executed_event_from_logs(&logs.unwrap_or_default())?
+            .filter(|event| !event.simulated);
+        Ok((signature, event))
+    }
+
+    /// Collect the program's events over a range of slots
+    ///
+    /// Pages `getBlocks` over the range, fetches every block and decodes the
+    /// events logged by successful transactions that invoked the program,
+    /// retrying throttled requests with backoff (see the `event_scan` module).
+    /// Blocks are read at the read commitment, which must be `confirmed` or
+    /// `finalized` for `getBlocks`.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_slot` - First slot to scan
+    /// * `to_slot` - Last slot to scan (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<ParsedEvent>)` - The events with their slot, signature and
+    ///   block time, in slot order and log order within a transaction
+    ///   (empty if `from_slot > to_slot`)
+    /// * `Err(ContractError)` - If a request keeps failing or an event cannot
+    ///   be decoded
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::NetworkError` - A `getBlocks` or `getBlock` request
+    ///   still failed after `event_scan::SCAN_MAX_RETRIES` retries
+    /// - `ContractError::SerializationError` - An event of the current schema
+    ///   version could not be decoded
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let events = client.scan_events(280_000_000, 280_010_000)?;
+    /// println!("{} events, last at slot {:?}", events.len(), events.last().map(|e| e.slot));
+    /// ```
+    #[allow(clippy::result_large_err)] // the retried closures return solana_client's own `ClientError`
+    pub fn scan_events(&self, from_slot: u64, to_slot: u64) -> Result<Vec<ParsedEvent>, ContractError> {
+        let rpc = self.program.rpc();
+        let program_id = self.program.id();
+        let commitment = self.read_commitment();
+        let mut parsed = Vec::new();
+
+        for (start, end) in event_scan::slot_pages(from_slot, to_slot) {
+            // Only slots that produced a block, so none is fetched in vain
+            let slots = event_scan::with_backoff("getBlocks", || {
+                rpc.get_blocks_with_commitment(start, Some(end), commitment)
+            })?;
+
+            for slot in slots {
+                let block = event_scan::with_backoff("getBlock", || {
+                    rpc.get_block_with_config(
+                        slot,
+                        RpcBlockConfig {
+                            encoding: Some(UiTransactionEncoding::Base64),
+                            transaction_details: Some(TransactionDetails::Full),
+                            rewards: Some(false),
+                            commitment: Some(commitment),
+                            max_supported_transaction_version: Some(0),
+                        },
+                    )
+                })?;
+
+                for transaction in block.transactions.unwrap_or_default() {
+                    // A failed transaction changed nothing, whatever it logged
+                    let Some(meta) = transaction.meta.filter(|meta| meta.err.is_none()) else {
+                        continue;
+                    };
+                    let logs: Vec<String> = Option::from(meta.log_messages).unwrap_or_default();
+                    if !event_scan::invokes_program(&logs, &program_id) {
+                        continue;
+                    }
+                    let Some(signature) = transaction
+                        .transaction
+                        .decode()
+                        .and_then(|decoded| decoded.signatures.first().copied())
+                    else {
+                        continue;
+                    };
+
+                    for event in event_scan::events_from_logs(&logs)? {
+                        parsed.push(ParsedEvent {
+                            slot,
+                            signature,
+                            block_time: block.block_time,
+                            event,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Fetch the swap stats of a (directional) pair
+    ///
+    /// # Arguments
+    ///
+    /// * `input_mint` - Input token mint of the pair
+    /// * `output_mint` - Output token mint of the pair
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PairStats)` - The decoded pair stats
+    /// * `Err(ContractError)` - If the account is missing or cannot be read
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::InvalidAccount` - The pair has never been swapped
+    ///   (account absent), the account is not a `PairStats` account, or its
+    ///   stored bump does not derive its address
+    /// - `ContractError::NetworkError` - The RPC request failed
+    /// - `ContractError::SerializationError` - The account data cannot be decoded
+    pub fn fetch_pair_stats(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+    ) -> Result<PairStats, ContractError> {
+        let (address, _) = pda::pair_stats_pda(&self.program.id(), &input_mint, &output_mint);
+        let data = self.fetch_account_data(&address, "PairStats", self.read_commitment())?;
+        let pair_stats = stats::decode_pair_stats(&data)?;
+        pda::verify_stored_bump(
+            &address,
+            &self.program.id(),
+            &[pda::PAIR_STATS_SEED, input_mint.as_ref(), output_mint.as_ref()],
+            pair_stats.bump,
+        )?;
+        Ok(pair_stats)
+    }
+
+    /// Fetch the decimals of a mint, caching them per mint
+    ///
+    /// Use this for the `decimals` argument of `transfer_checked` (Token-2022
+    /// mints), which the token program rejects with any other value.
+    /// Each mint is read once per client; decimals never change after a mint
+    /// is initialized.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - The mint (SPL Token or Token-2022)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(u8)` - The mint's decimals
+    /// * `Err(ContractError)` - If the mint cannot be read
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::InvalidAccount` - The account does not exist or is not a mint
+    /// - `ContractError::NetworkError` - The RPC request failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let decimals = client.fetch_mint_decimals(&usdc_mint)?;
+    /// ```
+    pub fn fetch_mint_decimals(&self, mint: &Pubkey) -> Result<u8, ContractError> {
+        let mut cache = self
+            .mint_decimals
+            .lock()
+            .map_err(|_| ContractError::ClientError("Mint decimals cache poisoned".to_string()))?;
+        cache.get_or_fetch(mint, |mint| self.fetch_account_data(mint, "Mint", self.read_commitment()))
+    }
+
+    /// Fetch the live protocol fee rate from the `Config` account
+    ///
+    /// Use this to display the current fee instead of hardcoding
+    /// [`PROTOCOL_FEE_BPS`](crate::PROTOCOL_FEE_BPS), which the admin can
+    /// change. The rate is cached for [`FEE_BPS_TTL`](config::FEE_BPS_TTL), so
+    /// an admin update shows up within that time.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(u16)` - `Config::fee_bps`, or `PROTOCOL_FEE_BPS` if the config
+    ///   is not initialized
+    /// * `Err(ContractError)` - If the config cannot be read
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::InvalidAccount` - The config PDA is not a `Config` account
+    /// - `ContractError::NetworkError` - The RPC request failed
+    /// - `ContractError::SerializationError` - The config is on an older
+    ///   layout (run `migrate_config`) or cannot be decoded
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let fee_bps = client.fetch_protocol_fee_bps()?;
+    /// println!("Protocol fee: {:.2}%", f64::from(fee_bps) / 100.0);
+    /// ```
+    pub fn fetch_protocol_fee_bps(&self) -> Result<u16, ContractError> {
+        let mut cache = self
+            .fee_bps
+            .lock()
+            .map_err(|_| ContractError::ClientError("Fee rate cache poisoned".to_string()))?;
+        cache.get_or_fetch(Instant::now(), || {
+            let (address, _) = pda::config_pda(&self.program.id());
+            let data = self.fetch_optional_account_data(&address, self.read_commitment())?;
+            config::protocol_fee_bps(data.as_deref())
+        })
+    }
+
+    /// Check whether the program admin has paused swaps
+    ///
+    /// While paused, the program rejects `batch_swap`, `execute_swap` and the
+    /// other swap instructions, so UIs can disable trading up front. The
+    /// state is cached for [`PAUSED_TTL`](config::PAUSED_TTL), so a pause or
+    /// unpause shows up within that time.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(bool)` - `Config::paused`, or `false` if the config is not
+    ///   initialized
+    /// * `Err(ContractError)` - If the config cannot be read
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::fetch_protocol_fee_bps`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// if client.is_paused()? {
+    ///     println!("Trading is paused");
+    /// }
+    /// ```
+    pub fn is_paused(&self) -> Result<bool, ContractError> {
+        let mut cache = self
+            .paused
+            .lock()
+            .map_err(|_| ContractError::ClientError("Pause state cache poisoned".to_string()))?;
+        cache.get_or_fetch(Instant::now(), || {
+            let (address, _) = pda::config_pda(&self.program.id());
+            let data = self.fetch_optional_account_data(&address, self.read_commitment())?;
+            config::program_paused(data.as_deref())
+        })
+    }
+
+    /// Fail with `ContractError::ProgramPaused` if the program is paused
+    fn ensure_not_paused(&self) -> Result<(), ContractError> {
+        if self.is_paused()? {
+            return Err(ContractError::ProgramPaused);
+        }
+        Ok(())
+    }
+
+    /// Build the `batch_swap` instruction for validated swaps
+    ///
+    /// Note: After building with Anchor, use the generated IDL types (see
+    /// "Implementation Notes" on [`Self::batch_swap`]).
+    #[allow(clippy::unused_self)] // builds on `self.program` once the IDL types exist
+    fn batch_swap_instructions(&self, _swaps: &[SwapParams]) -> Result<Vec<Instruction>, ContractError> {
+        // For now, this requires the IDL to be generated by running `anchor build`
+        Err(ContractError::TransactionFailed(
+            "Batch swap requires Anchor IDL types. Build the program with 'anchor build' first, then use the generated IDL types with anchor-client.".to_string()
+        ))
+    }
+
+    /// Build the `execute_swap` instruction for a validated swap
+    ///
+    /// Note: After building with Anchor, use the generated IDL types.
+    #[allow(clippy::unused_self)] // builds on `self.program` once the IDL types exist
+    fn execute_swap_instructions(
+        &self,
+        _input_token_account: Pubkey,
+        _output_token_account: Pubkey,
+        _swap: &SwapParams,
+        _expected_output: u64,
+    ) -> Result<Vec<Instruction>, ContractError> {
+        // For now, this requires the IDL to be generated by running `anchor build`
+        Err(ContractError::TransactionFailed(
+            "Execute swap requires Anchor IDL types. Build the program with 'anchor build' first, then use the generated IDL types with anchor-client.".to_string()
+        ))
+    }
+
+    /// Send instructions as one transaction signed by the payer
+    fn send_instructions(&self, instructions: Vec<Instruction>) -> Result<Signature, ContractError> {
+        let mut request = self.program.request();
+        for instruction in instructions {
+            request = request.instruction(instruction);
+        }
+        request
+            .send()
+            .map_err(|e| ContractError::TransactionFailed(e.to_string()))
+    }
+
+    /// Fetch a blockhash whose validity window ends at a swap deadline
+    ///
+    /// Reads the latest blockhash and block height at `confirmed` and caps the
+    /// blockhash's `last_valid_block_height` at the block height `deadline`
+    /// falls on, so a transaction built with it cannot land after the
+    /// deadline. The program itself has no deadline check; this window is the
+    /// only thing bounding it. Pass the earliest deadline across a batch's
+    /// legs ([`deadline::earliest_deadline`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The swap deadline as a Unix timestamp (`None` = blockhash expiry only)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ValidityWindow)` - The blockhash and the last block height to send at
+    /// * `Err(ContractError)` - If the deadline is too close or the RPC request fails
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::DeadlineTooShort` - Fewer than
+    ///   [`MIN_CONFIRMATION_SECS`](deadline::MIN_CONFIRMATION_SECS) remain
+    /// - `ContractError::NetworkError` - The RPC request failed
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let window = client.deadline_blockhash(Some(now + 20))?;
+    /// let tx = Transaction::new_signed_with_payer(&instructions, Some(&payer), &[&keypair], window.blockhash);
+    /// ```
+    pub fn deadline_blockhash(&self, deadline: Option<i64>) -> Result<ValidityWindow, ContractError> {
+        let rpc = self.program.rpc();
+        let (blockhash, last_valid_block_height) = rpc
+            .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
+            .map_err(|e| ContractError::NetworkError(e.to_string()))?;
+        let block_height = rpc
+            .get_block_height_with_commitment(CommitmentConfig::confirmed())
+            .map_err(|e| ContractError::NetworkError(e.to_string()))?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| ContractError::ClientError(e.to_string()))?
+            .as_secs();
+
+        deadline::validity_window(
+            blockhash,
+            last_valid_block_height,
+            block_height,
+            i64::try_from(now).unwrap_or(i64::MAX),
+            deadline,
+        )
+    }
+
+    /// Build the instructions creating a batch's missing output token accounts
+    ///
+    /// Checks the payer's associated token account of every distinct output
+    /// mint with one `getMultipleAccounts` request and returns an idempotent
+    /// create-ATA instruction for each missing one (see [`account_setup`]).
+    /// Prepend them to the batch transaction or send them beforehand.
+    ///
+    /// # Arguments
+    ///
+    /// * `swaps` - The batch's legs
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Instruction>)` - Create-ATA instructions, empty if every
+    ///   output account exists
+    /// * `Err(ContractError)` - If the RPC request fails
+    ///
+    /// # Errors
+    ///
+    /// Returns `ContractError::NetworkError` if `getMultipleAccounts` fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Create missing output accounts in the same transaction as the batch
+    /// let mut request = client.program().request();
+    /// for instruction in client.prepare_accounts(&swaps)? {
+    ///     request = request.instruction(instruction);
+    /// }
+    /// ```
+    pub fn prepare_accounts(&self, swaps: &[SwapParams]) -> Result<Vec<Instruction>, ContractError> {
+        let owner = self.program.payer();
+        let accounts = account_setup::output_token_accounts(&owner, swaps);
+        let addresses: Vec<Pubkey> = accounts.iter().map(|account| account.address).collect();
+
+        let rpc = self.program.rpc();
+        let mut exists = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(account_setup::MAX_MULTIPLE_ACCOUNTS) {
+            let found = rpc
+                .get_multiple_accounts_with_commitment(chunk, self.read_commitment())
+                .map_err(|e| ContractError::NetworkError(e.to_string()))?
+                .value;
+            exists.extend(found.iter().map(Option::is_some));
+        }
+
+        Ok(account_setup::create_missing_accounts(&owner, &owner, &accounts, &exists))
+    }
+
+    /// Surround swap instructions with the WSOL handling their legs need
+    ///
+    /// Checks whether the payer's WSOL account already exists and wraps the
+    /// instructions with [`wsol::with_wsol_handling`] using the client's
+    /// [`WsolOptions`]. An existing WSOL account is left open, so wrapped SOL
+    /// the payer already held is never unwrapped. [`Self::batch_swap`] and
+    /// [`Self::execute_swap`] call this; use it when sending swap
+    /// instructions built elsewhere.
+    ///
+    /// # Arguments
+    ///
+    /// * `swaps` - The swap legs the instructions execute
+    /// * `swap_instructions` - The swap instructions (route + router instruction)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Instruction>)` - The ordered instruction list, unchanged if
+    ///   no leg touches native SOL
+    /// * `Err(ContractError)` - If the RPC request fails or an instruction
+    ///   cannot be built
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::NetworkError` - The WSOL account could not be read
+    /// - Any error of [`wsol::with_wsol_handling`]
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let instructions = client.with_wsol_instructions(&swaps, route_instructions)?;
+    /// let (signature, event) = client.execute_swap_and_get_event(instructions)?;
+    /// ```
+    pub fn with_wsol_instructions(
+        &self,
+        swaps: &[SwapParams],
+        swap_instructions: Vec<Instruction>,
+    ) -> Result<Vec<Instruction>, ContractError> {
+        let owner = self.program.payer();
+        let touches_sol = swaps
+            .iter()
+            .any(|swap| wsol::is_native_mint(&swap.input_mint) || wsol::is_native_mint(&swap.output_mint));
+        // Only legs touching native SOL need the WSOL account read
+        let wsol_account_exists = touches_sol
+            && self
+                .fetch_optional_account_data(&wsol::wsol_account(&owner), self.read_commitment())?
+                .is_some();
+
+        wsol::with_wsol_handling(&owner, swaps, swap_instructions, wsol_account_exists, self.wsol)
+    }
+
+    /// Create or extend the Address Lookup Table used for batch swaps
+    ///
+    /// Ensures a lookup table owned by the payer contains the program ID, the
+    /// SPL Token program, the System program and `common_mints`, and waits
+    /// until newly added addresses are usable. The table address is cached in
+    /// [`LOOKUP_TABLE_CACHE_FILE`](lookup_table::LOOKUP_TABLE_CACHE_FILE) and
+    /// reused on later calls (see [`Self::ensure_lookup_table_cached`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `common_mints` - Frequently used mints to include in the table
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Pubkey)` - The lookup table address
+    /// * `Err(ContractError)` - If a transaction or RPC request fails
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::TransactionFailed` - Creating or extending the table failed
+    /// - `ContractError::NetworkError` - The RPC request failed, or the table did
+    ///   not become active in time
+    /// - `ContractError::ClientError` - The cache file cannot be written
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let table = client.ensure_lookup_table(&[sol_mint, usdc_mint])?;
+    /// ```
+    pub fn ensure_lookup_table(&self, common_mints: &[Pubkey]) -> Result<Pubkey, ContractError> {
+        self.ensure_lookup_table_cached(common_mints, Path::new(lookup_table::LOOKUP_TABLE_CACHE_FILE))
+    }
+
+    /// Create or extend the Address Lookup Table, caching its address in `cache_path`
+    ///
+    /// Same as [`Self::ensure_lookup_table`], with an explicit cache file (e.g.
+    /// one per cluster).
+    ///
+    /// # Arguments
+    ///
+    /// * `common_mints` - Frequently used mints to include in the table
+    /// * `cache_path` - File holding the table address between calls
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Pubkey)` - The lookup table address
+    /// * `Err(ContractError)` - If a transaction or RPC request fails
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::ensure_lookup_table`].
+    pub fn ensure_lookup_table_cached(
+        &self,
+        common_mints: &[Pubkey],
+        cache_path: &Path,
+    ) -> Result<Pubkey, ContractError> {
+        let rpc = self.program.rpc();
+        let authority = self.program.payer();
+        let wanted = lookup_table::lookup_table_addresses(&self.program.id(), common_mints);
+
+        // Reuse the cached table while it is still ours and active
+        let cached = lookup_table::read_cached_address(cache_path).and_then(|address| {
+            let account = rpc
+                .get_account_with_commitment(&address, rpc.commitment())
+                .ok()?
+                .value?;
+            lookup_table::usable_table(&account.data, &authority).map(|(existing, _)| (address, existing))
+        });
+
+        let (address, existing) = if let Some(table) = cached {
+            table
+        } else {
+            let recent_slot = rpc
+                .get_slot()
+                .map_err(|e| ContractError::NetworkError(e.to_string()))?;
+            let (instruction, address) = create_lookup_table(authority, authority, recent_slot);
+            self.send_instruction(instruction)?;
+            (address, Vec::new())
+        };
+
+        // Cache before extending so an interrupted run resumes with this table
+        lookup_table::write_cached_address(cache_path, &address)?;
+
+        let missing = lookup_table::missing_addresses(&existing, &wanted);
+        for chunk in missing.chunks(lookup_table::EXTEND_CHUNK_SIZE) {
+            self.send_instruction(extend_lookup_table(address, authority, Some(authority), chunk.to_vec()))?;
+        }
+
+        if !missing.is_empty() {
+            self.wait_for_lookup_table(&address)?;
+        }
+
+        Ok(address)
+    }
+
+    /// Wait until addresses added to a lookup table are usable
+    ///
+    /// Addresses become usable in the slot after the table was last extended.
+    fn wait_for_lookup_table(&self, address: &Pubkey) -> Result<(), ContractError> {
+        let rpc = self.program.rpc();
+        let data = self.fetch_account_data(address, "Lookup table", self.read_commitment())?;
+        let (_, last_extended_slot) = lookup_table::usable_table(&data, &self.program.payer())
+            .ok_or_else(|| ContractError::InvalidAccount(format!("{address} is not a usable lookup table")))?;
+
+        for _ in 0..lookup_table::ACTIVATION_POLL_ATTEMPTS {
+            let slot = rpc
+                .get_slot()
+                .map_err(|e| ContractError::NetworkError(e.to_string()))?;
+            if slot > last_extended_slot {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(lookup_table::ACTIVATION_POLL_INTERVAL_MS));
+        }
+
+        Err(ContractError::NetworkError(format!(
+            "Lookup table {address} was not active after {} slot polls",
+            lookup_table::ACTIVATION_POLL_ATTEMPTS
+        )))
+    }
+
+    /// Sign and send a single instruction with the payer
+    fn send_instruction(&self, instruction: Instruction) -> Result<Signature, ContractError> {
+        self.program
+            .request()
+            .instruction(instruction)
+            .send()
+            .map_err(|e| ContractError::TransactionFailed(e.to_string()))
+    }
+
+    /// Commitment for account reads: the configured read commitment, or the
+    /// RPC client's commitment
+    fn read_commitment(&self) -> CommitmentConfig {
+        self.read_commitment
+            .unwrap_or_else(|| self.program.rpc().commitment())
+    }
+
+    /// The configured durable nonce
+    fn durable_nonce(&self) -> Result<&DurableNonce, ContractError> {
+        self.durable_nonce.as_ref().ok_or_else(|| {
+            ContractError::ClientError("No durable nonce configured (see with_durable_nonce)".to_string())
+        })
+    }
+
+    /// Read the raw data of an account at `commitment`, mapping a missing
+    /// account to `ContractError::InvalidAccount`
+    fn fetch_account_data(
+        &self,
+        address: &Pubkey,
+        name: &str,
+        commitment: CommitmentConfig,
+    ) -> Result<Vec<u8>, ContractError> {
+        self.fetch_optional_account_data(address, commitment)?
+            .ok_or_else(|| ContractError::InvalidAccount(format!("{name} account {address} not found")))
+    }
+
+    /// Read the raw data of an account at `commitment`, `None` if it does not exist
+    fn fetch_optional_account_data(
+        &self,
+        address: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<Option<Vec<u8>>, ContractError> {
+        Ok(self
+            .program
+            .rpc()
+            .get_account_with_commitment(address, commitment)
+            .map_err(|e| ContractError::NetworkError(e.to_string()))?
+            .value
+            .map(|account| account.data))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
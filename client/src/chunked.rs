@@ -0,0 +1,143 @@
+//! # Chunked Batches
+//!
+//! This module splits a large set of swaps into several batch swaps of at
+//! most [`MAX_BATCH_SIZE`] legs each and reports the outcome per chunk.
+//!
+//! ## Outcomes
+//!
+//! [`BatchSwapRouterClient::batch_swap_chunked`] submits every chunk, even
+//! after one fails, and returns one [`ChunkOutcome`] per chunk in order. Each
+//! outcome keeps the swaps it carried, so a failed chunk can be retried as
+//! is, or all failed swaps can be collected with [`failed_swaps`] and
+//! resubmitted together.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::chunked::failed_swaps;
+//!
+//! let outcomes = swap_client.batch_swap_chunked(swaps, MAX_BATCH_SIZE)?;
+//! for outcome in outcomes.iter().filter(|outcome| outcome.result.is_err()) {
+//!     eprintln!("chunk {} failed", outcome.chunk_index);
+//! }
+//! let retry = swap_client.batch_swap_chunked(failed_swaps(&outcomes), MAX_BATCH_SIZE)?;
+//! ```
+//!
+//! [`BatchSwapRouterClient::batch_swap_chunked`]: crate::BatchSwapRouterClient::batch_swap_chunked
+
+use solana_sdk::signature::Signature;
+
+use crate::error::ContractError;
+use crate::types::{SwapParams, MAX_BATCH_SIZE};
+
+/// The result of submitting one chunk of a chunked batch swap
+#[derive(Debug)]
+pub struct ChunkOutcome {
+    /// Zero-based position of the chunk
+    pub chunk_index: usize,
+
+    /// The swaps submitted in this chunk, in order
+    pub swaps: Vec<SwapParams>,
+
+    /// The chunk's transaction signature or error
+    pub result: Result<Signature, ContractError>,
+}
+
+/// Split swaps into chunks of at most `chunk_size` swaps
+///
+/// # Arguments
+///
+/// * `swaps` - The swaps to split, in submission order
+/// * `chunk_size` - Maximum swaps per chunk (1 to [`MAX_BATCH_SIZE`])
+///
+/// # Returns
+///
+/// * `Ok(Vec<Vec<SwapParams>>)` - The chunks, in order
+/// * `Err(ContractError)` - If `chunk_size` is out of range
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if `chunk_size` is 0 or exceeds
+/// [`MAX_BATCH_SIZE`].
+pub fn chunk_swaps(swaps: Vec<SwapParams>, chunk_size: usize) -> Result<Vec<Vec<SwapParams>>, ContractError> {
+    if chunk_size == 0 || chunk_size > MAX_BATCH_SIZE {
+        return Err(ContractError::InvalidAccount(format!(
+            "Chunk size {chunk_size} must be between 1 and {MAX_BATCH_SIZE}"
+        )));
+    }
+
+    let mut chunks = Vec::with_capacity(swaps.len().div_ceil(chunk_size));
+    let mut swaps = swaps.into_iter().peekable();
+    while swaps.peek().is_some() {
+        chunks.push(swaps.by_ref().take(chunk_size).collect());
+    }
+    Ok(chunks)
+}
+
+/// Collect the swaps of every failed chunk for resubmission
+///
+/// # Arguments
+///
+/// * `outcomes` - The outcomes of a chunked batch swap
+///
+/// # Returns
+///
+/// The swaps of the failed chunks, in their original order
+#[must_use]
+pub fn failed_swaps(outcomes: &[ChunkOutcome]) -> Vec<SwapParams> {
+    outcomes
+        .iter()
+        .filter(|outcome| outcome.result.is_err())
+        .flat_map(|outcome| outcome.swaps.iter().cloned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn swaps(count: u64) -> Vec<SwapParams> {
+        (1..=count)
+            .map(|amount| SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), amount, 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_swaps_keeps_order() {
+        let chunks = chunk_swaps(swaps(23), MAX_BATCH_SIZE).unwrap();
+
+        let sizes: Vec<_> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![10, 10, 3]);
+        assert_eq!(chunks[2][0].amount, 21);
+        assert!(chunk_swaps(Vec::new(), 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_chunk_swaps_rejects_invalid_chunk_size() {
+        assert!(chunk_swaps(swaps(3), 0).is_err());
+        assert!(chunk_swaps(swaps(3), MAX_BATCH_SIZE + 1).is_err());
+    }
+
+    #[test]
+    fn test_failed_swaps_collects_only_failed_chunks() {
+        let chunks = chunk_swaps(swaps(5), 2).unwrap();
+        let outcomes: Vec<_> = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_index, swaps)| ChunkOutcome {
+                chunk_index,
+                swaps,
+                result: if chunk_index == 1 {
+                    Err(ContractError::TransactionFailed("blockhash expired".to_string()))
+                } else {
+                    Ok(Signature::default())
+                },
+            })
+            .collect();
+
+        let retry = failed_swaps(&outcomes);
+        let amounts: Vec<_> = retry.iter().map(|swap| swap.amount).collect();
+        assert_eq!(amounts, vec![3, 4]);
+    }
+}
@@ -0,0 +1,133 @@
+//! # Cluster Verification
+//!
+//! This module detects a client pointed at the wrong cluster for its program
+//! ID. The router is deployed at a different address per cluster, so using
+//! the devnet program ID against a mainnet RPC (or the reverse) makes every
+//! call fail with an opaque "account not found" or "program does not exist".
+//!
+//! ## Check
+//!
+//! Each cluster is identified by its genesis hash (`getGenesisHash`).
+//! [`KNOWN_DEPLOYMENTS`] maps the known program IDs to the cluster they are
+//! deployed on; [`check_cluster`] compares the RPC's genesis hash against it.
+//! Program IDs not in the table (local validators, forks, custom deployments)
+//! are not checked.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let swap_client = BatchSwapRouterClient::new(program);
+//! swap_client.verify_cluster()?; // ClientError on a cluster mismatch
+//! ```
+
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+
+/// Genesis hash of mainnet-beta
+pub const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dwqp2w";
+
+/// Genesis hash of devnet
+pub const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+/// Genesis hash of testnet
+pub const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+
+/// Known deployments of the router: `(program ID, cluster name, genesis hash)`
+pub const KNOWN_DEPLOYMENTS: &[(&str, &str, &str)] = &[(
+    "C48gmshkEL8UdCe8GcpZKGwrEfCLbWWq4zk23tHmNDcE",
+    "devnet",
+    DEVNET_GENESIS_HASH,
+)];
+
+/// Name of the public cluster with `genesis_hash`
+///
+/// # Returns
+///
+/// `"mainnet-beta"`, `"devnet"` or `"testnet"`, or `None` for any other
+/// cluster (e.g. a local validator)
+#[must_use]
+pub fn cluster_name(genesis_hash: &Hash) -> Option<&'static str> {
+    let genesis_hash = genesis_hash.to_string();
+    [
+        ("mainnet-beta", MAINNET_GENESIS_HASH),
+        ("devnet", DEVNET_GENESIS_HASH),
+        ("testnet", TESTNET_GENESIS_HASH),
+    ]
+    .into_iter()
+    .find(|(_, hash)| *hash == genesis_hash)
+    .map(|(name, _)| name)
+}
+
+/// Check that `program_id` is deployed on the cluster with `genesis_hash`
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID the client uses
+/// * `genesis_hash` - The genesis hash reported by the RPC node
+///
+/// # Returns
+///
+/// * `Ok(())` - The cluster matches, or the program ID is not a known deployment
+/// * `Err(ContractError)` - If the program ID belongs to another cluster
+///
+/// # Errors
+///
+/// Returns `ContractError::ClientError` naming the expected and actual
+/// clusters on a mismatch.
+pub fn check_cluster(program_id: &Pubkey, genesis_hash: &Hash) -> Result<(), ContractError> {
+    let program_id = program_id.to_string();
+    let Some((_, expected_cluster, expected_hash)) = KNOWN_DEPLOYMENTS
+        .iter()
+        .find(|(id, _, _)| *id == program_id)
+    else {
+        return Ok(());
+    };
+
+    if genesis_hash.to_string() == *expected_hash {
+        return Ok(());
+    }
+
+    Err(ContractError::ClientError(format!(
+        "Program {program_id} is deployed on {expected_cluster}, but the RPC node is on {} (genesis hash {genesis_hash})",
+        cluster_name(genesis_hash).unwrap_or("an unknown cluster"),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVNET_PROGRAM_ID: &str = "C48gmshkEL8UdCe8GcpZKGwrEfCLbWWq4zk23tHmNDcE";
+
+    fn hash(value: &str) -> Hash {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_known_program_on_its_cluster_passes() {
+        let program_id: Pubkey = DEVNET_PROGRAM_ID.parse().unwrap();
+        assert!(check_cluster(&program_id, &hash(DEVNET_GENESIS_HASH)).is_ok());
+    }
+
+    #[test]
+    fn test_known_program_on_another_cluster_fails() {
+        let program_id: Pubkey = DEVNET_PROGRAM_ID.parse().unwrap();
+
+        let Err(ContractError::ClientError(message)) = check_cluster(&program_id, &hash(MAINNET_GENESIS_HASH)) else {
+            panic!("expected a cluster mismatch");
+        };
+        assert!(message.contains("devnet"));
+        assert!(message.contains("mainnet-beta"));
+
+        // A local validator has its own genesis hash
+        assert!(check_cluster(&program_id, &Hash::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_unknown_program_is_not_checked() {
+        assert!(check_cluster(&Pubkey::new_unique(), &hash(MAINNET_GENESIS_HASH)).is_ok());
+        assert_eq!(cluster_name(&Hash::new_unique()), None);
+    }
+}
@@ -0,0 +1,340 @@
+//! # Program Config
+//!
+//! This module contains a typed mirror of the program's `Config` account and
+//! TTL caches for the live protocol fee rate and pause state. The config PDA
+//! helper lives in [`crate::pda`] and is re-exported here.
+//!
+//! ## Live Fee Rate
+//!
+//! The admin can change `Config::fee_bps` at any time, so front-ends should
+//! display the rate read from the chain rather than [`PROTOCOL_FEE_BPS`].
+//! [`BatchSwapRouterClient::fetch_protocol_fee_bps`] reads it at most once per
+//! [`FEE_BPS_TTL`] and falls back to [`PROTOCOL_FEE_BPS`] while the config is
+//! not initialized (the rate the program charges once it is).
+//!
+//! ## Pause State
+//!
+//! While the admin has set `Config::paused`, every swap instruction fails
+//! on-chain with `ProgramPaused`. [`BatchSwapRouterClient::is_paused`] reads
+//! the flag at most once per [`PAUSED_TTL`], shorter than the fee rate's so
+//! a pause (or its end) is noticed quickly, and reports `false` while the
+//! config is not initialized. The client's swap methods check it before
+//! sending and fail with [`ContractError::ProgramPaused`] instead of wasting
+//! a transaction.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let fee_bps = swap_client.fetch_protocol_fee_bps()?; // RPC read
+//! let fee_bps = swap_client.fetch_protocol_fee_bps()?; // cached for FEE_BPS_TTL
+//! println!("Protocol fee: {:.2}%", f64::from(fee_bps) / 100.0);
+//!
+//! if swap_client.is_paused()? {
+//!     println!("Trading is paused");
+//! }
+//! ```
+//!
+//! [`BatchSwapRouterClient::fetch_protocol_fee_bps`]: crate::BatchSwapRouterClient::fetch_protocol_fee_bps
+//! [`BatchSwapRouterClient::is_paused`]: crate::BatchSwapRouterClient::is_paused
+
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::borsh;
+use anchor_lang::AnchorDeserialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+use crate::stats::decode_account;
+use crate::types::PROTOCOL_FEE_BPS;
+
+pub use crate::pda::{config_pda, CONFIG_SEED};
+
+/// How long a fetched fee rate is served from the cache
+pub const FEE_BPS_TTL: Duration = Duration::from_secs(30);
+
+/// How long a fetched pause state is served from the cache
+pub const PAUSED_TTL: Duration = Duration::from_secs(5);
+
+/// Anchor account discriminator of `Config` (`sha256("account:Config")[..8]`)
+const CONFIG_DISCRIMINATOR: [u8; 8] = [155, 12, 170, 224, 30, 250, 204, 130];
+
+/// Program configuration
+///
+/// Mirrors the program's `Config` account on the current layout
+/// (`CONFIG_VERSION` 1). Accounts not yet migrated do not decode.
+#[derive(AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)] // mirrors the program's policy flags
+pub struct Config {
+    /// Admin allowed to update the config
+    pub admin: Pubkey,
+
+    /// Authorities exempt from protocol fees
+    pub fee_exemptions: Vec<Pubkey>,
+
+    /// Reject transactions signed by anyone other than the swap authority
+    pub require_authority_is_payer: bool,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Layout version of this account
+    pub version: u8,
+
+    /// Protocol fee rate in basis points
+    pub fee_bps: u16,
+
+    /// Owner of the accounts protocol fees are paid to (default: any owner)
+    pub fee_recipient: Pubkey,
+
+    /// Admin proposed by `propose_admin`, awaiting `accept_admin`
+    pub pending_admin: Pubkey,
+
+    /// Programs allowed as `batch_swap` per-leg callbacks
+    pub callback_programs: Vec<Pubkey>,
+
+    /// Share of each protocol fee burned, in basis points of the fee
+    pub burn_bps: u16,
+
+    /// Part of `fee_bps` paid to liquidity providers, in basis points
+    pub lp_fee_bps: u16,
+
+    /// Owner of the accounts LP fees are paid to (default: any owner)
+    pub lp_fee_recipient: Pubkey,
+
+    /// Reject `execute_swap` without an expected output (quote)
+    pub require_expected_output: bool,
+
+    /// Most distinct mints one batch may touch (0 = no cap)
+    pub max_distinct_mints: u8,
+
+    /// Expected realized fee rate, in basis points of volume (0 = off)
+    pub expected_fee_rate_bps: u16,
+
+    /// Largest price move of a pair within the jump window before it is
+    /// flagged, in basis points (0 = off)
+    pub max_price_jump_bps: u16,
+
+    /// Reject `execute_swap` token accounts that are not associated token accounts
+    pub strict_ata: bool,
+
+    /// Fees go to token accounts (false = a SOL wallet; fees must be paid in lamports)
+    pub treasury_is_token_account: bool,
+
+    /// Smallest `min_output_amount` a swap may ask for (0 = the program's `MIN_OUTPUT_AMOUNT`)
+    pub min_output_floor: u64,
+
+    /// Every swap instruction is rejected until the admin unpauses
+    pub paused: bool,
+
+    /// Mint loyalty discount volume is counted in (`Pubkey::default()` = none)
+    pub loyalty_mint: Pubkey,
+}
+
+/// Decode raw `Config` account data
+///
+/// # Arguments
+///
+/// * `data` - The account data, including the 8-byte discriminator
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the discriminator does not
+/// match, or `ContractError::SerializationError` if the data cannot be decoded.
+pub fn decode_config(data: &[u8]) -> Result<Config, ContractError> {
+    decode_account(data, CONFIG_DISCRIMINATOR, "Config")
+}
+
+/// Protocol fee rate of raw `Config` account data
+///
+/// # Arguments
+///
+/// * `data` - The config account data, or `None` if the account does not exist
+///
+/// # Returns
+///
+/// * `Ok(u16)` - `Config::fee_bps`, or [`PROTOCOL_FEE_BPS`] without a config
+/// * `Err(ContractError)` - If the account is not a `Config`
+///
+/// # Errors
+///
+/// Same as [`decode_config`].
+pub fn protocol_fee_bps(data: Option<&[u8]>) -> Result<u16, ContractError> {
+    data.map_or(Ok(PROTOCOL_FEE_BPS), |data| Ok(decode_config(data)?.fee_bps))
+}
+
+/// Pause state of raw `Config` account data
+///
+/// # Arguments
+///
+/// * `data` - The config account data, or `None` if the account does not exist
+///
+/// # Returns
+///
+/// * `Ok(bool)` - `Config::paused`, or `false` without a config
+/// * `Err(ContractError)` - If the account is not a `Config`
+///
+/// # Errors
+///
+/// Same as [`decode_config`].
+pub fn program_paused(data: Option<&[u8]>) -> Result<bool, ContractError> {
+    data.map_or(Ok(false), |data| Ok(decode_config(data)?.paused))
+}
+
+/// Cache of one config value that expires after a fixed TTL
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut cache = FeeBpsCache::new(FEE_BPS_TTL);
+/// let fee_bps = cache.get_or_fetch(Instant::now(), || fetch_fee_bps())?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct TtlCache<T> {
+    /// How long a fetched value stays fresh
+    ttl: Duration,
+
+    /// The last fetched value and when it was fetched
+    entry: Option<(Instant, T)>,
+}
+
+/// Cache of the live protocol fee rate
+pub type FeeBpsCache = TtlCache<u16>;
+
+/// Cache of the program's pause state
+pub type PausedCache = TtlCache<bool>;
+
+impl<T: Copy> TtlCache<T> {
+    /// Create an empty cache
+    ///
+    /// # Arguments
+    ///
+    /// * `ttl` - How long a fetched value is served before fetching again
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entry: None }
+    }
+
+    /// The cached value, fetching it if missing or older than the TTL
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current instant
+    /// * `fetch` - Reads the live value; only called when the cache is stale
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(T)` - The cached or freshly fetched value
+    /// * `Err(ContractError)` - If `fetch` fails
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `fetch`. Failures are not cached.
+    pub fn get_or_fetch<F>(&mut self, now: Instant, fetch: F) -> Result<T, ContractError>
+    where
+        F: FnOnce() -> Result<T, ContractError>,
+    {
+        if let Some((fetched_at, value)) = self.entry {
+            if now.saturating_duration_since(fetched_at) < self.ttl {
+                return Ok(value);
+            }
+        }
+
+        let value = fetch()?;
+        self.entry = Some((now, value));
+        Ok(value)
+    }
+}
+
+impl Default for FeeBpsCache {
+    /// An empty cache with [`FEE_BPS_TTL`]
+    fn default() -> Self {
+        Self::new(FEE_BPS_TTL)
+    }
+}
+
+impl Default for PausedCache {
+    /// An empty cache with [`PAUSED_TTL`]
+    fn default() -> Self {
+        Self::new(PAUSED_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_bytes(fee_bps: u16) -> Vec<u8> {
+        let mut data = CONFIG_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&[1, 254, 1]);
+        data.extend_from_slice(&fee_bps.to_le_bytes());
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&2_500u16.to_le_bytes());
+        data.extend_from_slice(&10u16.to_le_bytes());
+        data.extend_from_slice(&[0u8; 32]);
+        data.push(1);
+        data.push(8);
+        data.extend_from_slice(&30u16.to_le_bytes());
+        data.extend_from_slice(&500u16.to_le_bytes());
+        data.push(1);
+        data.push(0);
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&[7u8; 32]);
+        // Unused space of the maximum-length vectors
+        data.resize(data.len() + 512, 0);
+        data
+    }
+
+    #[test]
+    fn test_decode_config() {
+        let config = decode_config(&config_bytes(20)).unwrap();
+
+        assert!(config.require_authority_is_payer);
+        assert_eq!((config.bump, config.version), (254, 1));
+        assert_eq!(config.fee_bps, 20);
+        assert_eq!(config.burn_bps, 2_500);
+        assert_eq!(config.lp_fee_bps, 10);
+        assert!(config.require_expected_output);
+        assert_eq!(config.max_distinct_mints, 8);
+        assert_eq!(config.expected_fee_rate_bps, 30);
+        assert_eq!(config.max_price_jump_bps, 500);
+        assert!(config.strict_ata);
+        assert!(!config.treasury_is_token_account);
+        assert_eq!(config.min_output_floor, 1_000);
+        assert!(config.paused);
+        assert_eq!(config.loyalty_mint, Pubkey::new_from_array([7u8; 32]));
+    }
+
+    #[test]
+    fn test_protocol_fee_bps_falls_back_without_config() {
+        assert_eq!(protocol_fee_bps(Some(&config_bytes(20))).unwrap(), 20);
+        assert_eq!(protocol_fee_bps(None).unwrap(), PROTOCOL_FEE_BPS);
+        assert!(protocol_fee_bps(Some(&[0u8; 16])).is_err());
+    }
+
+    #[test]
+    fn test_program_paused_is_false_without_config() {
+        assert!(program_paused(Some(&config_bytes(20))).unwrap());
+        assert!(!program_paused(None).unwrap());
+        assert!(program_paused(Some(&[0u8; 16])).is_err());
+    }
+
+    #[test]
+    fn test_cache_refetches_after_ttl() {
+        let mut cache = FeeBpsCache::new(Duration::from_secs(30));
+        let start = Instant::now();
+        let mut fetches = 0;
+        let mut fetch = |fee_bps| {
+            fetches += 1;
+            Ok(fee_bps)
+        };
+
+        assert_eq!(cache.get_or_fetch(start, || fetch(30)).unwrap(), 30);
+        assert_eq!(cache.get_or_fetch(start + Duration::from_secs(29), || fetch(20)).unwrap(), 30);
+        assert_eq!(cache.get_or_fetch(start + Duration::from_secs(30), || fetch(20)).unwrap(), 20);
+        assert_eq!(fetches, 2);
+    }
+}
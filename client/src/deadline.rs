@@ -0,0 +1,180 @@
+//! # Deadline-Aware Blockhashes
+//!
+//! This module bounds how long a swap transaction can stay valid. A
+//! transaction is accepted until its blockhash expires, roughly 150 blocks
+//! (about a minute) after the blockhash was produced, so a swap with a
+//! shorter deadline could otherwise land after it, at a price the user no
+//! longer wanted.
+//!
+//! ## Validity Window
+//!
+//! [`validity_window`] caps the blockhash's `last_valid_block_height` at the
+//! block height the deadline corresponds to:
+//!
+//! ```text
+//! last_valid_block_height = min(blockhash expiry, block_height + (deadline - now) / slot time)
+//! ```
+//!
+//! Send and resend only while [`ValidityWindow::is_expired`] is false; past
+//! that height the transaction must be rebuilt (or abandoned) rather than
+//! retried. With several legs, the earliest deadline wins
+//! ([`earliest_deadline`]).
+//!
+//! ## Commitment
+//!
+//! The blockhash is fetched at `confirmed`: a `processed` blockhash can belong
+//! to a fork that is dropped (the transaction then never lands), and a
+//! `finalized` one is already ~32 slots old, wasting part of the window.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let deadline = earliest_deadline(legs.iter().map(|leg| leg.deadline));
+//! let window = swap_client.deadline_blockhash(deadline)?; // DeadlineTooShort if < 2s away
+//! // Build the transaction with `window.blockhash`; stop resending once
+//! // `window.is_expired(current_block_height)`
+//! ```
+
+use solana_sdk::hash::Hash;
+
+use crate::error::ContractError;
+
+/// Average slot duration, in milliseconds
+pub const SLOT_DURATION_MS: i64 = 400;
+
+/// Time a transaction typically needs to reach `confirmed`, in seconds
+///
+/// Deadlines closer than this are rejected with
+/// `ContractError::DeadlineTooShort`.
+pub const MIN_CONFIRMATION_SECS: i64 = 2;
+
+/// The blockhash to send with and the last block height to send at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidityWindow {
+    /// Recent blockhash for the transaction
+    pub blockhash: Hash,
+
+    /// Last block height at which the transaction may land: the blockhash's
+    /// expiry, or the deadline if it comes first
+    pub last_valid_block_height: u64,
+}
+
+impl ValidityWindow {
+    /// Whether the window has closed at `block_height`
+    ///
+    /// Once closed, the transaction must not be resent.
+    #[must_use]
+    pub fn is_expired(&self, block_height: u64) -> bool {
+        block_height > self.last_valid_block_height
+    }
+}
+
+/// The earliest deadline across a batch's legs
+///
+/// # Arguments
+///
+/// * `deadlines` - Each leg's deadline as a Unix timestamp, `None` for no deadline
+///
+/// # Returns
+///
+/// The earliest deadline, or `None` if no leg has one
+#[must_use]
+pub fn earliest_deadline<I>(deadlines: I) -> Option<i64>
+where
+    I: IntoIterator<Item = Option<i64>>,
+{
+    deadlines.into_iter().flatten().min()
+}
+
+/// Compute a transaction's validity window from its blockhash and deadline
+///
+/// # Arguments
+///
+/// * `blockhash` - The latest blockhash
+/// * `blockhash_last_valid_block_height` - Last block height the blockhash is valid for
+/// * `block_height` - The current block height
+/// * `now` - The current Unix timestamp
+/// * `deadline` - The swap deadline as a Unix timestamp (`None` = blockhash expiry only)
+///
+/// # Returns
+///
+/// * `Ok(ValidityWindow)` - The blockhash with its expiry capped at the deadline
+/// * `Err(ContractError)` - If the deadline is too close to meet
+///
+/// # Errors
+///
+/// Returns `ContractError::DeadlineTooShort` if fewer than
+/// [`MIN_CONFIRMATION_SECS`] remain until the deadline.
+pub fn validity_window(
+    blockhash: Hash,
+    blockhash_last_valid_block_height: u64,
+    block_height: u64,
+    now: i64,
+    deadline: Option<i64>,
+) -> Result<ValidityWindow, ContractError> {
+    let Some(deadline) = deadline else {
+        return Ok(ValidityWindow {
+            blockhash,
+            last_valid_block_height: blockhash_last_valid_block_height,
+        });
+    };
+
+    let remaining_secs = deadline.saturating_sub(now);
+    if remaining_secs < MIN_CONFIRMATION_SECS {
+        return Err(ContractError::DeadlineTooShort {
+            remaining_secs,
+            minimum_secs: MIN_CONFIRMATION_SECS,
+        });
+    }
+
+    // Whole blocks that fit before the deadline, rounded down
+    let blocks_until_deadline = u64::try_from(remaining_secs.saturating_mul(1000) / SLOT_DURATION_MS)
+        .unwrap_or(u64::MAX);
+
+    Ok(ValidityWindow {
+        blockhash,
+        last_valid_block_height: blockhash_last_valid_block_height
+            .min(block_height.saturating_add(blocks_until_deadline)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn test_short_deadline_caps_the_blockhash_expiry() {
+        // 10s = 25 blocks, well before the blockhash's 150 blocks
+        let window = validity_window(Hash::default(), 1_150, 1_000, NOW, Some(NOW + 10)).unwrap();
+
+        assert_eq!(window.last_valid_block_height, 1_025);
+        assert!(!window.is_expired(1_025));
+        assert!(window.is_expired(1_026));
+    }
+
+    #[test]
+    fn test_distant_or_missing_deadline_keeps_the_blockhash_expiry() {
+        let window = validity_window(Hash::default(), 1_150, 1_000, NOW, Some(NOW + 3_600)).unwrap();
+        assert_eq!(window.last_valid_block_height, 1_150);
+
+        let window = validity_window(Hash::default(), 1_150, 1_000, NOW, None).unwrap();
+        assert_eq!(window.last_valid_block_height, 1_150);
+    }
+
+    #[test]
+    fn test_deadline_shorter_than_confirmation_is_rejected() {
+        assert!(matches!(
+            validity_window(Hash::default(), 1_150, 1_000, NOW, Some(NOW + 1)),
+            Err(ContractError::DeadlineTooShort { remaining_secs: 1, minimum_secs: MIN_CONFIRMATION_SECS })
+        ));
+        assert!(validity_window(Hash::default(), 1_150, 1_000, NOW, Some(NOW - 5)).is_err());
+    }
+
+    #[test]
+    fn test_earliest_deadline() {
+        assert_eq!(earliest_deadline([Some(NOW + 60), None, Some(NOW + 30)]), Some(NOW + 30));
+        assert_eq!(earliest_deadline([None, None]), None);
+    }
+}
@@ -0,0 +1,195 @@
+//! # Durable Nonces
+//!
+//! This module builds batch transactions on a durable nonce instead of a
+//! recent blockhash, so relayers can sign them offline and send them later.
+//! A recent blockhash expires after about a minute; a nonce stays valid
+//! until a transaction advances it.
+//!
+//! ## Transaction Layout
+//!
+//! A durable transaction uses the nonce account's stored nonce as its
+//! blockhash, and its first instruction must advance that nonce (signed by
+//! the nonce authority). Advancing makes every transaction built on the same
+//! nonce single-use: once one lands, the others can no longer be processed,
+//! so a pre-signed batch can be resent safely until it lands.
+//!
+//! ## Offline Signing
+//!
+//! 1. Fetch the nonce ([`BatchSwapRouterClient::fetch_durable_nonce`]) while online
+//! 2. Build the unsigned transaction ([`DurableNonce::unsigned_transaction`])
+//! 3. Sign offline with the payer and the nonce authority, using the nonce
+//!    as the blockhash
+//! 4. Send whenever convenient; fetch a new nonce for the next transaction
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let client = BatchSwapRouterClient::new(program)
+//!     .with_durable_nonce(nonce_account, nonce_authority.pubkey());
+//!
+//! let nonce = client.fetch_durable_nonce()?;
+//! let mut transaction = client.durable_transaction(instructions)?;
+//! transaction.sign(&[&payer, &nonce_authority], nonce);
+//! ```
+//!
+//! [`BatchSwapRouterClient::fetch_durable_nonce`]: crate::BatchSwapRouterClient::fetch_durable_nonce
+
+use anchor_lang::solana_program::system_instruction;
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+
+use crate::error::ContractError;
+
+/// Size of a nonce account, in bytes
+pub const NONCE_ACCOUNT_SIZE: usize = 80;
+
+/// Nonce account state tag of an initialized nonce (`State::Initialized`)
+const STATE_INITIALIZED: u32 = 1;
+
+/// Nonce account version tag of the current layout (`Versions::Current`)
+const VERSION_CURRENT: u32 = 1;
+
+/// A nonce account and its authority, used in place of a recent blockhash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurableNonce {
+    /// The nonce account holding the durable nonce
+    pub nonce_account: Pubkey,
+
+    /// The account allowed to advance the nonce (must sign the transaction)
+    pub nonce_authority: Pubkey,
+}
+
+impl DurableNonce {
+    /// Prepend the advance-nonce instruction to `instructions`
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - The transaction's instructions (route, router, memo, ...)
+    ///
+    /// # Returns
+    ///
+    /// The advance-nonce instruction followed by `instructions`
+    #[must_use]
+    pub fn instructions(&self, instructions: Vec<Instruction>) -> Vec<Instruction> {
+        let mut with_nonce = Vec::with_capacity(instructions.len() + 1);
+        with_nonce.push(system_instruction::advance_nonce_account(
+            &self.nonce_account,
+            &self.nonce_authority,
+        ));
+        with_nonce.extend(instructions);
+        with_nonce
+    }
+
+    /// Build the unsigned durable transaction for offline signing
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - The transaction's instructions, without the
+    ///   advance-nonce instruction
+    /// * `payer` - The fee payer
+    /// * `nonce` - The nonce account's current nonce (the transaction's blockhash)
+    ///
+    /// # Returns
+    ///
+    /// A transaction that must be signed by `payer` and the nonce authority
+    #[must_use]
+    pub fn unsigned_transaction(&self, instructions: Vec<Instruction>, payer: &Pubkey, nonce: Hash) -> Transaction {
+        let message = Message::new_with_blockhash(&self.instructions(instructions), Some(payer), &nonce);
+        Transaction::new_unsigned(message)
+    }
+}
+
+/// Read the authority and current nonce of raw nonce account data
+///
+/// # Arguments
+///
+/// * `data` - The nonce account data
+///
+/// # Returns
+///
+/// * `Ok((Pubkey, Hash))` - The nonce authority and the durable nonce
+/// * `Err(ContractError)` - If the account is not an initialized nonce account
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the data is not a nonce
+/// account, the nonce is not initialized, or it still uses the legacy layout
+/// (advance it once to upgrade).
+pub fn parse_nonce_account(data: &[u8]) -> Result<(Pubkey, Hash), ContractError> {
+    if data.len() != NONCE_ACCOUNT_SIZE {
+        return Err(ContractError::InvalidAccount(format!(
+            "Nonce account data is {} bytes, expected {NONCE_ACCOUNT_SIZE}",
+            data.len()
+        )));
+    }
+
+    // Layout: version (u32), state (u32), authority, durable nonce, fee calculator
+    let tag = |offset: usize| u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+    if tag(0) != VERSION_CURRENT || tag(4) != STATE_INITIALIZED {
+        return Err(ContractError::InvalidAccount(
+            "Nonce account is not an initialized nonce on the current layout".to_string(),
+        ));
+    }
+
+    let authority = Pubkey::try_from(&data[8..40])
+        .map_err(|e| ContractError::SerializationError(e.to_string()))?;
+    let nonce = Hash::new_from_array(
+        data[40..72]
+            .try_into()
+            .map_err(|_| ContractError::SerializationError("Invalid nonce hash".to_string()))?,
+    );
+    Ok((authority, nonce))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nonce_account_data(version: u32, state: u32, authority: &Pubkey, nonce: &Hash) -> Vec<u8> {
+        let mut data = version.to_le_bytes().to_vec();
+        data.extend_from_slice(&state.to_le_bytes());
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(nonce.as_ref());
+        data.extend_from_slice(&5_000u64.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_parse_nonce_account() {
+        let (authority, nonce) = (Pubkey::new_unique(), Hash::new_unique());
+
+        let data = nonce_account_data(VERSION_CURRENT, STATE_INITIALIZED, &authority, &nonce);
+        assert_eq!(parse_nonce_account(&data).unwrap(), (authority, nonce));
+
+        // Uninitialized, legacy and truncated accounts are rejected
+        assert!(parse_nonce_account(&nonce_account_data(VERSION_CURRENT, 0, &authority, &nonce)).is_err());
+        assert!(parse_nonce_account(&nonce_account_data(0, STATE_INITIALIZED, &authority, &nonce)).is_err());
+        assert!(parse_nonce_account(&data[..40]).is_err());
+    }
+
+    #[test]
+    fn test_transaction_advances_the_nonce_first() {
+        let durable = DurableNonce {
+            nonce_account: Pubkey::new_unique(),
+            nonce_authority: Pubkey::new_unique(),
+        };
+        let (payer, nonce) = (Pubkey::new_unique(), Hash::new_unique());
+        let router = Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![]);
+
+        let instructions = durable.instructions(vec![router.clone()]);
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].accounts[0].pubkey, durable.nonce_account);
+        assert_eq!(instructions[1], router);
+
+        let transaction = durable.unsigned_transaction(vec![router], &payer, nonce);
+        assert_eq!(transaction.message.recent_blockhash, nonce);
+        assert_eq!(transaction.message.account_keys[0], payer);
+        // The payer and the nonce authority both sign
+        assert_eq!(transaction.message.header.num_required_signatures, 2);
+    }
+}
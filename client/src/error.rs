@@ -20,6 +20,11 @@ use thiserror::Error;
 /// - `TransactionFailed` - Transaction execution failed
 /// - `NetworkError` - Network-related errors
 /// - `SerializationError` - Serialization/deserialization errors
+/// - `TransactionTooLarge` - Transaction exceeds the packet size limit
+/// - `DeadlineTooShort` - Swap deadline is shorter than a typical confirmation
+/// - `TooManyMints` - Batch touches more distinct mints than the threshold
+/// - `TooManyAccounts` - Legacy transaction references too many accounts
+/// - `ProgramPaused` - The program admin has paused swaps
 ///
 /// # Example
 ///
@@ -110,6 +115,99 @@ pub enum ContractError {
     /// - Deserialization failure
     #[error("Serialization error: {0}")]
     SerializationError(String),
+
+    /// Transaction too large error
+    ///
+    /// This error occurs when a constructed transaction serializes to more
+    /// bytes than fit in a single packet, which the RPC node would reject.
+    ///
+    /// # Examples
+    ///
+    /// - Batch with many legs and long Jupiter routes
+    /// - Legacy transaction that would fit as a v0 transaction with a lookup table
+    #[error("Transaction too large: {size} bytes exceeds the {limit}-byte limit; split the batch into smaller batches")]
+    TransactionTooLarge {
+        /// Measured size of the serialized transaction, in bytes
+        size: usize,
+
+        /// Maximum transaction size, in bytes
+        limit: usize,
+    },
+
+    /// Deadline too short error
+    ///
+    /// This error occurs when a swap deadline leaves less time than a
+    /// transaction typically needs to confirm, so it would almost certainly
+    /// expire before landing.
+    ///
+    /// # Examples
+    ///
+    /// - Deadline already in the past
+    /// - Deadline a second away on a congested cluster
+    #[error("Deadline too short: {remaining_secs}s left, at least {minimum_secs}s needed to confirm")]
+    DeadlineTooShort {
+        /// Seconds left until the deadline (negative if it has passed)
+        remaining_secs: i64,
+
+        /// Minimum seconds a transaction typically needs to confirm
+        minimum_secs: i64,
+    },
+
+    /// Too many mints error
+    ///
+    /// This error occurs when a batch touches more distinct mints than the
+    /// preflight threshold, so its token and route accounts would likely
+    /// exceed the transaction's limits (or the program's
+    /// `max_distinct_mints` cap).
+    ///
+    /// # Examples
+    ///
+    /// - Ten legs, each between two mints no other leg uses
+    #[error("Too many mints: batch touches {mints} distinct mints, maximum is {limit}; split the batch into smaller batches")]
+    TooManyMints {
+        /// Distinct mints the batch touches
+        mints: usize,
+
+        /// Maximum distinct mints per batch
+        limit: usize,
+    },
+
+    /// Too many accounts error
+    ///
+    /// This error occurs when a legacy transaction references more unique
+    /// accounts, or more writable accounts, than a legacy transaction can
+    /// practically carry. A v0 transaction with an Address Lookup Table
+    /// stores the keys as 1-byte indexes instead.
+    ///
+    /// # Examples
+    ///
+    /// - Batch whose legs' routes add up to 40 writable pool accounts
+    #[error("Too many accounts: legacy transaction references {accounts} accounts ({writable} writable), limits are {max_accounts} ({max_writable} writable); use a v0 transaction with an Address Lookup Table")]
+    TooManyAccounts {
+        /// Unique accounts the transaction references
+        accounts: usize,
+
+        /// Writable accounts among them
+        writable: usize,
+
+        /// Maximum unique accounts of a legacy transaction
+        max_accounts: usize,
+
+        /// Practical maximum of writable accounts of a legacy transaction
+        max_writable: usize,
+    },
+
+    /// Program paused error
+    ///
+    /// This error occurs when the preflight of a swap finds the program
+    /// paused (`Config::paused`). The program would reject the swap with
+    /// `ProgramPaused`, so no transaction is sent.
+    ///
+    /// # Examples
+    ///
+    /// - Swap attempted while the admin has halted trading during an incident
+    #[error("Program paused: swaps are disabled until the admin unpauses the program")]
+    ProgramPaused,
 }
 
 impl ContractError {
@@ -157,6 +255,19 @@ impl ContractError {
             Self::TransactionFailed(msg) => format!("Transaction failed: {}", msg),
             Self::NetworkError(msg) => format!("Network error: {}", msg),
             Self::SerializationError(msg) => format!("Serialization error: {}", msg),
+            Self::TransactionTooLarge { size, limit } => format!(
+                "Transaction is {size} bytes (limit {limit}); split the batch into smaller batches"
+            ),
+            Self::DeadlineTooShort { remaining_secs, minimum_secs } => format!(
+                "Deadline is {remaining_secs}s away but confirmation takes about {minimum_secs}s; extend the deadline"
+            ),
+            Self::TooManyMints { mints, limit } => format!(
+                "Batch touches {mints} tokens (limit {limit}); split it into batches over fewer tokens"
+            ),
+            Self::TooManyAccounts { accounts, writable, max_accounts, max_writable } => format!(
+                "Transaction uses {accounts} accounts, {writable} writable (limits {max_accounts} and {max_writable}); send it as a v0 transaction with an Address Lookup Table"
+            ),
+            Self::ProgramPaused => "Trading is paused; try again later".to_string(),
         }
     }
 }
@@ -203,4 +314,18 @@ mod tests {
         let display = format!("{}", error);
         assert_eq!(display, "Client error: test");
     }
+
+    #[test]
+    fn test_transaction_too_large_message() {
+        let error = ContractError::TransactionTooLarge { size: 1300, limit: 1232 };
+        assert!(error.to_string().contains("1300 bytes"));
+        assert!(error.user_message().contains("split the batch"));
+    }
+
+    #[test]
+    fn test_too_many_accounts_message() {
+        let error = ContractError::TooManyAccounts { accounts: 70, writable: 30, max_accounts: 64, max_writable: 35 };
+        assert!(error.to_string().contains("70 accounts"));
+        assert!(error.user_message().contains("Address Lookup Table"));
+    }
 }
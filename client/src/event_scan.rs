@@ -0,0 +1,271 @@
+//! # Historical Event Scans
+//!
+//! This module contains the helpers behind
+//! [`BatchSwapRouterClient::scan_events`], which backfills the program's
+//! events from a range of slots without a third-party indexer: it pages
+//! `getBlocks` over the range, fetches each block with `getBlock`, keeps the
+//! successful transactions that invoked the program and decodes their
+//! `Program data:` log lines.
+//!
+//! ## Pagination
+//!
+//! `getBlocks` accepts at most [`MAX_BLOCKS_RANGE`] slots per request, so the
+//! range is split into pages of that size (see [`slot_pages`]). Skipped slots
+//! are not returned by `getBlocks` and are never fetched.
+//!
+//! ## Rate Limits
+//!
+//! Public RPC nodes throttle block requests. Every RPC request of a scan is
+//! retried up to [`SCAN_MAX_RETRIES`] times, waiting [`backoff_delay`]
+//! (exponential from [`SCAN_INITIAL_BACKOFF_MS`]) between attempts, before
+//! the scan fails with `ContractError::NetworkError`.
+//!
+//! ## Schema Versions
+//!
+//! Events logged by older program versions carry another `schema_version`
+//! and do not decode with the current mirrors; they are skipped, so a scan
+//! across an upgrade returns the events of the current layout only.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::event_scan::ProgramEvent;
+//!
+//! for parsed in swap_client.scan_events(280_000_000, 280_010_000)? {
+//!     if let ProgramEvent::SwapExecuted(event) = parsed.event {
+//!         println!("{} @ {}: {} out", parsed.signature, parsed.slot, event.net_output_amount);
+//!     }
+//! }
+//! ```
+//!
+//! [`BatchSwapRouterClient::scan_events`]: crate::BatchSwapRouterClient::scan_events
+
+use std::thread;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use crate::error::ContractError;
+use crate::events::{
+    BatchSwapEventData, SwapExecutedEventData, BATCH_SWAP_EVENT_DISCRIMINATOR, EVENT_SCHEMA_VERSION,
+    PROGRAM_DATA_LOG_PREFIX, SWAP_EXECUTED_EVENT_DISCRIMINATOR,
+};
+
+/// Most slots a single `getBlocks` request may span
+pub const MAX_BLOCKS_RANGE: u64 = 500_000;
+
+/// Retries of a throttled or failed RPC request before the scan fails
+pub const SCAN_MAX_RETRIES: u32 = 5;
+
+/// Wait before the first retry, in milliseconds; doubles on every retry
+pub const SCAN_INITIAL_BACKOFF_MS: u64 = 500;
+
+/// A program event decoded from a transaction's logs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgramEvent {
+    /// A `BatchSwapEvent`
+    BatchSwap(BatchSwapEventData),
+
+    /// A `SwapExecutedEvent`
+    SwapExecuted(SwapExecutedEventData),
+}
+
+/// A program event with the transaction it was logged in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedEvent {
+    /// Slot of the block holding the transaction
+    pub slot: u64,
+
+    /// Signature of the transaction
+    pub signature: Signature,
+
+    /// Estimated production time of the block, if the node reports one
+    pub block_time: Option<i64>,
+
+    /// The decoded event
+    pub event: ProgramEvent,
+}
+
+/// Split an inclusive slot range into `getBlocks` pages
+///
+/// # Arguments
+///
+/// * `from_slot` - First slot of the range
+/// * `to_slot` - Last slot of the range (inclusive)
+///
+/// # Returns
+///
+/// Inclusive `(start, end)` pages of at most [`MAX_BLOCKS_RANGE`] slots, in
+/// slot order; empty if `from_slot > to_slot`
+#[must_use]
+pub fn slot_pages(from_slot: u64, to_slot: u64) -> Vec<(u64, u64)> {
+    let mut pages = Vec::new();
+    let mut start = from_slot;
+    while start <= to_slot {
+        let end = start.saturating_add(MAX_BLOCKS_RANGE - 1).min(to_slot);
+        pages.push((start, end));
+        match end.checked_add(1) {
+            Some(next) => start = next,
+            None => break,
+        }
+    }
+    pages
+}
+
+/// Wait before retry number `attempt` (0 for the first retry)
+#[must_use]
+pub fn backoff_delay(attempt: u32) -> Duration {
+    Duration::from_millis(SCAN_INITIAL_BACKOFF_MS.saturating_mul(1 << attempt.min(16)))
+}
+
+/// Run an RPC request, retrying failures with exponential backoff
+///
+/// # Arguments
+///
+/// * `what` - Description of the request, for the error message
+/// * `request` - The request; called up to `SCAN_MAX_RETRIES + 1` times
+///
+/// # Errors
+///
+/// Returns `ContractError::NetworkError` with the last failure once the
+/// retries are used up.
+pub fn with_backoff<T, E: std::fmt::Display>(
+    what: &str,
+    mut request: impl FnMut() -> Result<T, E>,
+) -> Result<T, ContractError> {
+    let mut attempt = 0;
+    loop {
+        match request() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= SCAN_MAX_RETRIES => {
+                return Err(ContractError::NetworkError(format!(
+                    "{what} failed after {} attempts: {e}",
+                    attempt + 1
+                )));
+            }
+            Err(_) => {
+                thread::sleep(backoff_delay(attempt));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Whether a transaction's logs show an invocation of `program_id`
+///
+/// Matches top-level instructions and CPIs alike.
+#[must_use]
+pub fn invokes_program(logs: &[String], program_id: &Pubkey) -> bool {
+    let invoke = format!("Program {program_id} invoke");
+    logs.iter().any(|line| line.starts_with(&invoke))
+}
+
+/// Decode every program event in a transaction's logs, in log order
+///
+/// Lines that are not valid base64, belong to other events (including other
+/// programs' events) or carry another `schema_version` are skipped.
+///
+/// # Arguments
+///
+/// * `logs` - The transaction's log messages (`meta.logMessages`)
+///
+/// # Errors
+///
+/// Returns `ContractError::SerializationError` if an event of the current
+/// schema version does not decode.
+pub fn events_from_logs(logs: &[String]) -> Result<Vec<ProgramEvent>, ContractError> {
+    let mut events = Vec::new();
+    for data in logs
+        .iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA_LOG_PREFIX))
+        .filter_map(|encoded| STANDARD.decode(encoded).ok())
+    {
+        // The schema version is the first byte after the discriminator
+        if data.get(8) != Some(&EVENT_SCHEMA_VERSION) {
+            continue;
+        }
+        if data.starts_with(&BATCH_SWAP_EVENT_DISCRIMINATOR) {
+            events.push(ProgramEvent::BatchSwap(BatchSwapEventData::try_from(data.as_slice())?));
+        } else if data.starts_with(&SWAP_EXECUTED_EVENT_DISCRIMINATOR) {
+            events.push(ProgramEvent::SwapExecuted(SwapExecutedEventData::try_from(data.as_slice())?));
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorSerialize;
+
+    #[test]
+    fn test_slot_pages_cover_range() {
+        assert_eq!(slot_pages(10, 20), vec![(10, 20)]);
+        assert_eq!(slot_pages(7, 7), vec![(7, 7)]);
+        assert!(slot_pages(8, 7).is_empty());
+
+        let pages = slot_pages(0, 2 * MAX_BLOCKS_RANGE);
+        assert_eq!(
+            pages,
+            vec![
+                (0, MAX_BLOCKS_RANGE - 1),
+                (MAX_BLOCKS_RANGE, 2 * MAX_BLOCKS_RANGE - 1),
+                (2 * MAX_BLOCKS_RANGE, 2 * MAX_BLOCKS_RANGE),
+            ]
+        );
+        // The last slot of the chain ends the paging
+        assert_eq!(slot_pages(u64::MAX, u64::MAX), vec![(u64::MAX, u64::MAX)]);
+    }
+
+    #[test]
+    fn test_backoff_doubles_until_success() {
+        assert_eq!(backoff_delay(0), Duration::from_millis(SCAN_INITIAL_BACKOFF_MS));
+        assert_eq!(backoff_delay(2), 4 * backoff_delay(0));
+
+        let mut failures = 1;
+        let value = with_backoff("getBlock", || {
+            if failures > 0 {
+                failures -= 1;
+                Err("429 Too Many Requests")
+            } else {
+                Ok(42)
+            }
+        });
+        assert_eq!(value.unwrap(), 42);
+    }
+
+    #[test]
+    fn test_events_decoded_from_program_logs() {
+        let program_id = Pubkey::new_unique();
+        let event = BatchSwapEventData {
+            schema_version: EVENT_SCHEMA_VERSION,
+            authority: Pubkey::new_unique(),
+            swap_count: 2,
+            total_input_amount: 1_000,
+            total_protocol_fees: 3,
+            base_mint: Pubkey::new_unique(),
+            total_output_amount: 900,
+            vwap_bps: 9_000,
+            weighted_max_slippage_bps: 500,
+            memo_hash: [0u8; 32],
+            priority_fee_microlamports: 0,
+            timestamp: 1_700_000_000,
+        };
+        let mut data = BATCH_SWAP_EVENT_DISCRIMINATOR.to_vec();
+        data.extend(event.try_to_vec().unwrap());
+        let mut old_schema = data.clone();
+        old_schema[8] = EVENT_SCHEMA_VERSION - 1;
+
+        let logs = vec![
+            format!("Program {program_id} invoke [1]"),
+            format!("{PROGRAM_DATA_LOG_PREFIX}{}", STANDARD.encode(&old_schema)),
+            format!("{PROGRAM_DATA_LOG_PREFIX}{}", STANDARD.encode(&data)),
+            format!("Program {program_id} success"),
+        ];
+        assert!(invokes_program(&logs, &program_id));
+        assert!(!invokes_program(&logs, &Pubkey::new_unique()));
+        assert_eq!(events_from_logs(&logs).unwrap(), vec![ProgramEvent::BatchSwap(event)]);
+    }
+}
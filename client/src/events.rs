@@ -0,0 +1,327 @@
+//! # Event Mirrors
+//!
+//! This module contains plain mirrors of the program's `#[event]` structs so
+//! indexers can decode, clone, and re-serialize events without depending on
+//! the program crate.
+//!
+//! ## Layout
+//!
+//! Field order and types are identical to the program events, so the borsh
+//! encoding round-trips. Event data as logged by `emit!` (base64-decoded from
+//! a `Program data:` log line) is an 8-byte discriminator
+//! (`sha256("event:<Name>")[..8]`) followed by the borsh-encoded fields.
+//!
+//! `BatchSwapEventData` and `SwapExecutedEventData` start with
+//! `schema_version`, the byte right after the discriminator. Decoders that
+//! must handle events from several program versions check it against
+//! [`EVENT_SCHEMA_VERSION`] before decoding the rest.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::events::BatchSwapEventData;
+//!
+//! let event = BatchSwapEventData::try_from(data.as_slice())?;
+//! println!("{} swaps by {}", event.swap_count, event.authority);
+//! let json = serde_json::to_string(&event)?;
+//! ```
+//!
+//! ## Transaction Logs
+//!
+//! [`swap_executed_event_from_logs`] finds the `SwapExecutedEvent` among the
+//! log messages of a confirmed transaction (`meta.logMessages`), which is how
+//! `BatchSwapRouterClient::execute_swap_and_get_event` reads the event of the
+//! swap it just sent.
+
+use anchor_lang::prelude::borsh;
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+
+/// Anchor event discriminator of `BatchSwapEvent` (`sha256("event:BatchSwapEvent")[..8]`)
+pub const BATCH_SWAP_EVENT_DISCRIMINATOR: [u8; 8] = [36, 59, 5, 195, 253, 228, 170, 11];
+
+/// Anchor event discriminator of `SwapExecutedEvent` (`sha256("event:SwapExecutedEvent")[..8]`)
+pub const SWAP_EXECUTED_EVENT_DISCRIMINATOR: [u8; 8] = [183, 28, 219, 210, 164, 184, 62, 12];
+
+/// Mirror of the program's `EVENT_SCHEMA_VERSION`, the layout these mirrors decode
+pub const EVENT_SCHEMA_VERSION: u8 = 5;
+
+/// Mirror of the program's `FeeDenomination`, the asset a swap's fee was paid in
+#[derive(AnchorSerialize, AnchorDeserialize, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeeDenomination {
+    /// Fee paid in input tokens
+    #[default]
+    Token,
+
+    /// Fee paid in lamports from the authority's wallet (WSOL input only)
+    Lamports,
+}
+
+/// Mirror of the program's `BatchSwapEvent`
+#[derive(AnchorSerialize, AnchorDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BatchSwapEventData {
+    /// Layout version of the event (see [`EVENT_SCHEMA_VERSION`])
+    pub schema_version: u8,
+
+    /// The authority who executed the batch swap
+    pub authority: Pubkey,
+
+    /// The number of swaps executed in the batch
+    pub swap_count: u8,
+
+    /// Total input amount across all swaps
+    pub total_input_amount: u64,
+
+    /// Total protocol fees collected
+    pub total_protocol_fees: u64,
+
+    /// Settlement mint used for `total_output_amount`
+    pub base_mint: Pubkey,
+
+    /// Sum of minimum outputs over legs paying out in `base_mint`
+    pub total_output_amount: u64,
+
+    /// Volume-weighted average price across all legs, in basis points
+    pub vwap_bps: u64,
+
+    /// Per-leg slippage tolerance averaged by input amount, in basis points
+    pub weighted_max_slippage_bps: u64,
+
+    /// SHA-256 hash of the attached memo (all zeroes if none)
+    pub memo_hash: [u8; 32],
+
+    /// Compute unit price paid by the transaction, in micro-lamports (0 if none)
+    pub priority_fee_microlamports: u64,
+
+    /// Unix timestamp of execution
+    pub timestamp: i64,
+}
+
+/// Mirror of the program's `SwapExecutedEvent`
+#[derive(AnchorSerialize, AnchorDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SwapExecutedEventData {
+    /// Layout version of the event (see [`EVENT_SCHEMA_VERSION`])
+    pub schema_version: u8,
+
+    /// The authority who executed the swap
+    pub authority: Pubkey,
+
+    /// Owner of the account that received the output (the authority unless
+    /// an explicit recipient was given)
+    pub recipient: Pubkey,
+
+    /// Amount of input tokens swapped
+    pub input_amount: u64,
+
+    /// Amount of output tokens received
+    pub output_amount: u64,
+
+    /// Output the recipient keeps after all fees
+    pub net_output_amount: u64,
+
+    /// Input token mint
+    pub input_mint: Pubkey,
+
+    /// Output token mint
+    pub output_mint: Pubkey,
+
+    /// Protocol fee charged
+    pub protocol_fee: u64,
+
+    /// Part of `protocol_fee` transferred to the fee recipient
+    pub treasury_fee: u64,
+
+    /// Part of `protocol_fee` burned
+    pub burned_fee: u64,
+
+    /// Part of `protocol_fee` paid to liquidity providers
+    pub lp_fee: u64,
+
+    /// Asset `protocol_fee` was paid in
+    pub fee_denomination: FeeDenomination,
+
+    /// What the fee recipient's balance actually grew by (0 if no fee moved)
+    pub fee_collected: u64,
+
+    /// Loyalty discount taken off the protocol fee rate, in basis points
+    /// (0 unless the authority passed its `UserStats`)
+    pub fee_discount_bps: u16,
+
+    /// Slippage in basis points, measured on the output side
+    pub slippage_bps: u64,
+
+    /// SHA-256 hash of the attached memo (all zeroes if none)
+    pub memo_hash: [u8; 32],
+
+    /// Caller-defined label of the swap (0 if untagged)
+    pub tag: u64,
+
+    /// Whether the swap was simulated (no tokens moved; skip when indexing)
+    pub simulated: bool,
+
+    /// Whether the swap was a leg of a batch (false for `execute_swap`)
+    pub batch: bool,
+
+    /// Zero-based position of the leg in its batch (0 unless `batch`)
+    pub leg_index: u8,
+
+    /// Unix timestamp of execution
+    pub timestamp: i64,
+}
+
+impl TryFrom<&[u8]> for BatchSwapEventData {
+    type Error = ContractError;
+
+    /// Decode event data, including the 8-byte discriminator
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        decode_event(data, BATCH_SWAP_EVENT_DISCRIMINATOR, "BatchSwapEvent")
+    }
+}
+
+impl TryFrom<&[u8]> for SwapExecutedEventData {
+    type Error = ContractError;
+
+    /// Decode event data, including the 8-byte discriminator
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        decode_event(data, SWAP_EXECUTED_EVENT_DISCRIMINATOR, "SwapExecutedEvent")
+    }
+}
+
+/// Prefix of the log line `emit!` writes event data to
+pub const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+
+/// Find the `SwapExecutedEvent` in a transaction's log messages
+///
+/// Scans the `Program data:` lines in order and decodes the first one that
+/// carries the `SwapExecutedEvent` discriminator. Lines that are not valid
+/// base64 or belong to other events (including other programs' events) are
+/// skipped.
+///
+/// # Arguments
+///
+/// * `logs` - The transaction's log messages (`meta.logMessages`)
+///
+/// # Returns
+///
+/// * `Ok(Some(SwapExecutedEventData))` - The first swap event logged
+/// * `Ok(None)` - No swap event was logged (`emit_events = false`, or the
+///   logs were truncated before it)
+/// * `Err(ContractError)` - If a line has the discriminator but the fields
+///   cannot be decoded
+///
+/// # Errors
+///
+/// Returns `ContractError::SerializationError` if the event fields do not
+/// decode (the program and client event layouts differ).
+pub fn swap_executed_event_from_logs(logs: &[String]) -> Result<Option<SwapExecutedEventData>, ContractError> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA_LOG_PREFIX))
+        .filter_map(|encoded| STANDARD.decode(encoded).ok())
+        .find(|data| data.starts_with(&SWAP_EXECUTED_EVENT_DISCRIMINATOR))
+        .map(|data| SwapExecutedEventData::try_from(data.as_slice()))
+        .transpose()
+}
+
+/// Check the discriminator and borsh-decode the event fields
+fn decode_event<T: AnchorDeserialize>(
+    data: &[u8],
+    discriminator: [u8; 8],
+    name: &str,
+) -> Result<T, ContractError> {
+    let body = data
+        .strip_prefix(discriminator.as_slice())
+        .ok_or_else(|| ContractError::SerializationError(format!("Data is not a {name}")))?;
+
+    T::try_from_slice(body).map_err(|e| ContractError::SerializationError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch_event() -> BatchSwapEventData {
+        BatchSwapEventData {
+            schema_version: EVENT_SCHEMA_VERSION,
+            authority: Pubkey::new_unique(),
+            swap_count: 2,
+            total_input_amount: 1_000,
+            total_protocol_fees: 3,
+            base_mint: Pubkey::new_unique(),
+            total_output_amount: 900,
+            vwap_bps: 9_000,
+            weighted_max_slippage_bps: 500,
+            memo_hash: [7u8; 32],
+            priority_fee_microlamports: 25_000,
+            timestamp: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_batch_event_round_trips() {
+        let event = batch_event();
+        let mut data = BATCH_SWAP_EVENT_DISCRIMINATOR.to_vec();
+        data.extend(event.try_to_vec().unwrap());
+
+        // The schema version is the first byte after the discriminator
+        assert_eq!(data[8], EVENT_SCHEMA_VERSION);
+        assert_eq!(BatchSwapEventData::try_from(data.as_slice()).unwrap(), event);
+    }
+
+    #[test]
+    fn test_decode_rejects_other_event() {
+        let mut data = BATCH_SWAP_EVENT_DISCRIMINATOR.to_vec();
+        data.extend(batch_event().try_to_vec().unwrap());
+
+        assert!(matches!(
+            SwapExecutedEventData::try_from(data.as_slice()),
+            Err(ContractError::SerializationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_swap_event_found_in_logs() {
+        let event = SwapExecutedEventData {
+            schema_version: EVENT_SCHEMA_VERSION,
+            authority: Pubkey::new_unique(),
+            recipient: Pubkey::new_unique(),
+            input_amount: 1_000,
+            output_amount: 990,
+            net_output_amount: 990,
+            input_mint: Pubkey::new_unique(),
+            output_mint: Pubkey::new_unique(),
+            protocol_fee: 3,
+            treasury_fee: 3,
+            burned_fee: 0,
+            lp_fee: 0,
+            fee_denomination: FeeDenomination::Lamports,
+            fee_collected: 3,
+            fee_discount_bps: 0,
+            slippage_bps: 10,
+            memo_hash: [0u8; 32],
+            tag: 0,
+            simulated: false,
+            batch: false,
+            leg_index: 0,
+            timestamp: 1_700_000_000,
+        };
+        let mut data = SWAP_EXECUTED_EVENT_DISCRIMINATOR.to_vec();
+        data.extend(event.try_to_vec().unwrap());
+        let mut other = BATCH_SWAP_EVENT_DISCRIMINATOR.to_vec();
+        other.extend(batch_event().try_to_vec().unwrap());
+
+        let logs = vec![
+            "Program log: Instruction: ExecuteSwap".to_string(),
+            format!("{PROGRAM_DATA_LOG_PREFIX}{}", STANDARD.encode(&other)),
+            format!("{PROGRAM_DATA_LOG_PREFIX}not base64!"),
+            format!("{PROGRAM_DATA_LOG_PREFIX}{}", STANDARD.encode(&data)),
+        ];
+        assert_eq!(swap_executed_event_from_logs(&logs).unwrap(), Some(event));
+        // Events disabled: nothing to find
+        assert_eq!(swap_executed_event_from_logs(&logs[..3]).unwrap(), None);
+    }
+}
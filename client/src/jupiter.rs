@@ -0,0 +1,756 @@
+//! # Jupiter Quotes
+//!
+//! This module fetches swap quotes from the Jupiter quote API and turns them
+//! into fully-populated [`SwapParams`], so callers never have to pick a
+//! `min_output_amount` by hand.
+//!
+//! ## Minimum Output
+//!
+//! The minimum output is derived from the quoted output and a slippage
+//! tolerance, rounded down:
+//!
+//! ```text
+//! min_output_amount = out_amount * (10_000 - slippage_bps) / 10_000
+//! ```
+//!
+//! ## Minimum Output from a Price
+//!
+//! Without a quote, [`min_output_for_price`] derives the minimum from a
+//! price in whole tokens (as shown in UIs), scaled by [`PRICE_SCALE`]. Raw
+//! amounts are converted between the two mints' decimals, so a 9-decimal
+//! input and a 6-decimal output cannot be off by a factor of 1,000:
+//!
+//! ```text
+//! out_amount = amount * price * 10^output_decimals / (PRICE_SCALE * 10^input_decimals)
+//! ```
+//!
+//! The slippage tolerance is then applied as above.
+//!
+//! ## Failure Behavior
+//!
+//! If the quote endpoint is unreachable or returns an error, the methods in
+//! this module return `ContractError::NetworkError`. There is deliberately no
+//! fallback (such as assuming a 1:1 price): a guessed minimum is either too
+//! tight (the swap fails) or too loose (the swap can be sandwiched).
+//!
+//! ## Quote Cache
+//!
+//! UIs re-quote on every keystroke, and the quote API is slow and rate
+//! limited. [`QuoteClient::with_cache`] keeps quotes in a [`QuoteCache`] for
+//! a short TTL ([`QUOTE_CACHE_TTL`] by default), keyed by the pair and an
+//! amount bucket: amounts that agree in their top [`AMOUNT_BUCKET_BITS`]
+//! bits (within about 0.2%) share an entry, and a cached quote is scaled
+//! linearly to the requested amount (rounding the output down). The cache
+//! holds at most [`QuoteCacheConfig::max_entries`] quotes, evicting expired
+//! then oldest entries first.
+//!
+//! [`QuoteClient::quote`] and [`QuoteClient::swap_params_from_quote`] go
+//! through the cache; [`QuoteClient::fetch_quote`] always hits the API, and
+//! [`QuoteClient::refresh_quote`] drops the pair's cached quotes before
+//! fetching. Failures are never cached.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::jupiter::{QuoteCacheConfig, QuoteClient};
+//!
+//! let quotes = QuoteClient::new().with_cache(QuoteCacheConfig::default());
+//! // 1 SOL -> USDC with 0.5% slippage tolerance
+//! let swap = quotes.swap_params_from_quote(sol_mint, usdc_mint, 1_000_000_000, 50)?;
+//! // Served from the cache within QUOTE_CACHE_TTL
+//! let swap = quotes.swap_params_from_quote(sol_mint, usdc_mint, 1_000_100_000, 50)?;
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+use crate::security::assert_valid_slippage;
+use crate::types::SwapParams;
+
+/// Default Jupiter v6 quote endpoint
+pub const JUPITER_QUOTE_API_URL: &str = "https://quote-api.jup.ag/v6/quote";
+
+/// Basis points denominator (10,000 bps = 100%)
+const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Fixed-point scale of prices passed to [`min_output_for_price`]
+/// (`PRICE_SCALE` = a price of 1 output token per input token)
+pub const PRICE_SCALE: u64 = 1_000_000_000;
+
+/// How long a cached quote is served by default
+pub const QUOTE_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Default maximum number of cached quotes
+pub const DEFAULT_QUOTE_CACHE_ENTRIES: usize = 256;
+
+/// Significant bits of the amount kept in a quote cache key
+///
+/// Amounts are bucketed by their top bits, so a bucket spans at most
+/// `1 / 2^(AMOUNT_BUCKET_BITS - 1)` (about 0.2%) of the amount.
+pub const AMOUNT_BUCKET_BITS: u32 = 10;
+
+/// A swap quote returned by the Jupiter quote API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JupiterQuote {
+    /// Input token mint
+    pub input_mint: Pubkey,
+
+    /// Output token mint
+    pub output_mint: Pubkey,
+
+    /// Quoted input amount, in input token units
+    pub in_amount: u64,
+
+    /// Quoted output amount, in output token units
+    pub out_amount: u64,
+}
+
+/// Raw quote response (Jupiter encodes amounts as strings)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteResponse {
+    /// Quoted input amount
+    in_amount: String,
+
+    /// Quoted output amount
+    out_amount: String,
+}
+
+/// Settings of a [`QuoteCache`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteCacheConfig {
+    /// How long a fetched quote is served before fetching again
+    pub ttl: Duration,
+
+    /// Most quotes kept at once
+    pub max_entries: usize,
+}
+
+impl Default for QuoteCacheConfig {
+    /// [`QUOTE_CACHE_TTL`] and [`DEFAULT_QUOTE_CACHE_ENTRIES`]
+    fn default() -> Self {
+        Self { ttl: QUOTE_CACHE_TTL, max_entries: DEFAULT_QUOTE_CACHE_ENTRIES }
+    }
+}
+
+/// Cache key: the pair and the amount bucket (see [`amount_bucket`])
+type QuoteCacheKey = (Pubkey, Pubkey, (u32, u64));
+
+/// Quote cache that expires entries after a fixed TTL
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut cache = QuoteCache::new(QuoteCacheConfig::default());
+/// let quote = cache.get_or_fetch(Instant::now(), &sol_mint, &usdc_mint, amount, || fetch(amount))?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuoteCache {
+    /// TTL and size limit
+    config: QuoteCacheConfig,
+
+    /// Cached quotes and when they were fetched
+    entries: HashMap<QuoteCacheKey, (Instant, JupiterQuote)>,
+}
+
+impl QuoteCache {
+    /// Create an empty cache
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - TTL and size limit of the cache
+    #[must_use]
+    pub fn new(config: QuoteCacheConfig) -> Self {
+        Self { config, entries: HashMap::new() }
+    }
+
+    /// The cache's TTL and size limit
+    #[must_use]
+    pub fn config(&self) -> QuoteCacheConfig {
+        self.config
+    }
+
+    /// Number of cached quotes, including expired ones not yet evicted
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache holds no quotes
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// A fresh cached quote for the amount's bucket, scaled to `amount`
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current instant
+    /// * `input_mint` - Input token mint
+    /// * `output_mint` - Output token mint
+    /// * `amount` - Input amount, in input token units
+    ///
+    /// # Returns
+    ///
+    /// The quote (with `in_amount == amount`), or `None` if there is no
+    /// quote younger than the TTL
+    #[must_use]
+    pub fn get(&self, now: Instant, input_mint: &Pubkey, output_mint: &Pubkey, amount: u64) -> Option<JupiterQuote> {
+        let (fetched_at, quote) = self.entries.get(&(*input_mint, *output_mint, amount_bucket(amount)))?;
+        if now.saturating_duration_since(*fetched_at) >= self.config.ttl {
+            return None;
+        }
+        scale_quote(quote, amount)
+    }
+
+    /// Cache a fetched quote
+    ///
+    /// Makes room first when the cache is full: expired quotes are dropped,
+    /// then the oldest. A cache with `max_entries == 0` stores nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - When the quote was fetched
+    /// * `quote` - The quote
+    pub fn insert(&mut self, now: Instant, quote: JupiterQuote) {
+        if self.config.max_entries == 0 {
+            return;
+        }
+
+        let key = (quote.input_mint, quote.output_mint, amount_bucket(quote.in_amount));
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.config.max_entries {
+            let ttl = self.config.ttl;
+            self.entries.retain(|_, (fetched_at, _)| now.saturating_duration_since(*fetched_at) < ttl);
+            if self.entries.len() >= self.config.max_entries {
+                let oldest = self.entries.iter().min_by_key(|(_, (fetched_at, _))| *fetched_at).map(|(key, _)| *key);
+                if let Some(oldest) = oldest {
+                    self.entries.remove(&oldest);
+                }
+            }
+        }
+        self.entries.insert(key, (now, quote));
+    }
+
+    /// Drop every cached quote of a pair (all amount buckets)
+    ///
+    /// # Arguments
+    ///
+    /// * `input_mint` - Input token mint
+    /// * `output_mint` - Output token mint
+    pub fn invalidate(&mut self, input_mint: &Pubkey, output_mint: &Pubkey) {
+        self.entries.retain(|(input, output, _), _| input != input_mint || output != output_mint);
+    }
+
+    /// Drop every cached quote
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The cached quote for `amount`, fetching it if missing or stale
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current instant
+    /// * `input_mint` - Input token mint
+    /// * `output_mint` - Output token mint
+    /// * `amount` - Input amount, in input token units
+    /// * `fetch` - Fetches a quote for `amount`; only called on a miss
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(JupiterQuote)` - The quote
+    /// * `Err(ContractError)` - If `fetch` fails
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `fetch`. Failures are not cached.
+    pub fn get_or_fetch<F>(
+        &mut self,
+        now: Instant,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+        fetch: F,
+    ) -> Result<JupiterQuote, ContractError>
+    where
+        F: FnOnce() -> Result<JupiterQuote, ContractError>,
+    {
+        if let Some(quote) = self.get(now, input_mint, output_mint, amount) {
+            return Ok(quote);
+        }
+
+        let quote = fetch()?;
+        self.insert(now, quote.clone());
+        Ok(quote)
+    }
+}
+
+impl Default for QuoteCache {
+    /// An empty cache with [`QuoteCacheConfig::default`]
+    fn default() -> Self {
+        Self::new(QuoteCacheConfig::default())
+    }
+}
+
+/// Client for the Jupiter quote API
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let quotes = QuoteClient::with_base_url("http://localhost:8080/quote");
+/// let quote = quotes.fetch_quote(&sol_mint, &usdc_mint, 1_000_000_000)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuoteClient {
+    /// HTTP client used for quote requests
+    http: reqwest::blocking::Client,
+
+    /// Quote endpoint URL
+    base_url: String,
+
+    /// Quote cache, shared by clones (`None` = every quote is fetched)
+    cache: Option<Arc<Mutex<QuoteCache>>>,
+}
+
+impl Default for QuoteClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuoteClient {
+    /// Create a quote client for the public Jupiter v6 API
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_base_url(JUPITER_QUOTE_API_URL)
+    }
+
+    /// Create a quote client for a custom quote endpoint
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - URL of a Jupiter-compatible `/quote` endpoint
+    #[must_use]
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            http: reqwest::blocking::Client::new(),
+            base_url: base_url.to_string(),
+            cache: None,
+        }
+    }
+
+    /// Serve quotes from a cache (see "Quote Cache" in the module docs)
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - TTL and size limit of the cache
+    #[must_use]
+    pub fn with_cache(mut self, config: QuoteCacheConfig) -> Self {
+        self.cache = Some(Arc::new(Mutex::new(QuoteCache::new(config))));
+        self
+    }
+
+    /// Number of cached quotes (0 without a cache)
+    ///
+    /// # Errors
+    ///
+    /// Returns `ContractError::ClientError` if the cache lock is poisoned.
+    pub fn cached_quotes(&self) -> Result<usize, ContractError> {
+        self.cache.as_ref().map_or(Ok(0), |cache| Ok(lock_cache(cache)?.len()))
+    }
+
+    /// Drop every cached quote
+    ///
+    /// # Errors
+    ///
+    /// Returns `ContractError::ClientError` if the cache lock is poisoned.
+    pub fn clear_quote_cache(&self) -> Result<(), ContractError> {
+        if let Some(cache) = &self.cache {
+            lock_cache(cache)?.clear();
+        }
+        Ok(())
+    }
+
+    /// Quote `amount` of `input_mint` into `output_mint`, from the cache if fresh
+    ///
+    /// Without a cache this is [`Self::fetch_quote`].
+    ///
+    /// # Arguments
+    ///
+    /// * `input_mint` - Input token mint
+    /// * `output_mint` - Output token mint
+    /// * `amount` - Input amount, in input token units
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(JupiterQuote)` - The quote
+    /// * `Err(ContractError)` - If the quote cannot be fetched or decoded
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::fetch_quote`], plus `ContractError::ClientError` if
+    /// the cache lock is poisoned.
+    pub fn quote(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<JupiterQuote, ContractError> {
+        let Some(cache) = &self.cache else {
+            return self.fetch_quote(input_mint, output_mint, amount);
+        };
+
+        if let Some(quote) = lock_cache(cache)?.get(Instant::now(), input_mint, output_mint, amount) {
+            return Ok(quote);
+        }
+
+        // The lock is not held during the request
+        let quote = self.fetch_quote(input_mint, output_mint, amount)?;
+        lock_cache(cache)?.insert(Instant::now(), quote.clone());
+        Ok(quote)
+    }
+
+    /// Fetch a fresh quote, replacing the pair's cached quotes
+    ///
+    /// Use it when the user explicitly asks for a new quote: every cached
+    /// quote of the pair (all amounts) is dropped first.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_mint` - Input token mint
+    /// * `output_mint` - Output token mint
+    /// * `amount` - Input amount, in input token units
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(JupiterQuote)` - The fresh quote
+    /// * `Err(ContractError)` - If the quote cannot be fetched or decoded
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::quote`].
+    pub fn refresh_quote(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<JupiterQuote, ContractError> {
+        if let Some(cache) = &self.cache {
+            lock_cache(cache)?.invalidate(input_mint, output_mint);
+        }
+        self.quote(input_mint, output_mint, amount)
+    }
+
+    /// Fetch a quote for swapping `amount` of `input_mint` into `output_mint`
+    ///
+    /// # Arguments
+    ///
+    /// * `input_mint` - Input token mint
+    /// * `output_mint` - Output token mint
+    /// * `amount` - Input amount, in input token units
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(JupiterQuote)` - The quote
+    /// * `Err(ContractError)` - If the quote cannot be fetched or decoded
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::NetworkError` - The endpoint is unreachable or
+    ///   returned an error status
+    /// - `ContractError::SerializationError` - The response cannot be decoded
+    pub fn fetch_quote(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<JupiterQuote, ContractError> {
+        let response: QuoteResponse = self
+            .http
+            .get(&self.base_url)
+            .query(&[
+                ("inputMint", input_mint.to_string()),
+                ("outputMint", output_mint.to_string()),
+                ("amount", amount.to_string()),
+            ])
+            .send()
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .map_err(|e| ContractError::NetworkError(format!("Jupiter quote request failed: {e}")))?
+            .json()
+            .map_err(|e| ContractError::SerializationError(e.to_string()))?;
+
+        Ok(JupiterQuote {
+            input_mint: *input_mint,
+            output_mint: *output_mint,
+            in_amount: parse_amount(&response.in_amount)?,
+            out_amount: parse_amount(&response.out_amount)?,
+        })
+    }
+
+    /// Build swap parameters with a minimum output derived from a live quote
+    ///
+    /// The quote comes from [`Self::quote`], so it may be cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_mint` - Input token mint
+    /// * `output_mint` - Output token mint
+    /// * `amount` - Input amount, in input token units
+    /// * `slippage_bps` - Slippage tolerance applied to the quoted output
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SwapParams)` - Fully-populated swap parameters
+    /// * `Err(ContractError)` - If the quote fails or the result is unusable
+    ///
+    /// # Errors
+    ///
+    /// - `ContractError::NetworkError` - The quote endpoint is unavailable
+    ///   (no 1:1 fallback is used)
+    /// - `ContractError::InvalidAccount` - `slippage_bps` exceeds 100%, or the
+    ///   quote leaves a zero minimum output
+    pub fn swap_params_from_quote(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount: u64,
+        slippage_bps: u64,
+    ) -> Result<SwapParams, ContractError> {
+        assert_valid_slippage(slippage_bps, BPS_DENOMINATOR)?;
+
+        let quote = self.quote(&input_mint, &output_mint, amount)?;
+        let min_output_amount = min_output_from_quote(quote.out_amount, slippage_bps)?;
+
+        Ok(SwapParams::new(input_mint, output_mint, amount, min_output_amount))
+    }
+}
+
+/// Compute the minimum output for a quoted output and slippage tolerance
+///
+/// Rounds down, so the minimum never exceeds what the tolerance allows.
+///
+/// # Arguments
+///
+/// * `out_amount` - Quoted output amount
+/// * `slippage_bps` - Slippage tolerance in basis points (max 10,000)
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The minimum output amount (always > 0)
+/// * `Err(ContractError)` - If the tolerance is invalid or the minimum is zero
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if `slippage_bps` exceeds 10,000
+/// or the resulting minimum is zero (the program rejects a zero minimum).
+pub fn min_output_from_quote(out_amount: u64, slippage_bps: u64) -> Result<u64, ContractError> {
+    assert_valid_slippage(slippage_bps, BPS_DENOMINATOR)?;
+
+    let min_output = u128::from(out_amount) * u128::from(BPS_DENOMINATOR - slippage_bps)
+        / u128::from(BPS_DENOMINATOR);
+    // Never larger than `out_amount`, so the conversion cannot fail
+    let min_output = u64::try_from(min_output).unwrap_or(out_amount);
+
+    if min_output == 0 {
+        return Err(ContractError::InvalidAccount(
+            "Quoted output is too small for a non-zero minimum output".to_string(),
+        ));
+    }
+
+    Ok(min_output)
+}
+
+/// Compute the minimum output for an input amount at a given price
+///
+/// Converts between the mints' decimals before applying the slippage
+/// tolerance, rounding down at every step.
+///
+/// # Arguments
+///
+/// * `amount` - Input amount, in raw input token units
+/// * `input_decimals` - Decimals of the input mint
+/// * `output_decimals` - Decimals of the output mint
+/// * `price` - Whole output tokens per whole input token, scaled by
+///   [`PRICE_SCALE`] (e.g. `150 * PRICE_SCALE` for 150 USDC per SOL)
+/// * `slippage_bps` - Slippage tolerance in basis points (max 10,000)
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The minimum output amount, in raw output token units
+/// * `Err(ContractError)` - If the computation overflows or the minimum is zero
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the output amount overflows
+/// `u64`, `slippage_bps` exceeds 10,000, or the resulting minimum is zero.
+///
+/// # Example
+///
+/// ```rust
+/// use xforce_terminal_contracts_client::jupiter::{min_output_for_price, PRICE_SCALE};
+///
+/// // 1 SOL (9 decimals) at 150 USDC (6 decimals) per SOL, 0.5% slippage
+/// let min_output = min_output_for_price(1_000_000_000, 9, 6, 150 * PRICE_SCALE, 50).unwrap();
+/// assert_eq!(min_output, 149_250_000);
+/// ```
+pub fn min_output_for_price(
+    amount: u64,
+    input_decimals: u8,
+    output_decimals: u8,
+    price: u64,
+    slippage_bps: u64,
+) -> Result<u64, ContractError> {
+    let overflow = || ContractError::InvalidAccount("Output amount for the price overflows u64".to_string());
+    let pow10 = |exponent: u8| 10u128.checked_pow(u32::from(exponent)).ok_or_else(overflow);
+
+    // Scale by the decimals difference only, keeping intermediate values small
+    let value = u128::from(amount) * u128::from(price);
+    let out_amount = if output_decimals >= input_decimals {
+        value
+            .checked_mul(pow10(output_decimals - input_decimals)?)
+            .ok_or_else(overflow)?
+            / u128::from(PRICE_SCALE)
+    } else {
+        value / u128::from(PRICE_SCALE) / pow10(input_decimals - output_decimals)?
+    };
+    let out_amount = u64::try_from(out_amount).map_err(|_| overflow())?;
+
+    min_output_from_quote(out_amount, slippage_bps)
+}
+
+/// Lock a shared quote cache
+fn lock_cache(cache: &Mutex<QuoteCache>) -> Result<std::sync::MutexGuard<'_, QuoteCache>, ContractError> {
+    cache
+        .lock()
+        .map_err(|_| ContractError::ClientError("Quote cache poisoned".to_string()))
+}
+
+/// Bucket of an amount in a quote cache key
+///
+/// Keeps the top [`AMOUNT_BUCKET_BITS`] bits of the amount, with the shift
+/// so buckets of different magnitudes never collide. Amounts below
+/// `2^AMOUNT_BUCKET_BITS` get a bucket each.
+fn amount_bucket(amount: u64) -> (u32, u64) {
+    let shift = (u64::BITS - amount.leading_zeros()).saturating_sub(AMOUNT_BUCKET_BITS);
+    (shift, amount >> shift)
+}
+
+/// Scale a quote linearly to another input amount
+///
+/// The output is rounded down. Returns `None` for a quote of zero input or
+/// if the scaled output overflows `u64`.
+fn scale_quote(quote: &JupiterQuote, amount: u64) -> Option<JupiterQuote> {
+    if quote.in_amount == amount {
+        return Some(quote.clone());
+    }
+    if quote.in_amount == 0 {
+        return None;
+    }
+
+    let out_amount = u128::from(quote.out_amount) * u128::from(amount) / u128::from(quote.in_amount);
+    Some(JupiterQuote {
+        in_amount: amount,
+        out_amount: u64::try_from(out_amount).ok()?,
+        ..quote.clone()
+    })
+}
+
+/// Parse a string-encoded token amount from the quote API
+fn parse_amount(amount: &str) -> Result<u64, ContractError> {
+    amount
+        .parse()
+        .map_err(|_| ContractError::SerializationError(format!("Invalid quote amount: {amount}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_output_rounds_down() {
+        assert_eq!(min_output_from_quote(1_000_000, 50).unwrap(), 995_000);
+        assert_eq!(min_output_from_quote(999, 50).unwrap(), 994);
+        assert_eq!(min_output_from_quote(u64::MAX, 0).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_min_output_rejects_invalid_slippage() {
+        assert!(min_output_from_quote(1_000, 10_001).is_err());
+        assert!(min_output_from_quote(1_000, 10_000).is_err());
+    }
+
+    #[test]
+    fn test_min_output_for_price_converts_decimals() {
+        // 1 SOL (9 decimals) at 150 USDC (6 decimals), and 2 USDC at 0.5 SOL each
+        assert_eq!(min_output_for_price(1_000_000_000, 9, 6, 150 * PRICE_SCALE, 0).unwrap(), 150_000_000);
+        assert_eq!(min_output_for_price(2_000_000, 6, 9, PRICE_SCALE / 2, 0).unwrap(), 1_000_000_000);
+        assert_eq!(min_output_for_price(1_000, 6, 6, PRICE_SCALE, 100).unwrap(), 990);
+
+        // Overflowing outputs and dust-sized minimums are errors, not panics
+        assert!(min_output_for_price(u64::MAX, 0, 18, u64::MAX, 0).is_err());
+        assert!(min_output_for_price(u64::MAX, 0, 255, PRICE_SCALE, 0).is_err());
+        assert!(min_output_for_price(1, 255, 0, PRICE_SCALE, 0).is_err());
+    }
+
+    fn quote(input_mint: Pubkey, output_mint: Pubkey, in_amount: u64, out_amount: u64) -> JupiterQuote {
+        JupiterQuote { input_mint, output_mint, in_amount, out_amount }
+    }
+
+    #[test]
+    fn test_quote_cache_serves_nearby_amounts_within_ttl() {
+        let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut cache = QuoteCache::new(QuoteCacheConfig { ttl: Duration::from_secs(5), max_entries: 8 });
+        let start = Instant::now();
+        cache.insert(start, quote(sol, usdc, 1_000_000_000, 150_000_000));
+
+        // A slightly larger amount shares the bucket and is scaled to it
+        let nearby = cache.get(start, &sol, &usdc, 1_000_100_000).unwrap();
+        assert_eq!((nearby.in_amount, nearby.out_amount), (1_000_100_000, 150_015_000));
+
+        // A different amount bucket, the reverse pair and an expired entry all miss
+        assert!(cache.get(start, &sol, &usdc, 2_000_000_000).is_none());
+        assert!(cache.get(start, &usdc, &sol, 1_000_000_000).is_none());
+        assert!(cache.get(start + Duration::from_secs(5), &sol, &usdc, 1_000_000_000).is_none());
+
+        // Refresh drops the pair's quotes
+        cache.invalidate(&sol, &usdc);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_quote_cache_evicts_oldest_when_full() {
+        let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut cache = QuoteCache::new(QuoteCacheConfig { ttl: Duration::from_secs(30), max_entries: 2 });
+        let start = Instant::now();
+        cache.insert(start, quote(sol, usdc, 1_000, 10));
+        cache.insert(start + Duration::from_secs(1), quote(sol, usdc, 1_000_000, 10_000));
+        cache.insert(start + Duration::from_secs(2), quote(sol, usdc, 1_000_000_000, 10_000_000));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(start, &sol, &usdc, 1_000).is_none());
+
+        // Failed fetches are not cached
+        let mut fetches = 0;
+        let result = cache.get_or_fetch(start, &sol, &usdc, 7, || {
+            fetches += 1;
+            Err(ContractError::NetworkError("down".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!((fetches, cache.len()), (1, 2));
+    }
+
+    #[test]
+    fn test_unreachable_endpoint_is_network_error() {
+        // Nothing listens on port 9 (discard) locally
+        let quotes = QuoteClient::with_base_url("http://127.0.0.1:9/quote");
+        let result = quotes.swap_params_from_quote(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 50);
+
+        assert!(matches!(result, Err(ContractError::NetworkError(_))));
+    }
+}
@@ -18,15 +18,56 @@
 //!
 //! ```text
 //! lib.rs                    # Main library entry point
+//! ├── account_setup.rs      # Missing output ATAs created before a batch
+//! ├── batch_builder.rs      # Incremental batch construction
+//! ├── batch_spec.rs         # JSON batch files for scripted batches
 //! ├── batch_swap_router.rs  # Batch swap router client
+//! ├── chunked.rs            # Large swap sets split into per-chunk batches
+//! ├── cluster.rs            # Wrong-cluster detection via the genesis hash
+//! ├── config.rs             # Config account mirror and live fee rate
+//! ├── deadline.rs           # Blockhash validity capped at swap deadlines
+//! ├── durable_nonce.rs      # Durable nonce transactions for offline signing
 //! ├── error.rs              # Error definitions
-//! └── types.rs              # Type definitions
+//! ├── event_scan.rs         # Historical event scans over a slot range
+//! ├── events.rs             # Client-side mirrors of program events
+//! ├── jupiter.rs            # Jupiter quotes and min-output computation
+//! ├── lookup_table.rs       # Address Lookup Table for common accounts
+//! ├── memo.rs               # SPL memo attachment for compliance tagging
+//! ├── mint_decimals.rs      # Cached mint decimals for transfer_checked
+//! ├── multi_wallet.rs       # One batch per wallet across sub-accounts
+//! ├── pda.rs                # PDA seeds and address derivation
+//! ├── preview.rs            # Predicted SwapExecutedEvent for a quoted swap
+//! ├── priority_fee.rs       # Compute unit price attachment and reporting
+//! ├── rebalance.rs          # Target allocation to batch swaps
+//! ├── return_data.rs        # execute_swap return data decoding
+//! ├── rpc_pool.rs           # Multi-RPC failover with a circuit breaker
+//! ├── stats.rs              # Stats/PairStats account types and PDAs
+//! ├── tx_size.rs            # Transaction size checks (1232-byte limit)
+//! ├── types.rs              # Type definitions
+//! ├── version.rs            # Program version handshake (get_version)
+//! ├── wsol.rs               # Wrapped SOL wrap/unwrap helpers
+//! └── xforce_client.rs      # One-call client: connect, swap, read stats
 //! ```
 //!
 //! ## Usage
 //!
+//! ### Quick Start
+//!
+//! ```rust,no_run
+//! use xforce_terminal_contracts_client::*;
+//! use solana_sdk::signature::Keypair;
+//!
+//! // Connect and swap without touching anchor-client
+//! let client = XforceClient::connect("http://localhost:8899", Keypair::new())?;
+//! let signature = client.batch_swap(swaps)?;
+//! let stats = client.fetch_stats()?;
+//! ```
+//!
 //! ### Basic Usage
 //!
+//! The lower-level API below exposes the Anchor client and every
+//! [`BatchSwapRouterClient`] option.
+//!
 //! ```rust,no_run
 //! use xforce_terminal_contracts_client::*;
 //! use anchor_client::Client;
@@ -50,6 +91,9 @@
 //!         output_mint: mint_b,
 //!         amount: 1000,
 //!         min_output_amount: 900,
+//!         max_fee: None, // no absolute fee cap
+//!         max_slippage_bps: None, // global MAX_SLIPPAGE_BPS
+//!         tag: 0, // untagged
 //!     },
 //! ];
 //!
@@ -100,16 +144,49 @@ use anchor_client::solana_sdk::{
 };
 use std::rc::Rc;
 
+pub mod account_setup;
+pub mod batch_builder;
+pub mod batch_spec;
 pub mod batch_swap_router;
+pub mod chunked;
+pub mod cluster;
+pub mod config;
+pub mod deadline;
+pub mod durable_nonce;
 pub mod error;
+pub mod event_scan;
+pub mod events;
+pub mod jupiter;
+pub mod lookup_table;
+pub mod memo;
+pub mod mint_decimals;
+pub mod multi_wallet;
+pub mod pda;
+pub mod preview;
+pub mod priority_fee;
+pub mod rebalance;
+pub mod return_data;
+pub mod rpc_pool;
 pub mod security;
+pub mod stats;
+pub mod tx_size;
 pub mod types;
+pub mod version;
+pub mod wsol;
+pub mod xforce_client;
 
 /// Re-export commonly used types and clients for convenience.
+pub use batch_builder::BatchBuilder;
 pub use batch_swap_router::BatchSwapRouterClient;
 pub use error::ContractError;
+pub use events::{BatchSwapEventData, SwapExecutedEventData};
+pub use jupiter::QuoteClient;
+pub use rpc_pool::RpcPool;
 pub use security::*;
-pub use types::SwapParams;
+pub use stats::{PairStats, Stats};
+pub use types::{INSTRUCTION_VERSION, MAX_BATCH_SIZE, MAX_SLIPPAGE_BPS, PROTOCOL_FEE_BPS, SwapParams};
+pub use wsol::WsolOptions;
+pub use xforce_client::XforceClient;
 
 /// Create a client for interacting with XForce Terminal contracts
 ///
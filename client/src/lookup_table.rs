@@ -0,0 +1,192 @@
+//! # Address Lookup Tables
+//!
+//! This module contains the helpers behind
+//! [`BatchSwapRouterClient::ensure_lookup_table`], which maintains an Address
+//! Lookup Table (ALT) holding the accounts that appear in almost every batch.
+//! v0 transactions that reference the table replace each of those 32-byte
+//! keys with a 1-byte index, which is what lets large batches fit in a
+//! transaction.
+//!
+//! ## Table Contents
+//!
+//! The table always starts with the program ID, the SPL Token program and the
+//! System program, followed by the caller's frequently used mints. Addresses
+//! are only ever appended, so indexes stay stable across calls.
+//!
+//! ## Persistence
+//!
+//! The table address is written to a small cache file (by default
+//! [`LOOKUP_TABLE_CACHE_FILE`] in the working directory) and reused on the
+//! next call. A cached table that no longer exists, is owned by another
+//! authority, or has been deactivated is replaced by a new one.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let table = swap_client.ensure_lookup_table(&[sol_mint, usdc_mint])?;
+//! // Reference `table` when building v0 batch swap transactions
+//! ```
+//!
+//! [`BatchSwapRouterClient::ensure_lookup_table`]: crate::BatchSwapRouterClient::ensure_lookup_table
+
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+
+/// Default cache file holding the lookup table address
+pub const LOOKUP_TABLE_CACHE_FILE: &str = ".xforce-lookup-table";
+
+/// Maximum number of addresses appended per extend transaction
+///
+/// Keeps each extend transaction well under the packet size limit.
+pub const EXTEND_CHUNK_SIZE: usize = 20;
+
+/// Number of slot polls before giving up on table activation
+pub const ACTIVATION_POLL_ATTEMPTS: u32 = 30;
+
+/// Delay between slot polls while waiting for activation, in milliseconds
+pub const ACTIVATION_POLL_INTERVAL_MS: u64 = 400;
+
+/// Addresses a lookup table for `program_id` should contain, in table order
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+/// * `common_mints` - Frequently used mints to include
+///
+/// # Returns
+///
+/// The program ID, the SPL Token program, the System program and then
+/// `common_mints`, without duplicates
+#[must_use]
+pub fn lookup_table_addresses(program_id: &Pubkey, common_mints: &[Pubkey]) -> Vec<Pubkey> {
+    let mut addresses = Vec::with_capacity(3 + common_mints.len());
+    for address in [*program_id, anchor_spl::token::ID, anchor_lang::system_program::ID]
+        .iter()
+        .chain(common_mints)
+    {
+        if !addresses.contains(address) {
+            addresses.push(*address);
+        }
+    }
+    addresses
+}
+
+/// Addresses from `wanted` that are not yet in the table
+///
+/// # Arguments
+///
+/// * `existing` - Addresses already stored in the table
+/// * `wanted` - Addresses the table should contain
+///
+/// # Returns
+///
+/// The missing addresses, in `wanted` order
+#[must_use]
+pub fn missing_addresses(existing: &[Pubkey], wanted: &[Pubkey]) -> Vec<Pubkey> {
+    wanted
+        .iter()
+        .filter(|address| !existing.contains(address))
+        .copied()
+        .collect()
+}
+
+/// Read the cached lookup table address
+///
+/// # Arguments
+///
+/// * `path` - The cache file
+///
+/// # Returns
+///
+/// The cached address, or `None` if the file is missing or does not hold a
+/// valid address
+#[must_use]
+pub fn read_cached_address(path: &Path) -> Option<Pubkey> {
+    let contents = fs::read_to_string(path).ok()?;
+    Pubkey::from_str(contents.trim()).ok()
+}
+
+/// Persist the lookup table address for reuse
+///
+/// # Arguments
+///
+/// * `path` - The cache file
+/// * `address` - The lookup table address
+///
+/// # Errors
+///
+/// Returns `ContractError::ClientError` if the file cannot be written
+pub fn write_cached_address(path: &Path, address: &Pubkey) -> Result<(), ContractError> {
+    fs::write(path, format!("{address}\n"))
+        .map_err(|e| ContractError::ClientError(format!("Failed to write {}: {e}", path.display())))
+}
+
+/// Decode a lookup table account and check it can be extended by `authority`
+///
+/// # Arguments
+///
+/// * `data` - Raw lookup table account data
+/// * `authority` - The authority expected to own the table
+///
+/// # Returns
+///
+/// The table's addresses and the slot it was last extended in, or `None` if
+/// the data is not a lookup table, the table is deactivated or frozen, or it
+/// belongs to another authority
+#[must_use]
+pub fn usable_table(data: &[u8], authority: &Pubkey) -> Option<(Vec<Pubkey>, u64)> {
+    let table = AddressLookupTable::deserialize(data).ok()?;
+    let usable = table.meta.deactivation_slot == u64::MAX
+        && table.meta.authority.as_ref() == Some(authority);
+
+    usable.then(|| (table.addresses.to_vec(), table.meta.last_extended_slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_table_addresses_are_deduplicated_in_order() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        let addresses = lookup_table_addresses(&program_id, &[mint, anchor_spl::token::ID, mint]);
+
+        assert_eq!(
+            addresses,
+            vec![program_id, anchor_spl::token::ID, anchor_lang::system_program::ID, mint]
+        );
+    }
+
+    #[test]
+    fn test_missing_addresses() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(missing_addresses(&[b], &[a, b, c]), vec![a, c]);
+        assert!(missing_addresses(&[a, b], &[b, a]).is_empty());
+    }
+
+    #[test]
+    fn test_cached_address_round_trips() {
+        let path = std::env::temp_dir().join(format!("xforce-alt-{}", Pubkey::new_unique()));
+        let address = Pubkey::new_unique();
+
+        assert_eq!(read_cached_address(&path), None);
+        write_cached_address(&path, &address).unwrap();
+        assert_eq!(read_cached_address(&path), Some(address));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_usable_table_rejects_garbage() {
+        assert_eq!(usable_table(&[1, 2, 3], &Pubkey::new_unique()), None);
+    }
+}
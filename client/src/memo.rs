@@ -0,0 +1,155 @@
+//! # Compliance Memos
+//!
+//! This module attaches SPL memos to swap transactions. Institutional users
+//! often have to tag every transfer with a reference for their records; the
+//! helpers here prepend an `spl_memo` instruction to the swap instructions
+//! and compute the `memo_hash` argument the program records in its events.
+//!
+//! ## Transaction Layout
+//!
+//! ```text
+//! 1. spl_memo("<memo>")          # signed by the swap authority
+//! 2. <swap instructions>         # route + batch_swap/execute_swap(memo_hash)
+//! ```
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::memo::{memo_hash, with_memo};
+//!
+//! let memo = "desk-7 / ticket 1182";
+//! let hash = memo_hash(memo);                 // pass as `memo_hash` to the instruction
+//! let instructions = with_memo(memo, &authority, swap_instructions)?;
+//! ```
+
+use anchor_spl::memo::spl_memo;
+use sha2::{Digest, Sha256};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::error::ContractError;
+
+/// Maximum memo length in bytes
+///
+/// Transactions are capped at 1232 bytes and the swap route already uses most
+/// of that, so memos are kept short.
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// `memo_hash` value to pass when no memo is attached
+pub const NO_MEMO_HASH: [u8; 32] = [0u8; 32];
+
+/// Validate a memo before attaching it
+///
+/// # Arguments
+///
+/// * `memo` - The memo text
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the memo is empty or longer than
+/// [`MAX_MEMO_LEN`] bytes.
+pub fn validate_memo(memo: &str) -> Result<(), ContractError> {
+    if memo.is_empty() {
+        return Err(ContractError::InvalidAccount("Memo cannot be empty".to_string()));
+    }
+
+    if memo.len() > MAX_MEMO_LEN {
+        return Err(ContractError::InvalidAccount(format!(
+            "Memo is {} bytes, maximum is {MAX_MEMO_LEN}",
+            memo.len()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Compute the `memo_hash` argument for a memo
+///
+/// # Arguments
+///
+/// * `memo` - The memo text
+///
+/// # Returns
+///
+/// The SHA-256 hash of the memo's UTF-8 bytes
+#[must_use]
+pub fn memo_hash(memo: &str) -> [u8; 32] {
+    Sha256::digest(memo.as_bytes()).into()
+}
+
+/// Build an SPL memo instruction signed by `signer`
+///
+/// # Arguments
+///
+/// * `memo` - The memo text
+/// * `signer` - The account that signs the memo (the swap authority)
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the memo fails [`validate_memo`].
+pub fn memo_instruction(memo: &str, signer: &Pubkey) -> Result<Instruction, ContractError> {
+    validate_memo(memo)?;
+    Ok(spl_memo::build_memo(memo.as_bytes(), &[signer]))
+}
+
+/// Prepend a memo instruction to swap instructions
+///
+/// # Arguments
+///
+/// * `memo` - The memo text
+/// * `signer` - The account that signs the memo (the swap authority)
+/// * `swap_instructions` - The swap instructions (route + router instruction)
+///
+/// # Returns
+///
+/// * `Ok(Vec<Instruction>)` - The memo instruction followed by `swap_instructions`
+/// * `Err(ContractError)` - If the memo is invalid
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the memo fails [`validate_memo`].
+pub fn with_memo(
+    memo: &str,
+    signer: &Pubkey,
+    swap_instructions: Vec<Instruction>,
+) -> Result<Vec<Instruction>, ContractError> {
+    let mut instructions = Vec::with_capacity(swap_instructions.len() + 1);
+    instructions.push(memo_instruction(memo, signer)?);
+    instructions.extend(swap_instructions);
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memo_is_prepended() {
+        let signer = Pubkey::new_unique();
+        let swap_ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+
+        let ixs = with_memo("ticket 42", &signer, vec![swap_ix.clone()]).unwrap();
+
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(ixs[0].program_id, spl_memo::ID);
+        assert_eq!(ixs[0].data, b"ticket 42");
+        assert_eq!(ixs[0].accounts[0].pubkey, signer);
+        assert_eq!(ixs[1], swap_ix);
+    }
+
+    #[test]
+    fn test_memo_length_is_validated() {
+        assert!(validate_memo("").is_err());
+        assert!(validate_memo(&"x".repeat(MAX_MEMO_LEN)).is_ok());
+        assert!(validate_memo(&"x".repeat(MAX_MEMO_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_memo_hash_is_sha256() {
+        // sha256("abc")
+        assert_eq!(
+            memo_hash("abc")[..4],
+            [0xba, 0x78, 0x16, 0xbf]
+        );
+        assert_ne!(memo_hash("abc"), NO_MEMO_HASH);
+    }
+}
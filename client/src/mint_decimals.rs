@@ -0,0 +1,176 @@
+//! # Mint Decimals
+//!
+//! This module reads and caches mint decimals for `transfer_checked`, which
+//! Token-2022 mints with extensions require. `transfer_checked` fails unless
+//! it is given the mint's exact decimals, so decimals must come from the mint
+//! account rather than being assumed (6 for stablecoins, 9 for SOL, ...).
+//!
+//! ## Caching
+//!
+//! A mint's decimals never change after initialization, so each mint is read
+//! once and served from [`MintDecimalsCache`] afterwards.
+//! [`BatchSwapRouterClient::fetch_mint_decimals`] keeps one cache per client.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let decimals = swap_client.fetch_mint_decimals(&usdc_mint)?; // RPC read
+//! let decimals = swap_client.fetch_mint_decimals(&usdc_mint)?; // cached
+//! ```
+//!
+//! [`BatchSwapRouterClient::fetch_mint_decimals`]: crate::BatchSwapRouterClient::fetch_mint_decimals
+
+use std::collections::HashMap;
+
+use anchor_spl::token::spl_token::state::{Account as SplAccount, Mint as SplMint};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+
+/// Token-2022 account type tag of mints, stored after the base account size
+const TOKEN_2022_MINT_ACCOUNT_TYPE: u8 = 1;
+
+/// Decode the decimals of a mint from its account data
+///
+/// SPL Token mints are exactly `Mint::LEN` bytes, and Token-2022 mints carry
+/// the mint account type right after the `Account::LEN`-byte base.
+///
+/// # Arguments
+///
+/// * `data` - Raw mint account data (SPL Token or Token-2022)
+///
+/// # Returns
+///
+/// * `Ok(u8)` - The mint's decimals
+/// * `Err(ContractError)` - If the data is not an initialized mint
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the data is not an initialized
+/// mint of either token program.
+pub fn decode_mint_decimals(data: &[u8]) -> Result<u8, ContractError> {
+    let is_mint = match data.len() {
+        SplMint::LEN => true,
+        len if len > SplAccount::LEN => data[SplAccount::LEN] == TOKEN_2022_MINT_ACCOUNT_TYPE,
+        _ => false,
+    };
+
+    let mint = is_mint
+        .then(|| SplMint::unpack_from_slice(&data[..SplMint::LEN]).ok())
+        .flatten()
+        .filter(|mint| mint.is_initialized)
+        .ok_or_else(|| ContractError::InvalidAccount("Account is not an initialized mint".to_string()))?;
+
+    Ok(mint.decimals)
+}
+
+/// Per-mint cache of decimals
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut cache = MintDecimalsCache::default();
+/// let decimals = cache.get_or_fetch(&mint, |mint| fetch_account_data(mint))?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MintDecimalsCache {
+    /// Decimals of every mint read so far
+    decimals: HashMap<Pubkey, u8>,
+}
+
+impl MintDecimalsCache {
+    /// Cached decimals of `mint`, if it has been read before
+    #[must_use]
+    pub fn get(&self, mint: &Pubkey) -> Option<u8> {
+        self.decimals.get(mint).copied()
+    }
+
+    /// Decimals of `mint`, reading and caching them on first use
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - The mint
+    /// * `fetch` - Reads the raw mint account data; only called on a cache miss
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(u8)` - The mint's decimals
+    /// * `Err(ContractError)` - If the mint cannot be read or decoded
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `fetch`, or `ContractError::InvalidAccount` if the
+    /// account is not a mint. Failures are not cached.
+    pub fn get_or_fetch<F>(&mut self, mint: &Pubkey, fetch: F) -> Result<u8, ContractError>
+    where
+        F: FnOnce(&Pubkey) -> Result<Vec<u8>, ContractError>,
+    {
+        if let Some(decimals) = self.get(mint) {
+            return Ok(decimals);
+        }
+
+        let decimals = decode_mint_decimals(&fetch(mint)?)?;
+        self.decimals.insert(*mint, decimals);
+        Ok(decimals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mint_data(decimals: u8) -> Vec<u8> {
+        let mint = SplMint {
+            decimals,
+            is_initialized: true,
+            ..SplMint::default()
+        };
+        let mut data = vec![0u8; SplMint::LEN];
+        mint.pack_into_slice(&mut data);
+        data
+    }
+
+    #[test]
+    fn test_decode_spl_token_and_token_2022_mints() {
+        assert_eq!(decode_mint_decimals(&mint_data(6)).unwrap(), 6);
+
+        let mut data = mint_data(9);
+        data.resize(SplAccount::LEN, 0);
+        data.push(TOKEN_2022_MINT_ACCOUNT_TYPE);
+        assert_eq!(decode_mint_decimals(&data).unwrap(), 9);
+    }
+
+    #[test]
+    fn test_decode_rejects_non_mints() {
+        assert!(decode_mint_decimals(&[0u8; SplMint::LEN]).is_err());
+        assert!(decode_mint_decimals(&[0u8; SplAccount::LEN]).is_err());
+    }
+
+    #[test]
+    fn test_cache_fetches_each_mint_once() {
+        let mut cache = MintDecimalsCache::default();
+        let mint = Pubkey::new_unique();
+        let mut fetches = 0;
+
+        for _ in 0..3 {
+            let decimals = cache
+                .get_or_fetch(&mint, |_| {
+                    fetches += 1;
+                    Ok(mint_data(6))
+                })
+                .unwrap();
+            assert_eq!(decimals, 6);
+        }
+        assert_eq!(fetches, 1);
+    }
+
+    #[test]
+    fn test_cache_does_not_store_failures() {
+        let mut cache = MintDecimalsCache::default();
+        let mint = Pubkey::new_unique();
+
+        assert!(cache.get_or_fetch(&mint, |_| Ok(vec![0u8; 3])).is_err());
+        assert_eq!(cache.get(&mint), None);
+    }
+}
@@ -0,0 +1,156 @@
+//! # Multi-Wallet Batches
+//!
+//! This module submits one batch swap per wallet for callers that manage
+//! several sub-accounts, such as a fund manager rebalancing a set of vaults.
+//!
+//! ## Error Isolation
+//!
+//! Every wallet is validated and submitted independently. A wallet whose
+//! swaps are invalid, or whose transaction fails, gets an `Err` entry in the
+//! result map; the other wallets are still submitted.
+//!
+//! ## Parallelism
+//!
+//! [`batch_swap_wallets`] submits wallets one after another. With the `async`
+//! feature, [`batch_swap_wallets_async`] submits every wallet concurrently on
+//! tokio's blocking pool (the underlying RPC client is blocking).
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::multi_wallet::batch_swap_wallets;
+//!
+//! let results = batch_swap_wallets(
+//!     "https://api.devnet.solana.com",
+//!     vec![(vault_a, swaps_a), (vault_b, swaps_b)],
+//! );
+//!
+//! for (authority, result) in &results {
+//!     match result {
+//!         Ok(signature) => println!("{authority}: {signature}"),
+//!         Err(e) => eprintln!("{authority}: {e}"),
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+use crate::batch_swap_router::BatchSwapRouterClient;
+use crate::error::ContractError;
+use crate::types::SwapParams;
+use crate::{create_client, get_batch_swap_router_program_id};
+
+/// Per-wallet results, keyed by the wallet's public key
+pub type WalletResults = HashMap<Pubkey, Result<Signature, ContractError>>;
+
+/// Submit one batch swap per wallet, sequentially
+///
+/// # Arguments
+///
+/// * `cluster_url` - The RPC URL of the Solana cluster
+/// * `wallets` - Each wallet's keypair (the swap authority and fee payer)
+///   and the swaps to batch for it
+///
+/// # Returns
+///
+/// A map from each wallet's public key to its transaction signature or error.
+/// A wallet listed more than once keeps the result of its last entry.
+#[must_use]
+pub fn batch_swap_wallets(cluster_url: &str, wallets: Vec<(Keypair, Vec<SwapParams>)>) -> WalletResults {
+    wallets
+        .into_iter()
+        .map(|(wallet, swaps)| (wallet.pubkey(), submit_wallet(cluster_url, wallet, swaps)))
+        .collect()
+}
+
+/// Submit one batch swap per wallet, concurrently
+///
+/// Same behavior as [`batch_swap_wallets`], but all wallets are submitted at
+/// once. Requires the `async` feature and a tokio runtime.
+///
+/// # Arguments
+///
+/// * `cluster_url` - The RPC URL of the Solana cluster
+/// * `wallets` - Each wallet's keypair (the swap authority and fee payer)
+///   and the swaps to batch for it
+///
+/// # Returns
+///
+/// A map from each wallet's public key to its transaction signature or error.
+/// A wallet listed more than once keeps the result of its last entry.
+#[cfg(feature = "async")]
+pub async fn batch_swap_wallets_async(
+    cluster_url: &str,
+    wallets: Vec<(Keypair, Vec<SwapParams>)>,
+) -> WalletResults {
+    let tasks: Vec<_> = wallets
+        .into_iter()
+        .map(|(wallet, swaps)| {
+            let authority = wallet.pubkey();
+            let cluster_url = cluster_url.to_string();
+            let task = tokio::task::spawn_blocking(move || submit_wallet(&cluster_url, wallet, swaps));
+            (authority, task)
+        })
+        .collect();
+
+    let mut results = WalletResults::with_capacity(tasks.len());
+    for (authority, task) in tasks {
+        // A panicking submission only fails its own wallet
+        let result = task
+            .await
+            .unwrap_or_else(|e| Err(ContractError::ClientError(format!("Submission task failed: {e}"))));
+        results.insert(authority, result);
+    }
+    results
+}
+
+/// Validate and submit a single wallet's batch
+fn submit_wallet(
+    cluster_url: &str,
+    wallet: Keypair,
+    swaps: Vec<SwapParams>,
+) -> Result<Signature, ContractError> {
+    // Validate before connecting so bad input fails fast
+    for swap in &swaps {
+        swap.validate().map_err(ContractError::InvalidAccount)?;
+    }
+
+    let client = create_client(cluster_url, Rc::new(wallet));
+    let program = client
+        .program(get_batch_swap_router_program_id())
+        .map_err(|e| ContractError::ClientError(e.to_string()))?;
+
+    BatchSwapRouterClient::new(program).batch_swap(swaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_wallet_does_not_block_others() {
+        let good = Keypair::new();
+        let bad = Keypair::new();
+        let (good_key, bad_key) = (good.pubkey(), bad.pubkey());
+        let mint = Pubkey::new_unique();
+
+        let results = batch_swap_wallets(
+            "http://127.0.0.1:8899",
+            vec![
+                (bad, vec![SwapParams::new(mint, mint, 1_000, 900)]),
+                (good, vec![SwapParams::new(mint, Pubkey::new_unique(), 1_000, 900)]),
+            ],
+        );
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[&bad_key], Err(ContractError::InvalidAccount(_))));
+        // The valid wallet was still submitted (and failed only on submission)
+        assert!(!matches!(results[&good_key], Err(ContractError::InvalidAccount(_))));
+    }
+}
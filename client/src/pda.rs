@@ -0,0 +1,250 @@
+//! # Program Derived Addresses
+//!
+//! This module keeps every PDA seed of the batch swap router, and the
+//! helpers deriving the addresses, in one place. The seeds must match the
+//! program's `constants.rs` byte for byte: a drifted seed derives a
+//! different, empty address and fails with "account not found".
+//!
+//! ## Accounts
+//!
+//! | Account     | Seeds                                         |
+//! |-------------|-----------------------------------------------|
+//! | `Config`    | `[b"config"]`                                 |
+//! | `Stats`     | `[b"stats"]`                                  |
+//! | `PairStats` | `[b"pair_stats", input_mint, output_mint]`    |
+//! | `LastPrice` | `[b"last_price", input_mint, output_mint]`    |
+//! | `UserStats` | `[b"user_stats", authority]`                  |
+//!
+//! New PDAs get their seed constant and derivation helper here, next to the
+//! existing ones, rather than in the module that reads the account. The
+//! integration tests mirror the same seeds (`tests/batch-swap-router.ts`).
+//!
+//! ## Stored Bumps
+//!
+//! Every program PDA stores its canonical bump (the `bump` field) when it is
+//! created. The program re-derives `Config` and `Stats` from the stored bump
+//! with `create_program_address`, a single hash, instead of the
+//! `find_program_address` search the helpers below run. Readers can do the
+//! same once they hold an account: [`verify_stored_bump`] checks that the
+//! account's stored bump derives its address, which catches an account
+//! decoded from the wrong address or a corrupted bump.
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use xforce_terminal_contracts_client::get_batch_swap_router_program_id;
+//! use xforce_terminal_contracts_client::pda::{config_pda, pair_stats_pda};
+//! use solana_sdk::pubkey::Pubkey;
+//!
+//! let program_id = get_batch_swap_router_program_id();
+//! let (config, _bump) = config_pda(&program_id);
+//! let (pair_stats, _bump) = pair_stats_pda(&program_id, &Pubkey::new_unique(), &Pubkey::new_unique());
+//! assert_ne!(config, pair_stats);
+//! ```
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+
+/// PDA seed of the `Config` account (matches the program's `CONFIG_SEED`)
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// PDA seed of the `Stats` account (matches the program's `STATS_SEED`)
+pub const STATS_SEED: &[u8] = b"stats";
+
+/// PDA seed prefix of `PairStats` accounts (matches the program's `PAIR_STATS_SEED`)
+pub const PAIR_STATS_SEED: &[u8] = b"pair_stats";
+
+/// PDA seed prefix of `LastPrice` accounts (matches the program's `LAST_PRICE_SEED`)
+pub const LAST_PRICE_SEED: &[u8] = b"last_price";
+
+/// PDA seed prefix of `UserStats` accounts (matches the program's `USER_STATS_SEED`)
+pub const USER_STATS_SEED: &[u8] = b"user_stats";
+
+/// Derive the address of the `Config` account
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+///
+/// # Returns
+///
+/// The `(address, bump)` of the config PDA
+#[must_use]
+pub fn config_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+/// Derive the address of the `Stats` account
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+///
+/// # Returns
+///
+/// The `(address, bump)` of the stats PDA
+#[must_use]
+pub fn stats_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATS_SEED], program_id)
+}
+
+/// Derive the address of a pair's `PairStats` account
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+/// * `input_mint` - Input token mint of the pair
+/// * `output_mint` - Output token mint of the pair
+///
+/// # Returns
+///
+/// The `(address, bump)` of the pair stats PDA
+#[must_use]
+pub fn pair_stats_pda(program_id: &Pubkey, input_mint: &Pubkey, output_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PAIR_STATS_SEED, input_mint.as_ref(), output_mint.as_ref()],
+        program_id,
+    )
+}
+
+/// Derive the address of a pair's `LastPrice` account
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+/// * `input_mint` - Input token mint of the pair
+/// * `output_mint` - Output token mint of the pair
+///
+/// # Returns
+///
+/// The `(address, bump)` of the last price PDA
+#[must_use]
+pub fn last_price_pda(program_id: &Pubkey, input_mint: &Pubkey, output_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LAST_PRICE_SEED, input_mint.as_ref(), output_mint.as_ref()],
+        program_id,
+    )
+}
+
+/// Derive the address of an authority's `UserStats` account
+///
+/// Passing it to `execute_swap` applies the authority's volume-tier fee
+/// discount; it must be created first with `initialize_user_stats`.
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+/// * `authority` - The swap authority the stats belong to
+///
+/// # Returns
+///
+/// The `(address, bump)` of the user stats PDA
+#[must_use]
+pub fn user_stats_pda(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[USER_STATS_SEED, authority.as_ref()], program_id)
+}
+
+/// Derive a PDA from its seeds and a known bump
+///
+/// Runs one `create_program_address` hash instead of the bump search of
+/// `find_program_address`.
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+/// * `seeds` - The account's seeds, without the bump
+/// * `bump` - The bump (e.g. the account's stored `bump` field)
+///
+/// # Returns
+///
+/// The PDA, or `None` if the seeds and bump derive a point on the curve
+#[must_use]
+pub fn pda_with_bump(program_id: &Pubkey, seeds: &[&[u8]], bump: u8) -> Option<Pubkey> {
+    let bump = [bump];
+    let mut seeds_with_bump = seeds.to_vec();
+    seeds_with_bump.push(&bump);
+    Pubkey::create_program_address(&seeds_with_bump, program_id).ok()
+}
+
+/// Check that an account's stored bump derives its address
+///
+/// # Arguments
+///
+/// * `address` - The account's address
+/// * `program_id` - The batch swap router program ID
+/// * `seeds` - The account's seeds, without the bump
+/// * `bump` - The account's stored `bump` field
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if `seeds` and `bump` do not
+/// derive `address`.
+pub fn verify_stored_bump(
+    address: &Pubkey,
+    program_id: &Pubkey,
+    seeds: &[&[u8]],
+    bump: u8,
+) -> Result<(), ContractError> {
+    if pda_with_bump(program_id, seeds, bump) == Some(*address) {
+        Ok(())
+    } else {
+        Err(ContractError::InvalidAccount(format!(
+            "Stored bump {bump} does not derive {address}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeds_match_the_program() {
+        assert_eq!(CONFIG_SEED, b"config");
+        assert_eq!(STATS_SEED, b"stats");
+        assert_eq!(PAIR_STATS_SEED, b"pair_stats");
+        assert_eq!(LAST_PRICE_SEED, b"last_price");
+        assert_eq!(USER_STATS_SEED, b"user_stats");
+    }
+
+    #[test]
+    fn test_pdas_are_distinct_and_deterministic() {
+        let program_id = Pubkey::new_unique();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(config_pda(&program_id), config_pda(&program_id));
+        assert_ne!(config_pda(&program_id).0, stats_pda(&program_id).0);
+        assert_ne!(user_stats_pda(&program_id, &a).0, user_stats_pda(&program_id, &b).0);
+        // Pairs are directional
+        assert_ne!(
+            pair_stats_pda(&program_id, &a, &b).0,
+            pair_stats_pda(&program_id, &b, &a).0
+        );
+        assert_ne!(
+            pair_stats_pda(&program_id, &a, &b).0,
+            last_price_pda(&program_id, &a, &b).0
+        );
+    }
+
+    #[test]
+    fn test_stored_bump_derives_the_address() {
+        let program_id = Pubkey::new_unique();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (config, bump) = config_pda(&program_id);
+        let (pair_stats, pair_bump) = pair_stats_pda(&program_id, &a, &b);
+
+        assert_eq!(pda_with_bump(&program_id, &[CONFIG_SEED], bump), Some(config));
+        assert!(verify_stored_bump(&config, &program_id, &[CONFIG_SEED], bump).is_ok());
+        assert!(verify_stored_bump(
+            &pair_stats,
+            &program_id,
+            &[PAIR_STATS_SEED, a.as_ref(), b.as_ref()],
+            pair_bump
+        )
+        .is_ok());
+        // Another account's bump, or another account's seeds
+        assert!(verify_stored_bump(&config, &program_id, &[CONFIG_SEED], bump.wrapping_sub(1)).is_err());
+        assert!(verify_stored_bump(&config, &program_id, &[STATS_SEED], bump).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+//! # Swap Previews
+//!
+//! This module predicts the `SwapExecutedEvent` a swap will emit before it is
+//! sent, so a UI can show the exact outcome (fee, burned and LP shares,
+//! output and slippage) without a round trip to the chain.
+//!
+//! ## Consistency
+//!
+//! The prediction uses the same fee and slippage math as the program
+//! ([`calculate_protocol_fee`], [`split_lp_fee`], [`split_protocol_fee`] and
+//! [`calculate_slippage_bps`]), so for the same inputs it matches the emitted
+//! event field for field. The only difference is the output: the preview
+//! assumes the quoted output is received, so a real swap that fills worse
+//! than the quote reports a lower `output_amount` and a higher
+//! `slippage_bps`.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::preview::{preview_swap_executed_event, FeeSettings};
+//!
+//! let quote = quotes.fetch_quote(&swap.input_mint, &swap.output_mint, swap.amount)?;
+//! let event = preview_swap_executed_event(authority, &swap, &quote, &FeeSettings::default(), memo_hash, now)?;
+//! println!("fee {} -> output {}", event.protocol_fee, event.output_amount);
+//! ```
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+use crate::events::{FeeDenomination, SwapExecutedEventData, EVENT_SCHEMA_VERSION};
+use crate::jupiter::JupiterQuote;
+use crate::security::{
+    calculate_protocol_fee, calculate_slippage_bps, split_lp_fee, split_protocol_fee,
+};
+use crate::types::{SwapParams, PROTOCOL_FEE_BPS};
+
+/// Fee settings that determine a swap's protocol fee
+///
+/// Mirrors the fee fields of the program's `Config` account, plus whether the
+/// swap authority is on its exemption list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSettings {
+    /// Protocol fee rate in basis points (`Config::fee_bps`)
+    pub fee_bps: u16,
+
+    /// Share of the fee burned, in basis points (`Config::burn_bps`)
+    pub burn_bps: u16,
+
+    /// Part of the fee rate paid to LPs, in basis points (`Config::lp_fee_bps`)
+    pub lp_fee_bps: u16,
+
+    /// Whether the authority is fee-exempt (`Config::is_fee_exempt`)
+    pub fee_exempt: bool,
+}
+
+impl Default for FeeSettings {
+    /// The settings of a freshly initialized config: [`PROTOCOL_FEE_BPS`],
+    /// nothing burned or paid to LPs, no exemption
+    fn default() -> Self {
+        Self {
+            fee_bps: PROTOCOL_FEE_BPS,
+            burn_bps: 0,
+            lp_fee_bps: 0,
+            fee_exempt: false,
+        }
+    }
+}
+
+/// Predict the `SwapExecutedEvent` a swap will emit
+///
+/// # Arguments
+///
+/// * `authority` - The swap authority
+/// * `swap` - The swap parameters (amount, mints, minimum output, fee cap, tag)
+/// * `quote` - The quote for the swap; its `out_amount` is the expected output
+/// * `fees` - The config's fee settings for this authority
+/// * `memo_hash` - The memo hash the swap will be sent with
+/// * `timestamp` - The timestamp to report (the chain uses the block time)
+///
+/// # Returns
+///
+/// * `Ok(SwapExecutedEventData)` - The predicted event, assuming the swap
+///   fills at the quoted output
+/// * `Err(ContractError)` - If the program would reject the swap
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the quote is for different
+/// mints, the quoted output is below `min_output_amount` (the program fails
+/// with `SlippageExceeded`), or the fee cannot be calculated (including an
+/// LP part above the fee rate).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let fees = FeeSettings { fee_bps: 30, burn_bps: 2_500, lp_fee_bps: 10, fee_exempt: false };
+/// let event = preview_swap_executed_event(authority, &swap, &quote, &fees, [0u8; 32], now)?;
+/// assert_eq!(event.treasury_fee + event.burned_fee + event.lp_fee, event.protocol_fee);
+/// ```
+pub fn preview_swap_executed_event(
+    authority: Pubkey,
+    swap: &SwapParams,
+    quote: &JupiterQuote,
+    fees: &FeeSettings,
+    memo_hash: [u8; 32],
+    timestamp: i64,
+) -> Result<SwapExecutedEventData, ContractError> {
+    if quote.input_mint != swap.input_mint || quote.output_mint != swap.output_mint {
+        return Err(ContractError::InvalidAccount(
+            "Quote mints do not match the swap".to_string(),
+        ));
+    }
+
+    let protocol_fee =
+        calculate_protocol_fee(swap.amount, fees.fee_bps, fees.fee_exempt, swap.max_fee)
+            .ok_or_else(|| ContractError::InvalidAccount("Protocol fee overflow".to_string()))?;
+    // The LP part comes off first; the burn share splits what the protocol keeps
+    let (protocol_share, lp_fee) = split_lp_fee(protocol_fee, fees.fee_bps, fees.lp_fee_bps)
+        .ok_or_else(|| ContractError::InvalidAccount("LP fee share exceeds the fee rate".to_string()))?;
+    let (treasury_fee, burned_fee) = split_protocol_fee(protocol_share, fees.burn_bps)
+        .ok_or_else(|| ContractError::InvalidAccount("Invalid burn share".to_string()))?;
+
+    // The preview assumes the swap fills at the quote
+    let output_amount = quote.out_amount;
+    if output_amount < swap.min_output_amount {
+        return Err(ContractError::InvalidAccount(format!(
+            "Quoted output {} is below the minimum output {}",
+            output_amount, swap.min_output_amount
+        )));
+    }
+    let slippage_bps = calculate_slippage_bps(quote.out_amount, output_amount).unwrap_or(0);
+
+    Ok(SwapExecutedEventData {
+        schema_version: EVENT_SCHEMA_VERSION,
+        authority,
+        // `execute_swap` delivers to the authority unless given a recipient
+        recipient: authority,
+        input_amount: swap.amount,
+        output_amount,
+        // Fees are charged on the input side
+        net_output_amount: output_amount,
+        input_mint: swap.input_mint,
+        output_mint: swap.output_mint,
+        protocol_fee,
+        treasury_fee,
+        burned_fee,
+        lp_fee,
+        // Previews price the fee in input tokens
+        fee_denomination: FeeDenomination::Token,
+        // Assumes the whole treasury share arrives (no transfer-fee withholding)
+        fee_collected: treasury_fee,
+        // Previews charge the configured rate without a loyalty discount
+        fee_discount_bps: 0,
+        slippage_bps,
+        memo_hash,
+        tag: swap.tag,
+        // The preview is of a real swap
+        simulated: false,
+        batch: false,
+        leg_index: 0,
+        timestamp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap_and_quote() -> (SwapParams, JupiterQuote) {
+        let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000_000, 95_000)
+            .with_tag(7);
+        let quote = JupiterQuote {
+            input_mint: swap.input_mint,
+            output_mint: swap.output_mint,
+            in_amount: swap.amount,
+            out_amount: 100_000,
+        };
+        (swap, quote)
+    }
+
+    #[test]
+    fn test_preview_matches_program_math() {
+        let (swap, quote) = swap_and_quote();
+        let authority = Pubkey::new_unique();
+        let fees = FeeSettings {
+            burn_bps: 2_500,
+            ..FeeSettings::default()
+        };
+
+        let event = preview_swap_executed_event(authority, &swap, &quote, &fees, [1u8; 32], 42).unwrap();
+
+        assert_eq!(event.authority, authority);
+        assert_eq!(event.recipient, authority);
+        assert_eq!(event.input_amount, 1_000_000);
+        assert_eq!(event.output_amount, 100_000);
+        assert_eq!(event.protocol_fee, 3_000);
+        assert_eq!((event.treasury_fee, event.burned_fee), (2_250, 750));
+        assert_eq!(event.slippage_bps, 0);
+        assert_eq!(event.memo_hash, [1u8; 32]);
+        assert_eq!(event.tag, 7);
+        assert_eq!(event.timestamp, 42);
+    }
+
+    #[test]
+    fn test_preview_exempt_authority_pays_no_fee() {
+        let (swap, quote) = swap_and_quote();
+        let fees = FeeSettings {
+            fee_exempt: true,
+            ..FeeSettings::default()
+        };
+
+        let event =
+            preview_swap_executed_event(Pubkey::new_unique(), &swap, &quote, &fees, [0u8; 32], 0)
+                .unwrap();
+
+        assert_eq!(event.protocol_fee, 0);
+        assert_eq!((event.treasury_fee, event.burned_fee), (0, 0));
+    }
+
+    #[test]
+    fn test_preview_pays_lps_before_burning() {
+        let (swap, quote) = swap_and_quote();
+        let fees = FeeSettings {
+            burn_bps: 2_500,
+            lp_fee_bps: 10,
+            ..FeeSettings::default()
+        };
+
+        let event =
+            preview_swap_executed_event(Pubkey::new_unique(), &swap, &quote, &fees, [0u8; 32], 0)
+                .unwrap();
+
+        // 3_000 fee: 1_000 to LPs, a quarter of the other 2_000 burned
+        assert_eq!(event.lp_fee, 1_000);
+        assert_eq!((event.treasury_fee, event.burned_fee), (1_500, 500));
+    }
+
+    #[test]
+    fn test_preview_rejects_quote_below_minimum() {
+        let (swap, mut quote) = swap_and_quote();
+        quote.out_amount = 94_999;
+
+        assert!(preview_swap_executed_event(
+            Pubkey::new_unique(),
+            &swap,
+            &quote,
+            &FeeSettings::default(),
+            [0u8; 32],
+            0
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_preview_rejects_quote_for_other_mints() {
+        let (swap, mut quote) = swap_and_quote();
+        quote.output_mint = Pubkey::new_unique();
+
+        assert!(preview_swap_executed_event(
+            Pubkey::new_unique(),
+            &swap,
+            &quote,
+            &FeeSettings::default(),
+            [0u8; 32],
+            0
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,121 @@
+//! # Priority Fees
+//!
+//! This module attaches a compute unit price (priority fee) to swap
+//! transactions. `batch_swap` reads the price back from the instructions
+//! sysvar and records it in `BatchSwapEvent::priority_fee_microlamports`, so
+//! analytics can correlate landing rates with the fee paid.
+//!
+//! Setting a price is optional: batches sent without one report 0. The
+//! runtime rejects transactions with two `SetComputeUnitPrice` instructions,
+//! so attach at most one per transaction.
+//!
+//! [`compute_unit_limit_instruction`] builds the companion
+//! `SetComputeUnitLimit` instruction, since the fee paid is the price times
+//! the requested limit.
+//!
+//! ## Transaction Layout
+//!
+//! ```text
+//! 1. ComputeBudget::SetComputeUnitPrice(<micro-lamports>)
+//! 2. <swap instructions>         # route + batch_swap
+//! ```
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::priority_fee::with_compute_unit_price;
+//!
+//! // 25,000 micro-lamports per compute unit
+//! let instructions = with_compute_unit_price(25_000, swap_instructions);
+//! ```
+
+use solana_sdk::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+/// Program ID of the native Compute Budget program
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// First data byte of a `SetComputeUnitLimit` instruction
+pub const SET_COMPUTE_UNIT_LIMIT_TAG: u8 = 2;
+
+/// First data byte of a `SetComputeUnitPrice` instruction
+pub const SET_COMPUTE_UNIT_PRICE_TAG: u8 = 3;
+
+/// Largest compute unit limit a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Build a Compute Budget `SetComputeUnitLimit` instruction
+///
+/// # Arguments
+///
+/// * `units` - Compute units the transaction may consume (at most
+///   [`MAX_COMPUTE_UNIT_LIMIT`])
+///
+/// # Returns
+///
+/// The instruction (tag followed by the little-endian `u32` limit)
+#[must_use]
+pub fn compute_unit_limit_instruction(units: u32) -> Instruction {
+    let mut data = vec![SET_COMPUTE_UNIT_LIMIT_TAG];
+    data.extend(units.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+}
+
+/// Build a Compute Budget `SetComputeUnitPrice` instruction
+///
+/// # Arguments
+///
+/// * `micro_lamports` - Price per compute unit, in micro-lamports
+///
+/// # Returns
+///
+/// The instruction (tag followed by the little-endian `u64` price)
+#[must_use]
+pub fn compute_unit_price_instruction(micro_lamports: u64) -> Instruction {
+    let mut data = vec![SET_COMPUTE_UNIT_PRICE_TAG];
+    data.extend(micro_lamports.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+}
+
+/// Prepend a compute unit price instruction to swap instructions
+///
+/// # Arguments
+///
+/// * `micro_lamports` - Price per compute unit, in micro-lamports
+/// * `swap_instructions` - The swap instructions (route + router instruction)
+///
+/// # Returns
+///
+/// The price instruction followed by `swap_instructions`
+#[must_use]
+pub fn with_compute_unit_price(micro_lamports: u64, swap_instructions: Vec<Instruction>) -> Vec<Instruction> {
+    let mut instructions = Vec::with_capacity(swap_instructions.len() + 1);
+    instructions.push(compute_unit_price_instruction(micro_lamports));
+    instructions.extend(swap_instructions);
+    instructions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_unit_price_is_prepended() {
+        let swap_ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+
+        let ixs = with_compute_unit_price(25_000, vec![swap_ix.clone()]);
+
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(ixs[0].program_id, COMPUTE_BUDGET_PROGRAM_ID);
+        assert_eq!(ixs[0].data, [3, 0xa8, 0x61, 0, 0, 0, 0, 0, 0]);
+        assert!(ixs[0].accounts.is_empty());
+        assert_eq!(ixs[1], swap_ix);
+    }
+
+    #[test]
+    fn test_compute_unit_limit_encoding() {
+        let ix = compute_unit_limit_instruction(200_000);
+
+        assert_eq!(ix.program_id, COMPUTE_BUDGET_PROGRAM_ID);
+        assert_eq!(ix.data, [2, 0x40, 0x0d, 0x03, 0x00]);
+    }
+}
@@ -0,0 +1,269 @@
+//! # Portfolio Rebalancing
+//!
+//! This module turns a target allocation into the batch swaps that reach
+//! it, for rebalancers that think in portfolio weights rather than legs.
+//!
+//! ## Planning
+//!
+//! [`plan_rebalance`] values every holding in the base mint with a Jupiter
+//! quote, compares each mint's value with its target share of the total and
+//! plans one leg per mint that is off target:
+//!
+//! - Over-weight mints are sold into the base mint
+//! - Under-weight mints are bought with the base mint
+//!
+//! The base mint itself is never swapped; its balance is whatever the other
+//! legs leave. Held mints missing from the targets are sold entirely. Every
+//! leg's `min_output_amount` comes from a fresh quote for the exact leg
+//! amount, so no minimum is guessed.
+//!
+//! ## Ordering
+//!
+//! Sells come before buys, and the legs are chunked into batches of at most
+//! [`MAX_BATCH_SIZE`] in that order, so the base tokens a buy spends are
+//! raised by earlier legs. Buy amounts assume the sells fill at their
+//! quotes; a sell that fills near its minimum can leave the last buys short
+//! of base tokens.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::rebalance::plan_rebalance;
+//!
+//! // 60% SOL, 40% USDC, valued and settled in USDC, 0.5% slippage tolerance
+//! let batches = plan_rebalance(
+//!     &QuoteClient::new(),
+//!     &[(sol_mint, 10_000_000_000), (usdc_mint, 500_000_000)],
+//!     &[(sol_mint, 0.6), (usdc_mint, 0.4)],
+//!     usdc_mint,
+//!     50,
+//! )?;
+//! for swaps in batches {
+//!     swap_client.batch_swap(swaps)?;
+//! }
+//! ```
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::chunked::chunk_swaps;
+use crate::error::ContractError;
+use crate::jupiter::QuoteClient;
+use crate::types::{SwapParams, MAX_BATCH_SIZE};
+
+/// Fixed-point scale of target weights (1 part per billion)
+const WEIGHT_SCALE: u128 = 1_000_000_000;
+
+/// Allowed difference between the sum of the target weights and 1, in
+/// parts per billion (covers float rounding of weights like 1/3)
+const WEIGHT_SUM_TOLERANCE: u128 = 1_000;
+
+/// One planned rebalancing leg, before quoting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RebalanceLeg {
+    /// Mint sold
+    pub input_mint: Pubkey,
+
+    /// Mint bought
+    pub output_mint: Pubkey,
+
+    /// Amount sold, in input token units
+    pub amount: u64,
+}
+
+/// Plan the batch swaps that move a portfolio to a target allocation
+///
+/// # Arguments
+///
+/// * `quotes` - Quote client used to value holdings and set minimum outputs
+/// * `holdings` - Current balance of each mint, in raw token units
+/// * `targets` - Target share of the total value per mint (weights sum to 1)
+/// * `base_mint` - Mint holdings are valued in and every leg trades against
+/// * `slippage_bps` - Slippage tolerance applied to each leg's quote
+///
+/// # Returns
+///
+/// * `Ok(Vec<Vec<SwapParams>>)` - The batches to submit, in order (empty if
+///   the portfolio is on target)
+/// * `Err(ContractError)` - If the targets are invalid or a quote fails
+///
+/// # Errors
+///
+/// - `ContractError::InvalidAccount` - Invalid or duplicate holdings or
+///   targets (see [`rebalance_legs`]), or a leg too small for a non-zero
+///   minimum output
+/// - `ContractError::NetworkError` - A quote could not be fetched
+pub fn plan_rebalance(
+    quotes: &QuoteClient,
+    holdings: &[(Pubkey, u64)],
+    targets: &[(Pubkey, f64)],
+    base_mint: Pubkey,
+    slippage_bps: u64,
+) -> Result<Vec<Vec<SwapParams>>, ContractError> {
+    // Value every holding in base tokens (the base mint is its own value)
+    let valued = holdings
+        .iter()
+        .map(|&(mint, amount)| {
+            let value = if mint == base_mint || amount == 0 {
+                amount
+            } else {
+                quotes.fetch_quote(&mint, &base_mint, amount)?.out_amount
+            };
+            Ok((mint, amount, value))
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    let swaps = rebalance_legs(&valued, targets, &base_mint)?
+        .into_iter()
+        .map(|leg| quotes.swap_params_from_quote(leg.input_mint, leg.output_mint, leg.amount, slippage_bps))
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    chunk_swaps(swaps, MAX_BATCH_SIZE)
+}
+
+/// Compute the legs that move valued holdings to a target allocation
+///
+/// # Arguments
+///
+/// * `valued` - `(mint, amount, value)` per holding, with the value in base
+///   token units
+/// * `targets` - Target share of the total value per mint (weights sum to 1)
+/// * `base_mint` - Mint every leg trades against
+///
+/// # Returns
+///
+/// * `Ok(Vec<RebalanceLeg>)` - Sells, then buys (held mints in holding order,
+///   then new mints in target order); mints already on target get no leg
+/// * `Err(ContractError)` - If the holdings or targets are invalid
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if a mint appears twice in
+/// `valued` or `targets`, a weight is negative or not finite, the weights
+/// do not sum to 1, or the total value overflows.
+pub fn rebalance_legs(
+    valued: &[(Pubkey, u64, u64)],
+    targets: &[(Pubkey, f64)],
+    base_mint: &Pubkey,
+) -> Result<Vec<RebalanceLeg>, ContractError> {
+    let weights = targets
+        .iter()
+        .map(|&(mint, weight)| Ok((mint, weight_ppb(weight)?)))
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    reject_duplicates(valued.iter().map(|&(mint, _, _)| mint), "holdings")?;
+    reject_duplicates(weights.iter().map(|&(mint, _)| mint), "targets")?;
+
+    let weight_sum: u128 = weights.iter().map(|&(_, weight)| weight).sum();
+    if weight_sum.abs_diff(WEIGHT_SCALE) > WEIGHT_SUM_TOLERANCE {
+        return Err(ContractError::InvalidAccount(format!(
+            "Target weights sum to {weight_sum} parts per billion, expected {WEIGHT_SCALE}"
+        )));
+    }
+
+    let total_value = valued
+        .iter()
+        .try_fold(0u128, |total, &(_, _, value)| total.checked_add(u128::from(value)))
+        .ok_or_else(|| ContractError::InvalidAccount("Total portfolio value overflows".to_string()))?;
+    let target_value = |mint: &Pubkey| {
+        let weight = weights.iter().find(|(target, _)| target == mint).map_or(0, |&(_, weight)| weight);
+        total_value * weight / WEIGHT_SCALE
+    };
+    let to_u64 = |amount: u128| {
+        u64::try_from(amount).map_err(|_| ContractError::InvalidAccount("Rebalance amount overflows u64".to_string()))
+    };
+
+    let mut legs = Vec::new();
+    let mut buys = Vec::new();
+    for &(mint, amount, value) in valued.iter().filter(|(mint, _, _)| mint != base_mint) {
+        let (value, target) = (u128::from(value), target_value(&mint));
+        if value > target {
+            // Sell the excess share of the holding
+            let sell = to_u64(u128::from(amount) * (value - target) / value)?;
+            if sell > 0 {
+                legs.push(RebalanceLeg { input_mint: mint, output_mint: *base_mint, amount: sell });
+            }
+        } else if value < target {
+            buys.push((mint, target - value));
+        }
+    }
+    // Target mints not held yet are bought from scratch
+    for &(mint, _) in &weights {
+        if mint != *base_mint && !valued.iter().any(|(held, _, _)| *held == mint) {
+            buys.push((mint, target_value(&mint)));
+        }
+    }
+
+    for (mint, spend) in buys {
+        let spend = to_u64(spend)?;
+        if spend > 0 {
+            legs.push(RebalanceLeg { input_mint: *base_mint, output_mint: mint, amount: spend });
+        }
+    }
+    Ok(legs)
+}
+
+/// Convert a target weight to parts per billion
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn weight_ppb(weight: f64) -> Result<u128, ContractError> {
+    if !weight.is_finite() || !(0.0..=1.0).contains(&weight) {
+        return Err(ContractError::InvalidAccount(format!(
+            "Target weight {weight} must be between 0 and 1"
+        )));
+    }
+
+    // In range and non-negative, so the cast cannot truncate or lose the sign
+    Ok((weight * 1e9).round() as u128)
+}
+
+/// Reject a mint listed twice
+fn reject_duplicates(mints: impl Iterator<Item = Pubkey>, what: &str) -> Result<(), ContractError> {
+    let mut seen = Vec::new();
+    for mint in mints {
+        if seen.contains(&mint) {
+            return Err(ContractError::InvalidAccount(format!("Mint {mint} appears twice in the {what}")));
+        }
+        seen.push(mint);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebalance_sells_before_buys() {
+        let [base, sol, bonk, jup] = [(); 4].map(|()| Pubkey::new_unique());
+        // Total value 1,000: SOL 700, BONK 200, base 100
+        let valued = [(sol, 7_000, 700), (bonk, 50_000, 200), (base, 100, 100)];
+        let targets = [(sol, 0.5), (bonk, 0.25), (jup, 0.25)];
+
+        let legs = rebalance_legs(&valued, &targets, &base).unwrap();
+        assert_eq!(
+            legs,
+            vec![
+                // Sell 200 of 700 value: 2/7 of the SOL
+                RebalanceLeg { input_mint: sol, output_mint: base, amount: 2_000 },
+                RebalanceLeg { input_mint: base, output_mint: bonk, amount: 50 },
+                RebalanceLeg { input_mint: base, output_mint: jup, amount: 250 },
+            ]
+        );
+
+        // Already on target: nothing to do
+        let on_target = [(sol, 7_000, 500), (base, 500, 500)];
+        assert!(rebalance_legs(&on_target, &[(sol, 0.5), (base, 0.5)], &base).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rebalance_rejects_invalid_targets() {
+        let (base, sol) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let valued = [(sol, 1_000, 1_000)];
+
+        assert!(rebalance_legs(&valued, &[(sol, 0.5)], &base).is_err());
+        assert!(rebalance_legs(&valued, &[(sol, 1.5), (base, -0.5)], &base).is_err());
+        assert!(rebalance_legs(&valued, &[(sol, f64::NAN)], &base).is_err());
+        assert!(rebalance_legs(&valued, &[(sol, 0.5), (sol, 0.5)], &base).is_err());
+        // Float rounding of thirds is tolerated
+        assert!(rebalance_legs(&valued, &[(sol, 1.0 / 3.0), (base, 2.0 / 3.0)], &base).is_ok());
+    }
+}
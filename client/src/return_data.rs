@@ -0,0 +1,157 @@
+//! # Instruction Return Data
+//!
+//! This module decodes the return data of `execute_swap`: the output amount,
+//! protocol fee and realized slippage of the swap. Programs calling the router
+//! via CPI read it with `get_return_data`; off-chain callers get it from
+//! `simulateTransaction`, to check the slippage a swap would realize before
+//! sending it.
+//!
+//! ## Format
+//!
+//! `simulateTransaction` reports return data as the ID of the program that
+//! set it and the data, base64-encoded. The data is the borsh-encoded
+//! [`SwapReturnData`], without a discriminator. Return data is per
+//! transaction, so only the last instruction that sets it is visible: put
+//! `execute_swap` last, or the data may belong to another program.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::return_data::decode_swap_return_data;
+//!
+//! let result = rpc.simulate_transaction(&transaction)?.value;
+//! let return_data = result.return_data.ok_or("no return data")?;
+//! let swap = decode_swap_return_data(&program_id, &return_data.program_id, &return_data.data.0)?;
+//! if swap.slippage_bps > 50 {
+//!     // Requote instead of sending
+//! }
+//! ```
+
+use anchor_lang::prelude::borsh;
+use anchor_lang::AnchorDeserialize;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+
+/// Outcome of `execute_swap`, as set in its return data
+///
+/// Mirrors the program's `SwapReturnData`. The values match the
+/// `SwapExecutedEvent` of the same swap.
+#[derive(AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapReturnData {
+    /// Output tokens received by the delivery account
+    pub output_amount: u64,
+
+    /// Protocol fee charged, in input token units
+    pub protocol_fee: u64,
+
+    /// Realized slippage against `expected_output`, in basis points
+    pub slippage_bps: u64,
+}
+
+impl TryFrom<&[u8]> for SwapReturnData {
+    type Error = ContractError;
+
+    /// Decode raw (base64-decoded) return data
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        Self::try_from_slice(data).map_err(|e| ContractError::SerializationError(e.to_string()))
+    }
+}
+
+/// Decode `execute_swap` return data as reported by `simulateTransaction`
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+/// * `return_program_id` - The program that set the return data (`returnData.programId`)
+/// * `data_base64` - The base64-encoded return data (`returnData.data[0]`)
+///
+/// # Returns
+///
+/// * `Ok(SwapReturnData)` - The swap outcome
+/// * `Err(ContractError)` - If the data is not `execute_swap` return data
+///
+/// # Errors
+///
+/// - `ContractError::InvalidAccount` - The return data was set by another program
+/// - `ContractError::SerializationError` - The data is not valid base64 or
+///   not a `SwapReturnData`
+pub fn decode_swap_return_data(
+    program_id: &Pubkey,
+    return_program_id: &str,
+    data_base64: &str,
+) -> Result<SwapReturnData, ContractError> {
+    let data = router_return_data(program_id, return_program_id, data_base64)?;
+    SwapReturnData::try_from(data.as_slice())
+}
+
+/// Check return data was set by the router and base64-decode it
+///
+/// # Errors
+///
+/// Same as [`decode_swap_return_data`], except that the decoded bytes are
+/// not interpreted.
+pub(crate) fn router_return_data(
+    program_id: &Pubkey,
+    return_program_id: &str,
+    data_base64: &str,
+) -> Result<Vec<u8>, ContractError> {
+    if return_program_id != program_id.to_string() {
+        return Err(ContractError::InvalidAccount(format!(
+            "Return data was set by {return_program_id}, not the batch swap router"
+        )));
+    }
+
+    STANDARD
+        .decode(data_base64)
+        .map_err(|e| ContractError::SerializationError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(output_amount: u64, protocol_fee: u64, slippage_bps: u64) -> String {
+        let mut data = output_amount.to_le_bytes().to_vec();
+        data.extend_from_slice(&protocol_fee.to_le_bytes());
+        data.extend_from_slice(&slippage_bps.to_le_bytes());
+        STANDARD.encode(data)
+    }
+
+    #[test]
+    fn test_decode_swap_return_data() {
+        let program_id = Pubkey::new_unique();
+
+        let swap = decode_swap_return_data(&program_id, &program_id.to_string(), &encoded(950, 3, 52)).unwrap();
+
+        assert_eq!(
+            swap,
+            SwapReturnData {
+                output_amount: 950,
+                protocol_fee: 3,
+                slippage_bps: 52,
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_other_programs_and_bad_data() {
+        let program_id = Pubkey::new_unique();
+        let other = Pubkey::new_unique().to_string();
+
+        assert!(matches!(
+            decode_swap_return_data(&program_id, &other, &encoded(950, 3, 52)),
+            Err(ContractError::InvalidAccount(_))
+        ));
+        assert!(matches!(
+            decode_swap_return_data(&program_id, &program_id.to_string(), "not base64!"),
+            Err(ContractError::SerializationError(_))
+        ));
+        assert!(matches!(
+            decode_swap_return_data(&program_id, &program_id.to_string(), &STANDARD.encode([1u8; 8])),
+            Err(ContractError::SerializationError(_))
+        ));
+    }
+}
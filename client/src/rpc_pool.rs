@@ -0,0 +1,331 @@
+//! # RPC Failover
+//!
+//! This module spreads a bot's RPC traffic over several endpoints so a flaky
+//! or rate-limited node does not stop it. [`RpcPool`] runs each request
+//! against the current endpoint and rotates to the next one when it fails
+//! with `ContractError::NetworkError`. Any other error (an invalid swap, a
+//! failed transaction, ...) is returned as is: another node would not change
+//! the outcome.
+//!
+//! ## Circuit Breaker
+//!
+//! Each endpoint counts its consecutive network failures. After
+//! [`RpcPool::failure_threshold`] of them the endpoint is skipped for
+//! [`RpcPool::cooldown`]; afterwards it gets a single trial request, which
+//! closes the circuit on success or skips it for another cooldown on failure.
+//! A request fails with `NetworkError` only when every endpoint failed or is
+//! cooling down.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::rpc_pool::RpcPool;
+//!
+//! let mut pool = RpcPool::new(vec![
+//!     "https://rpc-a.example.com".to_string(),
+//!     "https://rpc-b.example.com".to_string(),
+//! ]);
+//!
+//! let signature = pool.with_client(&payer, |client| {
+//!     let program = client
+//!         .program(get_batch_swap_router_program_id())
+//!         .map_err(|e| ContractError::ClientError(e.to_string()))?;
+//!     BatchSwapRouterClient::new(program).batch_swap(swaps.clone())
+//! })?;
+//! ```
+
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use anchor_client::Client;
+use solana_sdk::signature::Signer;
+
+use crate::create_client;
+use crate::error::ContractError;
+
+/// Consecutive network failures after which an endpoint is skipped
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// How long an endpoint is skipped once its circuit opens
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Health of a single RPC endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// RPC URL of the endpoint
+    pub url: String,
+
+    /// Network failures since the last successful request
+    pub consecutive_failures: u32,
+
+    /// When the circuit last opened, while it is open
+    opened_at: Option<Instant>,
+}
+
+impl Endpoint {
+    /// Whether a request may be sent to this endpoint at `now`
+    fn is_available(&self, now: Instant, cooldown: Duration) -> bool {
+        !matches!(self.opened_at, Some(opened_at) if now.saturating_duration_since(opened_at) < cooldown)
+    }
+}
+
+/// RPC endpoints with failover and a per-endpoint circuit breaker
+///
+/// # Example
+///
+/// ```rust
+/// use xforce_terminal_contracts_client::rpc_pool::RpcPool;
+/// use xforce_terminal_contracts_client::ContractError;
+///
+/// let mut pool = RpcPool::new(vec!["http://down:8899".to_string(), "http://up:8899".to_string()]);
+///
+/// let slot = pool.call(|url| {
+///     if url.contains("down") {
+///         Err(ContractError::NetworkError("connection refused".to_string()))
+///     } else {
+///         Ok(42)
+///     }
+/// });
+/// assert_eq!(slot.unwrap(), 42);
+/// assert_eq!(pool.current_url(), "http://up:8899");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RpcPool {
+    /// The endpoints, in failover order
+    endpoints: Vec<Endpoint>,
+
+    /// Index of the endpoint requests start from
+    current: usize,
+
+    /// Consecutive network failures that open an endpoint's circuit
+    pub failure_threshold: u32,
+
+    /// How long an open circuit skips its endpoint
+    pub cooldown: Duration,
+}
+
+impl RpcPool {
+    /// Create a pool with [`DEFAULT_FAILURE_THRESHOLD`] and [`DEFAULT_COOLDOWN`]
+    ///
+    /// # Arguments
+    ///
+    /// * `urls` - RPC URLs in failover order; requests start at the first one
+    ///
+    /// # Panics
+    ///
+    /// Panics if `urls` is empty.
+    #[must_use]
+    pub fn new(urls: Vec<String>) -> Self {
+        assert!(!urls.is_empty(), "RpcPool needs at least one RPC URL");
+
+        Self {
+            endpoints: urls
+                .into_iter()
+                .map(|url| Endpoint {
+                    url,
+                    consecutive_failures: 0,
+                    opened_at: None,
+                })
+                .collect(),
+            current: 0,
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+
+    /// The endpoints and their health, in failover order
+    #[must_use]
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+
+    /// URL of the endpoint the next request starts from
+    #[must_use]
+    pub fn current_url(&self) -> &str {
+        &self.endpoints[self.current].url
+    }
+
+    /// Run `request` with failover, starting from the current endpoint
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Sends the request to the given RPC URL
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(T)` - The result of the first endpoint that did not fail with a
+    ///   network error
+    /// * `Err(ContractError)` - The request's non-network error, or a network
+    ///   error if no endpoint succeeded
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::call_at`].
+    pub fn call<T, F>(&mut self, request: F) -> Result<T, ContractError>
+    where
+        F: FnMut(&str) -> Result<T, ContractError>,
+    {
+        self.call_at(Instant::now(), request)
+    }
+
+    /// Run `request` with failover, as of `now`
+    ///
+    /// Endpoints are tried in order starting from the current one, skipping
+    /// those whose circuit is open. The endpoint that answers becomes the
+    /// current one.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current instant
+    /// * `request` - Sends the request to the given RPC URL
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(T)` - The result of the first endpoint that did not fail with a
+    ///   network error
+    /// * `Err(ContractError)` - The request's non-network error, or a network
+    ///   error if no endpoint succeeded
+    ///
+    /// # Errors
+    ///
+    /// - Any error of `request` other than `NetworkError`, unchanged
+    /// - `ContractError::NetworkError` - Every available endpoint failed (the
+    ///   last failure is returned), or every circuit is open
+    pub fn call_at<T, F>(&mut self, now: Instant, mut request: F) -> Result<T, ContractError>
+    where
+        F: FnMut(&str) -> Result<T, ContractError>,
+    {
+        let mut last_error = None;
+
+        for offset in 0..self.endpoints.len() {
+            let index = (self.current + offset) % self.endpoints.len();
+            if !self.endpoints[index].is_available(now, self.cooldown) {
+                continue;
+            }
+
+            match request(&self.endpoints[index].url) {
+                Err(ContractError::NetworkError(message)) => {
+                    self.record_failure(index, now);
+                    last_error = Some(ContractError::NetworkError(format!(
+                        "{}: {message}",
+                        self.endpoints[index].url
+                    )));
+                }
+                result => {
+                    // Any answer shows the endpoint is reachable
+                    let endpoint = &mut self.endpoints[index];
+                    endpoint.consecutive_failures = 0;
+                    endpoint.opened_at = None;
+                    self.current = index;
+                    return result;
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            ContractError::NetworkError("All RPC endpoints are cooling down".to_string())
+        }))
+    }
+
+    /// Run `request` with a client for each endpoint, with failover
+    ///
+    /// # Arguments
+    ///
+    /// * `payer` - The keypair that will pay for transactions
+    /// * `request` - Uses the client built by [`create_client`]; should map
+    ///   RPC failures to `ContractError::NetworkError`
+    ///
+    /// # Returns
+    ///
+    /// Same as [`Self::call`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::call_at`].
+    pub fn with_client<C, T, F>(&mut self, payer: &C, mut request: F) -> Result<T, ContractError>
+    where
+        C: Clone + Signer + 'static,
+        F: FnMut(&Client<Rc<C>>) -> Result<T, ContractError>,
+    {
+        self.call(|url| request(&create_client(url, payer.clone())))
+    }
+
+    /// Count a network failure, opening the circuit at the threshold
+    fn record_failure(&mut self, index: usize, now: Instant) {
+        let endpoint = &mut self.endpoints[index];
+        endpoint.consecutive_failures = endpoint.consecutive_failures.saturating_add(1);
+        if endpoint.consecutive_failures >= self.failure_threshold {
+            endpoint.opened_at = Some(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn pool() -> RpcPool {
+        RpcPool::new(vec!["http://first".to_string(), "http://second".to_string()])
+    }
+
+    fn first_always_fails(url: &str) -> Result<&'static str, ContractError> {
+        if url == "http://first" {
+            Err(ContractError::NetworkError("connection refused".to_string()))
+        } else {
+            Ok("ok")
+        }
+    }
+
+    #[test]
+    fn test_fails_over_to_the_second_endpoint() {
+        let mut pool = pool();
+        let now = Instant::now();
+
+        assert_eq!(pool.call_at(now, first_always_fails).unwrap(), "ok");
+        assert_eq!(pool.current_url(), "http://second");
+        assert_eq!(pool.endpoints()[0].consecutive_failures, 1);
+        assert_eq!(pool.endpoints()[1].consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_circuit_opens_and_retries_after_cooldown() {
+        let mut pool = pool();
+        let start = Instant::now();
+        let tried = Cell::new(0);
+        let request = |_: &str| {
+            tried.set(tried.get() + 1);
+            Err::<(), _>(ContractError::NetworkError("timeout".to_string()))
+        };
+
+        for _ in 0..DEFAULT_FAILURE_THRESHOLD {
+            assert!(pool.call_at(start, request).is_err());
+        }
+        assert_eq!(tried.get(), 2 * DEFAULT_FAILURE_THRESHOLD);
+
+        // Both circuits are open: nothing is sent until the cooldown ends
+        assert!(matches!(
+            pool.call_at(start + Duration::from_secs(1), request),
+            Err(ContractError::NetworkError(_))
+        ));
+        assert_eq!(tried.get(), 2 * DEFAULT_FAILURE_THRESHOLD);
+
+        assert_eq!(pool.call_at(start + DEFAULT_COOLDOWN, first_always_fails).unwrap(), "ok");
+        assert_eq!(pool.endpoints()[1].consecutive_failures, 0);
+    }
+
+    #[test]
+    fn test_other_errors_do_not_fail_over() {
+        let mut pool = pool();
+        let mut calls = 0;
+
+        let result = pool.call(|_| {
+            calls += 1;
+            Err::<(), _>(ContractError::TransactionFailed("slippage".to_string()))
+        });
+
+        assert!(matches!(result, Err(ContractError::TransactionFailed(_))));
+        assert_eq!(calls, 1);
+        assert_eq!(pool.endpoints()[0].consecutive_failures, 0);
+    }
+}
@@ -29,7 +29,7 @@
 
 use solana_sdk::pubkey::Pubkey;
 use crate::error::ContractError;
-use crate::types::SwapParams;
+use crate::types::{MAX_SLIPPAGE_BPS, SwapParams};
 
 /// Validate that a public key is not the default/null key
 ///
@@ -149,9 +149,68 @@ pub fn validate_swap_params(params: &SwapParams, min_amount: u64) -> Result<(),
         ));
     }
 
+    // Validate fee cap (the program rejects a zero cap)
+    if params.max_fee == Some(0) {
+        return Err(ContractError::InvalidAccount(
+            "Maximum fee must be greater than 0 when set".to_string(),
+        ));
+    }
+
+    // Validate slippage tolerance (a swap may only tighten the global limit)
+    if let Some(bps) = params.max_slippage_bps {
+        assert_valid_slippage(u64::from(bps), u64::from(MAX_SLIPPAGE_BPS))?;
+    }
+
     Ok(())
 }
 
+/// Validate every swap of a batch, collecting all invalid legs
+///
+/// Unlike [`validate_swap_params`], which stops at the first problem, this
+/// reports every invalid leg at once, e.g. to highlight all bad rows of a
+/// batch form. Each leg is checked with [`validate_swap_params`] against the
+/// program's minimum amount (`MIN_SWAP_AMOUNT`, 1); the batch size itself is
+/// checked by [`assert_valid_batch_size`].
+///
+/// # Arguments
+///
+/// * `swaps` - The swaps of the batch, in leg order
+///
+/// # Returns
+///
+/// The index and reason of each invalid leg, in leg order (empty if every
+/// leg is valid). A leg with several problems reports the first one.
+///
+/// # Example
+///
+/// ```rust
+/// use xforce_terminal_contracts_client::{validate_batch, SwapParams};
+/// use solana_sdk::pubkey::Pubkey;
+///
+/// let mint = Pubkey::new_unique();
+/// let swaps = vec![
+///     SwapParams::new(Pubkey::new_unique(), mint, 0, 900),
+///     SwapParams::new(Pubkey::new_unique(), mint, 1_000, 900),
+///     SwapParams::new(mint, mint, 1_000, 900),
+/// ];
+///
+/// let errors = validate_batch(&swaps);
+/// assert_eq!(errors.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![0, 2]);
+/// ```
+#[must_use]
+pub fn validate_batch(swaps: &[SwapParams]) -> Vec<(usize, String)> {
+    swaps
+        .iter()
+        .enumerate()
+        .filter_map(|(index, swap)| {
+            validate_swap_params(swap, 1).err().map(|error| match error {
+                ContractError::InvalidAccount(reason) => (index, reason),
+                error => (index, error.to_string()),
+            })
+        })
+        .collect()
+}
+
 /// Validate slippage tolerance
 ///
 /// # Arguments
@@ -206,6 +265,132 @@ pub fn calculate_slippage_bps(expected: u64, actual: u64) -> Option<u64> {
     u64::try_from(slippage_bps).ok()
 }
 
+/// Calculate the protocol fee the program charges on a swap
+///
+/// Mirrors the program's `calculate_protocol_fee`: the fee is
+/// `amount * fee_bps / 10_000`, rounded down and capped at `max_fee`, and
+/// zero for fee-exempt authorities.
+///
+/// # Arguments
+///
+/// * `amount` - The swap input amount
+/// * `fee_bps` - The protocol fee rate in basis points (`Config::fee_bps`)
+/// * `fee_exempt` - Whether the authority is on the config's exemption list
+/// * `max_fee` - Optional absolute cap on the fee (`SwapParams::max_fee`)
+///
+/// # Returns
+///
+/// * `Option<u64>` - The protocol fee, or None if calculation fails
+#[must_use]
+pub fn calculate_protocol_fee(
+    amount: u64,
+    fee_bps: u16,
+    fee_exempt: bool,
+    max_fee: Option<u64>,
+) -> Option<u64> {
+    if fee_exempt {
+        return Some(0);
+    }
+
+    let fee = u128::from(amount)
+        .checked_mul(u128::from(fee_bps))?
+        .checked_div(10000)?;
+
+    let fee = u64::try_from(fee).ok()?;
+    Some(max_fee.map_or(fee, |max_fee| fee.min(max_fee)))
+}
+
+/// Scale a minimum output from the gross amount to the amount actually swapped
+///
+/// The program takes the protocol fee out of the input before swapping, so
+/// only `amount - fee` reaches the DEX. A `min_output_amount` derived from a
+/// quote for the gross `amount` therefore asks for slightly more than the
+/// swap can deliver, and a swap that fills within tolerance can still fail
+/// with `SlippageExceeded`. This scales the minimum by `(amount - fee) / amount`,
+/// rounding down, so it applies to the net amount instead.
+///
+/// Quotes already requested for the net amount need no adjustment.
+///
+/// # Arguments
+///
+/// * `min_output` - Minimum output derived for the gross `amount`
+/// * `amount` - The swap input amount, fee included
+/// * `fee` - The protocol fee (see [`calculate_protocol_fee`])
+///
+/// # Returns
+///
+/// * `Option<u64>` - The minimum output for `amount - fee`, or None if
+///   `amount` is 0, the fee exceeds it, or the adjusted minimum is 0 (the
+///   program rejects a zero minimum)
+#[must_use]
+pub fn adjust_min_output_for_fee(min_output: u64, amount: u64, fee: u64) -> Option<u64> {
+    let swapped = amount.checked_sub(fee)?;
+    let adjusted = u128::from(min_output)
+        .checked_mul(u128::from(swapped))?
+        .checked_div(u128::from(amount))?;
+
+    // Never larger than `min_output`, so the conversion cannot fail
+    u64::try_from(adjusted).ok().filter(|adjusted| *adjusted > 0)
+}
+
+/// Split a protocol fee into its treasury and burned shares
+///
+/// Mirrors the program's `split_protocol_fee`: the burned share is
+/// `protocol_fee * burn_bps / 10_000`, rounded down, and the treasury
+/// receives the rest.
+///
+/// # Arguments
+///
+/// * `protocol_fee` - The full protocol fee
+/// * `burn_bps` - Share of the fee burned, in basis points (`Config::burn_bps`)
+///
+/// # Returns
+///
+/// * `Option<(u64, u64)>` - `(treasury_fee, burned_fee)`, or None if
+///   calculation fails
+#[must_use]
+pub fn split_protocol_fee(protocol_fee: u64, burn_bps: u16) -> Option<(u64, u64)> {
+    let burned = u128::from(protocol_fee)
+        .checked_mul(u128::from(burn_bps))?
+        .checked_div(10000)?;
+    let burned = u64::try_from(burned).ok()?;
+
+    Some((protocol_fee.checked_sub(burned)?, burned))
+}
+
+/// Split a fee into its protocol and liquidity-provider parts
+///
+/// Mirrors the program's `split_lp_fee`: the LP part is
+/// `fee * lp_fee_bps / fee_bps`, rounded down, and the protocol keeps the
+/// rest.
+///
+/// # Arguments
+///
+/// * `fee` - The full fee
+/// * `fee_bps` - The fee rate (`Config::fee_bps`)
+/// * `lp_fee_bps` - Part of the rate paid to LPs (`Config::lp_fee_bps`)
+///
+/// # Returns
+///
+/// * `Option<(u64, u64)>` - `(protocol_fee, lp_fee)`, or None if
+///   `lp_fee_bps` exceeds `fee_bps` (the program rejects such a config)
+#[must_use]
+pub fn split_lp_fee(fee: u64, fee_bps: u16, lp_fee_bps: u16) -> Option<(u64, u64)> {
+    if lp_fee_bps > fee_bps {
+        return None;
+    }
+    if lp_fee_bps == 0 {
+        return Some((fee, 0));
+    }
+
+    let lp_fee = u128::from(fee)
+        .checked_mul(u128::from(lp_fee_bps))?
+        .checked_div(u128::from(fee_bps))?;
+    let lp_fee = u64::try_from(lp_fee).ok()?;
+
+    Some((fee.checked_sub(lp_fee)?, lp_fee))
+}
+
 /// Validate batch size
 ///
 /// # Arguments
@@ -266,6 +451,24 @@ mod tests {
         assert!(assert_different_pubkeys(&key1, &key1).is_err());
     }
 
+    #[test]
+    fn test_validate_batch_reports_every_invalid_leg() {
+        let mint = Pubkey::new_unique();
+        let valid = SwapParams::new(Pubkey::new_unique(), mint, 1_000, 900);
+        let swaps = vec![
+            valid.clone(),
+            SwapParams { min_output_amount: 0, ..valid.clone() },
+            valid.clone(),
+            SwapParams { max_slippage_bps: Some(MAX_SLIPPAGE_BPS + 1), ..valid.clone() },
+            SwapParams { input_mint: Pubkey::default(), ..valid.clone() },
+        ];
+
+        let errors = validate_batch(&swaps);
+        assert_eq!(errors.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![1, 3, 4]);
+        assert!(errors[0].1.contains("Minimum output"));
+        assert!(validate_batch(&[valid]).is_empty());
+    }
+
     #[test]
     fn test_calculate_slippage_bps() {
         assert_eq!(calculate_slippage_bps(100, 95), Some(500)); // 5% slippage
@@ -273,5 +476,51 @@ mod tests {
         assert_eq!(calculate_slippage_bps(100, 105), Some(0)); // Better than expected
         assert_eq!(calculate_slippage_bps(0, 100), None); // Division by zero
     }
+
+    #[test]
+    fn test_calculate_protocol_fee() {
+        assert_eq!(calculate_protocol_fee(1_000_000, 30, false, None), Some(3_000)); // 0.3%
+        assert_eq!(calculate_protocol_fee(333, 30, false, None), Some(0)); // Rounds down
+        assert_eq!(calculate_protocol_fee(1_000_000, 30, true, None), Some(0)); // Exempt
+        assert_eq!(calculate_protocol_fee(1_000_000, 30, false, Some(1_000)), Some(1_000)); // Capped
+        assert_eq!(calculate_protocol_fee(u64::MAX, 10_000, false, None), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_adjust_min_output_for_fee() {
+        // 1:1 pair, 0.1% tolerance on a quote for the gross amount, 0.3% fee
+        let (amount, tolerance_bps) = (1_000_000u64, 10u64);
+        let naive_min = amount * (10_000 - tolerance_bps) / 10_000;
+        let fee = calculate_protocol_fee(amount, 30, false, None).unwrap();
+        let filled = amount - fee; // A fill at the quoted rate
+
+        assert!(filled < naive_min); // The naive minimum rejects it
+        let adjusted = adjust_min_output_for_fee(naive_min, amount, fee).unwrap();
+        assert_eq!(adjusted, 996_003);
+        assert!(filled >= adjusted);
+        // A fill beyond the tolerance still fails
+        assert!(adjusted > filled * (10_000 - 2 * tolerance_bps) / 10_000);
+
+        assert_eq!(adjust_min_output_for_fee(naive_min, amount, 0), Some(naive_min));
+        assert_eq!(adjust_min_output_for_fee(900, 1_000, 1_001), None);
+        assert_eq!(adjust_min_output_for_fee(900, 0, 0), None);
+        assert_eq!(adjust_min_output_for_fee(1, 1_000, 3), None); // Rounds to 0
+    }
+
+    #[test]
+    fn test_split_protocol_fee() {
+        assert_eq!(split_protocol_fee(3_000, 0), Some((3_000, 0)));
+        assert_eq!(split_protocol_fee(3_000, 2_500), Some((2_250, 750)));
+        assert_eq!(split_protocol_fee(3, 5_000), Some((2, 1))); // Burn rounds down
+        assert_eq!(split_protocol_fee(3_000, 10_000), Some((0, 3_000)));
+    }
+
+    #[test]
+    fn test_split_lp_fee() {
+        assert_eq!(split_lp_fee(3_000, 30, 10), Some((2_000, 1_000)));
+        assert_eq!(split_lp_fee(3_000, 30, 0), Some((3_000, 0)));
+        assert_eq!(split_lp_fee(2, 30, 10), Some((2, 0))); // LP part rounds down
+        assert_eq!(split_lp_fee(3_000, 10, 30), None);
+    }
 }
 
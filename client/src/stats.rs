@@ -0,0 +1,156 @@
+//! # On-Chain Stats
+//!
+//! This module contains typed mirrors of the program's analytics accounts.
+//! Their PDA derivation helpers live in [`crate::pda`] and are re-exported here.
+//!
+//! ## Accounts
+//!
+//! - [`Stats`] - Program-wide swap counters (seeds: `[b"stats"]`)
+//! - [`PairStats`] - Per-pair counters (seeds: `[b"pair_stats", input_mint, output_mint]`)
+//!
+//! Pairs are directional: A -> B and B -> A are tracked in separate accounts.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::pda::pair_stats_pda;
+//!
+//! let (address, _bump) = pair_stats_pda(&program_id, &sol_mint, &usdc_mint);
+//! let pair_stats = swap_client.fetch_pair_stats(sol_mint, usdc_mint)?;
+//! println!("{} swaps", pair_stats.swap_count);
+//! ```
+
+use anchor_lang::prelude::borsh;
+use anchor_lang::AnchorDeserialize;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::ContractError;
+
+pub use crate::pda::{pair_stats_pda, stats_pda, PAIR_STATS_SEED, STATS_SEED};
+
+/// Anchor account discriminator of `Stats` (`sha256("account:Stats")[..8]`)
+const STATS_DISCRIMINATOR: [u8; 8] = [190, 125, 51, 63, 169, 197, 36, 238];
+
+/// Anchor account discriminator of `PairStats` (`sha256("account:PairStats")[..8]`)
+const PAIR_STATS_DISCRIMINATOR: [u8; 8] = [14, 35, 141, 74, 246, 178, 213, 125];
+
+/// Program-wide swap analytics
+///
+/// Mirrors the program's `Stats` account. Volumes and fees are summed across
+/// all mints in raw token units.
+#[derive(AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of swaps recorded (each batch leg counts as one swap)
+    pub total_swaps: u64,
+
+    /// Sum of input amounts, in raw token units
+    pub total_volume: u128,
+
+    /// Sum of protocol fees, in raw token units
+    pub total_fees: u128,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Per-pair swap analytics
+///
+/// Mirrors the program's `PairStats` account.
+#[derive(AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PairStats {
+    /// Input token mint of the pair
+    pub input_mint: Pubkey,
+
+    /// Output token mint of the pair
+    pub output_mint: Pubkey,
+
+    /// Number of swaps recorded for the pair
+    pub swap_count: u64,
+
+    /// Sum of input amounts, in input token units
+    pub total_input: u128,
+
+    /// Sum of output amounts, in output token units
+    pub total_output: u128,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Decode raw `Stats` account data
+///
+/// # Arguments
+///
+/// * `data` - The account data, including the 8-byte discriminator
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the discriminator does not
+/// match, or `ContractError::SerializationError` if the data cannot be decoded.
+pub fn decode_stats(data: &[u8]) -> Result<Stats, ContractError> {
+    decode_account(data, STATS_DISCRIMINATOR, "Stats")
+}
+
+/// Decode raw `PairStats` account data
+///
+/// # Arguments
+///
+/// * `data` - The account data, including the 8-byte discriminator
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the discriminator does not
+/// match, or `ContractError::SerializationError` if the data cannot be decoded.
+pub fn decode_pair_stats(data: &[u8]) -> Result<PairStats, ContractError> {
+    decode_account(data, PAIR_STATS_DISCRIMINATOR, "PairStats")
+}
+
+/// Check the discriminator and borsh-decode the rest of the account data
+pub(crate) fn decode_account<T: AnchorDeserialize>(
+    data: &[u8],
+    discriminator: [u8; 8],
+    name: &str,
+) -> Result<T, ContractError> {
+    let body = data
+        .strip_prefix(discriminator.as_slice())
+        .ok_or_else(|| ContractError::InvalidAccount(format!("Account is not a {name} account")))?;
+
+    // Accounts are allocated at their maximum size, so trailing bytes are expected
+    T::deserialize(&mut &body[..]).map_err(|e| ContractError::SerializationError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats_bytes() -> Vec<u8> {
+        let mut data = STATS_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(&1_000u128.to_le_bytes());
+        data.extend_from_slice(&3u128.to_le_bytes());
+        data.push(254);
+        data
+    }
+
+    #[test]
+    fn test_decode_stats() {
+        let stats = decode_stats(&stats_bytes()).unwrap();
+
+        assert_eq!(
+            stats,
+            Stats {
+                total_swaps: 7,
+                total_volume: 1_000,
+                total_fees: 3,
+                bump: 254,
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_wrong_discriminator() {
+        let result = decode_pair_stats(&stats_bytes());
+
+        assert!(matches!(result, Err(ContractError::InvalidAccount(_))));
+    }
+}
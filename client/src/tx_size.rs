@@ -0,0 +1,510 @@
+//! # Transaction Size Checks
+//!
+//! This module measures constructed transactions against the packet size
+//! limit before they are sent. A batch can stay within `MAX_BATCH_SIZE` and
+//! still be too large once every leg's route accounts and data are included;
+//! the RPC node then rejects it with an opaque error. Checking client-side
+//! turns that into [`ContractError::TransactionTooLarge`] with the measured
+//! size.
+//!
+//! ## Limits
+//!
+//! Legacy and v0 transactions share the same 1232-byte limit
+//! ([`MAX_TRANSACTION_SIZE`]). v0 transactions usually fit more legs because
+//! accounts stored in an Address Lookup Table cost 1 byte instead of 32 (see
+//! [`crate::lookup_table`]).
+//!
+//! ## Account Limits
+//!
+//! A legacy transaction lists every account key inline, so in practice it
+//! tops out at about [`MAX_LEGACY_WRITABLE_ACCOUNTS`] writable accounts and
+//! [`MAX_LEGACY_ACCOUNTS`] accounts in total (the runtime's account lock
+//! limit) well before a batch reaches `MAX_BATCH_SIZE`. Past that, the
+//! failure surfaces as an opaque size or lock error from the RPC node.
+//! [`check_legacy_accounts`] counts the unique accounts of the built
+//! instructions first and rejects with [`ContractError::TooManyAccounts`],
+//! pointing at a v0 transaction with an Address Lookup Table.
+//!
+//! ## Distinct Mints
+//!
+//! Every distinct mint of a batch brings its own token accounts and route
+//! accounts, so the mint count predicts an oversized transaction before any
+//! instruction is built. [`check_distinct_mints`] rejects batches over a
+//! threshold ([`DEFAULT_MAX_DISTINCT_MINTS`] or the config's
+//! `max_distinct_mints`, which the program enforces as well).
+//!
+//! ## Estimates Before Building
+//!
+//! [`estimate_transaction_size`] predicts a batch's account count and size
+//! from its swaps alone, before any quote is fetched or instruction built,
+//! so callers can pick a legacy or v0 transaction (or split the batch) up
+//! front. It resolves the accounts the batch will reference: the router's
+//! fixed accounts and programs, each distinct mint with the authority's
+//! token account for it, and optionally a typical Jupiter route per leg.
+//! Route sizes vary by DEX, so the estimate is a guide; measure the built
+//! transaction with [`legacy_transaction_size`] before sending.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::tx_size::{check_distinct_mints, check_legacy_accounts, check_transaction_size, estimate_transaction_size, legacy_transaction_size};
+//!
+//! check_distinct_mints(&swaps, DEFAULT_MAX_DISTINCT_MINTS)?; // TooManyMints: split the batch
+//!
+//! if !estimate_transaction_size(&swaps, true).fits_legacy {
+//!     // Build a v0 transaction with a lookup table instead
+//! }
+//!
+//! check_legacy_accounts(&instructions, &payer)?; // TooManyAccounts: use v0 + ALT
+//! let size = legacy_transaction_size(&instructions, &payer);
+//! check_transaction_size(size)?; // TransactionTooLarge: split the batch
+//! ```
+
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, AddressLookupTableAccount, Message, VersionedMessage},
+    pubkey::Pubkey,
+};
+
+use crate::error::ContractError;
+use crate::types::SwapParams;
+
+/// Maximum serialized transaction size, in bytes
+///
+/// Matches Solana's `PACKET_DATA_SIZE` (1280-byte IPv6 MTU minus headers).
+/// The same limit applies to legacy and v0 transactions.
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+
+/// Practical maximum of writable accounts in a legacy transaction
+///
+/// Each writable account is an inline 32-byte key, usually next to read-only
+/// route accounts; past about 35 the transaction no longer fits in
+/// [`MAX_TRANSACTION_SIZE`].
+pub const MAX_LEGACY_WRITABLE_ACCOUNTS: usize = 35;
+
+/// Maximum of unique accounts in a legacy transaction
+///
+/// Matches the runtime's per-transaction account lock limit.
+pub const MAX_LEGACY_ACCOUNTS: usize = 64;
+
+/// Default preflight threshold of distinct mints per batch
+///
+/// A legacy transaction fits roughly 35 account keys; with token accounts
+/// and route accounts per mint, batches over 8 mints rarely fit.
+pub const DEFAULT_MAX_DISTINCT_MINTS: usize = 8;
+
+/// Size of one ed25519 signature, in bytes
+const SIGNATURE_SIZE: usize = 64;
+
+/// Accounts of the `batch_swap` instruction, including the router program
+///
+/// Program id, authority, config, stats, token program, system program and
+/// the instructions sysvar (the unset fee recipient reuses the program id).
+const ROUTER_ACCOUNTS: usize = 7;
+
+/// Route accounts of a typical single-hop Jupiter leg
+///
+/// Pool state, its vaults and authority, the DEX program, event authority
+/// and the like; the leg's mints and token accounts are counted separately.
+const ROUTE_ACCOUNTS_PER_LEG: usize = 10;
+
+/// Accounts a Jupiter leg's instruction references besides its route accounts
+///
+/// Jupiter program, authority, token program, the input and output token
+/// accounts and the output mint.
+const ROUTE_SHARED_ACCOUNTS: usize = 6;
+
+/// Instruction data of a typical Jupiter route, in bytes
+const ROUTE_DATA_SIZE: usize = 48;
+
+/// Borsh size of the `batch_swap` arguments other than the swaps
+///
+/// Discriminator (8), version (1), swaps length (4), base mint (32), emit
+/// events (1), memo hash (32), no callback program (1), max total fee (8),
+/// leg output accounts (1), global max slippage (2) and shared input
+/// account (1).
+const BATCH_SWAP_FIXED_DATA_SIZE: usize = 91;
+
+/// Predicted account count and size of a batch's transaction
+///
+/// Returned by [`estimate_transaction_size`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionSizeEstimate {
+    /// Distinct accounts the transaction references (programs included)
+    pub account_count: usize,
+
+    /// Estimated serialized size of a legacy transaction, in bytes
+    pub byte_estimate: usize,
+
+    /// Whether `byte_estimate` is within [`MAX_TRANSACTION_SIZE`]
+    pub fits_legacy: bool,
+}
+
+/// Serialized size of a legacy transaction carrying `instructions`
+///
+/// # Arguments
+///
+/// * `instructions` - The instructions to send (route, router, memo, ...)
+/// * `payer` - The fee payer
+///
+/// # Returns
+///
+/// The size in bytes, including signatures
+#[must_use]
+pub fn legacy_transaction_size(instructions: &[Instruction], payer: &Pubkey) -> usize {
+    let message = Message::new(instructions, Some(payer));
+    let signatures = usize::from(message.header.num_required_signatures);
+
+    signatures_size(signatures) + message.serialize().len()
+}
+
+/// Unique accounts of a legacy transaction carrying `instructions`
+///
+/// # Arguments
+///
+/// * `instructions` - The instructions to send
+/// * `payer` - The fee payer
+///
+/// # Returns
+///
+/// `(total, writable)` unique account counts, programs and payer included
+#[must_use]
+pub fn legacy_account_count(instructions: &[Instruction], payer: &Pubkey) -> (usize, usize) {
+    let message = Message::new(instructions, Some(payer));
+    let header = message.header;
+    let total = message.account_keys.len();
+    let signed = usize::from(header.num_required_signatures);
+    let writable = signed - usize::from(header.num_readonly_signed_accounts) + (total - signed)
+        - usize::from(header.num_readonly_unsigned_accounts);
+
+    (total, writable)
+}
+
+/// Reject legacy transactions over the practical account limits
+///
+/// # Arguments
+///
+/// * `instructions` - The instructions to send
+/// * `payer` - The fee payer
+///
+/// # Errors
+///
+/// Returns `ContractError::TooManyAccounts` with the counts if the
+/// transaction references more than [`MAX_LEGACY_ACCOUNTS`] unique accounts
+/// or more than [`MAX_LEGACY_WRITABLE_ACCOUNTS`] writable ones
+pub fn check_legacy_accounts(instructions: &[Instruction], payer: &Pubkey) -> Result<(), ContractError> {
+    let (accounts, writable) = legacy_account_count(instructions, payer);
+    if accounts > MAX_LEGACY_ACCOUNTS || writable > MAX_LEGACY_WRITABLE_ACCOUNTS {
+        return Err(ContractError::TooManyAccounts {
+            accounts,
+            writable,
+            max_accounts: MAX_LEGACY_ACCOUNTS,
+            max_writable: MAX_LEGACY_WRITABLE_ACCOUNTS,
+        });
+    }
+    Ok(())
+}
+
+/// Serialized size of a v0 transaction carrying `instructions`
+///
+/// # Arguments
+///
+/// * `instructions` - The instructions to send
+/// * `payer` - The fee payer
+/// * `lookup_tables` - Address Lookup Tables the transaction references
+///
+/// # Returns
+///
+/// The size in bytes, including signatures
+///
+/// # Errors
+///
+/// Returns `ContractError::SerializationError` if the message cannot be
+/// compiled (e.g. too many accounts)
+pub fn v0_transaction_size(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<usize, ContractError> {
+    // The blockhash doesn't affect the size
+    let message = v0::Message::try_compile(payer, instructions, lookup_tables, Hash::default())
+        .map_err(|e| ContractError::SerializationError(e.to_string()))?;
+    let signatures = usize::from(message.header.num_required_signatures);
+
+    Ok(signatures_size(signatures) + VersionedMessage::V0(message).serialize().len())
+}
+
+/// Estimate a batch's transaction size before building it
+///
+/// Resolves the accounts the batch references: the router's
+/// [`ROUTER_ACCOUNTS`], plus each distinct mint and the authority's token
+/// account for it. With `include_jupiter_accounts`, every leg also gets a
+/// Jupiter route instruction with a typical single-hop route's accounts and
+/// data. The size is that of a legacy transaction signed by the authority
+/// alone, with every account key inline.
+///
+/// Routes vary by DEX and hop count, so treat the result as a guide: a batch
+/// near the limit may still not fit once built. A v0 transaction with a
+/// lookup table fits far more accounts than `fits_legacy` suggests.
+///
+/// # Arguments
+///
+/// * `swaps` - The swaps of the batch
+/// * `include_jupiter_accounts` - Count a Jupiter route instruction per leg
+///
+/// # Returns
+///
+/// The estimated account count and size, and whether a legacy transaction fits
+///
+/// # Example
+///
+/// ```rust
+/// use xforce_terminal_contracts_client::{tx_size::estimate_transaction_size, SwapParams};
+/// use solana_sdk::pubkey::Pubkey;
+///
+/// let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000_000, 990_000);
+/// let estimate = estimate_transaction_size(&[swap], true);
+/// assert!(estimate.fits_legacy);
+/// ```
+#[must_use]
+pub fn estimate_transaction_size(swaps: &[SwapParams], include_jupiter_accounts: bool) -> TransactionSizeEstimate {
+    let legs = swaps.len();
+    // Each mint comes with the authority's token account for it
+    let mut account_count = ROUTER_ACCOUNTS + 2 * distinct_mints(swaps);
+
+    // The router instruction: its accounts other than the program id, and its data
+    let router_data = BATCH_SWAP_FIXED_DATA_SIZE + swaps.iter().map(swap_params_size).sum::<usize>();
+    let mut instructions_size = instruction_size(ROUTER_ACCOUNTS - 1, router_data);
+    let mut instruction_count = 1;
+
+    if include_jupiter_accounts && legs > 0 {
+        // The Jupiter program, then each leg's own route
+        account_count += 1 + legs * ROUTE_ACCOUNTS_PER_LEG;
+        instructions_size +=
+            legs * instruction_size(ROUTE_ACCOUNTS_PER_LEG + ROUTE_SHARED_ACCOUNTS, ROUTE_DATA_SIZE);
+        instruction_count += legs;
+    }
+
+    // Signatures, header (3), account keys, blockhash (32), instructions
+    let byte_estimate = signatures_size(1)
+        + 3
+        + compact_u16_size(account_count)
+        + account_count * 32
+        + 32
+        + compact_u16_size(instruction_count)
+        + instructions_size;
+
+    TransactionSizeEstimate {
+        account_count,
+        byte_estimate,
+        fits_legacy: byte_estimate <= MAX_TRANSACTION_SIZE,
+    }
+}
+
+/// Reject transactions larger than [`MAX_TRANSACTION_SIZE`]
+///
+/// # Arguments
+///
+/// * `size` - Measured transaction size, in bytes
+///
+/// # Errors
+///
+/// Returns `ContractError::TransactionTooLarge` with the measured size if
+/// the transaction would not fit in a packet
+pub fn check_transaction_size(size: usize) -> Result<(), ContractError> {
+    if size > MAX_TRANSACTION_SIZE {
+        return Err(ContractError::TransactionTooLarge {
+            size,
+            limit: MAX_TRANSACTION_SIZE,
+        });
+    }
+    Ok(())
+}
+
+/// Number of distinct input and output mints across a batch
+///
+/// # Arguments
+///
+/// * `swaps` - The swaps of the batch
+#[must_use]
+pub fn distinct_mints(swaps: &[SwapParams]) -> usize {
+    let mut mints: Vec<Pubkey> = swaps
+        .iter()
+        .flat_map(|swap| [swap.input_mint, swap.output_mint])
+        .collect();
+    mints.sort_unstable();
+    mints.dedup();
+    mints.len()
+}
+
+/// Check a batch's distinct mints against a threshold
+///
+/// # Arguments
+///
+/// * `swaps` - The swaps of the batch
+/// * `max_mints` - Most distinct mints allowed ([`DEFAULT_MAX_DISTINCT_MINTS`],
+///   or the config's `max_distinct_mints` when set)
+///
+/// # Errors
+///
+/// Returns `ContractError::TooManyMints` with the count if the batch touches
+/// more than `max_mints` distinct mints
+pub fn check_distinct_mints(swaps: &[SwapParams], max_mints: usize) -> Result<(), ContractError> {
+    let mints = distinct_mints(swaps);
+    if mints > max_mints {
+        return Err(ContractError::TooManyMints { mints, limit: max_mints });
+    }
+    Ok(())
+}
+
+/// Size of the signature list: a compact-u16 length followed by the signatures
+fn signatures_size(count: usize) -> usize {
+    compact_u16_size(count) + count * SIGNATURE_SIZE
+}
+
+/// Size of a compact-u16 length prefix
+fn compact_u16_size(value: usize) -> usize {
+    match value {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+/// Size of a compiled instruction: program index, account indexes and data
+fn instruction_size(accounts: usize, data: usize) -> usize {
+    1 + compact_u16_size(accounts) + accounts + compact_u16_size(data) + data
+}
+
+/// Borsh size of one leg in the `batch_swap` arguments
+fn swap_params_size(swap: &SwapParams) -> usize {
+    // Mints (64), amount, min output and tag (24), then the options' tags
+    let max_fee = if swap.max_fee.is_some() { 9 } else { 1 };
+    let max_slippage_bps = if swap.max_slippage_bps.is_some() { 3 } else { 1 };
+    88 + max_fee + max_slippage_bps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    fn instruction_with_accounts(count: usize) -> Instruction {
+        let accounts = (0..count)
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+            .collect();
+        Instruction::new_with_bytes(Pubkey::new_unique(), &[0u8; 64], accounts)
+    }
+
+    #[test]
+    fn test_small_transaction_fits() {
+        let payer = Pubkey::new_unique();
+        let size = legacy_transaction_size(&[instruction_with_accounts(4)], &payer);
+
+        assert!(size < MAX_TRANSACTION_SIZE);
+        assert!(check_transaction_size(size).is_ok());
+    }
+
+    #[test]
+    fn test_oversized_transaction_reports_size() {
+        let payer = Pubkey::new_unique();
+        let size = legacy_transaction_size(&[instruction_with_accounts(40)], &payer);
+
+        assert!(matches!(
+            check_transaction_size(size),
+            Err(ContractError::TransactionTooLarge { size: measured, limit: MAX_TRANSACTION_SIZE })
+                if measured == size
+        ));
+    }
+
+    #[test]
+    fn test_lookup_table_shrinks_v0_transaction() {
+        let payer = Pubkey::new_unique();
+        let instruction = instruction_with_accounts(40);
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: instruction.accounts.iter().map(|meta| meta.pubkey).collect(),
+        };
+
+        let legacy = legacy_transaction_size(std::slice::from_ref(&instruction), &payer);
+        let v0 = v0_transaction_size(&[instruction], &payer, &[table]).unwrap();
+
+        assert!(check_transaction_size(legacy).is_err());
+        assert!(check_transaction_size(v0).is_ok());
+    }
+
+    #[test]
+    fn test_legacy_account_limits() {
+        let payer = Pubkey::new_unique();
+        let writable = |count: usize| {
+            let accounts = (0..count)
+                .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+                .collect();
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[], accounts)
+        };
+
+        // The payer is writable too, the program read-only
+        assert_eq!(legacy_account_count(&[writable(34)], &payer), (36, 35));
+        assert!(check_legacy_accounts(&[writable(34)], &payer).is_ok());
+        assert!(matches!(
+            check_legacy_accounts(&[writable(35)], &payer),
+            Err(ContractError::TooManyAccounts { accounts: 37, writable: 36, .. })
+        ));
+
+        // Read-only accounts count towards the total only
+        assert!(check_legacy_accounts(&[instruction_with_accounts(62)], &payer).is_ok());
+        assert!(matches!(
+            check_legacy_accounts(&[instruction_with_accounts(63)], &payer),
+            Err(ContractError::TooManyAccounts { accounts: 65, writable: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_distinct_mints_threshold() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let swaps = [SwapParams::new(a, b, 10, 1), SwapParams::new(b, c, 10, 1), SwapParams::new(a, c, 10, 1)];
+
+        assert_eq!(distinct_mints(&swaps), 3);
+        assert!(check_distinct_mints(&swaps, 3).is_ok());
+        assert!(matches!(
+            check_distinct_mints(&swaps, 2),
+            Err(ContractError::TooManyMints { mints: 3, limit: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_estimate_transaction_size() {
+        let mints: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let swaps: Vec<SwapParams> = mints
+            .windows(2)
+            .map(|pair| SwapParams::new(pair[0], pair[1], 1_000, 900))
+            .collect();
+
+        // Router accounts, then 5 mints with a token account each
+        let router_only = estimate_transaction_size(&swaps, false);
+        assert_eq!(router_only.account_count, 7 + 10);
+        assert!(router_only.fits_legacy);
+
+        // One leg with its route fits; four legs with routes do not
+        let one_leg = estimate_transaction_size(&swaps[..1], true);
+        assert_eq!(one_leg.account_count, 7 + 4 + 1 + 10);
+        assert!(one_leg.fits_legacy);
+        let routed = estimate_transaction_size(&swaps, true);
+        assert_eq!(routed.account_count, 7 + 10 + 1 + 40);
+        assert!(routed.byte_estimate > MAX_TRANSACTION_SIZE);
+        assert!(!routed.fits_legacy);
+
+        // The router instruction's data grows with each leg's options
+        let capped = swaps[0].clone().with_max_fee(10).with_max_slippage_bps(50);
+        let delta = estimate_transaction_size(&[capped], false).byte_estimate
+            - estimate_transaction_size(&swaps[..1], false).byte_estimate;
+        assert_eq!(delta, 8 + 2);
+    }
+
+    #[test]
+    fn test_signatures_size() {
+        assert_eq!(signatures_size(1), 1 + 64);
+        assert_eq!(signatures_size(2), 1 + 128);
+    }
+}
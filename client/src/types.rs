@@ -4,8 +4,46 @@
 //! These types provide a type-safe interface for interacting with the batch
 //! swap router program.
 
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 
+use crate::security::{adjust_min_output_for_fee, calculate_protocol_fee, calculate_slippage_bps};
+
+/// Instruction format version sent with every `batch_swap`/`execute_swap`
+///
+/// Matches the program's `INSTRUCTION_VERSION`. The program rejects versions
+/// outside its supported range with `UnsupportedInstructionVersion`, so an
+/// outdated client fails loudly after an upgrade instead of sending arguments
+/// the program would misread.
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Maximum slippage tolerance in basis points (500 = 5%)
+///
+/// Matches the program's `MAX_SLIPPAGE_BPS`. A swap's `max_slippage_bps` can
+/// tighten this limit but not exceed it.
+pub const MAX_SLIPPAGE_BPS: u16 = 500;
+
+/// Default protocol fee in basis points (30 = 0.3%)
+///
+/// Matches the program's `PROTOCOL_FEE_BPS`, the rate a freshly initialized
+/// `Config` charges. The admin can change the live rate (`Config::fee_bps`).
+pub const PROTOCOL_FEE_BPS: u16 = 30;
+
+/// `execute_swap` amount meaning "swap the full input account balance"
+///
+/// Matches the program's `USE_FULL_BALANCE`. The program resolves it to the
+/// input token account's balance when the swap runs (the fee comes out of
+/// it), so callers need not read the balance first.
+pub const USE_FULL_BALANCE: u64 = u64::MAX;
+
+/// Maximum number of swaps in a single batch
+///
+/// Matches the program's `MAX_BATCH_SIZE`. Larger sets of swaps must be split
+/// into several batches (see [`crate::chunked`]).
+pub const MAX_BATCH_SIZE: usize = 10;
+
 /// Parameters for a single swap operation
 ///
 /// This structure contains all parameters needed to execute a single swap
@@ -17,6 +55,9 @@ use solana_sdk::pubkey::Pubkey;
 /// * `output_mint` - The mint address of the output token (token being swapped to)
 /// * `amount` - Amount of input tokens to swap (in token's smallest unit)
 /// * `min_output_amount` - Minimum output amount (slippage protection)
+/// * `max_fee` - Optional absolute cap on the protocol fee
+/// * `max_slippage_bps` - Optional per-swap slippage tolerance
+/// * `tag` - Caller-defined label echoed into the swap's events (0 = untagged)
 ///
 /// # Example
 ///
@@ -29,6 +70,9 @@ use solana_sdk::pubkey::Pubkey;
 ///     output_mint: Pubkey::new_unique(),
 ///     amount: 1_000_000_000, // 1 SOL (in lamports)
 ///     min_output_amount: 90_000_000, // 90 USDC minimum (10% slippage)
+///     max_fee: None, // no absolute fee cap
+///     max_slippage_bps: None, // global MAX_SLIPPAGE_BPS
+///     tag: 0, // untagged
 /// };
 /// ```
 ///
@@ -37,6 +81,8 @@ use solana_sdk::pubkey::Pubkey;
 /// - `input_mint` must differ from `output_mint`
 /// - `amount` must be >= 1 (MIN_SWAP_AMOUNT)
 /// - `min_output_amount` must be > 0
+/// - `max_fee` must be > 0 when set
+/// - `max_slippage_bps` must be <= [`MAX_SLIPPAGE_BPS`] when set
 ///
 /// # Slippage Protection
 ///
@@ -51,12 +97,27 @@ use solana_sdk::pubkey::Pubkey;
 /// - SOL: lamports (1 SOL = 1,000,000,000 lamports)
 /// - USDC: micro-USDC (1 USDC = 1,000,000 micro-USDC)
 /// - Other tokens: depends on token decimals
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// # JSON
+///
+/// Swaps serialize with serde, mints as base58 strings. `max_fee`,
+/// `max_slippage_bps` and `tag` may be omitted:
+///
+/// ```json
+/// {
+///   "input_mint": "So11111111111111111111111111111111111111112",
+///   "output_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+///   "amount": 1000000000,
+///   "min_output_amount": 90000000
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SwapParams {
     /// Input token mint (source token)
     ///
     /// This is the mint address of the token being swapped from.
     /// Must be a valid token mint address on Solana.
+    #[serde(with = "pubkey_base58")]
     pub input_mint: Pubkey,
 
     /// Output token mint (destination token)
@@ -64,6 +125,7 @@ pub struct SwapParams {
     /// This is the mint address of the token being swapped to.
     /// Must be a valid token mint address on Solana.
     /// Must differ from `input_mint`.
+    #[serde(with = "pubkey_base58")]
     pub output_mint: Pubkey,
 
     /// Amount of input tokens to swap
@@ -97,6 +159,36 @@ pub struct SwapParams {
     /// - Slippage tolerance: 5%
     /// - `min_output_amount`: 95 USDC (95% of expected)
     pub min_output_amount: u64,
+
+    /// Optional absolute cap on the protocol fee (in input token units)
+    ///
+    /// Partners with a negotiated cap set this so the fee on a large swap is
+    /// `min(amount * fee_bps / 10_000, max_fee)`. `None` means no cap.
+    ///
+    /// # Constraints
+    ///
+    /// - Must be > 0 when set
+    #[serde(default)]
+    pub max_fee: Option<u64>,
+
+    /// Optional slippage tolerance for this swap, in basis points
+    ///
+    /// The program applies `min(max_slippage_bps, MAX_SLIPPAGE_BPS)`, so a
+    /// volatile leg can use a tighter tolerance than the rest of the batch.
+    /// `None` uses the global limit.
+    ///
+    /// # Constraints
+    ///
+    /// - Must be <= [`MAX_SLIPPAGE_BPS`] when set
+    #[serde(default)]
+    pub max_slippage_bps: Option<u16>,
+
+    /// Caller-defined label for this swap, e.g. a strategy id
+    ///
+    /// Not interpreted by the program; echoed into `SwapLegEvent::tag` so
+    /// swaps can be grouped off-chain. 0 means untagged.
+    #[serde(default)]
+    pub tag: u64,
 }
 
 impl SwapParams {
@@ -111,7 +203,9 @@ impl SwapParams {
     ///
     /// # Returns
     ///
-    /// A new `SwapParams` instance
+    /// A new `SwapParams` instance with no fee cap (see [`Self::with_max_fee`]),
+    /// the global slippage tolerance (see [`Self::with_max_slippage_bps`])
+    /// and no tag (see [`Self::with_tag`])
     ///
     /// # Example
     ///
@@ -138,9 +232,115 @@ impl SwapParams {
             output_mint,
             amount,
             min_output_amount,
+            max_fee: None,
+            max_slippage_bps: None,
+            tag: 0,
         }
     }
 
+    /// Cap the protocol fee charged for this swap
+    ///
+    /// # Arguments
+    ///
+    /// * `max_fee` - Maximum fee in input token units (must be > 0)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xforce_terminal_contracts_client::SwapParams;
+    /// use solana_sdk::pubkey::Pubkey;
+    ///
+    /// // Fee on this 10,000 USDC swap is capped at 5 USDC
+    /// let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 10_000_000_000, 9_900_000_000)
+    ///     .with_max_fee(5_000_000);
+    /// ```
+    #[must_use]
+    pub fn with_max_fee(mut self, max_fee: u64) -> Self {
+        self.max_fee = Some(max_fee);
+        self
+    }
+
+    /// Use a tighter slippage tolerance for this swap
+    ///
+    /// # Arguments
+    ///
+    /// * `max_slippage_bps` - Tolerance in basis points (must be <= [`MAX_SLIPPAGE_BPS`])
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xforce_terminal_contracts_client::SwapParams;
+    /// use solana_sdk::pubkey::Pubkey;
+    ///
+    /// // Volatile leg: allow at most 0.5% slippage
+    /// let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000_000_000, 90_000_000)
+    ///     .with_max_slippage_bps(50);
+    /// ```
+    #[must_use]
+    pub fn with_max_slippage_bps(mut self, max_slippage_bps: u16) -> Self {
+        self.max_slippage_bps = Some(max_slippage_bps);
+        self
+    }
+
+    /// Apply `min_output_amount` to the amount left after the protocol fee
+    ///
+    /// Use this when `min_output_amount` was derived from a quote for the
+    /// full `amount`: the program swaps `amount` minus the fee, so the gross
+    /// minimum is slightly too strict (see [`adjust_min_output_for_fee`]).
+    /// The fee is computed like the program does, including `max_fee`.
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_bps` - The protocol fee rate (`Config::fee_bps`)
+    /// * `fee_exempt` - Whether the authority is fee-exempt
+    ///
+    /// # Returns
+    ///
+    /// The swap with the adjusted minimum, or `None` if the fee cannot be
+    /// computed or the adjusted minimum would be 0
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xforce_terminal_contracts_client::SwapParams;
+    /// use solana_sdk::pubkey::Pubkey;
+    ///
+    /// // Quoted 1,000,000 out for 1,000,000 in, 0.1% slippage, 0.3% fee
+    /// let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000_000, 999_000)
+    ///     .with_fee_adjusted_min_output(30, false)
+    ///     .unwrap();
+    /// assert_eq!(swap.min_output_amount, 996_003);
+    /// ```
+    #[must_use]
+    pub fn with_fee_adjusted_min_output(mut self, fee_bps: u16, fee_exempt: bool) -> Option<Self> {
+        let fee = calculate_protocol_fee(self.amount, fee_bps, fee_exempt, self.max_fee)?;
+        self.min_output_amount = adjust_min_output_for_fee(self.min_output_amount, self.amount, fee)?;
+        Some(self)
+    }
+
+    /// Label this swap, e.g. with a strategy id
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - Caller-defined label echoed into the swap's events
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xforce_terminal_contracts_client::SwapParams;
+    /// use solana_sdk::pubkey::Pubkey;
+    ///
+    /// const REBALANCE_STRATEGY: u64 = 42;
+    ///
+    /// let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000_000_000, 90_000_000)
+    ///     .with_tag(REBALANCE_STRATEGY);
+    /// ```
+    #[must_use]
+    pub fn with_tag(mut self, tag: u64) -> Self {
+        self.tag = tag;
+        self
+    }
+
     /// Validate swap parameters
     ///
     /// This function validates that the swap parameters are valid.
@@ -180,8 +380,100 @@ impl SwapParams {
             return Err("Minimum output amount must be greater than zero".to_string());
         }
 
+        if self.max_fee == Some(0) {
+            return Err("Maximum fee must be greater than zero when set".to_string());
+        }
+
+        if let Some(bps) = self.max_slippage_bps.filter(|&bps| bps > MAX_SLIPPAGE_BPS) {
+            return Err(format!(
+                "Slippage tolerance {bps} bps exceeds maximum {MAX_SLIPPAGE_BPS} bps"
+            ));
+        }
+
         Ok(())
     }
+
+    /// Slippage tolerance implied by `min_output_amount` for a quote
+    ///
+    /// How far the output may fall below `expected_output` before the swap
+    /// fails, i.e. the slippage the minimum output was set for.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected_output` - The quoted output amount
+    ///
+    /// # Returns
+    ///
+    /// The tolerance in basis points (0 if `min_output_amount` is at or above
+    /// the quote), or `None` if `expected_output` is 0
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xforce_terminal_contracts_client::SwapParams;
+    /// use solana_sdk::pubkey::Pubkey;
+    ///
+    /// let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 900);
+    /// assert_eq!(swap.implied_slippage_bps(1_000), Some(1_000)); // 10%
+    /// ```
+    #[must_use]
+    pub fn implied_slippage_bps(&self, expected_output: u64) -> Option<u64> {
+        calculate_slippage_bps(expected_output, self.min_output_amount)
+    }
+}
+
+impl fmt::Display for SwapParams {
+    /// Compact one-line form for logs, e.g. `1000 <input mint>→<output mint> (min 900)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}→{} (min {})",
+            self.amount, self.input_mint, self.output_mint, self.min_output_amount
+        )
+    }
+}
+
+/// Serde format of a `Pubkey` as its base58 string, for hand-edited files
+pub(crate) mod pubkey_base58 {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+
+    /// Write the pubkey as its base58 string
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(pubkey)
+    }
+
+    /// Parse a base58 string, rejecting anything that is not a pubkey
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        encoded
+            .parse()
+            .map_err(|e| D::Error::custom(format!("invalid pubkey {encoded:?}: {e}")))
+    }
+
+    /// Same format for an optional `Pubkey` (`null` or absent for `None`)
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serializer};
+        use solana_sdk::pubkey::Pubkey;
+
+        /// Write the pubkey as its base58 string, or `null`
+        #[allow(clippy::ref_option)] // serde's `with` passes the field by reference
+        pub fn serialize<S: Serializer>(pubkey: &Option<Pubkey>, serializer: S) -> Result<S::Ok, S::Error> {
+            match pubkey {
+                Some(pubkey) => serializer.collect_str(pubkey),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Parse a base58 string, or `null` as `None`
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pubkey>, D::Error> {
+            /// A pubkey in the base58 format
+            #[derive(Deserialize)]
+            struct Wrapper(#[serde(with = "super")] Pubkey);
+
+            Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(pubkey)| pubkey))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -246,5 +538,81 @@ mod tests {
 
         assert!(swap.validate().is_err());
     }
-}
 
+    #[test]
+    fn test_swap_params_validate_zero_max_fee() {
+        let swap = SwapParams::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000_000_000,
+            90_000_000,
+        );
+
+        assert_eq!(swap.max_fee, None);
+        assert!(swap.clone().with_max_fee(1).validate().is_ok());
+        assert!(swap.with_max_fee(0).validate().is_err());
+    }
+
+    #[test]
+    fn test_swap_params_validate_max_slippage() {
+        let swap = SwapParams::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000_000_000,
+            90_000_000,
+        );
+
+        assert_eq!(swap.max_slippage_bps, None);
+        assert!(swap.clone().with_max_slippage_bps(0).validate().is_ok());
+        assert!(swap.clone().with_max_slippage_bps(MAX_SLIPPAGE_BPS).validate().is_ok());
+        assert!(swap.with_max_slippage_bps(MAX_SLIPPAGE_BPS + 1).validate().is_err());
+    }
+
+    #[test]
+    fn test_swap_params_tag_defaults_to_untagged() {
+        let swap = SwapParams::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_000_000_000,
+            90_000_000,
+        );
+
+        assert_eq!(swap.tag, 0);
+        assert_eq!(swap.with_tag(42).tag, 42);
+    }
+
+    #[test]
+    fn test_swap_params_display() {
+        let (input_mint, output_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let swap = SwapParams::new(input_mint, output_mint, 1_000, 900);
+
+        assert_eq!(swap.to_string(), format!("1000 {input_mint}→{output_mint} (min 900)"));
+    }
+
+    #[test]
+    fn test_swap_params_implied_slippage_bps() {
+        let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000_000, 95_000);
+
+        assert_eq!(swap.implied_slippage_bps(100_000), Some(500));
+        assert_eq!(swap.implied_slippage_bps(95_000), Some(0));
+        // A minimum above the quote implies no tolerance at all
+        assert_eq!(swap.implied_slippage_bps(90_000), Some(0));
+        // 1 - 95_000 / 95_500 = 52.4 bps, rounded down
+        assert_eq!(swap.implied_slippage_bps(95_500), Some(52));
+        assert_eq!(swap.implied_slippage_bps(0), None);
+    }
+
+    #[test]
+    fn test_swap_params_fee_adjusted_min_output() {
+        let swap = SwapParams::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000_000, 999_000);
+
+        let adjusted = swap.clone().with_fee_adjusted_min_output(30, false).unwrap();
+        assert_eq!(adjusted.min_output_amount, 996_003);
+        // No fee, nothing to adjust
+        let exempt = swap.clone().with_fee_adjusted_min_output(30, true).unwrap();
+        assert_eq!(exempt.min_output_amount, 999_000);
+        // The fee cap applies: 1_000 * 999_000 / 1_000_000 less
+        let capped = swap.with_max_fee(1_000).with_fee_adjusted_min_output(30, false).unwrap();
+        assert_eq!(capped.min_output_amount, 998_001);
+    }
+}
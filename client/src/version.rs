@@ -0,0 +1,178 @@
+//! # Program Version Handshake
+//!
+//! This module reads which program version and features are deployed, via
+//! the program's `get_version` instruction. The instruction takes no
+//! accounts and returns a [`ProgramVersion`] as return data; the client
+//! simulates it (nothing is sent or paid) and decodes the result.
+//!
+//! ## Gating Client Behavior
+//!
+//! Fetch the version once per cluster and adapt to it:
+//!
+//! - [`ProgramVersion::supports_client`] tells whether the program accepts
+//!   this client's `INSTRUCTION_VERSION`; if not, the client must be
+//!   upgraded (or downgraded) before sending swaps
+//! - [`ProgramVersion::has_feature`] tells whether a build feature such as
+//!   [`FEATURE_LARGE_AMOUNTS`] is enabled, so e.g. `batch_swap_large` is
+//!   only used where it exists
+//!
+//! Programs deployed before the handshake have no `get_version`
+//! instruction; simulating it fails with `ContractError::TransactionFailed`,
+//! which callers can treat as instruction version 1 with no features.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let version = client.fetch_program_version()?;
+//! if !version.supports_client() {
+//!     return Err(format!("Program {} does not accept this client", version.program_version).into());
+//! }
+//! if version.has_feature(version::FEATURE_LARGE_AMOUNTS) {
+//!     // batch_swap_large is available
+//! }
+//! ```
+
+use anchor_lang::prelude::borsh;
+use anchor_lang::AnchorDeserialize;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::error::ContractError;
+use crate::return_data::router_return_data;
+use crate::types::INSTRUCTION_VERSION;
+
+/// Anchor instruction discriminator of `get_version` (`sha256("global:get_version")[..8]`)
+pub const GET_VERSION_DISCRIMINATOR: [u8; 8] = [168, 85, 244, 45, 81, 56, 130, 50];
+
+/// Feature bit: built with `large-amounts` (`batch_swap_large` is available)
+pub const FEATURE_LARGE_AMOUNTS: u64 = 1 << 0;
+
+/// Feature bit: built with `no-fees` (no protocol fee is charged)
+pub const FEATURE_NO_FEES: u64 = 1 << 1;
+
+/// Feature bit: built with `strict_sanity` (implausible minimum outputs and
+/// price jumps are rejected)
+pub const FEATURE_STRICT_SANITY: u64 = 1 << 2;
+
+/// Feature bit: built with `cu_profiling` (per-leg compute unit logs)
+pub const FEATURE_CU_PROFILING: u64 = 1 << 3;
+
+/// Program version and features, as returned by `get_version`
+///
+/// Mirrors the program's `VersionReturnData`.
+#[derive(AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProgramVersion {
+    /// Program version (e.g. "0.1.0")
+    pub program_version: String,
+
+    /// Newest accepted instruction format version
+    pub instruction_version: u8,
+
+    /// Oldest accepted instruction format version
+    pub min_supported_instruction_version: u8,
+
+    /// Build feature bits (`FEATURE_*`); unknown bits can be ignored
+    pub features: u64,
+}
+
+impl ProgramVersion {
+    /// Whether the program accepts instruction format `version`
+    #[must_use]
+    pub fn supports_instruction_version(&self, version: u8) -> bool {
+        (self.min_supported_instruction_version..=self.instruction_version).contains(&version)
+    }
+
+    /// Whether the program accepts this client's `INSTRUCTION_VERSION`
+    #[must_use]
+    pub fn supports_client(&self) -> bool {
+        self.supports_instruction_version(INSTRUCTION_VERSION)
+    }
+
+    /// Whether every bit of `feature` is enabled
+    #[must_use]
+    pub fn has_feature(&self, feature: u64) -> bool {
+        self.features & feature == feature
+    }
+}
+
+/// Build the `get_version` instruction (no accounts)
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+#[must_use]
+pub fn get_version_instruction(program_id: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(*program_id, &GET_VERSION_DISCRIMINATOR, vec![])
+}
+
+/// Decode `get_version` return data as reported by `simulateTransaction`
+///
+/// # Arguments
+///
+/// * `program_id` - The batch swap router program ID
+/// * `return_program_id` - The program that set the return data (`returnData.programId`)
+/// * `data_base64` - The base64-encoded return data (`returnData.data[0]`)
+///
+/// # Returns
+///
+/// * `Ok(ProgramVersion)` - The deployed program's version and features
+/// * `Err(ContractError)` - If the data is not `get_version` return data
+///
+/// # Errors
+///
+/// - `ContractError::InvalidAccount` - The return data was set by another program
+/// - `ContractError::SerializationError` - The data is not valid base64 or
+///   not a `ProgramVersion`
+pub fn decode_version_return_data(
+    program_id: &Pubkey,
+    return_program_id: &str,
+    data_base64: &str,
+) -> Result<ProgramVersion, ContractError> {
+    let data = router_return_data(program_id, return_program_id, data_base64)?;
+    ProgramVersion::try_from_slice(&data).map_err(|e| ContractError::SerializationError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+
+    fn encoded(program_version: &str, min_supported: u8, instruction_version: u8, features: u64) -> String {
+        let mut data = u32::try_from(program_version.len()).unwrap().to_le_bytes().to_vec();
+        data.extend_from_slice(program_version.as_bytes());
+        data.extend_from_slice(&[instruction_version, min_supported]);
+        data.extend_from_slice(&features.to_le_bytes());
+        STANDARD.encode(data)
+    }
+
+    #[test]
+    fn test_decode_and_gate_on_version() {
+        let program_id = Pubkey::new_unique();
+        let data = encoded("0.1.0", 1, 2, FEATURE_LARGE_AMOUNTS | 1 << 40);
+
+        let version = decode_version_return_data(&program_id, &program_id.to_string(), &data).unwrap();
+
+        assert_eq!(version.program_version, "0.1.0");
+        assert!(version.supports_client());
+        assert!(version.supports_instruction_version(2));
+        assert!(!version.supports_instruction_version(3));
+        assert!(version.has_feature(FEATURE_LARGE_AMOUNTS));
+        assert!(!version.has_feature(FEATURE_LARGE_AMOUNTS | FEATURE_NO_FEES));
+
+        let other = Pubkey::new_unique().to_string();
+        assert!(matches!(
+            decode_version_return_data(&program_id, &other, &data),
+            Err(ContractError::InvalidAccount(_))
+        ));
+    }
+
+    #[test]
+    fn test_get_version_instruction_has_no_accounts() {
+        let program_id = Pubkey::new_unique();
+        let instruction = get_version_instruction(&program_id);
+
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.data, GET_VERSION_DISCRIMINATOR);
+        assert!(instruction.accounts.is_empty());
+    }
+}
@@ -0,0 +1,344 @@
+//! # Wrapped SOL Handling
+//!
+//! This module contains helpers for swaps that start or end in native SOL.
+//! SPL token swaps only understand wrapped SOL (WSOL), so native SOL has to be
+//! wrapped into a temporary token account before the swap and unwrapped again
+//! afterwards.
+//!
+//! ## Transaction Layout
+//!
+//! The helpers in this module surround the caller's swap instructions with the
+//! wrap/unwrap instructions in a fixed order:
+//!
+//! ```text
+//! 1. create WSOL account (idempotent)   # only if a leg touches native SOL
+//! 2. transfer lamports + sync_native    # only if a leg spends native SOL
+//! 3. <swap instructions>                # Jupiter route + batch_swap/execute_swap
+//! 4. close WSOL account                 # only if this transaction created it
+//! ```
+//!
+//! Closing the WSOL account last recovers both the rent deposit and any dust
+//! the route did not consume (SOL -> token), or delivers the swap output as
+//! native SOL (token -> SOL).
+//!
+//! ## Existing WSOL Accounts
+//!
+//! The WSOL account is the owner's associated token account for the native
+//! mint, so the owner may already hold wrapped SOL in it. Closing it would
+//! unwrap that balance as a side effect of an unrelated swap, so the close is
+//! skipped whenever the account existed before the transaction. SOL input is
+//! still wrapped into it, and SOL output then stays as WSOL in the account.
+//!
+//! ## Usage
+//!
+//! `BatchSwapRouterClient::batch_swap` and `execute_swap` already do this
+//! (configure them with `with_wsol_options`). For swap instructions built
+//! elsewhere, use `BatchSwapRouterClient::with_wsol_instructions`, or call
+//! the helper directly:
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::wsol::{with_wsol_handling, WsolOptions};
+//!
+//! let wsol_account_exists = rpc.get_account(&wsol_account(&owner)).is_ok();
+//! let instructions = with_wsol_handling(
+//!     &owner,
+//!     &swaps,
+//!     swap_instructions,
+//!     wsol_account_exists,
+//!     WsolOptions::default(),
+//! )?;
+//! ```
+
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::associated_token::{
+    get_associated_token_address,
+    spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+};
+use anchor_spl::token::spl_token;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::error::ContractError;
+use crate::types::SwapParams;
+
+/// Options controlling how WSOL accounts are handled around a swap
+///
+/// Both options default to `true`, which unwraps everything back to native SOL
+/// once the swap instructions have run. Neither closes a WSOL account that
+/// existed before the transaction (see "Existing WSOL Accounts" above).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WsolOptions {
+    /// Close the WSOL input account after the swap, returning leftover WSOL
+    /// dust and the rent deposit to the owner as native SOL
+    pub close_input: bool,
+
+    /// Close the WSOL output account after the swap, delivering the swap
+    /// output to the owner as native SOL
+    pub close_output: bool,
+}
+
+impl Default for WsolOptions {
+    fn default() -> Self {
+        Self {
+            close_input: true,
+            close_output: true,
+        }
+    }
+}
+
+/// Check whether a mint is the native (wrapped SOL) mint
+///
+/// # Arguments
+///
+/// * `mint` - The mint address to check
+///
+/// # Returns
+///
+/// `true` if `mint` is the WSOL mint, `false` otherwise
+#[must_use]
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID
+}
+
+/// Get the WSOL token account used for an owner
+///
+/// The WSOL account is the owner's associated token account for the native
+/// mint.
+///
+/// # Arguments
+///
+/// * `owner` - The wallet that owns the WSOL account
+///
+/// # Returns
+///
+/// The address of the owner's WSOL associated token account
+#[must_use]
+pub fn wsol_account(owner: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, &spl_token::native_mint::ID)
+}
+
+/// Build the instructions that wrap native SOL into the owner's WSOL account
+///
+/// # Arguments
+///
+/// * `owner` - The wallet funding the WSOL account (also pays rent)
+/// * `lamports` - Amount of native SOL to wrap
+///
+/// # Returns
+///
+/// * `Ok(Vec<Instruction>)` - Create-account, transfer and `sync_native` instructions
+/// * `Err(ContractError)` - If the SPL token instruction cannot be built
+///
+/// # Errors
+///
+/// Returns `ContractError::ClientError` if the `sync_native` instruction
+/// cannot be constructed.
+pub fn wrap_sol_instructions(
+    owner: &Pubkey,
+    lamports: u64,
+) -> Result<Vec<Instruction>, ContractError> {
+    let account = wsol_account(owner);
+
+    let sync_native = spl_token::instruction::sync_native(&spl_token::ID, &account)
+        .map_err(|e| ContractError::ClientError(e.to_string()))?;
+
+    Ok(vec![
+        create_wsol_account_instruction(owner),
+        system_instruction::transfer(owner, &account, lamports),
+        sync_native,
+    ])
+}
+
+/// Build the instruction that closes the owner's WSOL account
+///
+/// Closing a native token account returns its whole lamport balance (wrapped
+/// amount plus rent deposit) to the owner.
+///
+/// # Arguments
+///
+/// * `owner` - The wallet that owns the WSOL account and receives the lamports
+///
+/// # Returns
+///
+/// * `Ok(Instruction)` - The `close_account` instruction
+/// * `Err(ContractError)` - If the SPL token instruction cannot be built
+///
+/// # Errors
+///
+/// Returns `ContractError::ClientError` if the `close_account` instruction
+/// cannot be constructed.
+pub fn close_wsol_instruction(owner: &Pubkey) -> Result<Instruction, ContractError> {
+    spl_token::instruction::close_account(
+        &spl_token::ID,
+        &wsol_account(owner),
+        owner,
+        owner,
+        &[],
+    )
+    .map_err(|e| ContractError::ClientError(e.to_string()))
+}
+
+/// Surround swap instructions with the WSOL wrap/unwrap instructions they need
+///
+/// Inspects the swaps for legs that spend or receive native SOL and returns
+/// the full, correctly ordered instruction list. Swaps that don't touch the
+/// native mint are returned unchanged.
+///
+/// The WSOL account is only closed if this transaction creates it, so
+/// wrapped SOL the owner already held is never unwrapped as a side effect.
+///
+/// # Arguments
+///
+/// * `owner` - The wallet executing the swaps
+/// * `swaps` - The swap legs the instructions execute
+/// * `swap_instructions` - The swap instructions (route + router instruction)
+/// * `wsol_account_exists` - Whether the owner's WSOL account (see
+///   [`wsol_account`]) already exists on chain
+/// * `options` - Which WSOL accounts to close after the swap
+///
+/// # Returns
+///
+/// * `Ok(Vec<Instruction>)` - The ordered instruction list
+/// * `Err(ContractError)` - If an instruction cannot be built or amounts overflow
+///
+/// # Errors
+///
+/// Returns `ContractError::InvalidAccount` if the total SOL input overflows,
+/// or `ContractError::ClientError` if an SPL token instruction cannot be built.
+pub fn with_wsol_handling(
+    owner: &Pubkey,
+    swaps: &[SwapParams],
+    swap_instructions: Vec<Instruction>,
+    wsol_account_exists: bool,
+    options: WsolOptions,
+) -> Result<Vec<Instruction>, ContractError> {
+    let sol_input = swaps
+        .iter()
+        .filter(|swap| is_native_mint(&swap.input_mint))
+        .try_fold(0u64, |total, swap| total.checked_add(swap.amount))
+        .ok_or_else(|| {
+            ContractError::InvalidAccount("Total SOL input overflows u64".to_string())
+        })?;
+    let spends_sol = swaps.iter().any(|swap| is_native_mint(&swap.input_mint));
+    let receives_sol = swaps.iter().any(|swap| is_native_mint(&swap.output_mint));
+
+    let mut instructions = Vec::with_capacity(swap_instructions.len() + 4);
+
+    // The WSOL account must exist before the swap either spends from it or
+    // receives into it
+    if spends_sol {
+        instructions.extend(wrap_sol_instructions(owner, sol_input)?);
+    } else if receives_sol {
+        instructions.push(create_wsol_account_instruction(owner));
+    }
+
+    instructions.extend(swap_instructions);
+
+    // Closing must come after every swap instruction, otherwise the route
+    // would find the account gone. An account the owner already had may hold
+    // WSOL of their own, so it is left open.
+    let close = !wsol_account_exists
+        && ((spends_sol && options.close_input) || (receives_sol && options.close_output));
+    if close {
+        instructions.push(close_wsol_instruction(owner)?);
+    }
+
+    Ok(instructions)
+}
+
+/// Build an idempotent create-ATA instruction for the owner's WSOL account
+fn create_wsol_account_instruction(owner: &Pubkey) -> Instruction {
+    create_associated_token_account_idempotent(
+        owner,
+        owner,
+        &spl_token::native_mint::ID,
+        &spl_token::ID,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> SwapParams {
+        SwapParams::new(input_mint, output_mint, amount, 1)
+    }
+
+    fn marker() -> Instruction {
+        Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![])
+    }
+
+    #[test]
+    fn test_sol_input_is_wrapped_then_closed() {
+        let owner = Pubkey::new_unique();
+        let swap_ix = marker();
+        let swaps = vec![swap(spl_token::native_mint::ID, Pubkey::new_unique(), 1_000)];
+
+        let ixs = with_wsol_handling(&owner, &swaps, vec![swap_ix.clone()], false, WsolOptions::default())
+            .unwrap();
+
+        // create, transfer, sync_native, swap, close
+        assert_eq!(ixs.len(), 5);
+        assert_eq!(ixs[3], swap_ix);
+        assert_eq!(ixs[4].program_id, spl_token::ID);
+        assert_eq!(ixs[4].accounts[0].pubkey, wsol_account(&owner));
+        assert_eq!(ixs[4].accounts[1].pubkey, owner);
+    }
+
+    #[test]
+    fn test_sol_output_is_closed_after_swap() {
+        let owner = Pubkey::new_unique();
+        let swap_ix = marker();
+        let swaps = vec![swap(Pubkey::new_unique(), spl_token::native_mint::ID, 1_000)];
+
+        let ixs = with_wsol_handling(&owner, &swaps, vec![swap_ix.clone()], false, WsolOptions::default())
+            .unwrap();
+
+        // create, swap, close
+        assert_eq!(ixs.len(), 3);
+        assert_eq!(ixs[1], swap_ix);
+        assert_eq!(ixs[2].accounts[0].pubkey, wsol_account(&owner));
+    }
+
+    #[test]
+    fn test_close_can_be_disabled() {
+        let owner = Pubkey::new_unique();
+        let swaps = vec![swap(spl_token::native_mint::ID, Pubkey::new_unique(), 1_000)];
+        let options = WsolOptions {
+            close_input: false,
+            close_output: true,
+        };
+
+        let ixs = with_wsol_handling(&owner, &swaps, vec![marker()], false, options).unwrap();
+
+        assert_eq!(ixs.len(), 4);
+    }
+
+    #[test]
+    fn test_existing_wsol_account_is_not_closed() {
+        let owner = Pubkey::new_unique();
+        let swap_ix = marker();
+        let swaps = vec![
+            swap(spl_token::native_mint::ID, Pubkey::new_unique(), 1_000),
+            swap(Pubkey::new_unique(), spl_token::native_mint::ID, 1_000),
+        ];
+
+        let ixs = with_wsol_handling(&owner, &swaps, vec![swap_ix.clone()], true, WsolOptions::default())
+            .unwrap();
+
+        // create (idempotent), transfer, sync_native, swap - and no close
+        assert_eq!(ixs.len(), 4);
+        assert_eq!(ixs[3], swap_ix);
+    }
+
+    #[test]
+    fn test_non_sol_swaps_are_unchanged() {
+        let owner = Pubkey::new_unique();
+        let swap_ix = marker();
+        let swaps = vec![swap(Pubkey::new_unique(), Pubkey::new_unique(), 1_000)];
+
+        let ixs = with_wsol_handling(&owner, &swaps, vec![swap_ix.clone()], false, WsolOptions::default())
+            .unwrap();
+
+        assert_eq!(ixs, vec![swap_ix]);
+    }
+}
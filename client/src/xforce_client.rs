@@ -0,0 +1,247 @@
+//! # One-Call Client
+//!
+//! This module provides [`XforceClient`], the shortest path from an RPC URL
+//! and a wallet to a swap: [`XforceClient::connect`] creates the Anchor
+//! client, loads the batch swap router program and wraps it in a
+//! [`BatchSwapRouterClient`], so callers never touch the anchor-client
+//! plumbing.
+//!
+//! ## Lower-Level API
+//!
+//! [`XforceClient`] only forwards the common calls. Everything else
+//! (durable nonces, lookup tables, read commitments, ...) stays on
+//! [`BatchSwapRouterClient`], reachable through [`XforceClient::router`],
+//! and [`create_client`] remains available for callers that need the Anchor
+//! client itself.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use xforce_terminal_contracts_client::XforceClient;
+//!
+//! let client = XforceClient::connect("https://api.devnet.solana.com", wallet)?;
+//! client.verify_cluster()?;
+//!
+//! let signature = client.batch_swap(swaps)?;
+//! let stats = client.fetch_stats()?;
+//! ```
+
+use std::rc::Rc;
+
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+
+use crate::batch_swap_router::BatchSwapRouterClient;
+use crate::error::ContractError;
+use crate::stats::{PairStats, Stats};
+use crate::types::SwapParams;
+use crate::version::ProgramVersion;
+use crate::{create_client, get_batch_swap_router_program_id};
+
+/// Signer handle the wrapped router sends with
+///
+/// `create_client` needs a cloneable signer and keypairs are not `Clone`,
+/// so the payer is shared behind an `Rc` (which `create_client` wraps again).
+type SharedPayer<S> = Rc<Rc<S>>;
+
+/// Batch swap router client created from an RPC URL and a payer
+///
+/// # Type Parameters
+///
+/// * `S` - The payer's signer type (e.g. `Keypair`)
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use xforce_terminal_contracts_client::XforceClient;
+/// use solana_sdk::signature::Keypair;
+///
+/// let client = XforceClient::connect("http://localhost:8899", Keypair::new())?;
+/// let fee_bps = client.fetch_protocol_fee_bps()?;
+/// ```
+pub struct XforceClient<S> {
+    /// The wrapped router client (the lower-level API)
+    router: BatchSwapRouterClient<SharedPayer<S>>,
+}
+
+impl<S> XforceClient<S>
+where
+    S: Signer + 'static,
+{
+    /// Connect to a cluster with a payer
+    ///
+    /// No RPC request is made; the first call that reads or sends does.
+    /// Reads and sends use `confirmed` commitment (see [`create_client`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster_url` - The RPC URL of the Solana cluster
+    /// * `payer` - The wallet that signs and pays for transactions (the swap
+    ///   authority)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(XforceClient)` - A client for the batch swap router program
+    /// * `Err(ContractError)` - If the program client cannot be created
+    ///
+    /// # Errors
+    ///
+    /// Returns `ContractError::ClientError` if anchor-client cannot create
+    /// the program client.
+    pub fn connect(cluster_url: &str, payer: S) -> Result<Self, ContractError> {
+        let client = create_client(cluster_url, Rc::new(payer));
+        let program = client
+            .program(get_batch_swap_router_program_id())
+            .map_err(|e| ContractError::ClientError(e.to_string()))?;
+
+        Ok(Self {
+            router: BatchSwapRouterClient::new(program),
+        })
+    }
+
+    /// The wrapped [`BatchSwapRouterClient`], for calls not forwarded here
+    #[must_use]
+    pub fn router(&self) -> &BatchSwapRouterClient<SharedPayer<S>> {
+        &self.router
+    }
+
+    /// Unwrap the [`BatchSwapRouterClient`]
+    ///
+    /// Use this to configure it (e.g. `with_read_commitment`), which takes
+    /// the client by value.
+    #[must_use]
+    pub fn into_router(self) -> BatchSwapRouterClient<SharedPayer<S>> {
+        self.router
+    }
+
+    /// Execute a batch swap
+    ///
+    /// See [`BatchSwapRouterClient::batch_swap`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BatchSwapRouterClient::batch_swap`].
+    pub fn batch_swap(&self, swaps: Vec<SwapParams>) -> Result<Signature, ContractError> {
+        self.router.batch_swap(swaps)
+    }
+
+    /// Execute a single swap
+    ///
+    /// See [`BatchSwapRouterClient::execute_swap`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BatchSwapRouterClient::execute_swap`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_swap(
+        &self,
+        input_token_account: Pubkey,
+        output_token_account: Pubkey,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount: u64,
+        min_output_amount: u64,
+        expected_output: u64,
+    ) -> Result<Signature, ContractError> {
+        self.router.execute_swap(
+            input_token_account,
+            output_token_account,
+            input_mint,
+            output_mint,
+            amount,
+            min_output_amount,
+            expected_output,
+        )
+    }
+
+    /// Check that the RPC node is on the cluster the program is deployed on
+    ///
+    /// See [`BatchSwapRouterClient::verify_cluster`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BatchSwapRouterClient::verify_cluster`].
+    pub fn verify_cluster(&self) -> Result<(), ContractError> {
+        self.router.verify_cluster()
+    }
+
+    /// Read the program's version
+    ///
+    /// See [`BatchSwapRouterClient::fetch_program_version`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BatchSwapRouterClient::fetch_program_version`].
+    pub fn fetch_program_version(&self) -> Result<ProgramVersion, ContractError> {
+        self.router.fetch_program_version()
+    }
+
+    /// Read the program-wide swap analytics
+    ///
+    /// See [`BatchSwapRouterClient::fetch_stats`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BatchSwapRouterClient::fetch_stats`].
+    pub fn fetch_stats(&self) -> Result<Stats, ContractError> {
+        self.router.fetch_stats()
+    }
+
+    /// Read the analytics of a (directional) pair
+    ///
+    /// See [`BatchSwapRouterClient::fetch_pair_stats`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BatchSwapRouterClient::fetch_pair_stats`].
+    pub fn fetch_pair_stats(&self, input_mint: Pubkey, output_mint: Pubkey) -> Result<PairStats, ContractError> {
+        self.router.fetch_pair_stats(input_mint, output_mint)
+    }
+
+    /// Read the live protocol fee rate, in basis points
+    ///
+    /// See [`BatchSwapRouterClient::fetch_protocol_fee_bps`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BatchSwapRouterClient::fetch_protocol_fee_bps`].
+    pub fn fetch_protocol_fee_bps(&self) -> Result<u16, ContractError> {
+        self.router.fetch_protocol_fee_bps()
+    }
+
+    /// Check whether the program admin has paused swaps
+    ///
+    /// See [`BatchSwapRouterClient::is_paused`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`BatchSwapRouterClient::is_paused`].
+    pub fn is_paused(&self) -> Result<bool, ContractError> {
+        self.router.is_paused()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+
+    #[test]
+    fn test_connect_forwards_to_router() {
+        // Connecting makes no RPC request
+        let client = XforceClient::connect("http://127.0.0.1:8899", Keypair::new()).unwrap();
+        let mint = Pubkey::new_unique();
+
+        // Swaps are validated by the router before anything is sent
+        assert!(matches!(
+            client.batch_swap(vec![SwapParams::new(mint, mint, 1_000, 900)]),
+            Err(ContractError::InvalidAccount(_))
+        ));
+        assert!(matches!(
+            client.execute_swap(Pubkey::new_unique(), Pubkey::new_unique(), mint, mint, 1_000, 900, 950),
+            Err(ContractError::InvalidAccount(_))
+        ));
+    }
+}
@@ -0,0 +1,56 @@
+//! Example: Executing a scripted batch from a batch file
+//!
+//! This example loads a batch spec (see `batch.json`), validates it and submits it
+//!
+//! Usage: cargo run --example batch_spec -- batch.json [cluster-url]
+
+use std::{env, fs};
+
+use xforce_terminal_contracts_client::{
+    batch_spec::BatchSpec,
+    create_client,
+    BatchSwapRouterClient,
+    get_batch_swap_router_program_id,
+};
+use solana_sdk::signature::Keypair;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = env::args().skip(1);
+    let path = args.next().ok_or("Usage: batch_spec <batch.json> [cluster-url]")?;
+    let cluster_url = args.next().unwrap_or_else(|| "http://127.0.0.1:8899".to_string());
+
+    // Load and validate the batch file before connecting
+    let json = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let spec = BatchSpec::from_json(&json)
+        .map_err(|e| format!("Invalid batch file {}: {}", path, e))?;
+
+    println!("Loaded {} swaps from {}:", spec.swaps.len(), path);
+    for swap in &spec.swaps {
+        println!("  {}", swap);
+    }
+
+    // Initialize wallet (load the ops wallet from a keypair file in practice)
+    let wallet = Keypair::new();
+
+    let client = create_client(&cluster_url, wallet)
+        .map_err(|e| format!("Failed to create client: {}", e))?;
+    let program = client.program(get_batch_swap_router_program_id())
+        .map_err(|e| format!("Failed to get program: {}", e))?;
+    let swap_client = BatchSwapRouterClient::new(program);
+
+    // Submit validates the spec again, then sends it as one batch
+    match spec.submit(&swap_client) {
+        Ok(signature) => {
+            println!("Batch executed successfully!");
+            println!("Transaction signature: {}", signature);
+        }
+        Err(e) => {
+            eprintln!("Failed to execute batch: {}", e);
+            eprintln!("Note: Build the program first with 'anchor build' to generate IDL types");
+            return Err(Box::new(e));
+        }
+    }
+
+    Ok(())
+}
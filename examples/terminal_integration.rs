@@ -3,10 +3,8 @@
 //! This example shows how to use the batch swap router client in your terminal application
 
 use xforce_terminal_contracts_client::{
-    create_client,
-    BatchSwapRouterClient,
-    get_batch_swap_router_program_id,
     SwapParams,
+    XforceClient,
 };
 use solana_sdk::{
     pubkey::Pubkey,
@@ -22,18 +20,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // For devnet: "https://api.devnet.solana.com"
     // For mainnet: "https://api.mainnet-beta.solana.com"
     
-    // Create client
-    let client = create_client(cluster_url, wallet)
+    // Create the batch swap router client
+    // (`swap_client.router()` exposes the lower-level API)
+    let swap_client = XforceClient::connect(cluster_url, wallet)
         .map_err(|e| format!("Failed to create client: {}", e))?;
     
-    // Get the batch swap router program
-    let program_id = get_batch_swap_router_program_id();
-    let program = client.program(program_id)
-        .map_err(|e| format!("Failed to get program: {}", e))?;
-    
-    // Create batch swap router client
-    let swap_client = BatchSwapRouterClient::new(program);
-    
     // Example: Execute batch swap
     let swaps = vec![
         SwapParams {
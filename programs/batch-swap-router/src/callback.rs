@@ -0,0 +1,205 @@
+//! # Per-Leg Callbacks
+//!
+//! This module contains the opt-in per-leg callback used by `batch_swap`.
+//! When the caller passes `callback_program`, the router CPIs into that
+//! program after each successfully processed leg, handing it the leg's
+//! result so integrators can run their own logic (bookkeeping, hedging,
+//! notifications) in the same atomic transaction.
+//!
+//! ## Allowlist
+//!
+//! Only programs the config admin added with `set_callback_program` can be
+//! called (`ErrorCode::CallbackProgramNotAllowed` otherwise). A callback that
+//! fails fails the whole batch.
+//!
+//! ## Accounts
+//!
+//! The callback program must be the first remaining account of the
+//! `batch_swap` instruction. Every remaining account after it is forwarded
+//! to the callback, after the swap authority. The authority and all
+//! forwarded accounts are passed as **non-signers**, so a callback can never
+//! move the authority's tokens.
+//!
+//! ## Instruction Data
+//!
+//! `LEG_CALLBACK_DISCRIMINATOR` followed by the Borsh-serialized
+//! [`LegCallbackData`]. The discriminator is Anchor's for an instruction
+//! named `on_swap_leg`, so an Anchor callback program can simply declare
+//! `pub fn on_swap_leg(ctx: Context<...>, leg: LegCallbackData)`.
+//!
+//! ## Compute Units
+//!
+//! Each callback is a full CPI: roughly 1,000 CU of invoke overhead plus
+//! whatever the callback itself consumes, paid once per leg. A 10-leg batch
+//! with a 5,000 CU callback needs about 60,000 extra CU, so callers should
+//! raise the transaction's compute unit limit accordingly. Batches without a
+//! `callback_program` pay nothing.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+
+use crate::errors::ErrorCode;
+use crate::state::Config;
+
+/// Instruction discriminator sent to callback programs
+///
+/// First 8 bytes of `sha256("global:on_swap_leg")`, i.e. Anchor's
+/// discriminator for an instruction named `on_swap_leg`.
+pub const LEG_CALLBACK_DISCRIMINATOR: [u8; 8] = [80, 44, 112, 111, 174, 7, 97, 15];
+
+/// Result of one batch leg, sent to the callback program
+///
+/// # Fields
+///
+/// * `authority` - The swap authority of the batch
+/// * `leg_index` - Index of the leg in the batch (legs run in index order)
+/// * `input_mint` / `output_mint` - The leg's token pair
+/// * `amount` - Input amount of the leg
+/// * `min_output_amount` - Minimum output the leg is guaranteed
+/// * `protocol_fee` - Protocol fee charged on the leg
+/// * `tag` - The leg's caller-defined label (`SwapParams::tag`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LegCallbackData {
+    /// The swap authority of the batch
+    pub authority: Pubkey,
+    
+    /// Index of the leg in the batch
+    pub leg_index: u8,
+    
+    /// Input token mint
+    pub input_mint: Pubkey,
+    
+    /// Output token mint
+    pub output_mint: Pubkey,
+    
+    /// Input amount of the leg
+    pub amount: u64,
+    
+    /// Minimum output the leg is guaranteed
+    pub min_output_amount: u64,
+    
+    /// Protocol fee charged on the leg
+    pub protocol_fee: u64,
+    
+    /// Caller-defined label of the leg (0 if untagged)
+    pub tag: u64,
+}
+
+impl LegCallbackData {
+    /// Instruction data for the callback CPI
+    ///
+    /// # Returns
+    ///
+    /// `LEG_CALLBACK_DISCRIMINATOR` followed by the Borsh-serialized leg
+    pub fn instruction_data(&self) -> Result<Vec<u8>> {
+        let mut data = LEG_CALLBACK_DISCRIMINATOR.to_vec();
+        self.serialize(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// A validated callback program and the accounts forwarded to it
+pub struct LegCallback<'a, 'info> {
+    /// The callback program account
+    program: &'a AccountInfo<'info>,
+    
+    /// Extra accounts forwarded to the callback, after the authority
+    accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> LegCallback<'a, 'info> {
+    /// Validate `program_id` and resolve its account
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The program config holding the allowlist
+    /// * `program_id` - The requested callback program
+    /// * `remaining_accounts` - The instruction's remaining accounts; the
+    ///   first must be the callback program
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::CallbackProgramNotAllowed` - Program is not on the allowlist
+    /// * `ErrorCode::InvalidAccount` - The first remaining account is missing,
+    ///   is not `program_id` or is not executable
+    pub fn resolve(
+        config: &Config,
+        program_id: &Pubkey,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self> {
+        require!(
+            config.is_callback_allowed(program_id),
+            ErrorCode::CallbackProgramNotAllowed
+        );
+        
+        let (program, accounts) = remaining_accounts
+            .split_first()
+            .ok_or(ErrorCode::InvalidAccount)?;
+        require!(
+            program.key == program_id && program.executable,
+            ErrorCode::InvalidAccount
+        );
+        
+        Ok(Self { program, accounts })
+    }
+    
+    /// CPI into the callback program with a leg's result
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The swap authority, forwarded as a non-signer
+    /// * `leg` - The leg's result
+    ///
+    /// # Errors
+    ///
+    /// Returns the callback's error if it fails
+    pub fn invoke(&self, authority: &AccountInfo<'info>, leg: &LegCallbackData) -> Result<()> {
+        // Signer privileges are never forwarded (see "Accounts" above)
+        let metas = std::iter::once(authority)
+            .chain(self.accounts)
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: false,
+                is_writable: account.is_writable,
+            })
+            .collect();
+        let instruction = Instruction {
+            program_id: *self.program.key,
+            accounts: metas,
+            data: leg.instruction_data()?,
+        };
+        
+        let mut infos = Vec::with_capacity(self.accounts.len() + 2);
+        infos.push(authority.clone());
+        infos.extend_from_slice(self.accounts);
+        infos.push(self.program.clone());
+        
+        invoke(&instruction, &infos)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_data_layout() {
+        let leg = LegCallbackData {
+            authority: Pubkey::new_unique(),
+            leg_index: 2,
+            input_mint: Pubkey::new_unique(),
+            output_mint: Pubkey::new_unique(),
+            amount: 1_000,
+            min_output_amount: 900,
+            protocol_fee: 3,
+            tag: 7,
+        };
+
+        let data = leg.instruction_data().unwrap();
+        assert_eq!(data[..8], LEG_CALLBACK_DISCRIMINATOR);
+        assert_eq!(data.len(), 8 + 32 + 1 + 32 + 32 + 8 + 8 + 8 + 8);
+        assert_eq!(LegCallbackData::try_from_slice(&data[8..]).unwrap(), leg);
+    }
+}
@@ -8,6 +8,28 @@
 //!
 //! - `MAX_BATCH_SIZE`: Maximum number of swaps allowed in a single batch
 //! - `MIN_SWAP_AMOUNT`: Minimum swap amount to prevent dust attacks
+//! - `MIN_OUTPUT_AMOUNT`: Default floor for a swap's `min_output_amount`
+//! - `USE_FULL_BALANCE`: `execute_swap` amount meaning "the whole input balance"
+//! - `CONFIG_SEED`: PDA seed of the program `Config` account
+//! - `STATS_SEED` / `PAIR_STATS_SEED` / `USER_STATS_SEED`: PDA seeds of the
+//!   analytics accounts
+//! - `LAST_PRICE_SEED`: PDA seed prefix of the per-pair `LastPrice` accounts
+//! - `LAST_PRICE_SCALE` / `PRICE_JUMP_WINDOW_SECS`: Price format and time
+//!   window of the price jump check
+//! - `MAX_FEE_EXEMPTIONS`: Maximum number of fee-exempt authorities
+//! - `CONFIG_VERSION`: Current layout version of the `Config` account
+//! - `MAX_PROTOCOL_FEE_BPS`: Upper bound for the admin-set protocol fee
+//! - `MAX_CALLBACK_PROGRAMS`: Maximum number of allowlisted callback programs
+//! - `MAX_BURN_BPS`: Upper bound for the burned share of the protocol fee
+//! - `INSTRUCTION_VERSION` / `MIN_SUPPORTED_INSTRUCTION_VERSION`: Accepted
+//!   instruction format versions
+//! - `FEATURE_*` / `ENABLED_FEATURES`: Build feature bits reported by
+//!   `get_version`
+//! - `FEE_DISCOUNT_TIERS`: Volume tiers of the loyalty fee discount
+//! - `FEE_ANOMALY_TOLERANCE_BPS`: Allowed drift of the realized fee rate
+//!   from `Config::expected_fee_rate_bps`
+//! - `COMPUTE_BUDGET_PROGRAM_ID` / `SET_COMPUTE_UNIT_PRICE_TAG`: How the
+//!   priority fee is found in the transaction's instructions
 
 /// Maximum number of swaps allowed in a single batch transaction
 ///
@@ -69,6 +91,30 @@ pub const MAX_BATCH_SIZE: usize = 10;
 /// - Could be different for different instruction types
 pub const MIN_SWAP_AMOUNT: u64 = 1;
 
+/// Default floor for the `min_output_amount` of every swap
+///
+/// `MIN_SWAP_AMOUNT` only guards the input side: a swap could still ask for
+/// next to nothing in return. `execute_swap`, `batch_swap` and `batch_ops`
+/// reject a `min_output_amount` below this floor with
+/// `ErrorCode::InvalidMinOutput`. The admin can raise it per deployment
+/// through `Config::min_output_floor` (0 keeps this default).
+///
+/// **Current Value**: 1 token unit
+pub const MIN_OUTPUT_AMOUNT: u64 = 1;
+
+/// `execute_swap` amount sentinel: swap the full input account balance
+///
+/// Passing this as `amount` makes `execute_swap` swap whatever the input
+/// token account holds when the instruction runs (the fee is taken out of
+/// it, so `balance - fee` is swapped). The balance is read on-chain, so
+/// there is no race between a client reading it and the swap landing. The
+/// resolved amount must still be at least `MIN_SWAP_AMOUNT`, and it is what
+/// `SwapExecutedEvent::input_amount` reports.
+///
+/// **Current Value**: `u64::MAX` (never a real balance worth swapping, since
+/// no SPL mint can supply more than `u64::MAX` in total)
+pub const USE_FULL_BALANCE: u64 = u64::MAX;
+
 /// Program name for logging and identification
 pub const PROGRAM_NAME: &str = "batch-swap-router";
 
@@ -80,6 +126,10 @@ pub const PROGRAM_VERSION: &str = "0.1.0";
 /// This fee is charged on each swap and distributed to the protocol treasury.
 /// The fee is calculated as a percentage of the swap amount.
 ///
+/// This is the default rate for new and migrated `Config` accounts; the
+/// rate actually charged is `Config::fee_bps`, which the admin can change
+/// with `update_config`.
+///
 /// **Current Value**: 30 basis points (0.3%)
 ///
 /// # Fee Calculation
@@ -100,6 +150,46 @@ pub const PROTOCOL_FEE_BPS: u64 = 30;
 /// **Current Value**: 500 basis points (5%)
 pub const MAX_SLIPPAGE_BPS: u64 = 500;
 
+/// Current instruction format version
+///
+/// Clients send this as the leading `version` argument of `batch_swap`,
+/// `batch_swap_large`, `batch_ops` and `execute_swap`. Bump it whenever the
+/// argument layout of any of them changes.
+///
+/// **Current Value**: 1
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Oldest instruction format version the program still accepts
+///
+/// Versions in `MIN_SUPPORTED_INSTRUCTION_VERSION..=INSTRUCTION_VERSION` are
+/// accepted; anything else fails with `ErrorCode::UnsupportedInstructionVersion`.
+/// Raise this once old clients no longer need to be served.
+pub const MIN_SUPPORTED_INSTRUCTION_VERSION: u8 = 1;
+
+/// `get_version` feature bit: built with `large-amounts` (`batch_swap_large`
+/// is available)
+pub const FEATURE_LARGE_AMOUNTS: u64 = 1 << 0;
+
+/// `get_version` feature bit: built with `no-fees` (no protocol fee is charged)
+pub const FEATURE_NO_FEES: u64 = 1 << 1;
+
+/// `get_version` feature bit: built with `strict_sanity` (implausible
+/// minimum outputs and price jumps are rejected)
+pub const FEATURE_STRICT_SANITY: u64 = 1 << 2;
+
+/// `get_version` feature bit: built with `cu_profiling` (per-leg compute
+/// unit logs)
+pub const FEATURE_CU_PROFILING: u64 = 1 << 3;
+
+/// Feature bits of this build, as reported by `get_version`
+///
+/// New bits are only ever added, so clients can treat unknown bits as
+/// features they do not use.
+pub const ENABLED_FEATURES: u64 = (if cfg!(feature = "large-amounts") { FEATURE_LARGE_AMOUNTS } else { 0 })
+    | (if cfg!(feature = "no-fees") { FEATURE_NO_FEES } else { 0 })
+    | (if cfg!(feature = "strict_sanity") { FEATURE_STRICT_SANITY } else { 0 })
+    | (if cfg!(feature = "cu_profiling") { FEATURE_CU_PROFILING } else { 0 });
+
 /// Jupiter program ID (v6)
 ///
 /// This is the program ID for Jupiter aggregator v6.
@@ -109,3 +199,152 @@ pub const MAX_SLIPPAGE_BPS: u64 = 500;
 pub const JUPITER_PROGRAM_ID: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
 
 
+
+/// PDA seed for the program `Config` account
+///
+/// The config account lives at `find_program_address(&[CONFIG_SEED], program_id)`.
+/// There is exactly one config account per program deployment.
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// PDA seed for the program-wide `Stats` account
+///
+/// The stats account lives at `find_program_address(&[STATS_SEED], program_id)`
+/// and is created alongside the config by `initialize_config`.
+pub const STATS_SEED: &[u8] = b"stats";
+
+/// PDA seed prefix for per-pair `PairStats` accounts
+///
+/// A pair's stats account lives at
+/// `find_program_address(&[PAIR_STATS_SEED, input_mint, output_mint], program_id)`.
+/// The pair is directional: A -> B and B -> A have separate accounts.
+pub const PAIR_STATS_SEED: &[u8] = b"pair_stats";
+
+/// PDA seed prefix for per-authority `UserStats` accounts
+///
+/// An authority's stats account lives at
+/// `find_program_address(&[USER_STATS_SEED, authority], program_id)`.
+pub const USER_STATS_SEED: &[u8] = b"user_stats";
+
+/// PDA seed prefix for per-pair `LastPrice` accounts
+///
+/// A pair's last price account lives at
+/// `find_program_address(&[LAST_PRICE_SEED, input_mint, output_mint], program_id)`.
+/// Like `PairStats`, the pair is directional.
+pub const LAST_PRICE_SEED: &[u8] = b"last_price";
+
+/// Fixed-point scale of `LastPrice::price`
+///
+/// A price is output units per input unit times this scale, so a 1:1 swap
+/// stores `LAST_PRICE_SCALE`.
+///
+/// **Current Value**: 1,000,000,000 (9 decimal places)
+pub const LAST_PRICE_SCALE: u128 = 1_000_000_000;
+
+/// Time window of the price jump check, in seconds
+///
+/// `execute_swap` only compares a pair's execution price with the stored
+/// one if the stored price is at most this old; older prices can
+/// legitimately have moved by any amount.
+///
+/// **Current Value**: 60 seconds
+///
+/// # Rationale
+///
+/// - Sandwiches and price manipulation move the price within a few slots
+/// - Ordinary market moves over a minute rarely exceed a sane jump limit
+pub const PRICE_JUMP_WINDOW_SECS: i64 = 60;
+
+/// Maximum number of authorities that can be exempt from protocol fees
+///
+/// Bounds the size of the `fee_exemptions` list stored in the `Config`
+/// account, which is allocated up front when the config is initialized.
+///
+/// **Current Value**: 16 authorities
+///
+/// # Rationale
+///
+/// - Exemptions are meant for a handful of DAOs and internal accounts
+/// - Keeps the linear lookup on every swap cheap
+/// - Keeps the config account small (16 * 32 bytes)
+pub const MAX_FEE_EXEMPTIONS: usize = 16;
+
+/// Current layout version of the `Config` account
+///
+/// Stored in `Config::version`. Configs created before the field existed
+/// read as version 0. Bump this whenever a deployed `Config` layout gains
+/// fields, and teach `migrate_config` to upgrade accounts from it.
+///
+/// **Current Value**: 1
+pub const CONFIG_VERSION: u8 = 1;
+
+/// Maximum protocol fee the admin can set, in basis points
+///
+/// `update_config` rejects higher rates with `ErrorCode::InvalidFeeBps`, so
+/// a compromised or mistaken admin can't take more than this from a swap.
+///
+/// **Current Value**: 100 basis points (1%)
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 100;
+
+/// Maximum number of programs on the per-leg callback allowlist
+///
+/// Limits the size of the `Config` account (one `Pubkey` per entry).
+///
+/// **Current Value**: 8 programs
+///
+/// # Rationale
+///
+/// - Callbacks are an advanced integration used by a few partners
+/// - Keeps the allowlist lookup on every callback batch cheap
+/// - Keeps the config account small (8 * 32 bytes)
+pub const MAX_CALLBACK_PROGRAMS: usize = 8;
+
+/// Maximum share of the protocol fee that can be burned, in basis points
+///
+/// `Config::burn_bps` is a share of the fee (not of the swap amount), so
+/// 10000 burns the whole fee and sends nothing to the treasury.
+/// `update_config` rejects higher values with `ErrorCode::InvalidBurnBps`.
+///
+/// **Current Value**: 10000 basis points (100% of the fee)
+pub const MAX_BURN_BPS: u16 = 10_000;
+
+/// Allowed drift of the realized fee rate from the expected one, in basis points
+///
+/// After each `execute_swap`, the program compares `Stats::total_fees` as a
+/// fraction of `Stats::total_volume` with `Config::expected_fee_rate_bps` and
+/// emits a `FeeAnomalyEvent` when they differ by more than this.
+///
+/// **Current Value**: 5 basis points (0.05%)
+///
+/// # Rationale
+///
+/// - Fee caps and rounding keep the realized rate slightly off the set rate
+/// - A wrong fee setting (e.g. 300 instead of 30) is far outside this band
+pub const FEE_ANOMALY_TOLERANCE_BPS: u64 = 5;
+
+/// Volume tiers of the loyalty fee discount, as `(min_volume, discount_bps)`
+///
+/// Sorted by volume. A user whose cumulative volume (raw units of
+/// `Config::loyalty_mint`, from their `UserStats`) reaches `min_volume` gets
+/// `discount_bps` off the protocol fee rate in `execute_swap` (see
+/// `fee_discount_bps_for_user`); the highest reached tier applies.
+///
+/// **Current Value**: 5 bps from 10^12, 10 bps from 10^13, 15 bps from 10^14
+/// (1M, 10M and 100M of a 6-decimal loyalty mint such as USDC)
+pub const FEE_DISCOUNT_TIERS: [(u128, u64); 3] = [
+    (1_000_000_000_000, 5),
+    (10_000_000_000_000, 10),
+    (100_000_000_000_000, 15),
+];
+
+/// Program ID of the native Compute Budget program
+///
+/// `batch_swap` looks for this program's `SetComputeUnitPrice` instruction
+/// in the instructions sysvar to report the batch's priority fee.
+pub const COMPUTE_BUDGET_PROGRAM_ID: anchor_lang::prelude::Pubkey =
+    anchor_lang::pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// First data byte of a Compute Budget `SetComputeUnitPrice` instruction
+///
+/// The tag is followed by the price in micro-lamports per compute unit, as a
+/// little-endian `u64`.
+pub const SET_COMPUTE_UNIT_PRICE_TAG: u8 = 3;
@@ -220,18 +220,19 @@ pub enum ErrorCode {
     /// # When This Error Occurs
     ///
     /// - Minimum output amount is zero
-    /// - Minimum output amount is negative (shouldn't happen with u64, but validated)
+    /// - Minimum output amount is below the output floor
+    ///   (`Config::min_output_floor`, or `MIN_OUTPUT_AMOUNT` if unset)
     /// - Minimum output amount validation fails
     ///
     /// # How to Fix
     ///
-    /// - Ensure min_output_amount > 0
+    /// - Ensure min_output_amount > 0 and at least the output floor
     /// - Check that amount is in the correct units
     /// - Verify amount accounts for expected slippage
     ///
     /// # Constraints
     ///
-    /// - Minimum output amount must be > 0
+    /// - Minimum output amount must be > 0 and >= the output floor
     /// - Should account for slippage (e.g., 5% slippage tolerance)
     ///
     /// # Example
@@ -307,6 +308,7 @@ pub enum ErrorCode {
     /// # When This Error Occurs
     ///
     /// - Authority doesn't own the source token account
+    /// - Authority doesn't own the `execute_swap` output token account
     /// - Authority doesn't have required permissions
     /// - Authority validation fails
     ///
@@ -381,13 +383,15 @@ pub enum ErrorCode {
     ///
     /// # When This Error Occurs
     ///
-    /// - Actual output < min_output_amount after swap execution
+    /// - 0 < actual output < min_output_amount after swap execution
+    ///   (logs `Slippage exceeded`; no output at all is `InsufficientOutput`)
     /// - Price moved unfavorably during swap execution
     /// - Insufficient liquidity causing worse execution price
+    /// - A batch leg sets `max_slippage_bps` above MAX_SLIPPAGE_BPS
     ///
     /// # How to Fix
     ///
-    /// - Increase slippage tolerance
+    /// - Increase slippage tolerance (up to MAX_SLIPPAGE_BPS)
     /// - Reduce swap amount
     /// - Wait for better market conditions
     /// - Check liquidity availability
@@ -402,14 +406,16 @@ pub enum ErrorCode {
     
     /// Insufficient output amount error
     ///
-    /// This error occurs when the actual output amount is less than the
-    /// minimum required output amount after accounting for fees.
+    /// This error occurs when a swap delivered no output at all, or when the
+    /// amount left after fees is below the required minimum. A swap that
+    /// delivered *some* output, just less than `min_output_amount`, fails
+    /// with `SlippageExceeded` instead.
     ///
     /// # When This Error Occurs
     ///
-    /// - Output amount after fees < min_output_amount
+    /// - The output token balance did not increase (logs `No output received`)
     /// - Fees exceed expected output
-    /// - Swap execution resulted in insufficient output
+    /// - Amount after fees is below the minimum swap amount
     ///
     /// # How to Fix
     ///
@@ -429,6 +435,9 @@ pub enum ErrorCode {
     /// - Fee recipient account has wrong mint
     /// - Fee recipient account is invalid
     /// - Fee recipient account cannot receive fees
+    /// - Fee recipient is the swap's own input or output token account
+    /// - Fee account is a token account while the treasury is a SOL wallet,
+    ///   or the other way round (`Config::treasury_is_token_account`)
     ///
     /// # How to Fix
     ///
@@ -514,6 +523,451 @@ pub enum ErrorCode {
     /// - Add more funds to account
     #[msg("Insufficient funds")]
     InsufficientFunds,
-}
 
+    /// Fee exemption list full error
+    ///
+    /// This error occurs when adding a fee exemption to a config that already
+    /// holds `MAX_FEE_EXEMPTIONS` exempt authorities.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `set_fee_exemption` is called with `exempt = true` and the list is full
+    ///
+    /// # How to Fix
+    ///
+    /// - Remove an exemption that is no longer needed
+    /// - Check the current list before adding
+    #[msg("Fee exemption list is full")]
+    FeeExemptionListFull,
+
+    /// Implausible minimum output error
+    ///
+    /// This error occurs when the opt-in `strict_sanity` check decides a
+    /// swap's minimum output is implausible for its input amount. The check is
+    /// a heuristic and only runs in builds with the `strict_sanity` feature.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `min_output_amount >= amount * 2`
+    /// - `min_output_amount <= amount / 1000`
+    ///
+    /// # How to Fix
+    ///
+    /// - Check the minimum output was computed in the output token's units
+    /// - Derive the minimum output from a live quote
+    /// - Use a deployment without `strict_sanity` for pairs with very
+    ///   different decimals or prices
+    #[msg("Implausible minimum output for swap amount")]
+    ImplausibleMinOutput,
+
+    /// Unsupported instruction version error
+    ///
+    /// This error occurs when the leading `version` argument of `batch_swap` or
+    /// `execute_swap` is outside the supported range. It replaces the opaque
+    /// deserialization failures an outdated client would otherwise hit after a
+    /// program upgrade.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `version < MIN_SUPPORTED_INSTRUCTION_VERSION` (client too old)
+    /// - `version > INSTRUCTION_VERSION` (client newer than the program)
+    ///
+    /// # How to Fix
+    ///
+    /// - Upgrade the client to match the deployed program
+    /// - Check the program was upgraded on the target cluster
+    #[msg("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+
+    /// Invalid maximum fee error
+    ///
+    /// This error occurs when a swap sets `max_fee` to `Some(0)`. A fee cap
+    /// of zero would silently make the swap fee-free; fee exemptions are
+    /// managed through the program config instead.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `SwapParams::max_fee == Some(0)`
+    ///
+    /// # How to Fix
+    ///
+    /// - Pass `None` for no cap, or a cap > 0
+    /// - Ask the config admin for a fee exemption
+    #[msg("Invalid maximum fee (must be > 0 when set)")]
+    InvalidMaxFee,
+
+    /// Authority/payer mismatch error
+    ///
+    /// This error occurs when the config requires the swap authority to be
+    /// the transaction's payer (`require_authority_is_payer`) and another
+    /// account signs an instruction in the same transaction.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - A relayer or second wallet signs an instruction alongside the authority
+    ///
+    /// # How to Fix
+    ///
+    /// - Have the authority pay for and sign the transaction alone
+    /// - Ask the config admin to allow relayer-paid transactions
+    #[msg("Authority must be the transaction payer")]
+    AuthorityPayerMismatch,
+
+    /// Config version mismatch error
+    ///
+    /// This error occurs when `migrate_config` is called on a config account
+    /// that is not on a layout version the migration upgrades from.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - The config was already migrated (or created on the current layout)
+    ///
+    /// # How to Fix
+    ///
+    /// - Nothing to do if the config is already on `CONFIG_VERSION`
+    #[msg("Config account is not on the expected prior version")]
+    ConfigVersionMismatch,
+
+    /// Invalid fee basis points error
+    ///
+    /// This error occurs when `update_config` is asked to set a protocol fee
+    /// above `MAX_PROTOCOL_FEE_BPS`.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `fee_bps` is greater than `MAX_PROTOCOL_FEE_BPS`
+    ///
+    /// # How to Fix
+    ///
+    /// - Choose a fee of at most `MAX_PROTOCOL_FEE_BPS` basis points
+    #[msg("Protocol fee exceeds the maximum allowed")]
+    InvalidFeeBps,
+
+    /// Callback program not allowed error
+    ///
+    /// This error occurs when `batch_swap` is given a `callback_program` that
+    /// is not on the config's callback allowlist.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - The program was never added with `set_callback_program`
+    /// - The program was removed from the allowlist
+    ///
+    /// # How to Fix
+    ///
+    /// - Ask the config admin to allowlist the callback program
+    /// - Pass `None` to run the batch without callbacks
+    #[msg("Callback program is not on the allowlist")]
+    CallbackProgramNotAllowed,
+
+    /// Callback program list full error
+    ///
+    /// This error occurs when allowlisting a callback program on a config that
+    /// already holds `MAX_CALLBACK_PROGRAMS` programs.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `set_callback_program` is called with `allowed = true` and the list is full
+    ///
+    /// # How to Fix
+    ///
+    /// - Remove a callback program that is no longer needed
+    #[msg("Callback program allowlist is full")]
+    CallbackProgramListFull,
+
+    /// Invalid burn basis points error
+    ///
+    /// This error occurs when `update_config` is asked to burn more than the
+    /// whole protocol fee.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `burn_bps` is greater than `MAX_BURN_BPS` (10000)
+    ///
+    /// # How to Fix
+    ///
+    /// - Choose a burn share between 0 and 10000 basis points of the fee
+    #[msg("Burn share exceeds 100% of the protocol fee")]
+    InvalidBurnBps,
+
+    /// Invalid recipient error
+    ///
+    /// This error occurs when `execute_swap` is asked to deliver output to an
+    /// explicit recipient and the recipient token account does not match.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `recipient` is set but no `recipient_token_account` is passed
+    /// - `recipient_token_account` is not the recipient's associated token
+    ///   account of the output mint
+    /// - `recipient_token_account` is passed without a `recipient`
+    ///
+    /// # How to Fix
+    ///
+    /// - Pass the recipient's ATA of the output mint (create it first if needed)
+    /// - Omit both to deliver output to the authority's `output_token_account`
+    #[msg("Recipient token account must be the recipient's ATA of the output mint")]
+    InvalidRecipient,
+
+    /// Fee exceeds amount error
+    ///
+    /// This error occurs when the input token account cannot cover the
+    /// protocol fee at the time it is collected.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `amount` is larger than the input account's balance
+    /// - Earlier instructions in the transaction (e.g. a swap route) already
+    ///   spent the input tokens the fee would be taken from
+    ///
+    /// # How to Fix
+    ///
+    /// - Swap no more than the input account holds
+    /// - Leave `amount` in the input account until `execute_swap` runs
+    #[msg("Input token balance does not cover the protocol fee")]
+    FeeExceedsAmount,
+
+    /// Invalid LP fee error
+    ///
+    /// This error occurs when the liquidity providers' part of the fee rate
+    /// would exceed the fee rate itself.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `update_config` sets `lp_fee_bps` above `fee_bps`
+    /// - `update_config` lowers `fee_bps` below the current `lp_fee_bps`
+    ///
+    /// # How to Fix
+    ///
+    /// - Keep `lp_fee_bps` at most `fee_bps` (lower both in the same update)
+    #[msg("LP fee share exceeds the protocol fee rate")]
+    InvalidLpFeeBps,
+
+    /// Invalid LP fee account error
+    ///
+    /// This error occurs when `execute_swap` has an LP part of the fee to pay
+    /// and the LP fee account is missing or does not match.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `Config::lp_fee_bps` is set and no `lp_fee_account` is passed
+    /// - `lp_fee_account` does not hold the input mint
+    /// - `lp_fee_account` is not owned by `Config::lp_fee_recipient`
+    ///
+    /// # How to Fix
+    ///
+    /// - Pass the LP fee recipient's token account of the input mint
+    #[msg("LP fee account is missing or does not match the config")]
+    InvalidLpFeeAccount,
+
+    /// Too many mints error
+    ///
+    /// This error occurs when a batch touches more distinct mints than the
+    /// config allows.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `Config::max_distinct_mints` is set and the distinct input, output
+    ///   (and, for `batch_ops`, transfer) mints of the batch exceed it
+    ///
+    /// # How to Fix
+    ///
+    /// - Split the batch into batches over fewer mints
+    /// - Ask the config admin to raise or clear `max_distinct_mints`
+    #[msg("Batch touches more distinct mints than the config allows")]
+    TooManyMints,
+
+    /// Price jump exceeded error
+    ///
+    /// This error occurs in `strict_sanity` builds when a swap's execution
+    /// price moved too far from the pair's recent price.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `Config::max_price_jump_bps` is set and the price differs from the
+    ///   pair's `LastPrice`, at most `PRICE_JUMP_WINDOW_SECS` old, by more
+    ///   than that
+    ///
+    /// # How to Fix
+    ///
+    /// - Retry once the pool price settles
+    /// - Check the route for a sandwich or a manipulated pool
+    /// - Ask the config admin to raise or clear `max_price_jump_bps`
+    #[msg("Execution price jumped more than the config allows")]
+    PriceJumpExceeded,
+
+    /// Non-canonical token account error
+    ///
+    /// This error occurs when `Config::strict_ata` is set and a swap token
+    /// account is not an associated token account.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - The input or output token account of `execute_swap` is not at the
+    ///   associated token account address of its owner and mint
+    ///
+    /// # How to Fix
+    ///
+    /// - Move the tokens to the owner's ATA and swap from (or into) it
+    /// - Ask the config admin to turn off `strict_ata` if non-ATA accounts
+    ///   are intended
+    #[msg("Token account is not the associated token account of its owner and mint")]
+    NonCanonicalTokenAccount,
+
+    /// Batch fee cap exceeded error
+    ///
+    /// This error occurs when the protocol fees of a batch add up to more
+    /// than the batch's `max_total_fee`.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `batch_swap` is called with a non-zero `max_total_fee` and the summed
+    ///   protocol fees of its legs exceed it (e.g. after a fee rate increase)
+    ///
+    /// # How to Fix
+    ///
+    /// - Check the current `Config::fee_bps` and re-quote the batch
+    /// - Raise `max_total_fee`, or split the batch
+    #[msg("Batch protocol fees exceed the batch fee cap")]
+    BatchFeeCapExceeded,
+
+    /// Quote expired error
+    ///
+    /// This error occurs when the quote behind `expected_output` is older
+    /// than the swap allows.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `execute_swap` is called with a non-zero `max_quote_age` and the
+    ///   cluster clock is more than `max_quote_age` seconds past
+    ///   `quote_timestamp` (e.g. the transaction landed late)
+    ///
+    /// # How to Fix
+    ///
+    /// - Fetch a fresh quote and resend the swap
+    /// - Raise `max_quote_age` if the transaction routinely takes longer to land
+    #[msg("The quote is older than max_quote_age")]
+    QuoteExpired,
+
+    /// Invalid fee denomination error
+    ///
+    /// This error occurs when the fee is to be paid in a denomination that
+    /// the swap or the fee configuration does not allow.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `execute_swap` is called with `FeeDenomination::Lamports` and the
+    ///   input mint is not the native (WSOL) mint
+    /// - `Config::burn_bps` or `Config::lp_fee_bps` is non-zero, so part of
+    ///   the fee must be burned or paid to LPs in input tokens
+    /// - `Config::treasury_is_token_account` is false (a SOL treasury) and
+    ///   the swap owes a fee in tokens
+    ///
+    /// # How to Fix
+    ///
+    /// - Pay the fee in tokens (`FeeDenomination::Token`)
+    /// - Only use lamport fees for WSOL input
+    /// - With a SOL treasury, pay the fee in lamports
+    #[msg("The fee can't be paid in this denomination for this swap")]
+    InvalidFeeDenomination,
+
+    /// Insufficient lamports error
+    ///
+    /// This error occurs when the authority's wallet cannot pay a lamport
+    /// fee and stay rent-exempt.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `execute_swap` is called with `FeeDenomination::Lamports` and the
+    ///   authority holds less than the fee plus the rent-exempt minimum
+    ///
+    /// # How to Fix
+    ///
+    /// - Fund the authority's wallet with more SOL
+    /// - Pay the fee in tokens (`FeeDenomination::Token`)
+    #[msg("Authority has insufficient lamports for the fee")]
+    InsufficientLamports,
+
+    /// Route accounts mismatch error
+    ///
+    /// This error occurs when the per-leg output accounts of a batch do not
+    /// match its legs.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - `batch_swap` is called with `leg_output_accounts = true` and fewer
+    ///   remaining accounts than legs
+    /// - A leg's output account is not a writable SPL Token account, or
+    ///   holds another mint than the leg's `output_mint`
+    /// - Accounts are left over after the output accounts without a
+    ///   `callback_program`
+    /// - `global_max_slippage_bps` is set without `leg_output_accounts`
+    ///
+    /// # How to Fix
+    ///
+    /// - Pass one output token account per leg, in leg order, before any
+    ///   callback accounts (see the `leg_accounts` module)
+    /// - Check the logged leg index for the mismatched account
+    #[msg("Leg output accounts do not match the batch's legs")]
+    RouteAccountsMismatch,
 
+    /// Batch slippage exceeded error
+    ///
+    /// This error occurs when a leg of a batch with leg output accounts
+    /// realized more slippage than its own `max_slippage_bps` or the batch's
+    /// `global_max_slippage_bps`, even if it met its own minimum output. The
+    /// whole batch is reverted.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - A leg's output account received less than its implied quote minus
+    ///   the smaller of its own tolerance and a non-zero
+    ///   `global_max_slippage_bps` (the leg index is logged)
+    /// - Any batch with leg output accounts while routes run client-side: the
+    ///   batch itself delivers no output, which counts as 100% slippage
+    ///
+    /// # How to Fix
+    ///
+    /// - Fetch fresh quotes and resend the batch
+    /// - Raise `global_max_slippage_bps` or the leg's `max_slippage_bps`, or
+    ///   send the batch without leg output accounts to rely on the per-leg
+    ///   minimums alone
+    #[msg("A leg of the batch exceeded the batch's slippage limit")]
+    BatchSlippageExceeded,
+    
+    /// Token program mismatch error
+    ///
+    /// This error occurs when a token account or mint is owned by another
+    /// token program than the `token_program` account, e.g. a Token-2022
+    /// account passed with the SPL Token program or vice versa.
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - A `batch_ops` transfer's source or destination account is not owned
+    ///   by `token_program`
+    ///
+    /// # How to Fix
+    ///
+    /// - Pass the token program that owns the mint (check the mint account's
+    ///   owner)
+    /// - Use token accounts created by that same program
+    #[msg("A token account or mint belongs to another token program than the one passed")]
+    TokenProgramMismatch,
+    
+    /// Program paused error
+    ///
+    /// This error occurs when a swap instruction is sent while the config
+    /// admin has paused the program (`Config::paused`).
+    ///
+    /// # When This Error Occurs
+    ///
+    /// - Any `batch_swap`, `batch_swap_large`, `batch_ops` or `execute_swap`
+    ///   call, simulated swaps included, while `paused` is set
+    ///
+    /// # How to Fix
+    ///
+    /// - Wait for the admin to unpause the program (`update_config`)
+    /// - Check `Config::paused` before sending a swap
+    #[msg("The program is paused")]
+    ProgramPaused,
+}
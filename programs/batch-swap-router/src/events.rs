@@ -6,7 +6,19 @@
 //! ## Events
 //!
 //! - `BatchSwapEvent`: Emitted when a batch swap is executed
-//! - `SwapExecutedEvent`: Emitted when a single swap is executed
+//! - `SwapExecutedEvent`: Emitted when a single swap is executed, and for
+//!   each leg of a batch swap (with `batch` set)
+//! - `SwapLegEvent`: Emitted for each leg of a batch swap, in leg order
+//!   (and for each swap op of `batch_ops`)
+//! - `TransferOpEvent`: Emitted for each transfer op of `batch_ops`
+//! - `LargeBatchSwapEvent`: Emitted when a `u128` batch swap is executed
+//!   (`large-amounts` feature)
+//! - `ConfigUpdatedEvent`: Emitted when the admin changes the protocol fee
+//!   rate, fee recipient or burn share
+//! - `FeeAnomalyEvent`: Emitted when the realized fee rate drifts from
+//!   `Config::expected_fee_rate_bps`
+//! - `PriceJumpEvent`: Emitted when a pair's execution price moves more than
+//!   `Config::max_price_jump_bps` within `PRICE_JUMP_WINDOW_SECS`
 //!
 //! ## Event Indexing
 //!
@@ -22,9 +34,28 @@
 //! - Timestamp of execution
 //! - Authority who executed the operation
 //! - Other operation-specific data
+//!
+//! ## Schema Versions
+//!
+//! `BatchSwapEvent` and `SwapExecutedEvent` start with a `schema_version`
+//! field set to [`EVENT_SCHEMA_VERSION`]. It is the first byte after the
+//! discriminator, so decoders can read it before the rest of the event and
+//! branch on the layout. The constant is bumped whenever either event's
+//! fields change.
 
 use anchor_lang::prelude::*;
 
+use crate::state::FeeDenomination;
+
+/// Layout version of `BatchSwapEvent` and `SwapExecutedEvent`
+///
+/// Emitted as each event's `schema_version`. Bump it in the same change that
+/// adds, removes or reorders a field of either event, and keep the client's
+/// mirror in step.
+///
+/// **Current Value**: 5 (`SwapExecutedEvent::fee_discount_bps` added)
+pub const EVENT_SCHEMA_VERSION: u8 = 5;
+
 /// Event emitted when a batch swap is executed
 ///
 /// This event is emitted after a successful batch swap execution. It contains
@@ -57,6 +88,7 @@ use anchor_lang::prelude::*;
 /// ```rust,ignore
 /// // Event is automatically emitted after successful batch swap
 /// emit!(BatchSwapEvent {
+///     schema_version: EVENT_SCHEMA_VERSION,
 ///     authority: authority.key(),
 ///     swap_count: swaps.len() as u8,
 ///     timestamp: clock.unix_timestamp,
@@ -65,6 +97,11 @@ use anchor_lang::prelude::*;
 ///
 /// # Event Data Details
 ///
+/// ## schema_version
+///
+/// [`EVENT_SCHEMA_VERSION`] at the time of emission. Indexers decoding events
+/// across program upgrades branch on it before reading the other fields.
+///
 /// ## authority
 ///
 /// The public key of the account that executed the batch swap. This is useful
@@ -77,6 +114,44 @@ use anchor_lang::prelude::*;
 /// - Understanding usage patterns
 /// - Calculating average batch sizes
 ///
+/// ## base_mint / total_output_amount
+///
+/// The settlement mint the caller passed to `batch_swap`, and the sum of the
+/// guaranteed (minimum) outputs of the legs that pay out in it. Summing
+/// outputs across heterogeneous mints is meaningless, so only legs whose
+/// `output_mint == base_mint` are counted; for a USDC-denominated rebalance
+/// this is the USDC the batch is guaranteed to produce.
+///
+/// ## vwap_bps
+///
+/// The batch's volume-weighted average price: total (minimum) output over
+/// total input across all legs, in basis points (10000 = 1:1). Each leg is
+/// weighted by its input amount, giving traders a single execution-quality
+/// number per batch. Like `total_output_amount`, it uses the guaranteed
+/// outputs, so the actual fill is at least this good.
+///
+/// ## weighted_max_slippage_bps
+///
+/// The legs' slippage tolerances averaged by input amount:
+/// `sum(effective_max_slippage_bps * amount) / sum(amount)`. This is the
+/// worst slippage the batch allows, not what it realized: `batch_swap` does
+/// not observe the fills (the swaps run client-side). Dashboards can track
+/// it per batch without decoding every `SwapLegEvent`.
+///
+/// ## memo_hash
+///
+/// SHA-256 hash of the compliance memo the client attached to the transaction
+/// (an SPL memo instruction), or all zeroes if there is none. Records can be
+/// matched to the memo text off-chain without storing it in the event.
+///
+/// ## priority_fee_microlamports
+///
+/// The compute unit price the transaction paid, in micro-lamports per
+/// compute unit, read from its Compute Budget `SetComputeUnitPrice`
+/// instruction via the instructions sysvar. Clients are not required to set
+/// a price: without one (or with malformed price data) this is 0. Analytics
+/// can correlate it with landing rates to tune priority fees.
+///
 /// ## timestamp
 ///
 /// The Unix timestamp when the batch swap was executed. This is useful for:
@@ -85,7 +160,11 @@ use anchor_lang::prelude::*;
 /// - Monitoring program activity over time
 ///
 #[event]
+#[derive(Clone, Debug)]
 pub struct BatchSwapEvent {
+    /// Layout version of the event (`EVENT_SCHEMA_VERSION`)
+    pub schema_version: u8,
+    
     /// The public key of the authority who executed the batch swap
     pub authority: Pubkey,
     
@@ -98,6 +177,74 @@ pub struct BatchSwapEvent {
     /// Total protocol fees collected
     pub total_protocol_fees: u64,
     
+    /// Settlement mint used for `total_output_amount`
+    pub base_mint: Pubkey,
+    
+    /// Sum of `min_output_amount` over legs whose output mint is `base_mint`
+    pub total_output_amount: u64,
+    
+    /// Volume-weighted average price across all legs, in basis points
+    pub vwap_bps: u64,
+    
+    /// Per-leg slippage tolerance averaged by input amount, in basis points
+    pub weighted_max_slippage_bps: u64,
+    
+    /// SHA-256 hash of the memo attached to the transaction
+    ///
+    /// All zeroes when no memo was attached.
+    pub memo_hash: [u8; 32],
+    
+    /// Compute unit price paid by the transaction, in micro-lamports (0 if none)
+    pub priority_fee_microlamports: u64,
+    
+    /// The Unix timestamp when the batch swap was executed
+    pub timestamp: i64,
+}
+
+/// Event emitted for each leg of a batch swap
+///
+/// `batch_swap` processes legs strictly in index order and emits one
+/// `SwapLegEvent` per leg, before the summary [`BatchSwapEvent`]. The
+/// `leg_index` lets indexers reconstruct the order of dependent legs (e.g.
+/// leg 0 swaps A -> B and leg 1 spends that B for B -> C).
+///
+/// # Event Data
+///
+/// * `authority` - The authority who executed the batch swap
+/// * `leg_index` - Zero-based position of the leg in the batch
+/// * `input_mint` / `output_mint` - The leg's token pair
+/// * `amount` - Input amount of the leg
+/// * `min_output_amount` - Minimum output of the leg
+/// * `protocol_fee` - Protocol fee calculated for the leg
+/// * `tag` - The leg's caller-defined label (`SwapParams::tag`, 0 if untagged)
+/// * `timestamp` - The Unix timestamp when the batch swap was executed
+#[event]
+#[derive(Clone, Debug)]
+pub struct SwapLegEvent {
+    /// The public key of the authority who executed the batch swap
+    pub authority: Pubkey,
+    
+    /// Zero-based position of this leg in the batch
+    pub leg_index: u8,
+    
+    /// Input token mint
+    pub input_mint: Pubkey,
+    
+    /// Output token mint
+    pub output_mint: Pubkey,
+    
+    /// Input token amount
+    pub amount: u64,
+    
+    /// Minimum output amount
+    pub min_output_amount: u64,
+    
+    /// Protocol fee calculated for this leg
+    pub protocol_fee: u64,
+    
+    /// Caller-defined label of this leg (0 if untagged)
+    pub tag: u64,
+    
     /// The Unix timestamp when the batch swap was executed
     pub timestamp: i64,
 }
@@ -135,6 +282,7 @@ pub struct BatchSwapEvent {
 /// ```rust,ignore
 /// // Event is automatically emitted after successful swap
 /// emit!(SwapExecutedEvent {
+///     schema_version: EVENT_SCHEMA_VERSION,
 ///     authority: authority.key(),
 ///     amount: amount,
 ///     from: from.key(),
@@ -145,11 +293,28 @@ pub struct BatchSwapEvent {
 ///
 /// # Event Data Details
 ///
+/// ## schema_version
+///
+/// [`EVENT_SCHEMA_VERSION`] at the time of emission (see [`BatchSwapEvent`]).
+///
 /// ## authority
 ///
 /// The public key of the account that executed the swap. This is useful
 /// for tracking which users are using the swap functionality.
 ///
+/// ## recipient
+///
+/// The wallet the output was delivered to: the authority, or the explicit
+/// `recipient` passed to `execute_swap` (e.g. a cold wallet).
+///
+/// ## net_output_amount
+///
+/// The output the recipient actually keeps, for accounting without
+/// recomputing fees. The protocol fee is charged on the input side and the
+/// output is measured as the delivery account's balance increase (after any
+/// token transfer fee), so today it equals `output_amount`; an output-side
+/// fee would be deducted here.
+///
 /// ## amount
 ///
 /// The amount of tokens transferred in this swap. This is expressed in
@@ -165,36 +330,335 @@ pub struct BatchSwapEvent {
 /// The public key of the destination token account. This is the account
 /// that tokens were transferred to.
 ///
+/// ## Denomination
+///
+/// Each amount field is on one side of the swap:
+///
+/// - Input side (input token units): `input_amount`, `protocol_fee`,
+///   `treasury_fee`, `burned_fee`, `lp_fee` and `fee_collected`. With
+///   `fee_denomination = Lamports` (WSOL input only) the fees were paid in
+///   lamports, which are WSOL units 1:1
+/// - Output side (output token units): `output_amount` and
+///   `net_output_amount`
+/// - `slippage_bps`: always output-based, the share of `expected_output`
+///   that did not arrive (rounded down, 0 without a quote), even when the
+///   swap was validated with `SlippageBasis::Input`
+///
+/// ## fee_discount_bps
+///
+/// The loyalty discount taken off `Config::fee_bps` for this swap, in basis
+/// points: the authority's volume tier when it passed its `UserStats`,
+/// clamped to the configured rate, and 0 otherwise (or when fee-exempt).
+/// `protocol_fee` was charged at `fee_bps - fee_discount_bps`.
+///
+/// ## tag
+///
+/// The caller-defined label passed to `execute_swap` (0 if untagged).
+/// Portfolio tools use it to group swaps by strategy without separate
+/// bookkeeping.
+///
+/// ## simulated
+///
+/// Whether `execute_swap` ran in simulation mode: no tokens moved and the
+/// amounts are computed from the quote. Only expected under
+/// `simulateTransaction`; indexers should skip simulated events that land
+/// on-chain.
+///
+/// ## batch / leg_index
+///
+/// Whether the swap ran as a leg of a batch, and its zero-based position in
+/// that batch, so one schema serves single and batched swaps. `execute_swap`
+/// leaves them at `false` / 0. `batch_swap` emits one `SwapExecutedEvent`
+/// per leg with `batch` set, next to the leg's [`SwapLegEvent`] (same
+/// `leg_index`). Its legs are routed client-side, so the program sees no
+/// fill: `output_amount` and `net_output_amount` report the leg's
+/// `min_output_amount`, `slippage_bps` is 0, and the fee split and
+/// `fee_collected` are 0 because no fee is transferred by the batch.
+///
 /// ## timestamp
 ///
 /// The Unix timestamp when the swap was executed. This is useful for
 /// time-based analysis and tracking.
 ///
 #[event]
+#[derive(Clone, Debug)]
 pub struct SwapExecutedEvent {
+    /// Layout version of the event (`EVENT_SCHEMA_VERSION`)
+    pub schema_version: u8,
+    
     /// The public key of the authority who executed the swap
     pub authority: Pubkey,
     
+    /// Owner of the account that received the output
+    ///
+    /// The authority unless `execute_swap` was given an explicit recipient.
+    pub recipient: Pubkey,
+    
     /// Input token amount
     pub input_amount: u64,
     
     /// Output token amount received
     pub output_amount: u64,
     
+    /// Output the recipient keeps after all fees
+    pub net_output_amount: u64,
+    
     /// Input token mint
     pub input_mint: Pubkey,
     
     /// Output token mint
     pub output_mint: Pubkey,
     
-    /// Protocol fee charged
+    /// Protocol fee charged, in input token units
     pub protocol_fee: u64,
     
-    /// Slippage in basis points
+    /// Part of `protocol_fee` transferred to the fee recipient
+    pub treasury_fee: u64,
+    
+    /// Part of `protocol_fee` burned (`Config::burn_bps`)
+    pub burned_fee: u64,
+    
+    /// Part of `protocol_fee` paid to liquidity providers (`Config::lp_fee_bps`)
+    pub lp_fee: u64,
+    
+    /// Asset `protocol_fee` was paid in: input tokens, or lamports from the
+    /// authority's wallet for a WSOL swap (all of it to the treasury)
+    pub fee_denomination: FeeDenomination,
+    
+    /// Balance increase of the fee recipient (or fee treasury) measured
+    /// around the fee transfer; `treasury_fee` unless the token withheld
+    /// part of it (0 when no fee was transferred)
+    pub fee_collected: u64,
+    
+    /// Loyalty discount off the protocol fee rate, in basis points
+    pub fee_discount_bps: u16,
+    
+    /// Slippage in basis points, measured on the output side
     pub slippage_bps: u64,
     
+    /// SHA-256 hash of the memo attached to the transaction
+    ///
+    /// All zeroes when no memo was attached.
+    pub memo_hash: [u8; 32],
+    
+    /// Caller-defined label of the swap, e.g. a strategy id (0 if untagged)
+    pub tag: u64,
+    
+    /// Whether the swap was simulated (no tokens moved)
+    pub simulated: bool,
+    
+    /// Whether the swap was a leg of a batch (false for `execute_swap`)
+    pub batch: bool,
+    
+    /// Zero-based position of the leg in its batch (0 unless `batch`)
+    pub leg_index: u8,
+    
     /// The Unix timestamp when the swap was executed
     pub timestamp: i64,
 }
 
+/// Event emitted when a `u128` batch swap is executed
+///
+/// `u128` counterpart of [`BatchSwapEvent`], emitted by the `batch_swap_large`
+/// instruction. Totals are reported in `u128` because they can exceed
+/// `u64::MAX` even when every individual leg fits in an SPL token amount.
+///
+/// Only available with the `large-amounts` feature.
+#[cfg(feature = "large-amounts")]
+#[event]
+#[derive(Clone, Debug)]
+pub struct LargeBatchSwapEvent {
+    /// The public key of the authority who executed the batch swap
+    pub authority: Pubkey,
+    
+    /// The number of swaps executed in this batch
+    pub swap_count: u8,
+    
+    /// Total input amount across all swaps
+    pub total_input_amount: u128,
+    
+    /// Total protocol fees collected
+    pub total_protocol_fees: u128,
+    
+    /// The Unix timestamp when the batch swap was executed
+    pub timestamp: i64,
+}
+
+/// Event emitted when the protocol fee settings change
+///
+/// Emitted by `update_config` whenever `Config::fee_bps`,
+/// `Config::fee_recipient`, `Config::burn_bps` or the LP fee settings change,
+/// so indexers and users can detect fee changes without polling the config
+/// account. Updates that only touch other settings do not emit it.
+///
+/// # Event Data
+///
+/// * `admin` - The config admin who made the change
+/// * `old_fee_bps` / `new_fee_bps` - Protocol fee rate before and after
+/// * `old_recipient` / `new_recipient` - Fee recipient owner before and after
+///   (`Pubkey::default()` means any owner)
+/// * `old_burn_bps` / `new_burn_bps` - Burned share of the fee before and after
+/// * `old_lp_fee_bps` / `new_lp_fee_bps` - LP part of the fee rate before and after
+/// * `old_lp_recipient` / `new_lp_recipient` - LP fee recipient owner before
+///   and after (`Pubkey::default()` means any owner)
+/// * `timestamp` - The Unix timestamp when the config was updated
+#[event]
+#[derive(Clone, Debug)]
+pub struct ConfigUpdatedEvent {
+    /// The config admin who made the change
+    pub admin: Pubkey,
+    
+    /// Protocol fee rate before the update, in basis points
+    pub old_fee_bps: u16,
+    
+    /// Protocol fee rate after the update, in basis points
+    pub new_fee_bps: u16,
+    
+    /// Fee recipient owner before the update
+    pub old_recipient: Pubkey,
+    
+    /// Fee recipient owner after the update
+    pub new_recipient: Pubkey,
+    
+    /// Burned share of the fee before the update, in basis points
+    pub old_burn_bps: u16,
+    
+    /// Burned share of the fee after the update, in basis points
+    pub new_burn_bps: u16,
+    
+    /// LP part of the fee rate before the update, in basis points
+    pub old_lp_fee_bps: u16,
+    
+    /// LP part of the fee rate after the update, in basis points
+    pub new_lp_fee_bps: u16,
+    
+    /// LP fee recipient owner before the update
+    pub old_lp_recipient: Pubkey,
+    
+    /// LP fee recipient owner after the update
+    pub new_lp_recipient: Pubkey,
+    
+    /// The Unix timestamp when the config was updated
+    pub timestamp: i64,
+}
+
+/// Event emitted for each transfer op of `batch_ops`
+///
+/// Swap ops of the same batch emit [`SwapLegEvent`] with `leg_index` set to
+/// their op index, so `op_index` orders both kinds of events.
+///
+/// # Event Data
+///
+/// * `authority` - The authority who executed the batch
+/// * `op_index` - Zero-based position of the op in the batch
+/// * `mint` - Mint of the transferred tokens
+/// * `from` / `to` - Source and destination token accounts
+/// * `amount` - Amount transferred
+/// * `timestamp` - The Unix timestamp when the batch was executed
+#[event]
+#[derive(Clone, Debug)]
+pub struct TransferOpEvent {
+    /// The public key of the authority who executed the batch
+    pub authority: Pubkey,
+    
+    /// Zero-based position of this op in the batch
+    pub op_index: u8,
+    
+    /// Mint of the transferred tokens
+    pub mint: Pubkey,
+    
+    /// Source token account (owned by the authority)
+    pub from: Pubkey,
+    
+    /// Destination token account
+    pub to: Pubkey,
+    
+    /// Amount transferred, in the mint's smallest unit
+    pub amount: u64,
+    
+    /// The Unix timestamp when the batch was executed
+    pub timestamp: i64,
+}
 
+/// Event emitted when the realized fee rate drifts from the expected one
+///
+/// Emitted by `execute_swap` when `Stats::total_fees` as a fraction of
+/// `Stats::total_volume` differs from `Config::expected_fee_rate_bps` by more
+/// than `FEE_ANOMALY_TOLERANCE_BPS`. It flags a fee misconfiguration or a fee
+/// bypass; the swap itself still succeeds. Emitted after every swap while the
+/// drift lasts, whether or not the swap asked for events.
+///
+/// # Event Data
+///
+/// * `authority` - The authority of the swap that triggered the check
+/// * `realized_fee_rate_bps` - All-time fees over all-time volume, in basis points
+/// * `expected_fee_rate_bps` - The admin-set expected rate, in basis points
+/// * `total_volume` / `total_fees` - The `Stats` counters the rate was computed from
+/// * `timestamp` - The Unix timestamp of the swap
+#[event]
+#[derive(Clone, Debug)]
+pub struct FeeAnomalyEvent {
+    /// The public key of the authority whose swap triggered the check
+    pub authority: Pubkey,
+    
+    /// Realized fee rate across all swaps, in basis points of volume
+    pub realized_fee_rate_bps: u64,
+    
+    /// Expected fee rate set by the admin, in basis points of volume
+    pub expected_fee_rate_bps: u16,
+    
+    /// All-time input volume, in raw token units
+    pub total_volume: u128,
+    
+    /// All-time protocol fees, in raw token units
+    pub total_fees: u128,
+    
+    /// The Unix timestamp of the swap
+    pub timestamp: i64,
+}
+
+/// Event emitted when a pair's execution price jumps
+///
+/// Emitted by `execute_swap` when the swap's execution price differs from
+/// the pair's `LastPrice`, stored at most `PRICE_JUMP_WINDOW_SECS` earlier,
+/// by more than `Config::max_price_jump_bps`. A jump within seconds points
+/// to a sandwich or a manipulated pool. The swap itself still succeeds,
+/// except in `strict_sanity` builds, which reject it instead of emitting
+/// this event. Emitted whether or not the swap asked for events.
+///
+/// # Event Data
+///
+/// * `authority` - The authority of the swap
+/// * `input_mint` / `output_mint` - The (directional) pair
+/// * `previous_price` / `price` - Stored and new execution price, in output
+///   units per input unit scaled by `LAST_PRICE_SCALE`
+/// * `jump_bps` - Move from the stored price, in basis points
+/// * `elapsed_secs` - Age of the stored price
+/// * `timestamp` - The Unix timestamp of the swap
+#[event]
+#[derive(Clone, Debug)]
+pub struct PriceJumpEvent {
+    /// The public key of the authority who executed the swap
+    pub authority: Pubkey,
+    
+    /// Input token mint of the pair
+    pub input_mint: Pubkey,
+    
+    /// Output token mint of the pair
+    pub output_mint: Pubkey,
+    
+    /// Execution price of the pair's previous swap, scaled by `LAST_PRICE_SCALE`
+    pub previous_price: u64,
+    
+    /// Execution price of this swap, scaled by `LAST_PRICE_SCALE`
+    pub price: u64,
+    
+    /// Move from the previous price, in basis points
+    pub jump_bps: u64,
+    
+    /// Seconds since the previous swap of the pair
+    pub elapsed_secs: i64,
+    
+    /// The Unix timestamp of the swap
+    pub timestamp: i64,
+}
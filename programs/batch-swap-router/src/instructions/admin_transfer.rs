@@ -0,0 +1,69 @@
+//! # Admin Transfer Instruction Handlers
+//!
+//! This module contains the handlers for the `propose_admin` and
+//! `accept_admin` instructions, which hand the
+//! [`Config`](crate::state::Config) admin role over in two steps.
+//!
+//! ## Process
+//!
+//! 1. The current admin calls `propose_admin` with the new key, which is
+//!    stored in `Config::pending_admin`
+//! 2. The proposed key signs `accept_admin`, becomes `Config::admin`, and
+//!    `pending_admin` is cleared
+//!
+//! A key that cannot sign (a typo, an uncontrolled address) can never accept,
+//! so the current admin stays in control and can propose again. Proposing
+//! `Pubkey::default()` cancels a pending transfer.
+
+use anchor_lang::prelude::*;
+
+use crate::state::{AcceptAdmin, ProposeAdmin};
+
+/// Handler for the propose admin instruction
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing the current admin and the config account
+/// * `new_admin` - The proposed admin (`Pubkey::default()` cancels)
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+///
+/// # Errors
+///
+/// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+pub fn propose_handler(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    
+    config.pending_admin = new_admin;
+    
+    msg!("Admin transfer proposed: {} -> {}", config.admin, new_admin);
+    
+    Ok(())
+}
+
+/// Handler for the accept admin instruction
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing the pending admin and the config account
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+///
+/// # Errors
+///
+/// * `ErrorCode::InvalidAuthority` - Signer is not the pending admin
+pub fn accept_handler(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+    
+    config.admin = ctx.accounts.pending_admin.key();
+    config.pending_admin = Pubkey::default();
+    
+    msg!("Admin transfer accepted: {} -> {}", previous_admin, config.admin);
+    
+    Ok(())
+}
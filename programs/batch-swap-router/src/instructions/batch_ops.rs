@@ -0,0 +1,355 @@
+//! # Batch Ops Instruction Handler
+//!
+//! This module contains the handler for the `batch_ops` instruction, which
+//! generalizes `batch_swap` to a batch of heterogeneous operations
+//! ([`BatchOp`]): swaps and plain token transfers, executed atomically.
+//!
+//! ## Process Flow
+//!
+//! 1. **Validate Ops**: Check the batch size and every op before touching state
+//! 2. **Process Ops**: Run each op in index order, emitting a per-op event
+//!
+//! ## Ops
+//!
+//! - `Swap` ops are validated, charged and recorded exactly like `batch_swap`
+//!   legs, and emit a [`SwapLegEvent`] whose `leg_index` is the op index
+//! - `Transfer` ops move tokens from the authority's token account with an
+//!   SPL transfer and emit a [`TransferOpEvent`]; they pay no protocol fee
+//!   and are not counted in the swap stats
+//!
+//! ## Transfer Accounts
+//!
+//! Each transfer op consumes the next two remaining accounts, in op order:
+//! the source token account and the destination token account (see
+//! [`BatchOp`]). A batch must pass exactly two remaining accounts per
+//! transfer op.
+//!
+//! ## Validation
+//!
+//! The handler validates:
+//! - Batch is not empty and has <= MAX_BATCH_SIZE (10) ops, swaps and
+//!   transfers combined
+//! - Each swap op passes the same checks as a `batch_swap` leg
+//! - Each transfer amount >= MIN_SWAP_AMOUNT (1), with non-default mint and
+//!   destination
+//! - Each transfer's source is owned by the authority, and both accounts
+//!   hold the op's mint
+//! - Distinct swap and transfer mints <= `Config::max_distinct_mints`, when set
+//!
+//! [`BatchOp`]: crate::state::BatchOp
+//! [`SwapLegEvent`]: crate::events::SwapLegEvent
+//! [`TransferOpEvent`]: crate::events::TransferOpEvent
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount, Transfer};
+
+use crate::constants::{MAX_BATCH_SIZE, MIN_SWAP_AMOUNT};
+use crate::errors::ErrorCode;
+use crate::events::{SwapLegEvent, TransferOpEvent};
+use crate::instructions::batch_swap::validate_swap;
+use crate::security::{
+    assert_authority_is_payer, assert_distinct_mints_within, assert_keys_equal,
+    assert_min_output_at_least, assert_not_default, assert_supported_version,
+    assert_token_account_mint, assert_token_account_owner, assert_token_program_matches,
+    validate_amount_after_fee,
+};
+use crate::state::{BatchOp, BatchSwap};
+use crate::swap_execution::calculate_protocol_fee;
+
+/// Handler for the batch ops instruction
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing account information (same accounts as
+///   `batch_swap`); remaining accounts hold the transfer ops' token accounts
+/// * `version` - Instruction format version (see `INSTRUCTION_VERSION`)
+/// * `ops` - The operations, run in index order (max 10 per batch)
+/// * `emit_events` - Emit the per-op events; `false` saves compute units
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+///
+/// # Errors
+///
+/// * `ErrorCode::UnsupportedInstructionVersion` - `version` is not supported
+/// * `ErrorCode::ProgramPaused` - The admin has paused the program (`Config::paused`)
+/// * `ErrorCode::EmptySwaps` - No ops provided
+/// * `ErrorCode::TooManySwaps` - More than MAX_BATCH_SIZE ops provided
+/// * Any per-leg error of `batch_swap` - A swap op is invalid
+/// * `ErrorCode::InvalidMinOutput` - A swap op's minimum output is below
+///   `Config::effective_min_output` (the op index is logged)
+/// * `ErrorCode::InvalidAmount` - A transfer amount is below MIN_SWAP_AMOUNT
+/// * `ErrorCode::InvalidAccount` - A transfer uses a default mint or
+///   destination, its accounts are missing or hold another mint, or the
+///   destination is not `to`
+/// * `ErrorCode::InvalidAuthority` - A transfer's source is not owned by the
+///   authority
+/// * `ErrorCode::TokenProgramMismatch` - A transfer account is not owned by
+///   `token_program`
+/// * `ErrorCode::AuthorityPayerMismatch` - Another account signs the
+///   transaction while `config.require_authority_is_payer` is set
+/// * `ErrorCode::TransferFailed` - A transfer CPI failed
+/// * `ErrorCode::TooManyMints` - The ops touch more distinct mints than
+///   `config.max_distinct_mints`
+///
+/// # Example
+///
+/// ```rust,ignore
+/// // Pay a counterparty in USDC, then swap SOL -> USDC, atomically
+/// batch_ops::handler(ctx, INSTRUCTION_VERSION, vec![
+///     BatchOp::Transfer { mint: usdc_mint, to: counterparty_usdc_account, amount: 5_000_000 },
+///     BatchOp::Swap(sol_to_usdc),
+/// ], true)?;
+/// ```
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchSwap<'info>>,
+    version: u8,
+    ops: Vec<BatchOp>,
+    emit_events: bool,
+) -> Result<()> {
+    // ========================================================================
+    // STEP 1: Validate Version and Ops
+    // ========================================================================
+    //
+    // Everything is validated before any op runs, so a single invalid op
+    // fails the batch without partial effects.
+    
+    assert_supported_version(version)?;
+    
+    // Nothing trades while the admin has paused the program
+    require!(!ctx.accounts.config.paused, ErrorCode::ProgramPaused);
+    
+    validate_ops(&ops)?;
+    
+    // Two remaining accounts (source, destination) per transfer op
+    let transfer_count = ops
+        .iter()
+        .filter(|op| matches!(op, BatchOp::Transfer { .. }))
+        .count();
+    require!(
+        ctx.remaining_accounts.len() == transfer_count * 2,
+        ErrorCode::InvalidAccount
+    );
+    
+    // ========================================================================
+    // STEP 2: Get Context Data
+    // ========================================================================
+    
+    let authority = ctx.accounts.authority.key();
+    let clock = Clock::get()?;
+    let fee_exempt = ctx.accounts.config.is_fee_exempt(&authority);
+    let fee_bps = ctx.accounts.config.fee_bps;
+    
+    // Optionally require the authority to be the transaction's only signer
+    if ctx.accounts.config.require_authority_is_payer {
+        assert_authority_is_payer(&ctx.accounts.instructions_sysvar, &authority)?;
+    }
+    
+    // Swap ops must ask for at least the configured output floor
+    let min_output_floor = ctx.accounts.config.effective_min_output();
+    for (index, op) in ops.iter().enumerate() {
+        if let BatchOp::Swap(swap) = op {
+            if let Err(error) = assert_min_output_at_least(swap.min_output_amount, min_output_floor) {
+                msg!("Op {} asks for less than the output floor", index);
+                return Err(error);
+            }
+        }
+    }
+    
+    // Transfer mints count towards the distinct mint cap like swap mints
+    assert_distinct_mints_within(
+        ops.iter()
+            .flat_map(|op| match op {
+                BatchOp::Swap(swap) => [Some(swap.input_mint), Some(swap.output_mint)],
+                BatchOp::Transfer { mint, .. } => [Some(*mint), None],
+            })
+            .flatten(),
+        ctx.accounts.config.max_distinct_mints,
+    )?;
+    
+    // ========================================================================
+    // STEP 3: Process Ops
+    // ========================================================================
+    //
+    // Ops run strictly in index order; each transfer takes the next pair of
+    // remaining accounts.
+    
+    let mut transfer_accounts = ctx.remaining_accounts.chunks_exact(2);
+    
+    for (index, op) in ops.iter().enumerate() {
+        match op {
+            BatchOp::Swap(swap) => {
+                // Same fee and stats accounting as a `batch_swap` leg
+                let fee = calculate_protocol_fee(swap.amount, fee_bps, fee_exempt, swap.max_fee)?;
+                if let Err(error) = validate_amount_after_fee(swap.amount, fee, MIN_SWAP_AMOUNT) {
+                    msg!("Op {} has less than {} left after its {} fee", index, MIN_SWAP_AMOUNT, fee);
+                    return Err(error);
+                }
+                ctx.accounts.stats.record(swap.amount, fee)?;
+                
+                if emit_events {
+                    emit!(SwapLegEvent {
+                        authority,
+                        leg_index: index as u8,
+                        input_mint: swap.input_mint,
+                        output_mint: swap.output_mint,
+                        amount: swap.amount,
+                        min_output_amount: swap.min_output_amount,
+                        protocol_fee: fee,
+                        tag: swap.tag,
+                        timestamp: clock.unix_timestamp,
+                    });
+                }
+            }
+            BatchOp::Transfer { mint, to, amount } => {
+                // Counted above: there is a pair for every transfer op
+                let [source_info, destination_info] = transfer_accounts
+                    .next()
+                    .ok_or(ErrorCode::InvalidAccount)?
+                else {
+                    return err!(ErrorCode::InvalidAccount);
+                };
+                
+                // Both accounts must belong to the program the transfer CPIs
+                // (security: a Token-2022 account fails clearly here)
+                let token_program = ctx.accounts.token_program.key;
+                assert_token_program_matches(token_program, source_info.owner)?;
+                assert_token_program_matches(token_program, destination_info.owner)?;
+                
+                // Tokens may only leave the authority's own account (security:
+                // prevent transfers from accounts the authority doesn't own)
+                let source = Account::<TokenAccount>::try_from(source_info)?;
+                assert_token_account_owner(&source, &authority)?;
+                assert_token_account_mint(&source, mint)?;
+                
+                // The destination is the account the op names
+                assert_keys_equal(destination_info.key, to)?;
+                let destination = Account::<TokenAccount>::try_from(destination_info)?;
+                assert_token_account_mint(&destination, mint)?;
+                
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: source_info.clone(),
+                            to: destination_info.clone(),
+                            authority: ctx.accounts.authority.to_account_info(),
+                        },
+                    ),
+                    *amount,
+                )
+                .map_err(|_| ErrorCode::TransferFailed)?;
+                
+                if emit_events {
+                    emit!(TransferOpEvent {
+                        authority,
+                        op_index: index as u8,
+                        mint: *mint,
+                        from: source_info.key(),
+                        to: *to,
+                        amount: *amount,
+                        timestamp: clock.unix_timestamp,
+                    });
+                }
+            }
+        }
+    }
+    
+    msg!("All {} ops executed ({} transfers)", ops.len(), transfer_count);
+    
+    Ok(())
+}
+
+/// Validate the batch size and every op
+///
+/// # Arguments
+///
+/// * `ops` - The decoded operations
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if every op is valid
+///
+/// # Errors
+///
+/// * `ErrorCode::EmptySwaps` - No ops provided
+/// * `ErrorCode::TooManySwaps` - More than MAX_BATCH_SIZE ops, swaps and
+///   transfers combined
+/// * Any error of `validate_swap` - A swap op is invalid
+/// * `ErrorCode::InvalidAccount` - A transfer uses the default pubkey as its
+///   mint or destination
+/// * `ErrorCode::InvalidAmount` - A transfer amount is below MIN_SWAP_AMOUNT
+pub(crate) fn validate_ops(ops: &[BatchOp]) -> Result<()> {
+    require!(!ops.is_empty(), ErrorCode::EmptySwaps);
+    
+    // Transfers count against the same cap as swaps
+    require!(
+        ops.len() <= MAX_BATCH_SIZE,
+        ErrorCode::TooManySwaps
+    );
+    
+    for (index, op) in ops.iter().enumerate() {
+        match op {
+            BatchOp::Swap(swap) => validate_swap(index, swap)?,
+            BatchOp::Transfer { mint, to, amount } => {
+                assert_not_default(mint)?;
+                assert_not_default(to)?;
+                require!(*amount >= MIN_SWAP_AMOUNT, ErrorCode::InvalidAmount);
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::SwapParams;
+
+    fn swap_op(amount: u64) -> BatchOp {
+        BatchOp::Swap(SwapParams {
+            input_mint: Pubkey::new_unique(),
+            output_mint: Pubkey::new_unique(),
+            amount,
+            min_output_amount: amount.saturating_sub(1),
+            max_fee: None,
+            max_slippage_bps: None,
+            tag: 0,
+        })
+    }
+
+    fn transfer_op(mint: Pubkey, to: Pubkey, amount: u64) -> BatchOp {
+        BatchOp::Transfer { mint, to, amount }
+    }
+
+    #[test]
+    fn test_mixed_ops_share_the_batch_cap() {
+        let transfer = transfer_op(Pubkey::new_unique(), Pubkey::new_unique(), 5);
+        let mut ops: Vec<_> = (0..MAX_BATCH_SIZE / 2).map(|_| swap_op(1_000)).collect();
+        ops.extend(vec![transfer.clone(); MAX_BATCH_SIZE - ops.len()]);
+
+        assert!(validate_ops(&ops).is_ok());
+        ops.push(transfer);
+        assert_eq!(validate_ops(&ops).unwrap_err(), ErrorCode::TooManySwaps.into());
+        assert_eq!(validate_ops(&[]).unwrap_err(), ErrorCode::EmptySwaps.into());
+    }
+
+    #[test]
+    fn test_invalid_ops() {
+        let (mint, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let cases = [
+            (transfer_op(mint, to, 0), ErrorCode::InvalidAmount),
+            (transfer_op(Pubkey::default(), to, 5), ErrorCode::InvalidAccount),
+            (transfer_op(mint, Pubkey::default(), 5), ErrorCode::InvalidAccount),
+            (swap_op(0), ErrorCode::InvalidAmount),
+        ];
+
+        for (op, error) in cases {
+            // A bad op fails the batch wherever it sits
+            let ops = vec![transfer_op(mint, to, 5), swap_op(1_000), op];
+            assert_eq!(validate_ops(&ops).unwrap_err(), error.into());
+        }
+    }
+}
@@ -13,9 +13,116 @@
 //! ## Process Flow
 //!
 //! 1. **Validate Batch Size**: Ensure batch is not empty and not too large
-//! 2. **Validate Each Swap**: Validate each swap parameter
-//! 3. **Process Swaps**: Execute each swap (currently logs, future: actual swaps)
-//! 4. **Emit Event**: Emit event for tracking and indexing
+//! 2. **Validate Each Swap**: Validate each swap parameter and its fee
+//! 3. **Process Swaps**: Process each swap in index order, emitting a per-leg event
+//! 4. **Check Fee Cap**: Reject the batch if its fees exceed `max_total_fee`
+//! 5. **Collect Fees**: Settle the batch's protocol fees
+//! 6. **Emit Event**: Emit event for tracking and indexing
+//!
+//! ## Execution Phases
+//!
+//! The handler runs three phases, each in its own function, and never
+//! interleaves them:
+//!
+//! 1. **Validate** ([`validate_swaps`], [`validate_leg_fees`]): every leg and
+//!    its fee are checked; no state is written and no callback is invoked
+//! 2. **Execute** ([`execute_legs`]): legs are processed in index order
+//!    (stats, per-leg events, callbacks)
+//! 3. **Collect fees** ([`collect_fees`]): runs only after every leg executed
+//!
+//! Batches are atomic, the only execution mode: an error in any phase
+//! reverts the whole transaction, including the stats and callback effects
+//! of legs that already executed. A validation error also fails before any
+//! callback runs, so integrators never see a leg of a batch that fails
+//! validation. Because fees are collected after execution from
+//! [`BatchTotals`], a best-effort mode that skips failing legs would only
+//! need `execute_legs` to leave them out of the totals to charge executed
+//! legs alone.
+//!
+//! ## Execution Order
+//!
+//! Legs are processed strictly in the order they appear in the `swaps`
+//! vector, and every leg emits a [`SwapLegEvent`] and a
+//! [`SwapExecutedEvent`] (with `batch` set) carrying its `leg_index`:
+//!
+//! 1. All legs are validated first (a single invalid leg fails the batch
+//!    before any leg is processed, see "Execution Phases")
+//! 2. Legs are then processed one at a time, index 0 first
+//!
+//! This makes dependent batches safe: when leg 0 swaps A -> B into an
+//! intermediate B account and leg 1 spends that B account for B -> C, leg 1
+//! is guaranteed to run after leg 0. Clients executing the swaps must keep
+//! their route instructions in the same order as the `swaps` vector.
+//!
+//! [`SwapLegEvent`]: crate::events::SwapLegEvent
+//! [`SwapExecutedEvent`]: crate::events::SwapExecutedEvent
+//!
+//! ## Per-Leg Callbacks
+//!
+//! When `callback_program` is set, the handler CPIs into that program after
+//! each leg with the leg's result (see [`callback`](crate::callback) for the
+//! allowlist, accounts, data format and compute unit costs). Callbacks are
+//! fully opt-in: batches without one behave exactly as before.
+//!
+//! ## Leg Output Accounts
+//!
+//! With `leg_output_accounts = true` the first remaining accounts are the
+//! legs' output token accounts, one per leg in leg order, ahead of any
+//! callback program. They are checked against each leg's `output_mint`
+//! before the validate phase, and their balances are read around the
+//! execute phase to log each leg's delta and check its slippage (see
+//! "Batch Slippage Limit" below, and [`leg_accounts`](crate::leg_accounts)
+//! for the exact layout).
+//!
+//! ## Shared Input Account
+//!
+//! With `shared_input_account = true` the first remaining account is one
+//! input token account every leg draws from (the one-to-many rebalance),
+//! ahead of any leg output accounts. The validate phase checks that it
+//! covers the sum of the legs' amounts and fails the batch with
+//! `InsufficientFunds` otherwise, so the batch cannot run dry halfway (see
+//! [`leg_accounts`](crate::leg_accounts)).
+//!
+//! ## Batch Slippage Limit
+//!
+//! With `leg_output_accounts`, the whole batch reverts with
+//! `BatchSlippageExceeded` when any leg's measured output slipped further
+//! from the leg's implied quote than the leg's own `max_slippage_bps`
+//! allows. A non-zero `global_max_slippage_bps` tightens every leg's limit
+//! to at most itself, even if the leg met its own minimum. The outputs are
+//! measured on the leg output accounts, so a non-zero limit requires
+//! `leg_output_accounts` (else `RouteAccountsMismatch`). 0 leaves each leg
+//! at its own limit (see [`leg_accounts`](crate::leg_accounts) for how
+//! slippage is measured).
+//!
+//! Routes run client-side today, so the execute phase delivers nothing and
+//! passing leg output accounts reverts every batch. Keep them off until the
+//! program executes the swaps itself.
+//!
+//! ## Compute Unit Profiling
+//!
+//! Building with the `cu_profiling` feature brackets each leg of the
+//! processing loop with `sol_log_compute_units()` and logs the units the leg
+//! consumed (`Leg <index> consumed <n> compute units`). Use it to measure the
+//! real per-leg cost when tuning `MAX_BATCH_SIZE`. It is off by default since
+//! the logging itself costs compute units.
+//!
+//! ## Priority Fees
+//!
+//! `BatchSwapEvent::priority_fee_microlamports` records the compute unit
+//! price the transaction paid, read from its Compute Budget instruction via
+//! the instructions sysvar (see [`read_compute_unit_price`]). Setting a price
+//! is optional; batches without one report 0.
+//!
+//! ## Batch Fee Cap
+//!
+//! `max_total_fee` bounds the protocol fees a whole batch may pay, in input
+//! token units summed across legs (as `BatchSwapEvent::total_protocol_fees`).
+//! Once every leg has executed, a batch whose fees exceed a non-zero cap
+//! fails with `BatchFeeCapExceeded` before any fee is collected or event
+//! emitted. This protects callers from a `Config::fee_bps` change landing
+//! between quoting and sending, and bounds the cost of large baskets. 0
+//! disables the cap; per-leg `max_fee` limits still apply.
 //!
 //! ## Validation
 //!
@@ -25,6 +132,10 @@
 //! - Each swap amount >= MIN_SWAP_AMOUNT (1)
 //! - Input and output mints differ for each swap
 //! - Minimum output amount > 0 for each swap
+//! - Maximum fee > 0 for each swap that sets one
+//! - Slippage tolerance <= MAX_SLIPPAGE_BPS for each swap that sets one
+//! - Each swap keeps >= MIN_SWAP_AMOUNT after its protocol fee
+//! - Distinct mints <= `Config::max_distinct_mints`, when set
 //!
 //! ## Security
 //!
@@ -35,12 +146,24 @@
 
 use anchor_lang::prelude::*;
 
-use crate::constants::{MAX_BATCH_SIZE, MIN_SWAP_AMOUNT};
+use crate::callback::{LegCallback, LegCallbackData};
+use crate::constants::{MAX_BATCH_SIZE, MAX_SLIPPAGE_BPS, MIN_SWAP_AMOUNT};
 use crate::errors::ErrorCode;
-use crate::events::BatchSwapEvent;
-use crate::security::{SafeMath, assert_different_mints, assert_not_default};
-use crate::state::{BatchSwap, SwapParams};
-use crate::swap_execution::calculate_protocol_fee;
+use crate::events::{BatchSwapEvent, SwapExecutedEvent, SwapLegEvent, EVENT_SCHEMA_VERSION};
+use crate::leg_accounts::{
+    assert_batch_slippage_within, leg_output_deltas, resolve_shared_input_account,
+    LegOutputAccounts,
+};
+use crate::security::{
+    assert_authority_is_payer, assert_batch_fee_within, assert_different_mints,
+    assert_distinct_mints_within, assert_min_output_at_least, assert_not_default,
+    assert_supported_version, assert_valid_slippage, validate_amount_after_fee, SafeMath,
+};
+use crate::state::{BatchSwap, FeeDenomination, Stats, SwapParams};
+use crate::swap_execution::{
+    calculate_protocol_fee, calculate_vwap_bps, calculate_weighted_max_slippage_bps,
+};
+use crate::utils::read_compute_unit_price;
 
 /// Handler for the batch swap instruction
 ///
@@ -50,7 +173,27 @@ use crate::swap_execution::calculate_protocol_fee;
 /// # Arguments
 ///
 /// * `ctx` - Context containing account information
+/// * `version` - Instruction format version (see `INSTRUCTION_VERSION`)
 /// * `swaps` - Vector of swap parameters (max 10 swaps per batch)
+/// * `base_mint` - Settlement mint for batch accounting; `total_output_amount`
+///   sums the minimum outputs of legs paying out in this mint
+/// * `emit_events` - Emit the per-leg and batch events (`true` by default in
+///   clients); `false` skips them to save compute units. Stats are updated
+///   either way.
+/// * `memo_hash` - SHA-256 hash of the SPL memo attached to the transaction,
+///   or all zeroes; recorded in `BatchSwapEvent`
+/// * `callback_program` - Optional allowlisted program called after each leg
+///   (first remaining account after any leg output accounts)
+/// * `max_total_fee` - Most protocol fees the batch may pay in total, 0 for
+///   no cap (see "Batch Fee Cap" in the module docs)
+/// * `leg_output_accounts` - The first remaining accounts are the legs'
+///   output token accounts (see "Leg Output Accounts" in the module docs)
+/// * `global_max_slippage_bps` - Most slippage any leg may realize, 0 to hold
+///   each leg to its own limit only (see "Batch Slippage Limit" in the
+///   module docs)
+/// * `shared_input_account` - The first remaining account is the input
+///   token account every leg draws from (see "Shared Input Account" in the
+///   module docs)
 ///
 /// # Returns
 ///
@@ -59,50 +202,101 @@ use crate::swap_execution::calculate_protocol_fee;
 /// # Errors
 ///
 /// This function can return the following errors:
+/// * `ErrorCode::UnsupportedInstructionVersion` - `version` is not supported
+/// * `ErrorCode::ProgramPaused` - The admin has paused the program (`Config::paused`)
+/// * `ErrorCode::AuthorityPayerMismatch` - Another account signs the transaction
+///   while `config.require_authority_is_payer` is set
 /// * `ErrorCode::EmptySwaps` - No swaps provided
 /// * `ErrorCode::TooManySwaps` - More than MAX_BATCH_SIZE swaps provided
 /// * `ErrorCode::InvalidAmount` - Invalid swap amount (zero or below minimum)
 /// * `ErrorCode::InvalidSwapPair` - Input and output mints are the same
-/// * `ErrorCode::InvalidMinOutput` - Invalid minimum output amount
+/// * `ErrorCode::InvalidMinOutput` - A leg's minimum output is zero or below
+///   `Config::effective_min_output` (the leg index is logged)
+/// * `ErrorCode::InvalidMaxFee` - A swap sets `max_fee` to zero
+/// * `ErrorCode::SlippageExceeded` - A swap sets `max_slippage_bps` above
+///   MAX_SLIPPAGE_BPS
+/// * `ErrorCode::InsufficientOutput` - A leg has less than MIN_SWAP_AMOUNT
+///   left after its fee (the leg index is logged)
+/// * `ErrorCode::ImplausibleMinOutput` - Minimum output fails the heuristic
+///   sanity check (`strict_sanity` feature only)
+/// * `ErrorCode::CallbackProgramNotAllowed` - `callback_program` is not on the
+///   config's allowlist
+/// * `ErrorCode::InvalidAccount` - The first remaining account (after any leg
+///   output accounts) is not the callback program, or a leg's input mint is
+///   not the shared input account's mint
+/// * `ErrorCode::RouteAccountsMismatch` - `leg_output_accounts` is set and
+///   an output account is missing, left over, or doesn't match its leg, or
+///   `global_max_slippage_bps` is set without `leg_output_accounts`
+/// * `ErrorCode::BatchSlippageExceeded` - With `leg_output_accounts`, a leg
+///   slipped more than its own `max_slippage_bps` or a non-zero
+///   `global_max_slippage_bps` (the leg index is logged)
+/// * `ErrorCode::InsufficientFunds` - The shared input account holds less
+///   than the legs' summed amounts
+/// * `ErrorCode::InvalidAuthority` - The authority doesn't own the shared
+///   input account
+/// * `ErrorCode::TooManyMints` - The swaps touch more distinct mints than
+///   `config.max_distinct_mints`
+/// * `ErrorCode::BatchFeeCapExceeded` - The summed protocol fees exceed a
+///   non-zero `max_total_fee`
 ///
 /// # Process
 ///
 /// 1. **Validate Batch**: Check that batch is not empty and not too large
-/// 2. **Validate Swaps**: Validate each swap parameter
-/// 3. **Process Swaps**: Execute each swap (currently logs, future: actual swaps)
-/// 4. **Emit Event**: Emit event for tracking and indexing
+/// 2. **Validate Swaps**: Validate each swap parameter and its fee
+///    ([`validate_leg_fees`])
+/// 3. **Process Swaps**: Process each swap in index order, emitting a per-leg
+///    event ([`execute_legs`])
+/// 4. **Check Fee Cap**: Check the summed fees against `max_total_fee`
+/// 5. **Collect Fees**: Settle the batch's protocol fees ([`collect_fees`])
+/// 6. **Emit Event**: Emit event for tracking and indexing
 ///
 /// # Example
 ///
 /// ```rust,ignore
 /// // Execute a batch of swaps
-/// batch_swap::handler(ctx, vec![
+/// batch_swap::handler(ctx, INSTRUCTION_VERSION, vec![
 ///     SwapParams {
 ///         input_mint: sol_mint,
 ///         output_mint: usdc_mint,
 ///         amount: 1_000_000_000,
 ///         min_output_amount: 90_000_000,
+///         max_fee: None,
+///         max_slippage_bps: None,
+///         tag: 0,
 ///     },
-/// ])?;
+/// ], usdc_mint, true, [0u8; 32], None, 0, false, 0, false)?;
 /// ```
-pub fn handler(ctx: Context<BatchSwap>, swaps: Vec<SwapParams>) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchSwap<'info>>,
+    version: u8,
+    swaps: Vec<SwapParams>,
+    base_mint: Pubkey,
+    emit_events: bool,
+    memo_hash: [u8; 32],
+    callback_program: Option<Pubkey>,
+    max_total_fee: u64,
+    leg_output_accounts: bool,
+    global_max_slippage_bps: u16,
+    shared_input_account: bool,
+) -> Result<()> {
     // ========================================================================
-    // STEP 1: Validate Batch Size
+    // STEP 1: Validate Version and Swaps
     // ========================================================================
     //
-    // We validate that the batch is not empty and not too large. This prevents
-    // DoS attacks and ensures the transaction stays within compute unit limits.
+    // We reject instruction formats this program version doesn't understand,
+    // then validate the batch size and each swap parameter (see
+    // `validate_swaps`). Everything is validated before processing, so a single
+    // invalid leg fails the batch without partial effects.
     
-    // Check that batch is not empty
-    // An empty batch would be a no-op and waste transaction fees
-    require!(!swaps.is_empty(), ErrorCode::EmptySwaps);
+    // Check the client speaks a supported instruction format
+    assert_supported_version(version)?;
     
-    // Check that batch size doesn't exceed the maximum
-    // This prevents DoS attacks and keeps compute units within limits
-    require!(
-        swaps.len() <= MAX_BATCH_SIZE,
-        ErrorCode::TooManySwaps
-    );
+    // Nothing trades while the admin has paused the program
+    require!(!ctx.accounts.config.paused, ErrorCode::ProgramPaused);
+    
+    // Check batch size and every leg before touching any state
+    validate_swaps(&swaps)?;
     
     // ========================================================================
     // STEP 2: Get Context Data
@@ -119,57 +313,82 @@ pub fn handler(ctx: Context<BatchSwap>, swaps: Vec<SwapParams>) -> Result<()> {
     // This is used for event timestamps
     let clock = Clock::get()?;
     
+    // Fee-exempt authorities (DAOs, internal accounts) pay no protocol fee
+    let fee_exempt = ctx.accounts.config.is_fee_exempt(&authority);
+    
+    // Live protocol fee rate, set by the config admin
+    let fee_bps = ctx.accounts.config.fee_bps;
+    
+    // Optionally require the authority to be the transaction's only signer
+    if ctx.accounts.config.require_authority_is_payer {
+        assert_authority_is_payer(&ctx.accounts.instructions_sysvar, &authority)?;
+    }
+    
+    // Every leg must ask for at least the configured output floor
+    let min_output_floor = ctx.accounts.config.effective_min_output();
+    for (index, swap) in swaps.iter().enumerate() {
+        if let Err(error) = assert_min_output_at_least(swap.min_output_amount, min_output_floor) {
+            msg!("Leg {} asks for less than the output floor", index);
+            return Err(error);
+        }
+    }
+    
+    // Optionally cap the distinct mints (and so the accounts) of the batch
+    assert_distinct_mints_within(
+        swaps.iter().flat_map(|swap| [swap.input_mint, swap.output_mint]),
+        ctx.accounts.config.max_distinct_mints,
+    )?;
+    
+    // The batch slippage limit is measured on the leg output accounts
+    require!(
+        global_max_slippage_bps == 0 || leg_output_accounts,
+        ErrorCode::RouteAccountsMismatch
+    );
+    
+    // Split off the opt-in shared input account, which every leg draws from
+    // and which must cover them all (checked before any leg executes)
+    let leg_accounts = if shared_input_account {
+        let (_, rest) = resolve_shared_input_account(&swaps, ctx.remaining_accounts, &authority)?;
+        rest
+    } else {
+        ctx.remaining_accounts
+    };
+    
+    // Split off the opt-in leg output accounts; the callback's accounts follow
+    let (output_accounts, callback_accounts) = if leg_output_accounts {
+        let (outputs, rest) =
+            LegOutputAccounts::resolve(&swaps, leg_accounts, callback_program.is_some())?;
+        (Some(outputs), rest)
+    } else {
+        (None, leg_accounts)
+    };
+    
+    // Resolve the opt-in per-leg callback before processing any leg
+    let callback = callback_program
+        .map(|program_id| {
+            LegCallback::resolve(&ctx.accounts.config, &program_id, callback_accounts)
+        })
+        .transpose()?;
+    let authority_info = ctx.accounts.authority.to_account_info();
+    
     // ========================================================================
-    // STEP 3: Validate Each Swap
+    // STEP 3: Validate Phase
     // ========================================================================
     //
-    // We validate each swap parameter to ensure they are all valid before
-    // processing. This prevents partial failures and ensures data integrity.
+    // Every leg's protocol fee is computed and checked before any leg is
+    // executed (see "Execution Phases" in the module docs). A leg left with
+    // nothing to swap after its fee fails the batch before any stats,
+    // events or callbacks.
     
-    // Iterate over each swap and validate its parameters
-    // We use enumerate to get the index for logging
-    for (index, swap) in swaps.iter().enumerate() {
-        // Validate input mint address (security: prevent default/null addresses)
-        assert_not_default(&swap.input_mint)?;
-        
-        // Validate output mint address (security: prevent default/null addresses)
-        assert_not_default(&swap.output_mint)?;
-        
-        // Validate swap amount (security: prevent dust attacks)
-        require!(
-            swap.amount >= MIN_SWAP_AMOUNT,
-            ErrorCode::InvalidAmount
-        );
-        
-        // Validate that input and output mints are different (security: prevent invalid swaps)
-        assert_different_mints(&swap.input_mint, &swap.output_mint)?;
-        
-        // Validate minimum output amount (security: require slippage protection)
-        require!(
-            swap.min_output_amount > 0,
-            ErrorCode::InvalidMinOutput
-        );
-        
-        // Log swap details for debugging and monitoring
-        // This helps with debugging and provides visibility into swap operations
-        msg!(
-            "Swap {}: {} tokens (min: {}) from {} to {}",
-            index + 1,                    // Swap index (1-based for user-friendliness)
-            swap.amount,                  // Amount of input tokens
-            swap.min_output_amount,       // Minimum output amount (slippage protection)
-            swap.input_mint,              // Input token mint
-            swap.output_mint              // Output token mint
-        );
-    }
+    let legs = validate_leg_fees(&swaps, fee_bps, fee_exempt)?;
     
     // ========================================================================
-    // STEP 4: Calculate Fees and Validate Swap Parameters
+    // STEP 4: Execute Phase
     // ========================================================================
     //
-    // For batch swaps, we calculate fees and validate all swap parameters.
-    // The actual swap execution happens client-side via Jupiter instructions
-    // included in the same transaction. This instruction validates parameters
-    // and tracks execution.
+    // For batch swaps, the actual swap execution happens client-side via
+    // Jupiter instructions included in the same transaction. This phase
+    // records each leg in index order and tracks the batch totals.
     //
     // Execution Strategy:
     //
@@ -186,77 +405,100 @@ pub fn handler(ctx: Context<BatchSwap>, swaps: Vec<SwapParams>) -> Result<()> {
     //    - Program validates slippage after each swap
     //    - All swaps execute atomically
     
-    // Calculate total input amount and fees
-    let mut total_input_amount: u64 = 0;
-    let mut total_protocol_fees: u64 = 0;
+    // Output balances before the legs, to measure what each leg received
+    let balances_before = output_accounts
+        .as_ref()
+        .map(LegOutputAccounts::balances)
+        .transpose()?;
     
-    for swap in &swaps {
-            // Calculate protocol fee for this swap (security: use safe math)
-            let fee = calculate_protocol_fee(swap.amount)?;
-            
-            // Accumulate totals with safe math (security: prevent overflow)
-            total_input_amount = total_input_amount.safe_add(swap.amount)?;
-            total_protocol_fees = total_protocol_fees.safe_add(fee)?;
-        
-        // Validate slippage tolerance
-        // Calculate expected slippage based on min_output_amount
-        // This is a simplified validation - in production, we'd compare with actual output
-        if swap.min_output_amount > 0 && swap.amount > 0 {
-            // Estimate expected output (this would come from Jupiter quote in production)
-            // For validation, we ensure min_output_amount is reasonable
-            // Actual slippage validation happens when swaps are executed
-            
-            msg!(
-                "Swap validated: {} -> {} (amount: {}, min_output: {}, fee: {})",
-                swap.input_mint,
-                swap.output_mint,
-                swap.amount,
-                swap.min_output_amount,
-                fee
-            );
+    let totals = execute_legs(
+        &mut ctx.accounts.stats,
+        &legs,
+        base_mint,
+        emit_events,
+        memo_hash,
+        callback.as_ref(),
+        &authority_info,
+        clock.unix_timestamp,
+    )?;
+    
+    if let (Some(outputs), Some(before)) = (&output_accounts, &balances_before) {
+        let deltas = leg_output_deltas(before, &outputs.balances()?);
+        for (index, delta) in deltas.iter().enumerate() {
+            msg!("Leg {} output account received {}", index, delta);
         }
+        
+        // One adverse leg reverts the whole batch, against its own limit
+        // even without a batch limit
+        assert_batch_slippage_within(&swaps, &deltas, global_max_slippage_bps)?;
     }
     
-    // Log that all swaps have been validated
-    msg!(
-        "All {} swaps validated successfully. Total input: {}, Total fees: {}",
-        swaps.len(),
-        total_input_amount,
-        total_protocol_fees
-    );
-    
     // ========================================================================
-    // STEP 5: Distribute Fees (if fee recipient provided)
+    // STEP 5: Check Batch Fee Cap
     // ========================================================================
     //
-    // In production, fees would be distributed to the fee recipient.
-    // For batch swaps with client-side execution, fees are handled by the
-    // client in the Jupiter swap instructions or collected separately.
+    // The summed fees are final once every leg has executed. A batch over
+    // its cap fails here, before fees are collected or events emitted.
+    
+    assert_batch_fee_within(totals.total_protocol_fees, max_total_fee)?;
+    
+    // ========================================================================
+    // STEP 6: Collect Fees Phase
+    // ========================================================================
     //
-    // Note: For program-side execution, we would distribute fees here.
-    // For client-side execution, the client handles fee distribution.
+    // Fees are only collected once every leg has executed (see
+    // `collect_fees`).
+    
+    collect_fees(swaps.len(), &totals);
     
     // ========================================================================
-    // STEP 6: Emit Event
+    // STEP 7: Emit Event
     // ========================================================================
     //
     // We emit an event to track the batch swap execution. This event can be
     // indexed by off-chain services for analytics, monitoring, and user interfaces.
+    // Callers that don't need events (e.g. latency-sensitive bots) can skip
+    // them with `emit_events = false`; the stats above are already recorded.
     
-    // Convert swap count to u8
-    let swap_count: u8 = swaps.len() as u8;
-    
-    // Emit the batch swap event
-    emit!(BatchSwapEvent {
-        authority,
-        swap_count,
-        total_input_amount,
-        total_protocol_fees,
-        timestamp: clock.unix_timestamp,
-    });
+    if emit_events {
+        // Convert swap count to u8
+        let swap_count: u8 = swaps.len() as u8;
+        
+        // Blended price of the batch, from the same guaranteed outputs as
+        // `total_output_amount`
+        let vwap_bps = calculate_vwap_bps(
+            swaps.iter().map(|swap| (swap.amount, swap.min_output_amount)),
+        )?;
+        
+        // Slippage the batch allows, as a single number
+        let weighted_max_slippage_bps = calculate_weighted_max_slippage_bps(
+            totals.weighted_max_slippage,
+            u128::from(totals.total_input_amount),
+        )?;
+        
+        // Priority fee of the transaction, 0 if the client set none
+        let priority_fee_microlamports =
+            read_compute_unit_price(&ctx.accounts.instructions_sysvar);
+        
+        // Emit the batch swap event
+        emit!(BatchSwapEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            authority,
+            swap_count,
+            total_input_amount: totals.total_input_amount,
+            total_protocol_fees: totals.total_protocol_fees,
+            base_mint,
+            total_output_amount: totals.total_output_amount,
+            vwap_bps,
+            weighted_max_slippage_bps,
+            memo_hash,
+            priority_fee_microlamports,
+            timestamp: clock.unix_timestamp,
+        });
+    }
     
     // ========================================================================
-    // STEP 7: Return Success
+    // STEP 8: Return Success
     // ========================================================================
     //
     // If we've reached here, all validations passed and the batch swap was
@@ -266,4 +508,639 @@ pub fn handler(ctx: Context<BatchSwap>, swaps: Vec<SwapParams>) -> Result<()> {
     Ok(())
 }
 
+/// Validate the batch size and every swap leg
+///
+/// Checks run in a fixed order so a malformed batch always fails with the
+/// same error: batch size first, then each leg in index order (mints, amount,
+/// pair, minimum output, maximum fee, slippage tolerance).
+///
+/// # Arguments
+///
+/// * `swaps` - The decoded swap parameters
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if every leg is valid
+///
+/// # Errors
+///
+/// * `ErrorCode::EmptySwaps` - No swaps provided
+/// * `ErrorCode::TooManySwaps` - More than MAX_BATCH_SIZE swaps provided
+/// * `ErrorCode::InvalidAccount` - A leg uses the default pubkey as a mint
+/// * `ErrorCode::InvalidAmount` - A leg's amount is below MIN_SWAP_AMOUNT
+/// * `ErrorCode::InvalidSwapPair` - A leg's input and output mints are the same
+/// * `ErrorCode::InvalidMinOutput` - A leg's minimum output is zero
+/// * `ErrorCode::InvalidMaxFee` - A leg sets `max_fee` to zero
+/// * `ErrorCode::SlippageExceeded` - A leg sets `max_slippage_bps` above
+///   MAX_SLIPPAGE_BPS (a leg may only tighten the global limit)
+/// * `ErrorCode::ImplausibleMinOutput` - A leg's minimum output fails the
+///   heuristic sanity check (`strict_sanity` feature only)
+pub(crate) fn validate_swaps(swaps: &[SwapParams]) -> Result<()> {
+    // Check that batch is not empty
+    // An empty batch would be a no-op and waste transaction fees
+    require!(!swaps.is_empty(), ErrorCode::EmptySwaps);
+    
+    // Check that batch size doesn't exceed the maximum
+    // This prevents DoS attacks and keeps compute units within limits
+    require!(
+        swaps.len() <= MAX_BATCH_SIZE,
+        ErrorCode::TooManySwaps
+    );
+    
+    // Iterate over each swap and validate its parameters
+    // We use enumerate to get the index for logging
+    for (index, swap) in swaps.iter().enumerate() {
+        validate_swap(index, swap)?;
+    }
+    
+    Ok(())
+}
+
+/// Validate a single swap leg
+///
+/// Shared by `batch_swap` and the swap ops of `batch_ops`, so a swap is held
+/// to the same rules wherever it appears.
+///
+/// # Arguments
+///
+/// * `index` - Position of the leg in its batch (for logging)
+/// * `swap` - The swap parameters
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` if the leg is valid
+///
+/// # Errors
+///
+/// Same per-leg errors as [`validate_swaps`], checked in the same order.
+pub(crate) fn validate_swap(index: usize, swap: &SwapParams) -> Result<()> {
+    // Validate input mint address (security: prevent default/null addresses)
+    assert_not_default(&swap.input_mint)?;
+    
+    // Validate output mint address (security: prevent default/null addresses)
+    assert_not_default(&swap.output_mint)?;
+    
+    // Validate swap amount (security: prevent dust attacks)
+    require!(
+        swap.amount >= MIN_SWAP_AMOUNT,
+        ErrorCode::InvalidAmount
+    );
+    
+    // Validate that input and output mints are different (security: prevent invalid swaps)
+    assert_different_mints(&swap.input_mint, &swap.output_mint)?;
+    
+    // Validate minimum output amount (security: require slippage protection)
+    require!(
+        swap.min_output_amount > 0,
+        ErrorCode::InvalidMinOutput
+    );
+    
+    // Validate fee cap (a zero cap would silently waive the fee)
+    require!(
+        swap.max_fee != Some(0),
+        ErrorCode::InvalidMaxFee
+    );
+    
+    // Validate leg slippage tolerance (it may tighten, never loosen, the global limit)
+    if let Some(max_slippage_bps) = swap.max_slippage_bps {
+        assert_valid_slippage(u64::from(max_slippage_bps), MAX_SLIPPAGE_BPS)?;
+    }
+    
+    // Heuristic check against misconfigured minimums (opt-in)
+    #[cfg(feature = "strict_sanity")]
+    crate::security::assert_plausible_min_output(swap.amount, swap.min_output_amount)?;
+    
+    // Log swap details for debugging and monitoring
+    // This helps with debugging and provides visibility into swap operations
+    msg!(
+        "Swap {}: {} tokens (min: {}) from {} to {}",
+        index + 1,                    // Swap index (1-based for user-friendliness)
+        swap.amount,                  // Amount of input tokens
+        swap.min_output_amount,       // Minimum output amount (slippage protection)
+        swap.input_mint,              // Input token mint
+        swap.output_mint              // Output token mint
+    );
+    
+    Ok(())
+}
+
+/// A leg that passed the validate phase, with its protocol fee
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ValidatedLeg<'a> {
+    /// Position of the leg in its batch
+    pub index: usize,
+    
+    /// The swap parameters
+    pub swap: &'a SwapParams,
+    
+    /// Protocol fee of the leg, after its `max_fee` cap
+    pub fee: u64,
+}
+
+/// Batch totals accumulated by the execute phase
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BatchTotals {
+    /// Sum of the legs' input amounts
+    pub total_input_amount: u64,
+    
+    /// Sum of the legs' protocol fees
+    pub total_protocol_fees: u64,
+    
+    /// Sum of the minimum outputs of legs paying out in the settlement mint;
+    /// adding up amounts of different tokens would be meaningless
+    pub total_output_amount: u64,
+    
+    /// Sum of each leg's slippage tolerance weighted by its input, in u128 so
+    /// a full batch of u64::MAX legs cannot overflow
+    pub weighted_max_slippage: u128,
+}
+
+impl BatchTotals {
+    /// Add an executed leg to the totals
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::MathOverflow` if a total overflows.
+    pub fn add_leg(&mut self, leg: &ValidatedLeg, base_mint: &Pubkey) -> Result<()> {
+        self.total_input_amount = self.total_input_amount.safe_add(leg.swap.amount)?;
+        self.total_protocol_fees = self.total_protocol_fees.safe_add(leg.fee)?;
+        if leg.swap.output_mint == *base_mint {
+            self.total_output_amount = self.total_output_amount.safe_add(leg.swap.min_output_amount)?;
+        }
+        
+        // Fills are not observed here (see STEP 4 of the handler), so each leg
+        // contributes the slippage it is allowed to realize
+        self.weighted_max_slippage = self.weighted_max_slippage.safe_add(
+            u128::from(leg.swap.amount).safe_mul(u128::from(leg.swap.effective_max_slippage_bps()))?,
+        )?;
+        
+        Ok(())
+    }
+}
+
+/// Validate phase: compute and check every leg's protocol fee
+///
+/// Runs after [`validate_swaps`] and before any leg is executed, so a batch
+/// with one zero-net leg fails without touching stats or invoking callbacks.
+///
+/// # Arguments
+///
+/// * `swaps` - The validated swap parameters
+/// * `fee_bps` - Live protocol fee rate
+/// * `fee_exempt` - Whether the authority pays no protocol fee
+///
+/// # Returns
+///
+/// * `Result<Vec<ValidatedLeg>>` - Every leg with its fee, in index order
+///
+/// # Errors
+///
+/// * `ErrorCode::InsufficientOutput` - A leg has less than MIN_SWAP_AMOUNT
+///   left after its fee (the leg index is logged)
+/// * `ErrorCode::MathOverflow` - A fee calculation overflows
+pub(crate) fn validate_leg_fees(
+    swaps: &[SwapParams],
+    fee_bps: u16,
+    fee_exempt: bool,
+) -> Result<Vec<ValidatedLeg<'_>>> {
+    swaps
+        .iter()
+        .enumerate()
+        .map(|(index, swap)| {
+            // A negotiated `max_fee` caps the fee; the capped fee is what's emitted
+            let fee = calculate_protocol_fee(swap.amount, fee_bps, fee_exempt, swap.max_fee)?;
+            
+            // Each leg must still have something to swap after its fee, matching
+            // `execute_swap` (security: a high-fee config can't produce zero-net legs)
+            if let Err(error) = validate_amount_after_fee(swap.amount, fee, MIN_SWAP_AMOUNT) {
+                msg!("Leg {} has less than {} left after its {} fee", index, MIN_SWAP_AMOUNT, fee);
+                return Err(error);
+            }
+            
+            Ok(ValidatedLeg { index, swap, fee })
+        })
+        .collect()
+}
+
+/// Execute phase: process every validated leg in index order
+///
+/// Each leg is recorded in the program-wide stats, emits its
+/// [`SwapLegEvent`] and [`SwapExecutedEvent`] and is handed to the callback
+/// program, if any.
+///
+/// # Arguments
+///
+/// * `stats` - The program-wide stats account
+/// * `legs` - The legs returned by [`validate_leg_fees`]
+/// * `base_mint` - Settlement mint for `total_output_amount`
+/// * `emit_events` - Emit the per-leg events
+/// * `memo_hash` - The batch's memo hash, echoed into each leg's
+///   `SwapExecutedEvent`
+/// * `callback` - The resolved per-leg callback, if any
+/// * `authority` - The batch authority
+/// * `timestamp` - Event timestamp
+///
+/// # Returns
+///
+/// * `Result<BatchTotals>` - The totals of the executed legs
+///
+/// # Errors
+///
+/// * `ErrorCode::MathOverflow` - A total or stats counter overflows
+/// * Any error returned by the callback program
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn execute_legs<'info>(
+    stats: &mut Stats,
+    legs: &[ValidatedLeg],
+    base_mint: Pubkey,
+    emit_events: bool,
+    memo_hash: [u8; 32],
+    callback: Option<&LegCallback<'_, 'info>>,
+    authority: &AccountInfo<'info>,
+    timestamp: i64,
+) -> Result<BatchTotals> {
+    let mut totals = BatchTotals::default();
+    
+    // Legs are processed strictly in index order (see "Execution Order" in the
+    // module docs). Each leg's event carries its index so dependent legs can be
+    // matched up off-chain.
+    for leg in legs {
+        let ValidatedLeg { index, swap, fee } = *leg;
+        
+        // Bracket the leg with compute unit logs (`cu_profiling` feature only)
+        #[cfg(feature = "cu_profiling")]
+        let leg_start_units = {
+            solana_program::log::sol_log_compute_units();
+            solana_program::compute_units::sol_remaining_compute_units()
+        };
+        
+        // Accumulate totals with safe math (security: prevent overflow)
+        totals.add_leg(leg, &base_mint)?;
+        
+        // Each leg counts as one swap in the program-wide stats
+        stats.record(swap.amount, fee)?;
+        
+        if emit_events {
+            emit!(SwapLegEvent {
+                authority: *authority.key,
+                leg_index: index as u8,
+                input_mint: swap.input_mint,
+                output_mint: swap.output_mint,
+                amount: swap.amount,
+                min_output_amount: swap.min_output_amount,
+                protocol_fee: fee,
+                tag: swap.tag,
+                timestamp,
+            });
+            emit!(leg_swap_executed_event(leg, *authority.key, memo_hash, timestamp));
+        }
+        
+        // Hand the leg's result to the integrator's callback program
+        if let Some(callback) = callback {
+            callback.invoke(
+                authority,
+                &LegCallbackData {
+                    authority: *authority.key,
+                    leg_index: index as u8,
+                    input_mint: swap.input_mint,
+                    output_mint: swap.output_mint,
+                    amount: swap.amount,
+                    min_output_amount: swap.min_output_amount,
+                    protocol_fee: fee,
+                    tag: swap.tag,
+                },
+            )?;
+        }
+        
+        // Actual slippage validation happens when swaps are executed, against
+        // the leg's own tolerance (never looser than MAX_SLIPPAGE_BPS)
+        msg!(
+            "Swap validated: {} -> {} (amount: {}, min_output: {}, fee: {}, max slippage: {} bps)",
+            swap.input_mint,
+            swap.output_mint,
+            swap.amount,
+            swap.min_output_amount,
+            fee,
+            swap.effective_max_slippage_bps()
+        );
+        
+        #[cfg(feature = "cu_profiling")]
+        {
+            solana_program::log::sol_log_compute_units();
+            let leg_end_units = solana_program::compute_units::sol_remaining_compute_units();
+            msg!(
+                "Leg {} consumed {} compute units",
+                index,
+                leg_start_units.saturating_sub(leg_end_units)
+            );
+        }
+    }
+    
+    Ok(totals)
+}
+
+/// Build the `SwapExecutedEvent` of a batch leg
+///
+/// The leg is routed client-side, so no fill is observed: the output fields
+/// carry the leg's minimum output, slippage is 0 and no part of the fee is
+/// reported as transferred (see "batch / leg_index" on [`SwapExecutedEvent`]).
+///
+/// # Arguments
+///
+/// * `leg` - The executed leg
+/// * `authority` - The batch authority (also the recipient)
+/// * `memo_hash` - The batch's memo hash
+/// * `timestamp` - Event timestamp
+///
+/// # Returns
+///
+/// The event, with `batch` set and the leg's `leg_index`
+pub(crate) fn leg_swap_executed_event(
+    leg: &ValidatedLeg,
+    authority: Pubkey,
+    memo_hash: [u8; 32],
+    timestamp: i64,
+) -> SwapExecutedEvent {
+    SwapExecutedEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        authority,
+        recipient: authority,
+        input_amount: leg.swap.amount,
+        output_amount: leg.swap.min_output_amount,
+        net_output_amount: leg.swap.min_output_amount,
+        input_mint: leg.swap.input_mint,
+        output_mint: leg.swap.output_mint,
+        protocol_fee: leg.fee,
+        treasury_fee: 0,
+        burned_fee: 0,
+        lp_fee: 0,
+        fee_denomination: FeeDenomination::Token,
+        fee_collected: 0,
+        // Loyalty discounts only apply to `execute_swap`
+        fee_discount_bps: 0,
+        slippage_bps: 0,
+        memo_hash,
+        tag: leg.swap.tag,
+        simulated: false,
+        batch: true,
+        leg_index: leg.index as u8,
+        timestamp,
+    }
+}
+
+/// Collect-fees phase: settle the batch's protocol fees
+///
+/// Runs only once every leg has executed. With client-side execution the
+/// fees are taken by the route instructions, so no transfer is made here;
+/// program-side execution will transfer `totals.total_protocol_fees` to the
+/// fee recipient in this phase. The total has already been checked against
+/// `max_total_fee` (see "Batch Fee Cap" in the module docs).
+///
+/// # Arguments
+///
+/// * `swap_count` - Number of executed legs
+/// * `totals` - The totals returned by [`execute_legs`]
+pub(crate) fn collect_fees(swap_count: usize, totals: &BatchTotals) {
+    msg!(
+        "All {} swaps validated successfully. Total input: {}, Total fees: {}",
+        swap_count,
+        totals.total_input_amount,
+        totals.total_protocol_fees
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::PROTOCOL_FEE_BPS;
+
+    /// Small deterministic xorshift generator so fuzz failures are reproducible
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
 
+        fn pick<T: Copy>(&mut self, values: &[T]) -> T {
+            values[(self.next() % values.len() as u64) as usize]
+        }
+    }
+
+    fn leg(input_mint: Pubkey, output_mint: Pubkey, amount: u64, min_output_amount: u64) -> SwapParams {
+        SwapParams { input_mint, output_mint, amount, min_output_amount, max_fee: None, max_slippage_bps: None, tag: 0 }
+    }
+
+    /// Independent model of the validation order in `validate_swaps`
+    fn expected_error(swaps: &[SwapParams]) -> Option<ErrorCode> {
+        if swaps.is_empty() {
+            return Some(ErrorCode::EmptySwaps);
+        }
+        if swaps.len() > MAX_BATCH_SIZE {
+            return Some(ErrorCode::TooManySwaps);
+        }
+        swaps.iter().find_map(|swap| {
+            if swap.input_mint == Pubkey::default() || swap.output_mint == Pubkey::default() {
+                Some(ErrorCode::InvalidAccount)
+            } else if swap.amount < MIN_SWAP_AMOUNT {
+                Some(ErrorCode::InvalidAmount)
+            } else if swap.input_mint == swap.output_mint {
+                Some(ErrorCode::InvalidSwapPair)
+            } else if swap.min_output_amount == 0 {
+                Some(ErrorCode::InvalidMinOutput)
+            } else if swap.max_fee == Some(0) {
+                Some(ErrorCode::InvalidMaxFee)
+            } else if swap.max_slippage_bps.is_some_and(|bps| u64::from(bps) > MAX_SLIPPAGE_BPS) {
+                Some(ErrorCode::SlippageExceeded)
+            } else if cfg!(feature = "strict_sanity")
+                && crate::security::assert_plausible_min_output(swap.amount, swap.min_output_amount).is_err()
+            {
+                Some(ErrorCode::ImplausibleMinOutput)
+            } else {
+                None
+            }
+        })
+    }
+
+    #[test]
+    fn test_batch_size_boundaries() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let valid = leg(a, b, 1_000, 999);
+
+        assert_eq!(validate_swaps(&[]).unwrap_err(), ErrorCode::EmptySwaps.into());
+        assert!(validate_swaps(&vec![valid.clone(); MAX_BATCH_SIZE]).is_ok());
+        assert_eq!(
+            validate_swaps(&vec![valid; MAX_BATCH_SIZE + 1]).unwrap_err(),
+            ErrorCode::TooManySwaps.into()
+        );
+    }
+
+    #[test]
+    fn test_malformed_legs() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let cases = [
+            (leg(Pubkey::default(), b, 1_000, 999), ErrorCode::InvalidAccount),
+            (leg(a, Pubkey::default(), 1_000, 999), ErrorCode::InvalidAccount),
+            (leg(a, b, 0, 999), ErrorCode::InvalidAmount),
+            (leg(a, a, 1_000, 999), ErrorCode::InvalidSwapPair),
+            (leg(a, b, 1_000, 0), ErrorCode::InvalidMinOutput),
+            (leg(a, b, u64::MAX, 0), ErrorCode::InvalidMinOutput),
+            (SwapParams { max_fee: Some(0), ..leg(a, b, 1_000, 999) }, ErrorCode::InvalidMaxFee),
+            (
+                SwapParams { max_slippage_bps: Some(MAX_SLIPPAGE_BPS as u16 + 1), ..leg(a, b, 1_000, 999) },
+                ErrorCode::SlippageExceeded,
+            ),
+        ];
+
+        for (swap, error) in cases {
+            // A bad leg fails the batch wherever it sits
+            let mut swaps = vec![leg(a, b, 1_000, 999); MAX_BATCH_SIZE];
+            swaps[MAX_BATCH_SIZE - 1] = swap;
+            assert_eq!(validate_swaps(&swaps).unwrap_err(), error.into());
+        }
+    }
+
+    #[test]
+    fn test_max_amounts_overflow_without_panicking() {
+        let fee = calculate_protocol_fee(u64::MAX, PROTOCOL_FEE_BPS as u16, false, None).unwrap();
+        assert!(fee < u64::MAX);
+
+        // Two u64::MAX legs validate, but their total overflows cleanly
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let swaps = [leg(a, b, u64::MAX, u64::MAX), leg(b, a, u64::MAX, u64::MAX)];
+        assert!(validate_swaps(&swaps).is_ok());
+        assert_eq!(
+            u64::MAX.safe_add(u64::MAX).unwrap_err(),
+            ErrorCode::MathOverflow.into()
+        );
+    }
+
+    #[test]
+    fn test_zero_net_leg_is_insufficient_output() {
+        // A fee consuming the whole leg leaves nothing to swap
+        assert_eq!(
+            validate_amount_after_fee(1_000, 1_000, MIN_SWAP_AMOUNT).unwrap_err(),
+            ErrorCode::InsufficientOutput.into()
+        );
+        assert!(validate_amount_after_fee(1_000, 999, MIN_SWAP_AMOUNT).is_ok());
+    }
+
+    #[cfg(not(feature = "no-fees"))]
+    #[test]
+    fn test_validate_phase_checks_every_fee_first() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let swaps = vec![leg(a, b, 1_000, 999); MAX_BATCH_SIZE];
+
+        let legs = validate_leg_fees(&swaps, PROTOCOL_FEE_BPS as u16, false).unwrap();
+        assert_eq!(legs.len(), MAX_BATCH_SIZE);
+        assert!(legs.iter().enumerate().all(|(index, leg)| leg.index == index));
+
+        // At a full-rate fee only the last leg has no `max_fee` cap and keeps
+        // nothing to swap: the phase fails as a whole, before any leg executes
+        let mut zero_net = vec![SwapParams { max_fee: Some(1), ..leg(a, b, 1_000, 999) }; MAX_BATCH_SIZE];
+        zero_net[MAX_BATCH_SIZE - 1].max_fee = None;
+        assert!(validate_leg_fees(&zero_net[..MAX_BATCH_SIZE - 1], 10_000, false).is_ok());
+        assert_eq!(
+            validate_leg_fees(&zero_net, 10_000, false).unwrap_err(),
+            ErrorCode::InsufficientOutput.into()
+        );
+        assert!(validate_leg_fees(&zero_net, 10_000, true).is_ok());
+    }
+
+    #[test]
+    fn test_batch_totals_sum_only_base_mint_outputs() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (to_b, to_a) = (leg(a, b, 1_000, 900), leg(b, a, 2_000, 1_800));
+        let mut totals = BatchTotals::default();
+
+        totals.add_leg(&ValidatedLeg { index: 0, swap: &to_b, fee: 3 }, &b).unwrap();
+        totals.add_leg(&ValidatedLeg { index: 1, swap: &to_a, fee: 6 }, &b).unwrap();
+
+        assert_eq!(totals.total_input_amount, 3_000);
+        assert_eq!(totals.total_protocol_fees, 9);
+        assert_eq!(totals.total_output_amount, 900);
+        assert_eq!(totals.weighted_max_slippage, 3_000 * u128::from(MAX_SLIPPAGE_BPS));
+    }
+
+    #[test]
+    fn test_leg_swap_executed_event_marks_batch_leg() {
+        let (a, b, authority) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let swap = SwapParams { tag: 7, ..leg(a, b, 1_000, 900) };
+        let memo_hash = [9u8; 32];
+
+        let event = leg_swap_executed_event(&ValidatedLeg { index: 3, swap: &swap, fee: 3 }, authority, memo_hash, 42);
+
+        assert!(event.batch);
+        assert_eq!(event.leg_index, 3);
+        assert_eq!(event.schema_version, EVENT_SCHEMA_VERSION);
+        assert_eq!((event.authority, event.recipient), (authority, authority));
+        assert_eq!((event.input_mint, event.output_mint), (a, b));
+        assert_eq!((event.input_amount, event.output_amount, event.net_output_amount), (1_000, 900, 900));
+        assert_eq!((event.protocol_fee, event.fee_collected, event.slippage_bps), (3, 0, 0));
+        assert_eq!((event.memo_hash, event.tag, event.timestamp), (memo_hash, 7, 42));
+        assert!(!event.simulated);
+    }
+
+    #[test]
+    fn test_fuzz_validate_swaps() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mints = [a, b, Pubkey::default()];
+        let amounts = [0, 1, 2, 1_000, u64::MAX / 2, u64::MAX - 1, u64::MAX];
+        let max_fees = [None, Some(0), Some(1), Some(u64::MAX)];
+        let slippages = [None, Some(0), Some(MAX_SLIPPAGE_BPS as u16), Some(MAX_SLIPPAGE_BPS as u16 + 1), Some(u16::MAX)];
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+
+        for _ in 0..10_000 {
+            let len = (rng.next() % (MAX_BATCH_SIZE as u64 + 3)) as usize;
+            let swaps: Vec<_> = (0..len)
+                .map(|_| SwapParams {
+                    max_fee: rng.pick(&max_fees),
+                    max_slippage_bps: rng.pick(&slippages),
+                    ..leg(
+                        rng.pick(&mints),
+                        rng.pick(&mints),
+                        rng.pick(&amounts),
+                        rng.pick(&amounts),
+                    )
+                })
+                .collect();
+
+            match expected_error(&swaps) {
+                Some(error) => assert_eq!(validate_swaps(&swaps).unwrap_err(), error.into()),
+                None => {
+                    assert!(validate_swaps(&swaps).is_ok());
+                    // Accounting never panics: it either sums or reports MathOverflow
+                    let total = swaps
+                        .iter()
+                        .try_fold(0u64, |total, swap| total.safe_add(swap.amount));
+                    for swap in &swaps {
+                        let fee = calculate_protocol_fee(swap.amount, PROTOCOL_FEE_BPS as u16, false, swap.max_fee).unwrap();
+                        assert!(fee <= swap.max_fee.unwrap_or(u64::MAX));
+                        assert!(validate_amount_after_fee(swap.amount, fee, MIN_SWAP_AMOUNT).is_ok());
+                        assert!(swap.effective_max_slippage_bps() <= MAX_SLIPPAGE_BPS);
+                    }
+                    if let Err(error) = total {
+                        assert_eq!(error, ErrorCode::MathOverflow.into());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_leg_slippage_only_tightens_global_limit() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let tight = SwapParams { max_slippage_bps: Some(50), ..leg(a, b, 1_000, 999) };
+        let at_max = SwapParams { max_slippage_bps: Some(MAX_SLIPPAGE_BPS as u16), ..leg(a, b, 1_000, 999) };
+
+        assert_eq!(leg(a, b, 1_000, 999).effective_max_slippage_bps(), MAX_SLIPPAGE_BPS);
+        assert_eq!(tight.effective_max_slippage_bps(), 50);
+        assert_eq!(at_max.effective_max_slippage_bps(), MAX_SLIPPAGE_BPS);
+
+        // Tight and loose legs can share a batch
+        assert!(validate_swaps(&[tight, at_max, leg(b, a, 1_000, 999)]).is_ok());
+    }
+}
@@ -0,0 +1,179 @@
+//! # Large Batch Swap Instruction Handler
+//!
+//! This module contains the handler for the `batch_swap_large` instruction, the
+//! `u128` counterpart of [`batch_swap`](super::batch_swap). It exists for
+//! tokens with 18+ decimals whose meaningful trade sizes exceed `u64::MAX`.
+//!
+//! Only available with the `large-amounts` feature. It is a separate
+//! instruction so the `u64` ABI of `batch_swap` is unchanged.
+//!
+//! ## Validation
+//!
+//! The handler enforces the batch-wide rules of `batch_swap`:
+//! - `version` is a supported instruction format version
+//! - The program is not paused
+//! - Batch is not empty
+//! - Batch size <= MAX_BATCH_SIZE (10)
+//! - Each swap amount >= MIN_SWAP_AMOUNT (1)
+//! - Input and output mints are set and differ for each swap
+//! - Minimum output amount >= `Config::effective_min_output` for each swap
+//! - The authority is the only signer, if `Config::require_authority_is_payer`
+//! - The swaps touch at most `Config::max_distinct_mints` distinct mints
+//!
+//! `LargeSwapParams` has no per-leg `max_fee`, `max_slippage_bps` or `tag`,
+//! and the instruction takes none of `batch_swap`'s optional arguments (batch
+//! fee cap, callbacks, leg output accounts, shared input account), so the
+//! checks tied to those do not apply.
+//!
+//! ## Truncation Rules
+//!
+//! Fees and totals are computed with `u128` SafeMath and never truncated.
+//! Anything that moves SPL tokens must convert through
+//! [`to_token_amount`](crate::utils::to_token_amount), which rejects amounts
+//! above `u64::MAX` instead of truncating them.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_BATCH_SIZE, MIN_SWAP_AMOUNT};
+use crate::errors::ErrorCode;
+use crate::events::LargeBatchSwapEvent;
+use crate::security::{
+    assert_authority_is_payer, assert_different_mints, assert_distinct_mints_within,
+    assert_min_output_at_least_u128, assert_not_default, assert_supported_version, SafeMath,
+};
+use crate::state::{BatchSwap, LargeSwapParams};
+use crate::swap_execution::calculate_protocol_fee_u128;
+
+/// Handler for the large batch swap instruction
+///
+/// Validates every leg, accumulates totals and fees in `u128`, and emits a
+/// [`LargeBatchSwapEvent`].
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing account information
+/// * `version` - Instruction format version sent by the client
+/// * `swaps` - Vector of `u128` swap parameters (max 10 swaps per batch)
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+///
+/// # Errors
+///
+/// * `ErrorCode::UnsupportedInstructionVersion` - `version` outside the supported range
+/// * `ErrorCode::ProgramPaused` - The admin has paused the program (`Config::paused`)
+/// * `ErrorCode::AuthorityPayerMismatch` - Another account signs the transaction
+///   while `config.require_authority_is_payer` is set
+/// * `ErrorCode::EmptySwaps` - No swaps provided
+/// * `ErrorCode::TooManySwaps` - More than MAX_BATCH_SIZE swaps provided
+/// * `ErrorCode::InvalidAmount` - Invalid swap amount (zero or below minimum)
+/// * `ErrorCode::InvalidSwapPair` - Input and output mints are the same
+/// * `ErrorCode::InvalidMinOutput` - A leg's minimum output is zero or below
+///   `Config::effective_min_output` (the leg index is logged)
+/// * `ErrorCode::TooManyMints` - The swaps touch more distinct mints than
+///   `config.max_distinct_mints`
+/// * `ErrorCode::MathOverflow` - A fee or total overflows `u128`
+pub fn handler(ctx: Context<BatchSwap>, version: u8, swaps: Vec<LargeSwapParams>) -> Result<()> {
+    // ========================================================================
+    // STEP 1: Validate Version and Batch Size
+    // ========================================================================
+    
+    // Check the client speaks a supported instruction format
+    assert_supported_version(version)?;
+    
+    // Nothing trades while the admin has paused the program
+    require!(!ctx.accounts.config.paused, ErrorCode::ProgramPaused);
+    
+    require!(!swaps.is_empty(), ErrorCode::EmptySwaps);
+    require!(
+        swaps.len() <= MAX_BATCH_SIZE,
+        ErrorCode::TooManySwaps
+    );
+    
+    let authority = ctx.accounts.authority.key();
+    let clock = Clock::get()?;
+    let fee_exempt = ctx.accounts.config.is_fee_exempt(&authority);
+    let fee_bps = ctx.accounts.config.fee_bps;
+    
+    // Optionally require the authority to be the transaction's only signer
+    if ctx.accounts.config.require_authority_is_payer {
+        assert_authority_is_payer(&ctx.accounts.instructions_sysvar, &authority)?;
+    }
+    
+    // Every leg must ask for at least the configured output floor
+    let min_output_floor = ctx.accounts.config.effective_min_output();
+    for (index, swap) in swaps.iter().enumerate() {
+        if let Err(error) = assert_min_output_at_least_u128(swap.min_output_amount, min_output_floor) {
+            msg!("Leg {} asks for less than the output floor", index);
+            return Err(error);
+        }
+    }
+    
+    // Optionally cap the distinct mints (and so the accounts) of the batch
+    assert_distinct_mints_within(
+        swaps.iter().flat_map(|swap| [swap.input_mint, swap.output_mint]),
+        ctx.accounts.config.max_distinct_mints,
+    )?;
+    
+    // ========================================================================
+    // STEP 2: Validate Each Swap and Accumulate Totals
+    // ========================================================================
+    
+    let mut total_input_amount: u128 = 0;
+    let mut total_protocol_fees: u128 = 0;
+    
+    for (index, swap) in swaps.iter().enumerate() {
+        assert_not_default(&swap.input_mint)?;
+        assert_not_default(&swap.output_mint)?;
+        
+        require!(
+            swap.amount >= MIN_SWAP_AMOUNT as u128,
+            ErrorCode::InvalidAmount
+        );
+        
+        assert_different_mints(&swap.input_mint, &swap.output_mint)?;
+        
+        require!(
+            swap.min_output_amount > 0,
+            ErrorCode::InvalidMinOutput
+        );
+        
+        // Calculate protocol fee for this swap (security: u128 safe math)
+        let fee = calculate_protocol_fee_u128(swap.amount, fee_bps, fee_exempt)?;
+        
+        total_input_amount = total_input_amount.safe_add(swap.amount)?;
+        total_protocol_fees = total_protocol_fees.safe_add(fee)?;
+        
+        msg!(
+            "Swap {}: {} tokens (min: {}, fee: {}) from {} to {}",
+            index + 1,
+            swap.amount,
+            swap.min_output_amount,
+            fee,
+            swap.input_mint,
+            swap.output_mint
+        );
+    }
+    
+    msg!(
+        "All {} large swaps validated successfully. Total input: {}, Total fees: {}",
+        swaps.len(),
+        total_input_amount,
+        total_protocol_fees
+    );
+    
+    // ========================================================================
+    // STEP 3: Emit Event
+    // ========================================================================
+    
+    emit!(LargeBatchSwapEvent {
+        authority,
+        swap_count: swaps.len() as u8,
+        total_input_amount,
+        total_protocol_fees,
+        timestamp: clock.unix_timestamp,
+    });
+    
+    Ok(())
+}
@@ -18,17 +18,162 @@
 //! 2. **Validate Accounts**: Ensure accounts are valid and mints differ
 //! 3. **Validate Authority**: Ensure authority owns the input account
 //! 4. **Get Swap Quote**: Get expected output from Jupiter/DEX
-//! 5. **Execute Swap**: Perform swap via DEX (Jupiter CPI)
-//! 6. **Validate Slippage**: Ensure output meets minimum requirement
-//! 7. **Calculate Fees**: Calculate and distribute protocol fees
-//! 8. **Emit Event**: Emit event for tracking and indexing
+//! 5. **Calculate Fees**: Calculate and distribute protocol fees from the input
+//! 6. **Execute Swap**: Swap the amount after fee via DEX (Jupiter CPI)
+//! 7. **Validate Slippage**: Ensure output meets minimum requirement
+//! 8. **Record Stats**: Update the `Stats` and `PairStats` accounts
+//! 9. **Emit Event**: Emit event for tracking and indexing
+//! 10. **Return Data**: Return the output, fee and slippage ([`SwapReturnData`])
+//!
+//! ## Fee Ordering
+//!
+//! The protocol fee is transferred out of the input account before the swap
+//! runs, and the swap only consumes `amount - fee`. The input account is
+//! therefore debited by exactly `amount` in total.
+//!
+//! `min_output_amount` is checked against the output of that net swap. A
+//! minimum taken from a quote for the gross `amount` is therefore slightly
+//! too strict; clients scale it to the net amount with
+//! `adjust_min_output_for_fee` (or `SwapParams::with_fee_adjusted_min_output`).
+//!
+//! ## Fee Burning
+//!
+//! When `Config::burn_bps` is set, the fee is split with
+//! [`split_protocol_fee`]: the treasury share is transferred to the fee
+//! recipient and the burn share is burned from the input account (the input
+//! mint is writable for this). Both shares come out of the same fee, so the
+//! input account is still debited by exactly `amount`.
+//!
+//! ## LP Fee Share
+//!
+//! When `Config::lp_fee_bps` is set, [`split_lp_fee`] first takes the
+//! liquidity providers' part of the fee, which is transferred to the
+//! `lp_fee_account`; the burn split above applies to the protocol's part
+//! only. `SwapExecutedEvent` reports `treasury_fee`, `burned_fee` and
+//! `lp_fee`, which add up to `protocol_fee`, and the `net_output_amount` the
+//! recipient keeps after all fees (`output_amount`, as no fee is taken from
+//! the output).
+//!
+//! ## Loyalty Discount
+//!
+//! An authority that passes its `user_stats` account has its swap volume
+//! recorded there, and its cumulative volume selects a discount off
+//! `Config::fee_bps` ([`fee_discount_bps_for_user`], clamped at a 0 rate).
+//! Only volume in `Config::loyalty_mint` counts ([`loyalty_volume`]), so
+//! swaps of other pairs add nothing however large their raw amounts.
+//! Exempt authorities pay no fee and get no discount.
+//! `SwapExecutedEvent::fee_discount_bps` reports the discount applied.
+//! Discounted swaps lower the realized rate that "Fee Monitoring" compares
+//! with `Config::expected_fee_rate_bps`.
+//!
+//! ## Simulation Mode
+//!
+//! With `simulate = true` the handler runs every validation and computes
+//! the fees, but makes no token transfer or burn, does not measure a swap
+//! and does not record stats. The output is taken to be the quote
+//! (`expected_output`, or `min_output_amount` without one), so the return
+//! data and `SwapExecutedEvent` (with `simulated` set) show what a real swap
+//! at the quoted price would report. The flag is only meaningful under
+//! `simulateTransaction`: a simulated swap that lands on-chain moves no
+//! tokens, and indexers must skip events with `simulated` set.
+//!
+//! ## Fee Monitoring
+//!
+//! After recording stats, the handler compares the all-time realized fee
+//! rate in `Stats` with `Config::expected_fee_rate_bps` and emits a
+//! `FeeAnomalyEvent` when it is off by more than
+//! `FEE_ANOMALY_TOLERANCE_BPS` (see [`Stats::fee_rate_anomaly`]). The swap
+//! still succeeds; simulated swaps are not checked.
+//!
+//! [`Stats::fee_rate_anomaly`]: crate::state::Stats::fee_rate_anomaly
+//!
+//! ## Fee Reconciliation
+//!
+//! The handler reads the fee recipient's balance (or the fee treasury's
+//! lamports) before and after the fee transfer and reports the delta as
+//! `SwapExecutedEvent::fee_collected`. It normally equals `treasury_fee`;
+//! when it doesn't (e.g. a transfer-fee token withholding part of the
+//! transfer) the real amount is reported and the shortfall is logged, so
+//! reconciliation catches silent fee loss. It is 0 when no fee was
+//! transferred (no fee recipient, fee-exempt or simulated swaps).
+//!
+//! ## Price Jumps
+//!
+//! Every swap that moves tokens stores its execution price (output per
+//! input actually swapped) in the pair's `LastPrice` account. If the
+//! previous price is at most `PRICE_JUMP_WINDOW_SECS` old and the new one
+//! moved more than `Config::max_price_jump_bps` from it, the handler emits a
+//! `PriceJumpEvent`, or fails with `ErrorCode::PriceJumpExceeded` in
+//! `strict_sanity` builds. A swap that is not rejected stores its price.
+//!
+//! ## Full Balance Swaps
+//!
+//! `amount = USE_FULL_BALANCE` (`u64::MAX`) swaps the input token account's
+//! whole balance, read on-chain when the handler runs: the fee comes out of
+//! it and the rest is swapped. Traders don't have to read the balance first,
+//! so a balance change between that read and the swap cannot make the swap
+//! fail or leave dust. From STEP 2 on, `amount` is the resolved balance: it
+//! must be at least `MIN_SWAP_AMOUNT`, and it is the `input_amount` reported
+//! in `SwapExecutedEvent` and recorded in stats.
+//!
+//! ## Quote Expiry
+//!
+//! `expected_output` comes from an off-chain quote, which goes stale while
+//! the transaction waits to land. Clients pass the quote's
+//! `quote_timestamp` and a `max_quote_age` in seconds; the handler rejects
+//! the swap with `QuoteExpired` when the cluster clock is more than
+//! `max_quote_age` past the quote. `max_quote_age = 0` disables the check.
+//!
+//! ## Slippage Failure Mode
+//!
+//! A swap that delivers less than `min_output_amount` always fails. When it
+//! meets the minimum but slipped further from `expected_output` than
+//! `MAX_SLIPPAGE_BPS`, `SlippageFailureMode::Revert` (the default) fails it
+//! with `SlippageExceeded`, while `SlippageFailureMode::AcceptMin` lets it
+//! through and logs the slippage, for traders who would rather fill at their
+//! minimum than not at all. The mode only matters when `expected_output` is
+//! optimistic, i.e. above what `min_output_amount` and the tolerance imply.
+//!
+//! ## Lamport Fees
+//!
+//! A WSOL swap can pay its fee in SOL instead of WSOL: with
+//! `fee_denomination = FeeDenomination::Lamports` the protocol fee is a
+//! system transfer from the authority's wallet to the `fee_treasury`
+//! wallet (`Config::fee_recipient` once pinned), and the whole `amount` is
+//! swapped. The wallet must keep its rent-exempt minimum after paying. The
+//! fee can't be split, so lamport fees require `Config::burn_bps` and
+//! `Config::lp_fee_bps` to be 0. `SwapExecutedEvent::fee_denomination`
+//! tells indexers which asset `protocol_fee` was paid in.
+//!
+//! ## Treasury Kind
+//!
+//! `Config::treasury_is_token_account` says which kind of account the
+//! treasury takes fees in. With `true` (the default) `fee_recipient` may not
+//! be a SOL wallet; with `false` `fee_recipient` may not be a token account
+//! and a `fee_treasury` wallet may not be a token account, and a swap that
+//! owes a fee must pay it in lamports (the fee is converted to SOL), else it
+//! fails with `InvalidFeeDenomination`. Fee-exempt swaps are unaffected.
+//!
+//! ## Slippage Basis
+//!
+//! The relative slippage check measures on the output side by default: the
+//! share of `expected_output` that did not arrive. Callers whose tolerances
+//! are stated on the input side pass `SlippageBasis::Input`, which measures
+//! the extra input consumed for the output received (see [`SlippageBasis`]).
+//! Either way, `SwapExecutedEvent` and the return data report output-side
+//! slippage, so indexers see one consistent measure.
 //!
 //! ## Validation
 //!
 //! The handler validates:
 //! - Amount >= MIN_SWAP_AMOUNT (1)
+//! - Input and output token accounts are different accounts
 //! - Input and output accounts have different mints
 //! - Authority owns the input token account
+//! - With `Config::strict_ata`, input and output accounts are canonical ATAs
+//! - Fee recipient is neither the input nor the output token account
+//! - Lamport fees are only paid on WSOL input, to a treasury other than the
+//!   authority, by a wallet that can cover them
 //! - Slippage is within tolerance
 //! - Output meets minimum requirement
 //!
@@ -41,17 +186,27 @@
 //! - Fee calculation is transparent
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Transfer};
+use anchor_lang::system_program;
+use anchor_spl::token::{self, spl_token::native_mint, Burn, Transfer};
 
-use crate::constants::{MAX_SLIPPAGE_BPS, MIN_SWAP_AMOUNT};
+use crate::constants::{FEE_ANOMALY_TOLERANCE_BPS, MAX_SLIPPAGE_BPS, MIN_SWAP_AMOUNT};
 use crate::errors::ErrorCode;
-use crate::events::SwapExecutedEvent;
+use crate::events::{FeeAnomalyEvent, PriceJumpEvent, SwapExecutedEvent, EVENT_SCHEMA_VERSION};
 use crate::security::{
-    assert_different_mints, assert_keys_equal, assert_signer, assert_token_account_mint,
-    assert_token_account_owner, amount_after_fee, validate_amount_after_fee,
+    amount_after_fee, assert_authority_is_payer, assert_canonical_ata, assert_different_mints,
+    assert_fee_covered, assert_fee_recipient_distinct, assert_keys_differ, assert_keys_equal,
+    assert_lamports_cover_fee, assert_min_output_at_least, assert_recipient_token_account,
+    assert_signer, assert_supported_version, assert_token_account_owner, assert_treasury_kind,
+    validate_amount_after_fee,
+};
+use crate::state::{
+    ExecuteSwap, FeeDenomination, LastPrice, SlippageBasis, SlippageFailureMode, SwapReturnData,
+};
+use crate::swap_execution::{
+    calculate_protocol_fee, check_expected_output, check_quote_age, fee_discount_bps_for_user,
+    loyalty_volume, received_output, resolve_swap_amount, split_lp_fee, split_protocol_fee,
+    validate_slippage,
 };
-use crate::state::ExecuteSwap;
-use crate::swap_execution::{calculate_protocol_fee, validate_slippage};
 use crate::utils;
 
 /// Handler for the execute swap instruction
@@ -63,46 +218,119 @@ use crate::utils;
 /// # Arguments
 ///
 /// * `ctx` - Context containing token accounts, mints, and authority
+/// * `version` - Instruction format version (see `INSTRUCTION_VERSION`)
 /// * `amount` - Amount of input tokens to swap (in token's smallest unit)
+///   (fee included), or `USE_FULL_BALANCE` to swap the input account's
+///   whole balance
 /// * `min_output_amount` - Minimum output amount (slippage protection)
-/// * `expected_output` - Expected output amount (from Jupiter quote, client-provided)
+/// * `expected_output` - Expected output amount (from Jupiter quote, client-provided);
+///   0 skips the relative slippage check (see [`check_expected_output`])
+/// * `emit_events` - Emit `SwapExecutedEvent` (`true` by default in clients);
+///   `false` skips it to save compute units. Stats are updated either way.
+/// * `memo_hash` - SHA-256 hash of the SPL memo attached to the transaction,
+///   or all zeroes; recorded in `SwapExecutedEvent`
+/// * `tag` - Caller-defined label (e.g. a strategy id, 0 if untagged);
+///   recorded in `SwapExecutedEvent`
+/// * `recipient` - Wallet to deliver the output to instead of the authority;
+///   requires `recipient_token_account` (its ATA of the output mint)
+/// * `simulate` - Dry run for `simulateTransaction`: validate and compute
+///   fees without moving tokens (see "Simulation Mode" in the module docs)
+/// * `slippage_basis` - Side the relative slippage check measures on
+///   (see [`SlippageBasis`]); reported slippage is always output-side
+/// * `quote_timestamp` - Unix timestamp of the quote behind `expected_output`
+/// * `max_quote_age` - Oldest accepted quote in seconds, measured against
+///   the cluster clock (see [`check_quote_age`]); 0 disables the check
+/// * `fee_denomination` - Asset the protocol fee is paid in (see
+///   [`FeeDenomination`] and "Lamport Fees" in the module docs)
+/// * `slippage_failure_mode` - Whether a failed relative slippage check
+///   reverts or accepts an output at or above the minimum (see
+///   [`SlippageFailureMode`])
 ///
 /// # Returns
 ///
-/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+/// * `Result<SwapReturnData>` - The output, fee and slippage of the swap
+///   (set as return data for CPI callers), or an error on failure
 ///
 /// # Errors
 ///
 /// This function can return the following errors:
-/// * `ErrorCode::InvalidAmount` - Amount is zero or below minimum
+/// * `ErrorCode::UnsupportedInstructionVersion` - `version` is not supported
+/// * `ErrorCode::ProgramPaused` - The admin has paused the program (`Config::paused`)
+/// * `ErrorCode::InvalidAmount` - Amount (after resolving `USE_FULL_BALANCE`)
+///   is zero or below minimum, or `expected_output` is 0 while
+///   `Config::require_expected_output` is set
+/// * `ErrorCode::InvalidMinOutput` - `min_output_amount` is below
+///   `Config::effective_min_output`, or at the 1-unit floor without an
+///   `expected_output`
+/// * `ErrorCode::QuoteExpired` - `max_quote_age` is set and the quote is older
+/// * `ErrorCode::InvalidAccount` - The input and output token accounts are
+///   the same account, or a mint does not match its token account
 /// * `ErrorCode::InvalidSwapPair` - Input and output mints are the same
-/// * `ErrorCode::InvalidAuthority` - Authority doesn't own input account
-/// * `ErrorCode::SlippageExceeded` - Actual output < min_output_amount
+/// * `ErrorCode::InvalidAuthority` - Authority doesn't own the input account, or
+///   the output account without a `recipient`
+/// * `ErrorCode::InvalidRecipient` - `recipient` and `recipient_token_account`
+///   don't match (see [`assert_recipient_token_account`])
+/// * `ErrorCode::AuthorityPayerMismatch` - Another account signs the transaction
+///   while `config.require_authority_is_payer` is set
+/// * `ErrorCode::NonCanonicalTokenAccount` - The input or output account is
+///   not the ATA of its owner and mint while `config.strict_ata` is set
+/// * `ErrorCode::InvalidFeeRecipient` - The fee recipient has another mint,
+///   is not owned by `Config::fee_recipient`, is the input or output token
+///   account, or is not of the kind `Config::treasury_is_token_account` says
+/// * `ErrorCode::InvalidLpFeeAccount` - `Config::lp_fee_bps` is set and the
+///   LP fee account is missing, holds another mint or has another owner
+/// * `ErrorCode::FeeExceedsAmount` - The input account cannot cover the
+///   protocol fee when it is collected
+/// * `ErrorCode::InvalidFeeDenomination` - Lamport fees for a non-WSOL input,
+///   or while `Config::burn_bps` or `Config::lp_fee_bps` is set, or token
+///   fees owed to a SOL treasury
+/// * `ErrorCode::InsufficientLamports` - The authority cannot pay a lamport
+///   fee and stay rent-exempt
+/// * `ErrorCode::InsufficientOutput` - No output tokens arrived (logs `No output received`)
+/// * `ErrorCode::SlippageExceeded` - Some output arrived but < min_output_amount
+///   (logs `Slippage exceeded`)
 /// * `ErrorCode::SwapExecutionFailed` - Swap execution failed
+/// * `ErrorCode::ImplausibleMinOutput` - Minimum output fails the heuristic
+///   sanity check (`strict_sanity` feature only)
+/// * `ErrorCode::PriceJumpExceeded` - The execution price jumped more than
+///   `Config::max_price_jump_bps` (`strict_sanity` feature only)
 ///
 /// # Process
 ///
 /// 1. **Validate Amount**: Check that amount is valid
 /// 2. **Validate Accounts**: Check that accounts are compatible (different mints)
-/// 3. **Validate Authority**: Check that authority owns input account
+/// 3. **Validate Authority**: Check that authority owns the input account, and
+///    that the output goes to the authority or the recipient's ATA
 /// 4. **Get Quote**: Get expected output (from parameter, would be from Jupiter in production)
-/// 5. **Execute Swap**: Execute swap via DEX (simplified for MVP)
-/// 6. **Validate Slippage**: Ensure output meets minimum requirement
-/// 7. **Calculate Fees**: Calculate and distribute protocol fees
+/// 5. **Calculate Fees**: Calculate and distribute protocol fees from the input
+/// 6. **Execute Swap**: Swap the amount after fee via DEX (simplified for MVP)
+/// 7. **Validate Slippage**: Ensure output meets minimum requirement
 /// 8. **Emit Event**: Emit event for tracking and indexing
 ///
 /// # Example
 ///
 /// ```rust,ignore
 /// // Swap 1000 tokens from mint A to mint B
-/// execute_swap::handler(ctx, 1000, 900, 950)?;
+/// execute_swap::handler(ctx, INSTRUCTION_VERSION, 1000, 900, 950, true, [0u8; 32], 0, None, false, SlippageBasis::Output, 0, 0, FeeDenomination::Token, SlippageFailureMode::Revert)?;
 /// ```
+#[allow(clippy::too_many_arguments)]
 pub fn handler(
     ctx: Context<ExecuteSwap>,
+    version: u8,
     amount: u64,
     min_output_amount: u64,
     expected_output: u64,
-) -> Result<()> {
+    emit_events: bool,
+    memo_hash: [u8; 32],
+    tag: u64,
+    recipient: Option<Pubkey>,
+    simulate: bool,
+    slippage_basis: SlippageBasis,
+    quote_timestamp: i64,
+    max_quote_age: i64,
+    fee_denomination: FeeDenomination,
+    slippage_failure_mode: SlippageFailureMode,
+) -> Result<SwapReturnData> {
     // ========================================================================
     // STEP 1: Security Validations
     // ========================================================================
@@ -110,6 +338,12 @@ pub fn handler(
     // We perform comprehensive security validations before any operations.
     // This includes signer validation, account ownership, and input validation.
     
+    // Reject instruction formats this program version doesn't understand
+    assert_supported_version(version)?;
+    
+    // Nothing trades while the admin has paused the program
+    require!(!ctx.accounts.config.paused, ErrorCode::ProgramPaused);
+    
     // Validate authority is a signer (security: prevent unauthorized access)
     assert_signer(ctx.accounts.authority.as_ref())?;
     
@@ -117,20 +351,45 @@ pub fn handler(
     // STEP 2: Validate Amount
     // ========================================================================
     
+    // `USE_FULL_BALANCE` swaps the whole input balance, read here rather than
+    // by the client so it cannot go stale before the swap lands
+    let amount = resolve_swap_amount(amount, ctx.accounts.input_token_account.amount);
+    
     require!(
         amount >= MIN_SWAP_AMOUNT,
         ErrorCode::InvalidAmount
     );
     
-    require!(
-        min_output_amount > 0,
-        ErrorCode::InvalidMinOutput
-    );
+    // The output side needs a floor too (`MIN_OUTPUT_AMOUNT` unless the
+    // admin raised it)
+    assert_min_output_at_least(min_output_amount, ctx.accounts.config.effective_min_output())?;
+    
+    // Without a quote only the absolute minimum protects the swap
+    check_expected_output(
+        expected_output,
+        min_output_amount,
+        ctx.accounts.config.require_expected_output,
+    )?;
+    
+    // A quote that is too old no longer reflects the market (opt-in)
+    let clock = Clock::get()?;
+    check_quote_age(quote_timestamp, max_quote_age, clock.unix_timestamp)?;
+    
+    // Heuristic check against misconfigured minimums (opt-in)
+    #[cfg(feature = "strict_sanity")]
+    crate::security::assert_plausible_min_output(amount, min_output_amount)?;
     
     // ========================================================================
     // STEP 3: Validate Accounts and Mints
     // ========================================================================
     
+    // Validate that input and output are different accounts (security: the
+    // output balance delta is meaningless if the swap also debits it)
+    assert_keys_differ(
+        &ctx.accounts.input_token_account.key(),
+        &ctx.accounts.output_token_account.key(),
+    )?;
+    
     // Validate that input and output accounts have different mints (security: prevent invalid swaps)
     assert_different_mints(
         &ctx.accounts.input_token_account.mint,
@@ -159,15 +418,68 @@ pub fn handler(
         ctx.accounts.authority.key,
     )?;
     
+    // Output goes to the authority, or to the ATA of an explicitly named
+    // recipient (security: output can't be routed to an arbitrary account)
+    match (recipient, &ctx.accounts.recipient_token_account) {
+        (None, None) => assert_token_account_owner(
+            &ctx.accounts.output_token_account,
+            ctx.accounts.authority.key,
+        )?,
+        (Some(recipient), Some(recipient_token_account)) => assert_recipient_token_account(
+            &recipient_token_account.key(),
+            &recipient_token_account.owner,
+            &recipient_token_account.mint,
+            &recipient,
+            ctx.accounts.output_mint.key,
+        )?,
+        _ => return err!(ErrorCode::InvalidRecipient),
+    }
+    
+    // Optionally require the authority to be the transaction's only signer
+    if ctx.accounts.config.require_authority_is_payer {
+        assert_authority_is_payer(&ctx.accounts.instructions_sysvar, ctx.accounts.authority.key)?;
+    }
+    
+    // Optionally require canonical ATAs (the recipient's is always checked)
+    if ctx.accounts.config.strict_ata {
+        for token_account in [&ctx.accounts.input_token_account, &ctx.accounts.output_token_account] {
+            assert_canonical_ata(&token_account.key(), &token_account.owner, &token_account.mint)?;
+        }
+    }
+    
     // ========================================================================
     // STEP 5: Validate Fee Recipient (if provided)
     // ========================================================================
     
     // Check if fee recipient is provided (owned by token program)
     // If owner is token program, it's a valid token account
-    let fee_recipient_provided = ctx.accounts.fee_recipient.owner == &anchor_spl::token::ID;
+    // `no-fees` builds collect nothing, so the fee recipient is ignored,
+    // and a fee paid in lamports goes to the fee treasury instead
+    let pay_fee_in_lamports = fee_denomination == FeeDenomination::Lamports;
+    let fee_recipient_provided = !cfg!(feature = "no-fees")
+        && !pay_fee_in_lamports
+        && ctx.accounts.fee_recipient.owner == &anchor_spl::token::ID;
+    
+    // The account the fee goes to must be of the configured treasury kind:
+    // a token account, or a SOL wallet for lamport fees
+    let treasury_is_token_account = ctx.accounts.config.treasury_is_token_account;
+    if !cfg!(feature = "no-fees") {
+        if !pay_fee_in_lamports {
+            assert_treasury_kind(treasury_is_token_account, ctx.accounts.fee_recipient.owner)?;
+        } else if let Some(fee_treasury) = &ctx.accounts.fee_treasury {
+            assert_treasury_kind(treasury_is_token_account, fee_treasury.owner)?;
+        }
+    }
     
     if fee_recipient_provided {
+        // A fee paid to the swap's own accounts would be a no-op self-transfer
+        // (security: keep collected fees and reported fees consistent)
+        assert_fee_recipient_distinct(
+            ctx.accounts.fee_recipient.key,
+            &ctx.accounts.input_token_account.key(),
+            &ctx.accounts.output_token_account.key(),
+        )?;
+        
         // Validate fee recipient is a valid token account
         let fee_recipient = anchor_spl::token::TokenAccount::try_deserialize(
             &mut &ctx.accounts.fee_recipient.data.borrow()[..]
@@ -178,6 +490,64 @@ pub fn handler(
             fee_recipient.mint == ctx.accounts.input_token_account.mint,
             ErrorCode::InvalidFeeRecipient
         );
+        
+        // Once the admin pins a fee recipient, fees may only go to its accounts
+        let configured_recipient = ctx.accounts.config.fee_recipient;
+        require!(
+            configured_recipient == Pubkey::default() || fee_recipient.owner == configured_recipient,
+            ErrorCode::InvalidFeeRecipient
+        );
+    }
+    
+    // Lamports price the fee 1:1 only for WSOL input, and can't be burned or
+    // paid to LPs in input tokens
+    if pay_fee_in_lamports {
+        require!(
+            ctx.accounts.input_mint.key() == native_mint::ID
+                && ctx.accounts.config.burn_bps == 0
+                && ctx.accounts.config.lp_fee_bps == 0,
+            ErrorCode::InvalidFeeDenomination
+        );
+    }
+    
+    let fee_treasury_provided = !cfg!(feature = "no-fees")
+        && pay_fee_in_lamports
+        && ctx.accounts.fee_treasury.is_some();
+    
+    if let Some(fee_treasury) = ctx.accounts.fee_treasury.as_ref().filter(|_| fee_treasury_provided) {
+        // Paying the fee to the authority itself would collect nothing
+        require!(
+            fee_treasury.key() != ctx.accounts.authority.key(),
+            ErrorCode::InvalidFeeRecipient
+        );
+        
+        // Once the admin pins a fee recipient, lamport fees go to its wallet
+        let configured_recipient = ctx.accounts.config.fee_recipient;
+        require!(
+            configured_recipient == Pubkey::default() || fee_treasury.key() == configured_recipient,
+            ErrorCode::InvalidFeeRecipient
+        );
+    }
+    
+    // The LP part of the fee needs an account of the input mint, owned by the
+    // pinned LP fee recipient if there is one
+    if fee_recipient_provided && ctx.accounts.config.lp_fee_bps > 0 {
+        let lp_fee_account = ctx
+            .accounts
+            .lp_fee_account
+            .as_ref()
+            .ok_or(ErrorCode::InvalidLpFeeAccount)?;
+        
+        require!(
+            lp_fee_account.mint == ctx.accounts.input_token_account.mint,
+            ErrorCode::InvalidLpFeeAccount
+        );
+        
+        let configured_recipient = ctx.accounts.config.lp_fee_recipient;
+        require!(
+            configured_recipient == Pubkey::default() || lp_fee_account.owner == configured_recipient,
+            ErrorCode::InvalidLpFeeAccount
+        );
     }
     
     // ========================================================================
@@ -185,22 +555,191 @@ pub fn handler(
     // ========================================================================
     
     // Calculate protocol fee (security: use safe math to prevent overflow)
-    let protocol_fee = calculate_protocol_fee(amount)?;
+    // Fee-exempt authorities pay no fee, so the fee transfer below is skipped
+    let fee_exempt = ctx.accounts.config.is_fee_exempt(ctx.accounts.authority.key);
+    
+    // Authorities passing their user stats get a volume-tier discount off
+    // the rate, clamped at 0
+    let discount_bps = match &ctx.accounts.user_stats {
+        Some(user_stats) if !fee_exempt => fee_discount_bps_for_user(user_stats.total_volume),
+        _ => 0,
+    };
+    let fee_bps = ctx
+        .accounts
+        .config
+        .fee_bps
+        .saturating_sub(u16::try_from(discount_bps).unwrap_or(u16::MAX));
+    let fee_discount_bps = ctx.accounts.config.fee_bps - fee_bps;
+    let protocol_fee = calculate_protocol_fee(amount, fee_bps, fee_exempt, None)?;
+    
+    // A SOL treasury can't hold input tokens: the fee has to be converted,
+    // i.e. paid in lamports (WSOL input only)
+    require!(
+        cfg!(feature = "no-fees") || treasury_is_token_account || pay_fee_in_lamports || protocol_fee == 0,
+        ErrorCode::InvalidFeeDenomination
+    );
+    
+    // A lamport fee is paid from the authority's wallet, not the input
+    let input_fee = if pay_fee_in_lamports { 0 } else { protocol_fee };
     
     // Validate amount after fee is sufficient (security: prevent underflow)
-    validate_amount_after_fee(amount, protocol_fee, MIN_SWAP_AMOUNT)?;
+    validate_amount_after_fee(amount, input_fee, MIN_SWAP_AMOUNT)?;
     
     // Amount after fee (this is what gets swapped) (security: use safe math)
-    // Note: This is calculated but not used directly as swap execution
-    // happens client-side via Jupiter instructions
-    let _swap_amount = amount_after_fee(amount, protocol_fee)?;
+    // The fee and the swapped amount together consume exactly `amount` from
+    // the input account.
+    let swap_amount = amount_after_fee(amount, input_fee)?;
+    
+    // Liquidity providers get their part of the fee first; deflationary
+    // tokens then burn part of the protocol's share instead of sending it
+    // all to the treasury
+    let (protocol_share, lp_fee) = split_lp_fee(
+        protocol_fee,
+        ctx.accounts.config.fee_bps,
+        ctx.accounts.config.lp_fee_bps,
+    )?;
+    let (treasury_fee, burned_fee) = split_protocol_fee(protocol_share, ctx.accounts.config.burn_bps)?;
     
     // ========================================================================
-    // STEP 7: Execute Swap
+    // STEP 7: Distribute Fees
+    // ========================================================================
+    //
+    // The fee is taken from the input account *before* the swap. If the swap
+    // ran first, a program-side swap of the full amount would leave nothing
+    // to collect the fee from. Deducting first means the swap only ever sees
+    // `swap_amount`.
+    
+    // Re-check the balance the fee comes from: earlier instructions (or a
+    // program-side swap) may have spent the input, and the token program
+    // would otherwise fail with a bare InsufficientFunds
+    if fee_recipient_provided && protocol_fee > 0 {
+        ctx.accounts.input_token_account.reload()?;
+        assert_fee_covered(ctx.accounts.input_token_account.amount, protocol_fee)?;
+    }
+    
+    // Likewise for a lamport fee, which must leave the wallet rent-exempt
+    if fee_treasury_provided && protocol_fee > 0 {
+        assert_lamports_cover_fee(
+            ctx.accounts.authority.lamports(),
+            protocol_fee,
+            Rent::get()?.minimum_balance(0),
+        )?;
+    }
+    
+    // A simulated swap computes the fees above but moves no tokens
+    let transfer_fees = fee_recipient_provided && !simulate;
+    
+    // Fee account balances before the transfer, to report what actually
+    // arrived (see "Fee Reconciliation" in the module docs)
+    let fee_recipient_amount = |account: &AccountInfo| -> Result<u64> {
+        let fee_recipient = anchor_spl::token::TokenAccount::try_deserialize(&mut &account.data.borrow()[..])
+            .map_err(|_| ErrorCode::InvalidFeeRecipient)?;
+        Ok(fee_recipient.amount)
+    };
+    let fee_recipient_before = if transfer_fees && treasury_fee > 0 {
+        Some(fee_recipient_amount(&ctx.accounts.fee_recipient)?)
+    } else {
+        None
+    };
+    let fee_treasury_before = ctx
+        .accounts
+        .fee_treasury
+        .as_ref()
+        .filter(|_| fee_treasury_provided && !simulate && protocol_fee > 0)
+        .map(|fee_treasury| fee_treasury.lamports());
+    
+    if fee_treasury_provided && !simulate && protocol_fee > 0 {
+        // Checked in STEP 5 whenever the fee is paid in lamports
+        let fee_treasury = ctx
+            .accounts
+            .fee_treasury
+            .as_ref()
+            .ok_or(ErrorCode::InvalidFeeRecipient)?;
+        
+        // Transfer the whole fee (no burn or LP share) to the treasury wallet
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: fee_treasury.to_account_info(),
+            },
+        );
+        
+        system_program::transfer(transfer_ctx, protocol_fee)
+            .map_err(|_| ErrorCode::TransferFailed)?;
+    }
+    
+    // If fee recipient is provided, collect fees: the treasury and LP shares
+    // are transferred and the burn share is burned from the input account
+    if transfer_fees && treasury_fee > 0 {
+        // Transfer the treasury share to fee recipient
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.input_token_account.to_account_info(),
+                to: ctx.accounts.fee_recipient.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        
+        token::transfer(transfer_ctx, treasury_fee)
+            .map_err(|_| ErrorCode::TransferFailed)?;
+    }
+    
+    if transfer_fees && lp_fee > 0 {
+        // Checked in STEP 5 whenever the LP share can be non-zero
+        let lp_fee_account = ctx
+            .accounts
+            .lp_fee_account
+            .as_ref()
+            .ok_or(ErrorCode::InvalidLpFeeAccount)?;
+        
+        // Transfer the LP share to the LP fee account
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.input_token_account.to_account_info(),
+                to: lp_fee_account.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        
+        token::transfer(transfer_ctx, lp_fee)
+            .map_err(|_| ErrorCode::TransferFailed)?;
+    }
+    
+    if transfer_fees && burned_fee > 0 {
+        // Burn the rest, reducing the input token's supply
+        let burn_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.input_mint.to_account_info(),
+                from: ctx.accounts.input_token_account.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        
+        token::burn(burn_ctx, burned_fee)
+            .map_err(|_| ErrorCode::TransferFailed)?;
+    }
+    
+    // What the fee recipient (or treasury) actually received
+    let fee_collected = match (fee_recipient_before, fee_treasury_before, &ctx.accounts.fee_treasury) {
+        (Some(before), _, _) => fee_recipient_amount(&ctx.accounts.fee_recipient)?.saturating_sub(before),
+        (None, Some(before), Some(fee_treasury)) => fee_treasury.lamports().saturating_sub(before),
+        _ => 0,
+    };
+    // A lamport fee has no burn or LP share, so it is all `treasury_fee` too
+    if (fee_recipient_before.is_some() || fee_treasury_before.is_some()) && fee_collected != treasury_fee {
+        msg!("Fee recipient received {} of a {} fee", fee_collected, treasury_fee);
+    }
+    
+    // ========================================================================
+    // STEP 8: Execute Swap
     // ========================================================================
     //
     // In production, this would:
-    // 1. Call Jupiter program via CPI to execute the swap
+    // 1. Call Jupiter program via CPI to swap `swap_amount` (never `amount`)
     // 2. Jupiter handles the DEX routing and execution
     // 3. Output tokens are received in output_token_account
     //
@@ -210,80 +749,207 @@ pub fn handler(
     // - Validating balance after
     // - This allows slippage validation
     
-    // Get balance before swap (for validation)
-    let output_balance_before = ctx.accounts.output_token_account.amount;
-    
-    // In production, Jupiter swap would happen here via CPI
-    // For MVP, we assume the client has included Jupiter swap instructions
-    // in the same transaction, so the swap has already executed
-    
-    // Get balance after swap (for validation)
-    // Note: In production, Jupiter swap happens here via CPI
-    // For MVP, client includes Jupiter instructions in the same transaction
-    let output_balance_after = ctx.accounts.output_token_account.amount;
+    // Output is delivered to the recipient's account when one is given
+    let delivery_account = match ctx.accounts.recipient_token_account.as_mut() {
+        Some(recipient_token_account) => recipient_token_account,
+        None => &mut ctx.accounts.output_token_account,
+    };
+    
+    let actual_output = if simulate {
+        // No swap runs: report the quoted output (the guaranteed minimum
+        // without a quote)
+        msg!("Simulating swap of {} input after {} fee", swap_amount, protocol_fee);
+        if expected_output > 0 { expected_output } else { min_output_amount }
+    } else {
+        // Get balance before swap (for validation)
+        let output_balance_before = delivery_account.amount;
+        
+        // In production, Jupiter swap of `swap_amount` would happen here via CPI
+        // For MVP, we assume the client has included Jupiter swap instructions
+        // in the same transaction, so the swap has already executed
+        msg!("Swapping {} input after {} fee", swap_amount, protocol_fee);
+        
+        // Get balance after swap (for validation)
+        // Reload so a program-side swap CPI above is reflected in the balance
+        delivery_account.reload()?;
+        let output_balance_after = delivery_account.amount;
+        
+        // Calculate actual output (security: prevent underflow)
+        // Nothing arriving is `InsufficientOutput`; too little arriving is
+        // `SlippageExceeded` from the slippage check below
+        received_output(output_balance_before, output_balance_after)?
+    };
     
-    // Calculate actual output with safe math (security: prevent underflow)
-    let actual_output = output_balance_after
-        .checked_sub(output_balance_before)
-        .ok_or(ErrorCode::InsufficientOutput)?;
+    // What the recipient keeps: fees are charged on the input side and the
+    // balance increase already excludes any token transfer fee
+    let net_output_amount = actual_output;
     
     // ========================================================================
-    // STEP 8: Validate Slippage
+    // STEP 9: Validate Slippage
     // ========================================================================
     
     // Validate slippage with comprehensive checks (security: prevent slippage attacks)
-    validate_slippage(expected_output, actual_output, min_output_amount, MAX_SLIPPAGE_BPS)?;
+    validate_slippage(
+        expected_output,
+        actual_output,
+        min_output_amount,
+        MAX_SLIPPAGE_BPS,
+        slippage_basis,
+        slippage_failure_mode,
+    )?;
     
-    // Calculate slippage for event
+    // Calculate slippage for event (output basis, whatever basis was validated)
     let slippage_bps = utils::calculate_slippage(expected_output, actual_output)
         .unwrap_or(0);
     
     // ========================================================================
-    // STEP 9: Distribute Fees
+    // STEP 10: Get Context Data for Event
     // ========================================================================
     
-    // If fee recipient is provided, transfer fees
-    if fee_recipient_provided && protocol_fee > 0 {
-        // Transfer protocol fee to fee recipient
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.input_token_account.to_account_info(),
-                to: ctx.accounts.fee_recipient.to_account_info(),
-                authority: ctx.accounts.authority.to_account_info(),
-            },
-        );
+    let authority = ctx.accounts.authority.key();
+    let input_mint_key = *ctx.accounts.input_mint.key;
+    let output_mint_key = *ctx.accounts.output_mint.key;
+    
+    // ========================================================================
+    // STEP 11: Record Stats
+    // ========================================================================
+    
+    // A simulated swap moved nothing, so it is not counted
+    if !simulate {
+        ctx.accounts.stats.record(amount, protocol_fee)?;
         
-        token::transfer(transfer_ctx, protocol_fee)
-            .map_err(|_| ErrorCode::TransferFailed)?;
+        let pair_stats = &mut ctx.accounts.pair_stats;
+        if pair_stats.swap_count == 0 {
+            // First swap of this pair: the account was just created
+            pair_stats.input_mint = input_mint_key;
+            pair_stats.output_mint = output_mint_key;
+            pair_stats.bump = ctx.bumps.pair_stats;
+        }
+        pair_stats.record(amount, actual_output)?;
+        
+        if let Some(user_stats) = &mut ctx.accounts.user_stats {
+            user_stats.record(loyalty_volume(
+                &ctx.accounts.config.loyalty_mint,
+                &input_mint_key,
+                &output_mint_key,
+                amount,
+                actual_output,
+            ))?;
+        }
     }
     
     // ========================================================================
-    // STEP 10: Get Context Data for Event
+    // STEP 12: Check Price Jump
     // ========================================================================
     
-    let clock = Clock::get()?;
-    let authority = ctx.accounts.authority.key();
-    let input_mint_key = *ctx.accounts.input_mint.key;
-    let output_mint_key = *ctx.accounts.output_mint.key;
+    // A simulated swap's price is the quote's, so it is neither checked nor stored
+    if !simulate {
+        let price = LastPrice::execution_price(swap_amount, actual_output)?;
+        let max_price_jump_bps = ctx.accounts.config.max_price_jump_bps;
+        
+        let last_price = &mut ctx.accounts.last_price;
+        if last_price.timestamp == 0 {
+            // First swap of this pair: the account was just created
+            last_price.input_mint = input_mint_key;
+            last_price.output_mint = output_mint_key;
+            last_price.bump = ctx.bumps.last_price;
+        }
+        
+        if max_price_jump_bps > 0 {
+            if let Some(jump_bps) = last_price.jump_bps(price, clock.unix_timestamp) {
+                if jump_bps > max_price_jump_bps as u64 {
+                    msg!(
+                        "Price jump: {} bps from {} to {}",
+                        jump_bps,
+                        last_price.price,
+                        price
+                    );
+                    
+                    // Strict builds refuse the swap instead of flagging it
+                    if cfg!(feature = "strict_sanity") {
+                        return err!(ErrorCode::PriceJumpExceeded);
+                    }
+                    
+                    emit!(PriceJumpEvent {
+                        authority,
+                        input_mint: input_mint_key,
+                        output_mint: output_mint_key,
+                        previous_price: last_price.price,
+                        price,
+                        jump_bps,
+                        elapsed_secs: clock.unix_timestamp.saturating_sub(last_price.timestamp),
+                        timestamp: clock.unix_timestamp,
+                    });
+                }
+            }
+        }
+        
+        last_price.record(price, clock.unix_timestamp);
+    }
     
     // ========================================================================
-    // STEP 11: Emit Event
+    // STEP 13: Emit Event
     // ========================================================================
     
-    emit!(SwapExecutedEvent {
-        authority,
-        input_amount: amount,
-        output_amount: actual_output,
-        input_mint: input_mint_key,
-        output_mint: output_mint_key,
-        protocol_fee,
-        slippage_bps,
-        timestamp: clock.unix_timestamp,
-    });
+    // Skipped with `emit_events = false`; the stats above are recorded regardless
+    if emit_events {
+        emit!(SwapExecutedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            authority,
+            recipient: recipient.unwrap_or(authority),
+            input_amount: amount,
+            output_amount: actual_output,
+            net_output_amount,
+            input_mint: input_mint_key,
+            output_mint: output_mint_key,
+            protocol_fee,
+            treasury_fee,
+            burned_fee,
+            lp_fee,
+            fee_denomination,
+            fee_collected,
+            fee_discount_bps,
+            slippage_bps,
+            memo_hash,
+            tag,
+            simulated: simulate,
+            // A single swap, not a batch leg
+            batch: false,
+            leg_index: 0,
+            timestamp: clock.unix_timestamp,
+        });
+    }
     
     // ========================================================================
-    // STEP 12: Return Success
+    // STEP 14: Check Realized Fee Rate
+    // ========================================================================
+    
+    // Flags a drift from the expected fee rate without blocking the swap.
+    // Emitted regardless of `emit_events`: it is an alert, not swap data.
+    if !simulate {
+        let stats = &ctx.accounts.stats;
+        let expected_fee_rate_bps = ctx.accounts.config.expected_fee_rate_bps;
+        if let Some(realized_fee_rate_bps) =
+            stats.fee_rate_anomaly(expected_fee_rate_bps, FEE_ANOMALY_TOLERANCE_BPS)?
+        {
+            msg!(
+                "Fee anomaly: realized {} bps, expected {} bps",
+                realized_fee_rate_bps,
+                expected_fee_rate_bps
+            );
+            emit!(FeeAnomalyEvent {
+                authority,
+                realized_fee_rate_bps,
+                expected_fee_rate_bps,
+                total_volume: stats.total_volume,
+                total_fees: stats.total_fees,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+    }
+    
+    // ========================================================================
+    // STEP 15: Return Success
     // ========================================================================
     
     msg!(
@@ -294,5 +960,10 @@ pub fn handler(
         protocol_fee
     );
     
-    Ok(())
+    // Anchor sets this as return data for CPI callers
+    Ok(SwapReturnData {
+        output_amount: actual_output,
+        protocol_fee,
+        slippage_bps,
+    })
 }
@@ -0,0 +1,60 @@
+//! # Get Version Instruction Handler
+//!
+//! This module contains the handler for the `get_version` instruction, the
+//! client handshake: it returns the program version, the accepted
+//! instruction format versions and the build feature bits as return data
+//! ([`VersionReturnData`]).
+//!
+//! The instruction takes no accounts and changes no state. Clients run it
+//! with `simulateTransaction` (nothing is paid) and read the return data,
+//! then only use instruction formats and features the deployed program
+//! supports.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{
+    ENABLED_FEATURES, INSTRUCTION_VERSION, MIN_SUPPORTED_INSTRUCTION_VERSION, PROGRAM_VERSION,
+};
+use crate::state::{GetVersion, VersionReturnData};
+
+/// Handler for the get version instruction
+///
+/// # Arguments
+///
+/// * `_ctx` - Empty context (the instruction reads no accounts)
+///
+/// # Returns
+///
+/// * `Result<VersionReturnData>` - The program version and features (set
+///   as return data)
+pub fn handler(_ctx: Context<GetVersion>) -> Result<VersionReturnData> {
+    Ok(version())
+}
+
+/// The version and features of this build
+pub(crate) fn version() -> VersionReturnData {
+    VersionReturnData {
+        program_version: PROGRAM_VERSION.to_string(),
+        instruction_version: INSTRUCTION_VERSION,
+        min_supported_instruction_version: MIN_SUPPORTED_INSTRUCTION_VERSION,
+        features: ENABLED_FEATURES,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{FEATURE_CU_PROFILING, FEATURE_LARGE_AMOUNTS, FEATURE_NO_FEES, FEATURE_STRICT_SANITY};
+
+    #[test]
+    fn test_version_reports_build_features() {
+        let version = version();
+
+        assert_eq!(version.program_version, PROGRAM_VERSION);
+        assert!(version.min_supported_instruction_version <= version.instruction_version);
+        assert_eq!(version.features & FEATURE_LARGE_AMOUNTS != 0, cfg!(feature = "large-amounts"));
+        assert_eq!(version.features & FEATURE_NO_FEES != 0, cfg!(feature = "no-fees"));
+        assert_eq!(version.features & FEATURE_STRICT_SANITY != 0, cfg!(feature = "strict_sanity"));
+        assert_eq!(version.features & FEATURE_CU_PROFILING != 0, cfg!(feature = "cu_profiling"));
+    }
+}
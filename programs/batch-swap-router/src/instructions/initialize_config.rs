@@ -0,0 +1,85 @@
+//! # Initialize Config Instruction Handler
+//!
+//! This module contains the handler for the `initialize_config` instruction,
+//! which creates the program-wide [`Config`](crate::state::Config) and
+//! [`Stats`](crate::state::Stats) PDAs.
+//!
+//! The config must exist before `batch_swap` or `execute_swap` can be called,
+//! since both read it to look up fee exemptions. It can only be created once;
+//! a second call fails because the PDA is already initialized.
+//!
+//! ## Initial State
+//!
+//! - `admin` is the signer of this instruction
+//! - `fee_exemptions` is empty (every authority pays protocol fees)
+//! - `require_authority_is_payer` is off (relayer-paid transactions allowed)
+//! - `version` is `CONFIG_VERSION` (no migration needed)
+//! - `fee_bps` is `PROTOCOL_FEE_BPS` and `fee_recipient` is unset (any owner)
+//! - `pending_admin` is unset (no admin transfer in progress)
+//! - `callback_programs` is empty (no per-leg callbacks allowed)
+//! - `burn_bps` is 0 (the whole protocol fee goes to the treasury)
+//! - `lp_fee_bps` is 0 and `lp_fee_recipient` is unset (no LP fee share)
+//! - `require_expected_output` is off (unquoted swaps allowed, with a warning)
+//! - `max_distinct_mints` is 0 (no cap on the mints of a batch)
+//! - `expected_fee_rate_bps` is 0 (no fee rate monitoring)
+//! - `max_price_jump_bps` is 0 (no price jump check)
+//! - `strict_ata` is off (any token account of the right owner and mint)
+//! - `treasury_is_token_account` is on (fees go to token accounts)
+//! - `min_output_floor` is 0 (the `MIN_OUTPUT_AMOUNT` default)
+//! - `paused` is off (swaps allowed)
+//! - `loyalty_mint` is unset (no loyalty volume accrues)
+//! - All `Stats` counters are zero
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_VERSION, PROTOCOL_FEE_BPS};
+use crate::state::InitializeConfig;
+
+/// Handler for the initialize config instruction
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing the admin and the config account to create
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+///
+/// # Errors
+///
+/// Fails (via Anchor's `init` constraint) if the config or stats already exist.
+pub fn handler(ctx: Context<InitializeConfig>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    
+    config.admin = ctx.accounts.admin.key();
+    config.fee_exemptions = Vec::new();
+    config.require_authority_is_payer = false;
+    config.bump = ctx.bumps.config;
+    config.version = CONFIG_VERSION;
+    config.fee_bps = PROTOCOL_FEE_BPS as u16;
+    config.fee_recipient = Pubkey::default();
+    config.pending_admin = Pubkey::default();
+    config.callback_programs = Vec::new();
+    config.burn_bps = 0;
+    config.lp_fee_bps = 0;
+    config.lp_fee_recipient = Pubkey::default();
+    config.require_expected_output = false;
+    config.max_distinct_mints = 0;
+    config.expected_fee_rate_bps = 0;
+    config.max_price_jump_bps = 0;
+    config.strict_ata = false;
+    config.treasury_is_token_account = true;
+    config.min_output_floor = 0;
+    config.paused = false;
+    config.loyalty_mint = Pubkey::default();
+    
+    let stats = &mut ctx.accounts.stats;
+    stats.total_swaps = 0;
+    stats.total_volume = 0;
+    stats.total_fees = 0;
+    stats.bump = ctx.bumps.stats;
+    
+    msg!("Config initialized with admin {}", config.admin);
+    
+    Ok(())
+}
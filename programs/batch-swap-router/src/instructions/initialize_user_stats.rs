@@ -0,0 +1,35 @@
+//! # Initialize User Stats Instruction Handler
+//!
+//! This module contains the handler for the `initialize_user_stats`
+//! instruction, which creates the signer's
+//! [`UserStats`](crate::state::UserStats) account.
+//!
+//! `execute_swap` records the authority's volume in it whenever it is passed,
+//! and that volume selects the authority's loyalty discount on the protocol
+//! fee. Volume starts at 0: only swaps recorded after creation count.
+
+use anchor_lang::prelude::*;
+
+use crate::state::InitializeUserStats;
+
+/// Handler for the initialize user stats instruction
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing the authority and the user stats PDA
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+pub fn handler(ctx: Context<InitializeUserStats>) -> Result<()> {
+    let user_stats = &mut ctx.accounts.user_stats;
+    
+    user_stats.authority = ctx.accounts.authority.key();
+    user_stats.swap_count = 0;
+    user_stats.total_volume = 0;
+    user_stats.bump = ctx.bumps.user_stats;
+    
+    msg!("User stats initialized for {}", user_stats.authority);
+    
+    Ok(())
+}
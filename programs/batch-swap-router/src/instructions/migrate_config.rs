@@ -0,0 +1,231 @@
+//! # Migrate Config Instruction Handler
+//!
+//! This module contains the handler for the `migrate_config` admin
+//! instruction, which upgrades the [`Config`](crate::state::Config) account
+//! to the current layout after a program upgrade.
+//!
+//! ## Migrations
+//!
+//! | From | To | Changes                                                      |
+//! |------|----|--------------------------------------------------------------|
+//! | 0    | 1  | Adds `version` and every setting after it, with defaults    |
+//!
+//! Layout version 0 is the original `Config` ([`ConfigV0`]), which ends at
+//! `bump`. Version 1 appends the `version` byte and the remaining settings:
+//!
+//! - `fee_bps` is `PROTOCOL_FEE_BPS` and `treasury_is_token_account` is on,
+//!   matching how version 0 charged fees
+//! - Every other setting is off, empty, zero or unset
+//!
+//! ## Process
+//!
+//! 1. Read the old layout and check its version and admin
+//! 2. Grow the account to the current `Config` size, with the admin paying
+//!    any extra rent
+//! 3. Rewrite the account on the current layout with defaults for new fields
+//!    and `version = CONFIG_VERSION`
+//!
+//! Running the migration twice fails with `ConfigVersionMismatch`, so it is
+//! safe to retry.
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+use crate::constants::{CONFIG_VERSION, PROTOCOL_FEE_BPS};
+use crate::errors::ErrorCode;
+use crate::state::{Config, ConfigV0, MigrateConfig};
+
+/// Handler for the migrate config instruction
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing the admin and the config account
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+///
+/// # Errors
+///
+/// * `ErrorCode::InvalidAccount` - Account is not a `Config`
+/// * `ErrorCode::ConfigVersionMismatch` - Config is already on `CONFIG_VERSION`
+/// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+pub fn handler(ctx: Context<MigrateConfig>) -> Result<()> {
+    let config_info = ctx.accounts.config.to_account_info();
+    
+    // Read the old layout
+    let config = migrated_config(&config_info.try_borrow_data()?)?;
+    
+    // Only the admin recorded in the config may migrate it
+    require_keys_eq!(
+        config.admin,
+        ctx.accounts.admin.key(),
+        ErrorCode::InvalidAuthority
+    );
+    
+    // Grow the account to the current layout, topping up rent from the admin
+    let space = 8 + Config::INIT_SPACE;
+    if config_info.data_len() < space {
+        let rent = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(config_info.lamports());
+        if rent > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: config_info.clone(),
+                    },
+                ),
+                rent,
+            )?;
+        }
+        config_info.resize(space)?;
+    }
+    
+    // Write the current layout over the old one
+    let mut data = config_info.try_borrow_mut_data()?;
+    config.try_serialize(&mut &mut data[..])?;
+    
+    msg!("Config migrated to version {}", config.version);
+    
+    Ok(())
+}
+
+/// Upgrade raw config account data from an older layout
+///
+/// Version 0 accounts have no `version` byte: whatever follows the old
+/// fields is zero padding (the fee exemption list is allocated at its
+/// maximum length), or nothing at all when the list is full, so it reads
+/// as 0. Later versions store their version in that byte.
+///
+/// # Arguments
+///
+/// * `data` - Config account data, including the discriminator
+///
+/// # Returns
+///
+/// * `Result<Config>` - The config on the current layout
+///
+/// # Errors
+///
+/// * `ErrorCode::InvalidAccount` - Data does not start with the `Config` discriminator
+/// * `ErrorCode::ConfigVersionMismatch` - Data is already on `CONFIG_VERSION`
+pub(crate) fn migrated_config(data: &[u8]) -> Result<Config> {
+    let mut body = data
+        .strip_prefix(Config::DISCRIMINATOR)
+        .ok_or(ErrorCode::InvalidAccount)?;
+    
+    let old = ConfigV0::deserialize(&mut body)?;
+    let version = body.first().copied().unwrap_or(0);
+    
+    // Only upgrade from the layouts this migration understands
+    require!(
+        version < CONFIG_VERSION,
+        ErrorCode::ConfigVersionMismatch
+    );
+    
+    Ok(Config {
+        admin: old.admin,
+        fee_exemptions: old.fee_exemptions,
+        require_authority_is_payer: old.require_authority_is_payer,
+        bump: old.bump,
+        version: CONFIG_VERSION,
+        fee_bps: PROTOCOL_FEE_BPS as u16,
+        fee_recipient: Pubkey::default(),
+        pending_admin: Pubkey::default(),
+        callback_programs: Vec::new(),
+        burn_bps: 0,
+        lp_fee_bps: 0,
+        lp_fee_recipient: Pubkey::default(),
+        require_expected_output: false,
+        max_distinct_mints: 0,
+        expected_fee_rate_bps: 0,
+        max_price_jump_bps: 0,
+        strict_ata: false,
+        // Fees went to token accounts before the flag existed
+        treasury_is_token_account: true,
+        min_output_floor: 0,
+        paused: false,
+        loyalty_mint: Pubkey::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{MAX_FEE_EXEMPTIONS, MIN_OUTPUT_AMOUNT};
+
+    fn v0_data(fee_exemptions: Vec<Pubkey>, padding: usize) -> Vec<u8> {
+        let old = ConfigV0 {
+            admin: Pubkey::new_unique(),
+            fee_exemptions,
+            require_authority_is_payer: true,
+            bump: 254,
+        };
+        let mut data = Config::DISCRIMINATOR.to_vec();
+        data.extend(old.try_to_vec().unwrap());
+        data.resize(data.len() + padding, 0);
+        data
+    }
+
+    #[test]
+    fn test_migrates_legacy_layouts() {
+        let one = vec![Pubkey::new_unique()];
+        let full = vec![Pubkey::new_unique(); MAX_FEE_EXEMPTIONS];
+        // (fee exemptions, zero padding after `bump`)
+        let layouts = [
+            (Vec::new(), 0),
+            (Vec::new(), MAX_FEE_EXEMPTIONS * 32),
+            (one.clone(), 0),
+            (one, (MAX_FEE_EXEMPTIONS - 1) * 32),
+            (full, 0),
+        ];
+
+        for (fee_exemptions, padding) in layouts {
+            let data = v0_data(fee_exemptions.clone(), padding);
+            let config = migrated_config(&data).unwrap();
+
+            assert_eq!(config.version, CONFIG_VERSION);
+            assert_eq!(config.fee_exemptions, fee_exemptions);
+            assert!(config.require_authority_is_payer);
+            assert_eq!(config.bump, 254);
+            assert_eq!(u64::from(config.fee_bps), PROTOCOL_FEE_BPS);
+            assert_eq!(config.fee_recipient, Pubkey::default());
+            assert!(config.callback_programs.is_empty());
+            assert!(config.treasury_is_token_account);
+            assert_eq!(config.effective_min_output(), MIN_OUTPUT_AMOUNT);
+            assert!(!config.paused);
+            assert_eq!(config.loyalty_mint, Pubkey::default());
+
+            // The current layout fits the resized account
+            let mut out = vec![0u8; 8 + Config::INIT_SPACE];
+            config.try_serialize(&mut &mut out[..]).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rejects_already_migrated_config() {
+        let config = migrated_config(&v0_data(Vec::new(), 1)).unwrap();
+        let mut data = vec![0u8; 8 + Config::INIT_SPACE];
+        config.try_serialize(&mut &mut data[..]).unwrap();
+
+        assert_eq!(
+            migrated_config(&data).err(),
+            Some(ErrorCode::ConfigVersionMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_accounts() {
+        let mut data = v0_data(Vec::new(), 1);
+        data[0] ^= 0xff;
+
+        assert_eq!(
+            migrated_config(&data).err(),
+            Some(ErrorCode::InvalidAccount.into())
+        );
+    }
+}
@@ -9,7 +9,17 @@
 //! Each instruction handler is in its own module:
 //!
 //! - [`batch_swap`] - Batch swap instruction handler
+//! - [`batch_ops`] - Mixed swap/transfer batch instruction handler
+//! - `batch_swap_large` - `u128` batch swap instruction handler (`large-amounts` feature)
 //! - [`execute_swap`] - Single swap instruction handler
+//! - [`initialize_config`] - Config account creation handler
+//! - [`initialize_user_stats`] - Per-authority stats account creation handler
+//! - [`set_fee_exemption`] - Fee exemption admin handler
+//! - [`update_config`] - Config policy admin handler
+//! - [`migrate_config`] - Config layout migration handler
+//! - [`admin_transfer`] - Two-step admin transfer handlers
+//! - [`set_callback_program`] - Callback allowlist admin handler
+//! - [`get_version`] - Program version handshake handler
 //!
 //! ## Handler Pattern
 //!
@@ -34,6 +44,7 @@
 //!
 //! - [`BatchSwapEvent`] - Emitted by `batch_swap` handler
 //! - [`SwapExecutedEvent`] - Emitted by `execute_swap` handler
+//! - [`TransferOpEvent`] - Emitted by `batch_ops` handler for each transfer op
 //!
 //! ## Usage
 //!
@@ -44,10 +55,34 @@
 //! [`ErrorCode`]: crate::errors::ErrorCode
 //! [`BatchSwapEvent`]: crate::events::BatchSwapEvent
 //! [`SwapExecutedEvent`]: crate::events::SwapExecutedEvent
+//! [`TransferOpEvent`]: crate::events::TransferOpEvent
 
+pub mod admin_transfer;
+pub mod batch_ops;
 pub mod batch_swap;
+#[cfg(feature = "large-amounts")]
+pub mod batch_swap_large;
 pub mod execute_swap;
+pub mod get_version;
+pub mod initialize_config;
+pub mod initialize_user_stats;
+pub mod migrate_config;
+pub mod set_callback_program;
+pub mod set_fee_exemption;
+pub mod update_config;
 
 // Re-export handlers for convenience
+pub use admin_transfer::accept_handler as accept_admin_handler;
+pub use admin_transfer::propose_handler as propose_admin_handler;
+pub use batch_ops::handler as batch_ops_handler;
 pub use batch_swap::handler as batch_swap_handler;
+#[cfg(feature = "large-amounts")]
+pub use batch_swap_large::handler as batch_swap_large_handler;
 pub use execute_swap::handler as execute_swap_handler;
+pub use get_version::handler as get_version_handler;
+pub use initialize_config::handler as initialize_config_handler;
+pub use initialize_user_stats::handler as initialize_user_stats_handler;
+pub use migrate_config::handler as migrate_config_handler;
+pub use set_callback_program::handler as set_callback_program_handler;
+pub use set_fee_exemption::handler as set_fee_exemption_handler;
+pub use update_config::handler as update_config_handler;
@@ -0,0 +1,56 @@
+//! # Set Callback Program Instruction Handler
+//!
+//! This module contains the handler for the `set_callback_program` admin
+//! instruction, which adds a program to or removes it from the per-leg
+//! callback allowlist in [`Config`](crate::state::Config).
+//!
+//! `batch_swap` only CPIs into allowlisted programs (see
+//! [`callback`](crate::callback)), so the admin decides which integrator
+//! code can run inside a batch.
+//!
+//! ## Idempotency
+//!
+//! Adding a program that is already allowed, or removing one that is not,
+//! is a no-op rather than an error, so the instruction can be safely retried.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_CALLBACK_PROGRAMS;
+use crate::errors::ErrorCode;
+use crate::state::SetCallbackProgram;
+
+/// Handler for the set callback program instruction
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing the admin and the config account
+/// * `program_id` - The callback program to update
+/// * `allowed` - `true` to allow the program, `false` to remove it
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+///
+/// # Errors
+///
+/// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+/// * `ErrorCode::CallbackProgramListFull` - List already holds MAX_CALLBACK_PROGRAMS programs
+pub fn handler(ctx: Context<SetCallbackProgram>, program_id: Pubkey, allowed: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    
+    if allowed {
+        if !config.is_callback_allowed(&program_id) {
+            require!(
+                config.callback_programs.len() < MAX_CALLBACK_PROGRAMS,
+                ErrorCode::CallbackProgramListFull
+            );
+            config.callback_programs.push(program_id);
+        }
+    } else {
+        config.callback_programs.retain(|key| key != &program_id);
+    }
+    
+    msg!("Callback program {} allowed: {}", program_id, allowed);
+    
+    Ok(())
+}
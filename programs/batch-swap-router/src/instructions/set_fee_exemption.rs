@@ -0,0 +1,55 @@
+//! # Set Fee Exemption Instruction Handler
+//!
+//! This module contains the handler for the `set_fee_exemption` admin
+//! instruction, which adds an authority to or removes it from the fee
+//! exemption list in [`Config`](crate::state::Config).
+//!
+//! Exempt authorities (DAOs, internal accounts) swap fee-free: the protocol
+//! fee is 0, no fee transfer is made, and events report `protocol_fee = 0`.
+//!
+//! ## Idempotency
+//!
+//! Adding an authority that is already exempt, or removing one that is not,
+//! is a no-op rather than an error, so the instruction can be safely retried.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_FEE_EXEMPTIONS;
+use crate::errors::ErrorCode;
+use crate::state::SetFeeExemption;
+
+/// Handler for the set fee exemption instruction
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing the admin and the config account
+/// * `authority` - The swap authority to update
+/// * `exempt` - `true` to exempt the authority from fees, `false` to remove the exemption
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+///
+/// # Errors
+///
+/// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+/// * `ErrorCode::FeeExemptionListFull` - List already holds MAX_FEE_EXEMPTIONS authorities
+pub fn handler(ctx: Context<SetFeeExemption>, authority: Pubkey, exempt: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    
+    if exempt {
+        if !config.is_fee_exempt(&authority) {
+            require!(
+                config.fee_exemptions.len() < MAX_FEE_EXEMPTIONS,
+                ErrorCode::FeeExemptionListFull
+            );
+            config.fee_exemptions.push(authority);
+        }
+    } else {
+        config.fee_exemptions.retain(|key| key != &authority);
+    }
+    
+    msg!("Fee exemption for {} set to {}", authority, exempt);
+    
+    Ok(())
+}
@@ -0,0 +1,225 @@
+//! # Update Config Instruction Handler
+//!
+//! This module contains the handler for the `update_config` admin
+//! instruction, which changes program-wide policy and fee settings in
+//! [`Config`](crate::state::Config).
+//!
+//! ## Settings
+//!
+//! - `require_authority_is_payer` - Reject swap transactions signed by anyone
+//!   other than the swap authority (see "Authority and Payer" on `Config`)
+//! - `require_expected_output` - Reject `execute_swap` calls without a quote
+//!   (see "Swaps Without a Quote" on `Config`)
+//! - `max_distinct_mints` - Cap the distinct mints of a batch (see "Distinct
+//!   Mints per Batch" on `Config`)
+//! - `expected_fee_rate_bps` - Realized fee rate to monitor swaps against,
+//!   at most `MAX_PROTOCOL_FEE_BPS` (see "Fee Monitoring" on `Config`)
+//! - `max_price_jump_bps` - Largest price move of a pair within
+//!   `PRICE_JUMP_WINDOW_SECS` before it is flagged (see "Price Jumps" on
+//!   `Config`)
+//! - `strict_ata` - Require `execute_swap` token accounts to be canonical
+//!   associated token accounts (see "Canonical Token Accounts" on `Config`)
+//! - `treasury_is_token_account` - Whether fees go to token accounts or, as
+//!   lamports, to a SOL wallet (see "Treasury Kind" on `Config`)
+//! - `min_output_floor` - Smallest `min_output_amount` a swap may ask for
+//!   (see "Output Floor" on `Config`)
+//! - `paused` - Halt or resume every swap instruction (see "Pause" on
+//!   `Config`)
+//! - `loyalty_mint` - Mint loyalty discount volume is counted in (see
+//!   "Loyalty Volume" on `Config`)
+//! - `fee_bps` - Protocol fee rate, at most `MAX_PROTOCOL_FEE_BPS`
+//! - `fee_recipient` - Owner that fee recipient token accounts must belong to
+//! - `burn_bps` - Share of each protocol fee burned, at most `MAX_BURN_BPS`
+//! - `lp_fee_bps` - Part of the fee rate paid to liquidity providers, at most
+//!   `fee_bps` (checked against the rate after the update)
+//! - `lp_fee_recipient` - Owner that LP fee token accounts must belong to
+//!
+//! ## Events
+//!
+//! A [`ConfigUpdatedEvent`] is emitted when `fee_bps`, `fee_recipient`,
+//! `burn_bps`, `lp_fee_bps` or `lp_fee_recipient` changes, carrying both the
+//! old and new values.
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{MAX_BURN_BPS, MAX_PROTOCOL_FEE_BPS};
+use crate::errors::ErrorCode;
+use crate::events::ConfigUpdatedEvent;
+use crate::state::UpdateConfig;
+
+/// Handler for the update config instruction
+///
+/// # Arguments
+///
+/// * `ctx` - Context containing the admin and the config account
+/// * `require_authority_is_payer` - New authority/payer policy (`None` keeps
+///   the current one)
+/// * `fee_bps` - New protocol fee rate (`None` keeps the current rate)
+/// * `fee_recipient` - New fee recipient owner (`None` keeps the current one)
+/// * `burn_bps` - New burned share of the fee (`None` keeps the current share)
+/// * `lp_fee_bps` - New LP part of the fee rate (`None` keeps the current part)
+/// * `lp_fee_recipient` - New LP fee recipient owner (`None` keeps the current one)
+/// * `require_expected_output` - New quote policy (`None` keeps the current one)
+/// * `max_distinct_mints` - New distinct mint cap, 0 for none (`None` keeps
+///   the current one)
+/// * `expected_fee_rate_bps` - New expected realized fee rate, 0 to stop
+///   monitoring (`None` keeps the current one)
+/// * `max_price_jump_bps` - New price jump limit, 0 for no check (`None`
+///   keeps the current one)
+/// * `strict_ata` - New token account policy (`None` keeps the current one)
+/// * `treasury_is_token_account` - New treasury kind (`None` keeps the
+///   current one)
+/// * `min_output_floor` - New output floor, 0 for `MIN_OUTPUT_AMOUNT`
+///   (`None` keeps the current one)
+/// * `paused` - New pause state (`None` keeps the current one)
+/// * `loyalty_mint` - New loyalty mint, `Pubkey::default()` to count no
+///   volume (`None` keeps the current one)
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns `Ok(())` on success, or an error on failure
+///
+/// # Errors
+///
+/// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+/// * `ErrorCode::InvalidFeeBps` - `fee_bps` or `expected_fee_rate_bps`
+///   exceeds `MAX_PROTOCOL_FEE_BPS`
+/// * `ErrorCode::InvalidBurnBps` - `burn_bps` exceeds `MAX_BURN_BPS`
+/// * `ErrorCode::InvalidLpFeeBps` - The LP part would exceed the fee rate
+#[allow(clippy::too_many_arguments)]
+pub fn handler(
+    ctx: Context<UpdateConfig>,
+    require_authority_is_payer: Option<bool>,
+    fee_bps: Option<u16>,
+    fee_recipient: Option<Pubkey>,
+    burn_bps: Option<u16>,
+    lp_fee_bps: Option<u16>,
+    lp_fee_recipient: Option<Pubkey>,
+    require_expected_output: Option<bool>,
+    max_distinct_mints: Option<u8>,
+    expected_fee_rate_bps: Option<u16>,
+    max_price_jump_bps: Option<u16>,
+    strict_ata: Option<bool>,
+    treasury_is_token_account: Option<bool>,
+    min_output_floor: Option<u64>,
+    paused: Option<bool>,
+    loyalty_mint: Option<Pubkey>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    
+    if let Some(fee_bps) = fee_bps {
+        require!(fee_bps <= MAX_PROTOCOL_FEE_BPS, ErrorCode::InvalidFeeBps);
+    }
+    if let Some(expected_fee_rate_bps) = expected_fee_rate_bps {
+        require!(expected_fee_rate_bps <= MAX_PROTOCOL_FEE_BPS, ErrorCode::InvalidFeeBps);
+    }
+    if let Some(burn_bps) = burn_bps {
+        require!(burn_bps <= MAX_BURN_BPS, ErrorCode::InvalidBurnBps);
+    }
+    
+    let old_fee_bps = config.fee_bps;
+    let old_recipient = config.fee_recipient;
+    let old_burn_bps = config.burn_bps;
+    let old_lp_fee_bps = config.lp_fee_bps;
+    let old_lp_recipient = config.lp_fee_recipient;
+    
+    config.fee_bps = fee_bps.unwrap_or(old_fee_bps);
+    config.fee_recipient = fee_recipient.unwrap_or(old_recipient);
+    config.burn_bps = burn_bps.unwrap_or(old_burn_bps);
+    config.lp_fee_bps = lp_fee_bps.unwrap_or(old_lp_fee_bps);
+    config.lp_fee_recipient = lp_fee_recipient.unwrap_or(old_lp_recipient);
+    
+    // The LP part is carved out of the fee rate, so it can't exceed it
+    require!(
+        config.lp_fee_bps <= config.fee_bps,
+        ErrorCode::InvalidLpFeeBps
+    );
+    
+    if let Some(require_authority_is_payer) = require_authority_is_payer {
+        config.require_authority_is_payer = require_authority_is_payer;
+        msg!("require_authority_is_payer set to {}", require_authority_is_payer);
+    }
+    
+    if let Some(require_expected_output) = require_expected_output {
+        config.require_expected_output = require_expected_output;
+        msg!("require_expected_output set to {}", require_expected_output);
+    }
+    
+    if let Some(max_distinct_mints) = max_distinct_mints {
+        config.max_distinct_mints = max_distinct_mints;
+        msg!("max_distinct_mints set to {}", max_distinct_mints);
+    }
+    
+    if let Some(expected_fee_rate_bps) = expected_fee_rate_bps {
+        config.expected_fee_rate_bps = expected_fee_rate_bps;
+        msg!("expected_fee_rate_bps set to {}", expected_fee_rate_bps);
+    }
+    
+    if let Some(max_price_jump_bps) = max_price_jump_bps {
+        config.max_price_jump_bps = max_price_jump_bps;
+        msg!("max_price_jump_bps set to {}", max_price_jump_bps);
+    }
+    
+    if let Some(strict_ata) = strict_ata {
+        config.strict_ata = strict_ata;
+        msg!("strict_ata set to {}", strict_ata);
+    }
+    
+    if let Some(treasury_is_token_account) = treasury_is_token_account {
+        config.treasury_is_token_account = treasury_is_token_account;
+        msg!("treasury_is_token_account set to {}", treasury_is_token_account);
+    }
+    
+    if let Some(min_output_floor) = min_output_floor {
+        config.min_output_floor = min_output_floor;
+        msg!("min_output_floor set to {}", min_output_floor);
+    }
+    
+    if let Some(paused) = paused {
+        config.paused = paused;
+        msg!("paused set to {}", paused);
+    }
+    
+    if let Some(loyalty_mint) = loyalty_mint {
+        config.loyalty_mint = loyalty_mint;
+        msg!("loyalty_mint set to {}", loyalty_mint);
+    }
+    
+    // Announce fee changes so indexers and users can react
+    if config.fee_bps != old_fee_bps
+        || config.fee_recipient != old_recipient
+        || config.burn_bps != old_burn_bps
+        || config.lp_fee_bps != old_lp_fee_bps
+        || config.lp_fee_recipient != old_lp_recipient
+    {
+        msg!(
+            "Protocol fee updated: {} -> {} bps, recipient {} -> {}, burn {} -> {} bps, LP {} -> {} bps",
+            old_fee_bps,
+            config.fee_bps,
+            old_recipient,
+            config.fee_recipient,
+            old_burn_bps,
+            config.burn_bps,
+            old_lp_fee_bps,
+            config.lp_fee_bps
+        );
+        msg!("LP fee recipient: {} -> {}", old_lp_recipient, config.lp_fee_recipient);
+        
+        emit!(ConfigUpdatedEvent {
+            admin: ctx.accounts.admin.key(),
+            old_fee_bps,
+            new_fee_bps: config.fee_bps,
+            old_recipient,
+            new_recipient: config.fee_recipient,
+            old_burn_bps,
+            new_burn_bps: config.burn_bps,
+            old_lp_fee_bps,
+            new_lp_fee_bps: config.lp_fee_bps,
+            old_lp_recipient,
+            new_lp_recipient: config.lp_fee_recipient,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+    
+    Ok(())
+}
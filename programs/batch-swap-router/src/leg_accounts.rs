@@ -0,0 +1,503 @@
+//! # Leg Output Accounts
+//!
+//! This module contains the opt-in per-leg output accounts of `batch_swap`,
+//! and its opt-in shared input account. `BatchSwap` itself holds no token
+//! accounts, so without them the program can only validate and record legs.
+//! Passing each leg's output token account lets the handler check it against
+//! the leg and measure what it received, which program-side execution needs
+//! for its slippage checks.
+//!
+//! ## Account Layout
+//!
+//! With `leg_output_accounts = true`, the remaining accounts of `batch_swap`
+//! are:
+//!
+//! ```text
+//! [0 .. n)    output token account of leg i, in leg order (writable)
+//! [n]         callback program            (only with `callback_program`)
+//! [n + 1 ..]  accounts forwarded to the callback
+//! ```
+//!
+//! where `n` is the number of legs. Each output account must be an SPL
+//! Token account of its leg's `output_mint`. Without a callback there must
+//! be exactly `n` remaining accounts; any mismatch fails the batch with
+//! `ErrorCode::RouteAccountsMismatch` (the leg index is logged). With
+//! `leg_output_accounts = false` the layout is unchanged and the callback
+//! program comes first.
+//!
+//! With `shared_input_account = true`, one more account comes first, ahead
+//! of the output accounts: the input token account every leg draws from
+//! (see below).
+//!
+//! ## Shared Input Account
+//!
+//! The one-to-many rebalance swaps one token into several others, so every
+//! leg drains the same input account. Instead of letting a late leg fail
+//! mid-batch once the account runs dry, the handler checks up front, before
+//! any leg executes, that the account covers the sum of the legs' amounts,
+//! and fails the batch with `ErrorCode::InsufficientFunds` otherwise. Each
+//! leg's protocol fee is taken out of its `amount`, so the summed amounts
+//! already include the fees. The account must be a writable SPL Token
+//! account owned by the authority and of every leg's `input_mint`.
+//!
+//! ## Balance Deltas
+//!
+//! The handler reads every output balance before the execute phase and
+//! again after it, and logs each leg's delta. With client-side execution
+//! the route instructions run before `batch_swap`, so the deltas are 0
+//! today; a program-side swap CPI in the execute phase will show up in them.
+//!
+//! ## Batch Slippage Limit
+//!
+//! Whenever leg output accounts are passed, every leg's delta is checked
+//! against its slippage limit, and a leg that slipped further reverts the
+//! whole batch with `ErrorCode::BatchSlippageExceeded`. A leg's limit is its
+//! own `effective_max_slippage_bps`; a non-zero `global_max_slippage_bps` is a
+//! portfolio-level circuit breaker that tightens it to
+//! `min(global_max_slippage_bps, effective_max_slippage_bps)`, even if the
+//! leg met its own `min_output_amount`. 0 leaves each leg at its own limit.
+//! Legs carry no quote, so the quote is the one implied by the minimum and
+//! the leg's tolerance:
+//! `min_output_amount * 10_000 / (10_000 - effective_max_slippage_bps)`.
+//! A leg that received nothing slipped 100%, whatever the reason.
+//!
+//! Only output the execute phase delivers is measured. Routes still run
+//! client-side, before `batch_swap`, so every delta is 0 today and passing
+//! leg output accounts reverts every batch. Leave them off until the program
+//! executes the swaps itself.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::errors::ErrorCode;
+use crate::security::SafeMath;
+use crate::state::SwapParams;
+use crate::utils::calculate_slippage;
+
+/// The validated output token accounts of a batch, in leg order
+pub struct LegOutputAccounts<'a, 'info> {
+    /// One output token account per leg
+    accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'a, 'info> LegOutputAccounts<'a, 'info> {
+    /// Validate the leg output accounts and split them off the remaining accounts
+    ///
+    /// # Arguments
+    ///
+    /// * `swaps` - The batch's legs
+    /// * `remaining_accounts` - The instruction's remaining accounts; the
+    ///   first `swaps.len()` are the output accounts
+    /// * `has_callback` - Whether the callback program and its accounts follow
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(Self, &[AccountInfo])>` - The output accounts and the
+    ///   remaining accounts after them
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::RouteAccountsMismatch` if an output account is
+    /// missing, not a writable SPL Token account or of another mint than its
+    /// leg's `output_mint`, or if accounts are left over without a callback.
+    pub fn resolve(
+        swaps: &[SwapParams],
+        remaining_accounts: &'a [AccountInfo<'info>],
+        has_callback: bool,
+    ) -> Result<(Self, &'a [AccountInfo<'info>])> {
+        require!(
+            remaining_accounts.len() >= swaps.len(),
+            ErrorCode::RouteAccountsMismatch
+        );
+        let (accounts, rest) = remaining_accounts.split_at(swaps.len());
+        require!(has_callback || rest.is_empty(), ErrorCode::RouteAccountsMismatch);
+
+        for (index, (swap, account)) in swaps.iter().zip(accounts).enumerate() {
+            if let Err(error) = assert_leg_output_account(account, &swap.output_mint) {
+                msg!("Leg {} output account {} does not match the leg", index, account.key);
+                return Err(error);
+            }
+        }
+
+        Ok((Self { accounts }, rest))
+    }
+
+    /// Current balance of every output account, in leg order
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::RouteAccountsMismatch` if an account no longer
+    /// decodes as a token account.
+    pub fn balances(&self) -> Result<Vec<u64>> {
+        self.accounts.iter().map(token_balance).collect()
+    }
+}
+
+/// Validate the shared input account and split it off the remaining accounts
+///
+/// See "Shared Input Account" in the module docs.
+///
+/// # Arguments
+///
+/// * `swaps` - The batch's legs
+/// * `remaining_accounts` - The instruction's remaining accounts; the first
+///   is the shared input account
+/// * `authority` - The batch authority
+///
+/// # Returns
+///
+/// * `Result<(&AccountInfo, &[AccountInfo])>` - The input account and the
+///   remaining accounts after it
+///
+/// # Errors
+///
+/// * `ErrorCode::RouteAccountsMismatch` - The account is missing, or not a
+///   writable SPL Token account
+/// * `ErrorCode::InvalidAuthority` - The authority doesn't own the account
+/// * `ErrorCode::InvalidAccount` - A leg's `input_mint` is not the account's
+///   mint (the leg index is logged)
+/// * `ErrorCode::InsufficientFunds` - The account holds less than the legs'
+///   summed amounts
+/// * `ErrorCode::MathOverflow` - The summed amounts overflow
+pub fn resolve_shared_input_account<'a, 'info>(
+    swaps: &[SwapParams],
+    remaining_accounts: &'a [AccountInfo<'info>],
+    authority: &Pubkey,
+) -> Result<(&'a AccountInfo<'info>, &'a [AccountInfo<'info>])> {
+    let (account, rest) = remaining_accounts
+        .split_first()
+        .ok_or(ErrorCode::RouteAccountsMismatch)?;
+    require!(
+        account.is_writable && account.owner == &anchor_spl::token::ID,
+        ErrorCode::RouteAccountsMismatch
+    );
+    let token_account = TokenAccount::try_deserialize(&mut &account.data.borrow()[..])
+        .map_err(|_| ErrorCode::RouteAccountsMismatch)?;
+    require!(token_account.owner == *authority, ErrorCode::InvalidAuthority);
+    
+    for (index, swap) in swaps.iter().enumerate() {
+        if swap.input_mint != token_account.mint {
+            msg!("Leg {} does not draw from the shared input account's mint", index);
+            return err!(ErrorCode::InvalidAccount);
+        }
+    }
+    assert_shared_input_covers(swaps, token_account.amount)?;
+    
+    Ok((account, rest))
+}
+
+/// Check that a shared input balance covers every leg of the batch
+///
+/// # Arguments
+///
+/// * `swaps` - The batch's legs, all drawing from the account
+/// * `balance` - The shared input account's balance
+///
+/// # Errors
+///
+/// * `ErrorCode::InsufficientFunds` - `balance` is below the legs' summed
+///   amounts (fees included)
+/// * `ErrorCode::MathOverflow` - The summed amounts overflow
+pub fn assert_shared_input_covers(swaps: &[SwapParams], balance: u64) -> Result<()> {
+    let required = swaps
+        .iter()
+        .try_fold(0u64, |total, swap| total.safe_add(swap.amount))?;
+    if balance < required {
+        msg!("Shared input account holds {} but the legs need {}", balance, required);
+        return err!(ErrorCode::InsufficientFunds);
+    }
+    Ok(())
+}
+
+/// Output each leg received between two balance snapshots
+///
+/// # Arguments
+///
+/// * `before` - Balances from [`LegOutputAccounts::balances`] before the legs
+/// * `after` - Balances after the legs
+///
+/// # Returns
+///
+/// Each leg's increase, 0 where the balance did not grow. Several legs may
+/// share an output account; each of them then reports the account's total
+/// increase.
+pub fn leg_output_deltas(before: &[u64], after: &[u64]) -> Vec<u64> {
+    before
+        .iter()
+        .zip(after)
+        .map(|(before, after)| after.saturating_sub(*before))
+        .collect()
+}
+
+/// Revert the batch if any executed leg slipped past its limit
+///
+/// See "Batch Slippage Limit" in the module docs.
+///
+/// # Arguments
+///
+/// * `swaps` - The batch's legs
+/// * `received` - Each leg's output from [`leg_output_deltas`]
+/// * `global_max_slippage_bps` - The batch's limit, 0 for none (each leg
+///   is still held to its own limit)
+///
+/// # Errors
+///
+/// * `ErrorCode::BatchSlippageExceeded` - A leg received more than its limit
+///   (see [`leg_slippage_limit_bps`]) less than its implied quote (the leg
+///   index is logged)
+/// * `ErrorCode::MathOverflow` - A leg's implied quote overflows
+pub fn assert_batch_slippage_within(
+    swaps: &[SwapParams],
+    received: &[u64],
+    global_max_slippage_bps: u16,
+) -> Result<()> {
+    for (index, (swap, received)) in swaps.iter().zip(received).enumerate() {
+        // A leg that received nothing counts as a total loss
+        let expected = implied_expected_output(swap)?;
+        let slippage_bps = calculate_slippage(expected, *received).ok_or(ErrorCode::MathOverflow)?;
+        let limit_bps = leg_slippage_limit_bps(swap, global_max_slippage_bps);
+        if slippage_bps > limit_bps {
+            msg!(
+                "Leg {} slipped {} bps, over its limit of {} bps",
+                index,
+                slippage_bps,
+                limit_bps
+            );
+            return err!(ErrorCode::BatchSlippageExceeded);
+        }
+    }
+
+    Ok(())
+}
+
+/// Most slippage a leg may realize, in basis points
+///
+/// # Arguments
+///
+/// * `swap` - The leg
+/// * `global_max_slippage_bps` - The batch's limit, 0 for none
+///
+/// # Returns
+///
+/// The leg's `effective_max_slippage_bps`, tightened to a non-zero
+/// `global_max_slippage_bps`
+pub fn leg_slippage_limit_bps(swap: &SwapParams, global_max_slippage_bps: u16) -> u64 {
+    let own = swap.effective_max_slippage_bps();
+    if global_max_slippage_bps == 0 {
+        own
+    } else {
+        own.min(u64::from(global_max_slippage_bps))
+    }
+}
+
+/// Quote implied by a leg's minimum output and slippage tolerance
+fn implied_expected_output(swap: &SwapParams) -> Result<u64> {
+    // The tolerance is capped at MAX_SLIPPAGE_BPS, so the divisor is non-zero
+    let expected = u128::from(swap.min_output_amount) * 10_000
+        / u128::from(10_000 - swap.effective_max_slippage_bps());
+    u64::try_from(expected).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Check one leg's output account
+///
+/// # Errors
+///
+/// Returns `ErrorCode::RouteAccountsMismatch` if the account is not a
+/// writable SPL Token account of `output_mint`.
+fn assert_leg_output_account(account: &AccountInfo, output_mint: &Pubkey) -> Result<()> {
+    require!(
+        account.is_writable && account.owner == &anchor_spl::token::ID,
+        ErrorCode::RouteAccountsMismatch
+    );
+    let token_account = TokenAccount::try_deserialize(&mut &account.data.borrow()[..])
+        .map_err(|_| ErrorCode::RouteAccountsMismatch)?;
+    require!(token_account.mint == *output_mint, ErrorCode::RouteAccountsMismatch);
+    Ok(())
+}
+
+/// Token balance of a (validated) output account
+fn token_balance(account: &AccountInfo) -> Result<u64> {
+    let token_account = TokenAccount::try_deserialize(&mut &account.data.borrow()[..])
+        .map_err(|_| ErrorCode::RouteAccountsMismatch)?;
+    Ok(token_account.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::{Account as SplTokenAccount, AccountState};
+
+    fn token_account_data(mint: Pubkey, amount: u64) -> Vec<u8> {
+        owned_token_account_data(mint, Pubkey::new_unique(), amount)
+    }
+
+    fn owned_token_account_data(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; SplTokenAccount::LEN];
+        SplTokenAccount {
+            mint,
+            owner,
+            amount,
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+
+    fn leg(output_mint: Pubkey) -> SwapParams {
+        SwapParams {
+            input_mint: Pubkey::new_unique(),
+            output_mint,
+            amount: 1_000,
+            min_output_amount: 900,
+            max_fee: None,
+            max_slippage_bps: None,
+            tag: 0,
+        }
+    }
+
+    #[test]
+    fn test_leg_output_accounts_match_legs() {
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (key_a, key_b, key_c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let token = anchor_spl::token::ID;
+        let (mut lamports_a, mut lamports_b, mut lamports_c) = (0, 0, 0);
+        let mut data_a = token_account_data(mint_a, 10);
+        let mut data_b = token_account_data(mint_b, 20);
+        let mut data_c = token_account_data(mint_b, 30);
+        let account_a = AccountInfo::new(&key_a, false, true, &mut lamports_a, &mut data_a, &token, false, 0);
+        let account_b = AccountInfo::new(&key_b, false, true, &mut lamports_b, &mut data_b, &token, false, 0);
+        let read_only = AccountInfo::new(&key_c, false, false, &mut lamports_c, &mut data_c, &token, false, 0);
+        let swaps = [leg(mint_a), leg(mint_b)];
+        let accounts = [account_a.clone(), account_b.clone()];
+
+        let (outputs, rest) = LegOutputAccounts::resolve(&swaps, &accounts, false).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(outputs.balances().unwrap(), vec![10, 20]);
+
+        // Out of leg order, read-only, missing or left-over accounts
+        let mismatched = [
+            vec![account_b.clone(), account_a.clone()],
+            vec![account_a.clone(), read_only],
+            vec![account_a.clone()],
+            vec![account_a.clone(), account_b.clone(), account_b.clone()],
+        ];
+        for accounts in &mismatched {
+            assert_eq!(
+                LegOutputAccounts::resolve(&swaps, accounts, false).err().unwrap(),
+                ErrorCode::RouteAccountsMismatch.into()
+            );
+        }
+
+        // The callback program and its accounts follow the output accounts
+        let (_, rest) = LegOutputAccounts::resolve(&swaps, &mismatched[3], true).unwrap();
+        assert_eq!(rest.len(), 1);
+    }
+
+    #[test]
+    fn test_one_adverse_leg_reverts_batch() {
+        // Quotes of 1_000_000 at a 5% tolerance (the global maximum)
+        let swaps: Vec<SwapParams> = (0..4)
+            .map(|_| SwapParams { min_output_amount: 950_000, ..leg(Pubkey::new_unique()) })
+            .collect();
+        let good = [999_000, 1_000_000, 998_000, 1_020_000];
+
+        assert!(assert_batch_slippage_within(&swaps, &good, 25).is_ok());
+
+        // Leg 2 meets its own minimum but slipped 0.4%, over the 0.25% limit
+        let one_bad = [999_000, 1_000_000, 996_000, 1_020_000];
+        assert!(one_bad[2] >= swaps[2].min_output_amount);
+        assert_eq!(
+            assert_batch_slippage_within(&swaps, &one_bad, 25).unwrap_err(),
+            ErrorCode::BatchSlippageExceeded.into()
+        );
+        assert!(assert_batch_slippage_within(&swaps, &one_bad, 40).is_ok());
+        assert!(assert_batch_slippage_within(&swaps, &one_bad, 0).is_ok()); // Each leg's own 5%
+
+        // A leg that received nothing slipped 100%
+        let one_lost = [999_000, 1_000_000, 0, 1_020_000];
+        assert_eq!(
+            assert_batch_slippage_within(&swaps, &one_lost, 9_999).unwrap_err(),
+            ErrorCode::BatchSlippageExceeded.into()
+        );
+    }
+
+    #[test]
+    fn test_tight_leg_reverts_without_batch_limit() {
+        // Quotes of 1_000_000: leg 1 tolerates 0.5%, the others the global 5%
+        let mut swaps: Vec<SwapParams> = (0..3)
+            .map(|_| SwapParams { min_output_amount: 950_000, ..leg(Pubkey::new_unique()) })
+            .collect();
+        swaps[1] = SwapParams {
+            min_output_amount: 995_000,
+            max_slippage_bps: Some(50),
+            ..leg(Pubkey::new_unique())
+        };
+        assert_eq!(leg_slippage_limit_bps(&swaps[1], 0), 50);
+        assert_eq!(leg_slippage_limit_bps(&swaps[1], 25), 25);
+        assert_eq!(leg_slippage_limit_bps(&swaps[0], 25), 25);
+
+        // Exactly at each leg's own minimum
+        assert!(assert_batch_slippage_within(&swaps, &[950_000, 995_000, 950_000], 0).is_ok());
+
+        // Leg 1 slipped 1%: fine for the others' 5%, over its own 0.5%
+        let tight_leg_slipped = [990_000, 990_000, 990_000];
+        assert_eq!(
+            assert_batch_slippage_within(&swaps, &tight_leg_slipped, 0).unwrap_err(),
+            ErrorCode::BatchSlippageExceeded.into()
+        );
+        // A looser batch limit does not loosen the leg
+        assert_eq!(
+            assert_batch_slippage_within(&swaps, &tight_leg_slipped, 200).unwrap_err(),
+            ErrorCode::BatchSlippageExceeded.into()
+        );
+    }
+
+    #[test]
+    fn test_shared_input_account_covers_every_leg() {
+        let (mint, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (key, token) = (Pubkey::new_unique(), anchor_spl::token::ID);
+        let mut lamports = 0;
+        let mut data = owned_token_account_data(mint, authority, 3_000);
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &token, false, 0);
+        let legs: Vec<SwapParams> = (0..3)
+            .map(|_| SwapParams { input_mint: mint, ..leg(Pubkey::new_unique()) })
+            .collect();
+        let accounts = [account];
+
+        // Three legs of 1_000 drain the account exactly
+        let (input, rest) = resolve_shared_input_account(&legs, &accounts, &authority).unwrap();
+        assert_eq!(input.key, &key);
+        assert!(rest.is_empty());
+
+        // A fourth leg is rejected before any leg executes
+        let mut too_many = legs.clone();
+        too_many.push(legs[0].clone());
+        assert_eq!(
+            resolve_shared_input_account(&too_many, &accounts, &authority).unwrap_err(),
+            ErrorCode::InsufficientFunds.into()
+        );
+
+        // Another owner, another input mint, or no account at all
+        assert_eq!(
+            resolve_shared_input_account(&legs, &accounts, &Pubkey::new_unique()).unwrap_err(),
+            ErrorCode::InvalidAuthority.into()
+        );
+        let mut mixed = legs.clone();
+        mixed[1].input_mint = Pubkey::new_unique();
+        assert_eq!(
+            resolve_shared_input_account(&mixed, &accounts, &authority).unwrap_err(),
+            ErrorCode::InvalidAccount.into()
+        );
+        assert_eq!(
+            resolve_shared_input_account(&legs, &[], &authority).unwrap_err(),
+            ErrorCode::RouteAccountsMismatch.into()
+        );
+    }
+
+    #[test]
+    fn test_leg_output_deltas() {
+        assert_eq!(leg_output_deltas(&[10, 20, 30], &[15, 20, 25]), vec![5, 0, 0]);
+    }
+}
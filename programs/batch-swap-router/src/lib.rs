@@ -26,12 +26,25 @@
 //! ├── errors.rs             # Error definitions
 //! ├── events.rs             # Event definitions
 //! ├── state.rs              # Account structures and state types
+//! ├── callback.rs           # Opt-in per-leg callback CPI
+//! ├── leg_accounts.rs       # Opt-in per-leg output token accounts
 //! ├── utils.rs              # Utility functions
 //! ├── swap_execution.rs     # Swap execution logic
+//! ├── test_utils.rs         # Account fixtures for tests (test-utils feature)
 //! └── instructions/         # Instruction handlers
 //!     ├── mod.rs           # Instruction module
+//!     ├── admin_transfer.rs # Two-step admin transfer
+//!     ├── batch_ops.rs     # Mixed swap/transfer batch instruction
 //!     ├── batch_swap.rs    # Batch swap instruction
-//!     └── execute_swap.rs  # Single swap instruction
+//!     ├── batch_swap_large.rs # u128 batch swap instruction (large-amounts feature)
+//!     ├── execute_swap.rs  # Single swap instruction
+//!     ├── get_version.rs   # Program version handshake
+//!     ├── initialize_config.rs # Config account creation
+//!     ├── initialize_user_stats.rs # Per-authority stats account creation
+//!     ├── migrate_config.rs # Config layout migration
+//!     ├── set_callback_program.rs # Callback allowlist admin instruction
+//!     ├── set_fee_exemption.rs # Fee exemption admin instruction
+//!     └── update_config.rs # Config policy admin instruction
 //! ```
 //!
 //! ## Instructions
@@ -47,6 +60,8 @@
 //! - Comprehensive validation
 //! - Fee calculation and tracking
 //! - Event emission for tracking
+//! - Optional per-leg callback CPI into an allowlisted program
+//! - Optional cap on the batch's total protocol fees (`max_total_fee`)
 //!
 //! ### `execute_swap`
 //!
@@ -60,6 +75,41 @@
 //! - Account validation
 //! - Authority verification
 //! - Event emission
+//! - Simulation mode for `simulateTransaction` dry runs (no token transfers)
+//!
+//! ### `initialize_config` / `set_fee_exemption` / `update_config` / `migrate_config`
+//!
+//! Create the program `Config` PDA and manage its fee exemption list. Exempt
+//! authorities (DAOs, internal accounts) swap without paying protocol fees.
+//! `update_config` toggles policy settings such as `require_authority_is_payer`,
+//! `require_expected_output`, `max_distinct_mints`, the monitored
+//! `expected_fee_rate_bps`, `max_price_jump_bps`, `strict_ata`,
+//! `treasury_is_token_account`, `min_output_floor`, `paused` and
+//! `loyalty_mint`, and changes the protocol fee rate, recipient and burn
+//! share (emitting `ConfigUpdatedEvent`).
+//! `migrate_config` upgrades a config created on an older account layout.
+//!
+//! ### `set_callback_program`
+//!
+//! Manage the allowlist of programs `batch_swap` may call after each leg
+//! (see the `callback` module for the CPI format and compute unit costs).
+//!
+//! ### `propose_admin` / `accept_admin`
+//!
+//! Two-step admin transfer: the current admin proposes a new key, which only
+//! becomes admin once it signs `accept_admin`.
+//!
+//! ### `initialize_user_stats`
+//!
+//! Create the signer's `UserStats` PDA. `execute_swap` records the volume of
+//! authorities that pass it, counted in `Config::loyalty_mint`, and discounts
+//! their protocol fee rate by volume tier (`FEE_DISCOUNT_TIERS`).
+//!
+//! ### `get_version`
+//!
+//! Client handshake: returns the program version, accepted instruction
+//! versions and build feature bits as return data, for clients to simulate
+//! before choosing which instruction formats and features to use.
 //!
 //! ## Security Considerations
 //!
@@ -77,14 +127,27 @@
 //!
 //! ```rust,ignore
 //! // Execute batch swap
-//! batch_swap(ctx, vec![
-//!     SwapParams {
+//! batch_swap(
+//!     ctx,
+//!     INSTRUCTION_VERSION,
+//!     vec![SwapParams {
 //!         input_mint: sol_mint,
 //!         output_mint: usdc_mint,
 //!         amount: 1_000_000_000, // 1 SOL
 //!         min_output_amount: 90_000_000, // 90 USDC (10% slippage)
-//!     },
-//! ])?;
+//!         max_fee: None, // no absolute fee cap
+//!         max_slippage_bps: None, // global MAX_SLIPPAGE_BPS
+//!         tag: 0, // untagged
+//!     }],
+//!     usdc_mint,      // Base mint of the batch totals
+//!     true,           // Emit events
+//!     [0u8; 32],      // No memo
+//!     None,           // No callback program
+//!     0,              // No batch fee cap
+//!     false,          // No leg output accounts
+//!     0,              // No batch slippage limit
+//!     false,          // Each leg uses its own input account
+//! )?;
 //! ```
 //!
 //! ### Single Swap
@@ -93,22 +156,41 @@
 //! // Execute single swap
 //! execute_swap(
 //!     ctx,
+//!     INSTRUCTION_VERSION,
 //!     1_000_000_000,  // Input amount: 1 SOL
 //!     90_000_000,     // Min output: 90 USDC
 //!     95_000_000,     // Expected output: 95 USDC (from Jupiter quote)
+//!     true,           // Emit events
+//!     [0u8; 32],      // No memo
+//!     0,              // Untagged
+//!     None,           // Output goes to the authority
+//!     false,          // Execute, not simulate
+//!     SlippageBasis::Output,
+//!     quote_timestamp, // When the quote was fetched
+//!     30,             // Reject quotes older than 30 seconds
+//!     FeeDenomination::Token,
+//!     SlippageFailureMode::Revert,
 //! )?;
 //! ```
 //!
 //! ## Events
 //!
-//! The program emits events for tracking and indexing:
+//! The program emits events for tracking and indexing. Both swap instructions
+//! take an `emit_events` flag; passing `false` skips the events below to save
+//! compute units (stats accounts are still updated):
 //!
 //! - `BatchSwapEvent` - Emitted when a batch swap is executed
-//!   - Contains: authority, swap_count, total_input_amount, total_protocol_fees, timestamp
+//!   - Contains: authority, swap_count, total_input_amount, total_protocol_fees,
+//!     memo_hash, timestamp
 //!
 //! - `SwapExecutedEvent` - Emitted when a single swap is executed
 //!   - Contains: authority, input_amount, output_amount, input_mint, output_mint,
-//!     protocol_fee, slippage_bps, timestamp
+//!     protocol_fee, treasury_fee, burned_fee, slippage_bps, memo_hash, tag,
+//!     timestamp
+//!
+//! - `SwapLegEvent` - Emitted for each leg of a batch swap, in leg order
+//!   - Contains: authority, leg_index, input_mint, output_mint, amount,
+//!     min_output_amount, protocol_fee, tag, timestamp
 //!
 //! ## Error Handling
 //!
@@ -117,6 +199,7 @@
 //!
 //! - `EmptySwaps` - No swaps provided in batch
 //! - `TooManySwaps` - Batch exceeds maximum size
+//! - `UnsupportedInstructionVersion` - Client sent an outdated/unknown instruction format
 //! - `InvalidAmount` - Invalid swap amount
 //! - `SlippageExceeded` - Slippage tolerance exceeded
 //! - `SwapExecutionFailed` - Swap execution failed
@@ -142,23 +225,32 @@
 //! This program is licensed under the MIT License.
 
 #![allow(clippy::result_large_err)]
-#![warn(missing_docs)]
+// `missing_docs` is not enabled crate-wide: the `#[program]` and
+// `#[derive(Accounts)]` expansions emit public items that cannot carry docs.
 #![warn(clippy::missing_docs_in_private_items)]
 
 use anchor_lang::prelude::*;
 
+// A fee-free mainnet deployment would give away the protocol fee
+#[cfg(all(feature = "no-fees", feature = "mainnet"))]
+compile_error!("The `no-fees` feature is for test forks and cannot be combined with `mainnet`");
+
 // Declare the program ID
 // Program ID for devnet deployment
 declare_id!("HS63bw1V1qTM5uWf92q3uaFdqogrc4SN9qUJSR8aqBMx");
 
 // Module declarations
+pub mod callback;
 pub mod constants;
 pub mod errors;
 pub mod events;
 pub mod instructions;
+pub mod leg_accounts;
 pub mod security;
 pub mod state;
 pub mod swap_execution;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 pub mod utils;
 
 // Re-export commonly used types
@@ -189,18 +281,62 @@ pub mod batch_swap_router {
     ///
     /// 1. Validate the batch size (not empty, not too large)
     /// 2. Validate each swap parameter
-    /// 3. Calculate fees for all swaps
-    /// 4. Validate swap parameters
+    /// 3. Calculate fees for all swaps, in index order
+    /// 4. Emit a `SwapLegEvent` per leg, in index order
     /// 5. Emit event for tracking
     ///
+    /// # Execution Order
+    ///
+    /// Legs are processed strictly in the order of the `swaps` vector. A leg
+    /// may depend on the output of an earlier leg (e.g. A -> B then B -> C
+    /// through a shared intermediate account); each per-leg event carries its
+    /// `leg_index` so the order is visible off-chain.
+    ///
     /// # Arguments
     ///
     /// * `ctx` - Context containing account information
+    /// * `version` - Instruction format version; clients send `INSTRUCTION_VERSION`
     /// * `swaps` - Vector of swap parameters (max 10 swaps per batch)
+    /// * `base_mint` - Settlement mint; `BatchSwapEvent::total_output_amount`
+    ///   only sums legs paying out in this mint
+    /// * `emit_events` - Emit events (pass `true` by default). `false` skips the
+    ///   `SwapLegEvent`s and `BatchSwapEvent` to save compute units for
+    ///   latency-sensitive callers; stats are still updated.
+    /// * `memo_hash` - SHA-256 hash of the SPL memo the client attached to the
+    ///   transaction (all zeroes if none), recorded in `BatchSwapEvent`. The
+    ///   hash is recorded as supplied; the memo instruction is not re-read.
+    /// * `callback_program` - Optional allowlisted program to CPI into after
+    ///   each leg with the leg's result (see the `callback` module). It must
+    ///   be the first remaining account (after any leg output accounts);
+    ///   later remaining accounts are forwarded to it. Each callback costs a
+    ///   CPI per leg, so raise the compute unit limit when using one. `None`
+    ///   disables callbacks.
+    /// * `max_total_fee` - Most protocol fees the whole batch may pay, summed
+    ///   across legs in input token units; the batch fails with
+    ///   `ErrorCode::BatchFeeCapExceeded` above it. Guards against a fee rate
+    ///   change between quoting and sending. 0 disables the cap.
+    /// * `leg_output_accounts` - The first remaining accounts are the legs'
+    ///   output token accounts, one per leg in leg order (see the
+    ///   `leg_accounts` module for the layout); each is checked against its
+    ///   leg's `output_mint`, its balance change is logged and checked
+    ///   against the leg's slippage limit. `false` passes none.
+    /// * `global_max_slippage_bps` - Portfolio-level circuit breaker: the
+    ///   batch fails with `ErrorCode::BatchSlippageExceeded` if any leg's
+    ///   measured output slipped more than this from the leg's implied quote,
+    ///   even if the leg met its own minimum. Needs `leg_output_accounts`.
+    ///   0 disables it; with leg output accounts every leg is still held to
+    ///   its own `max_slippage_bps`.
+    /// * `shared_input_account` - The first remaining account (ahead of any
+    ///   leg output accounts) is one input token account every leg draws
+    ///   from, for one-to-many rebalances; the batch fails up front with
+    ///   `ErrorCode::InsufficientFunds` unless it covers the legs' summed
+    ///   amounts. `false` passes none.
     ///
     /// # Accounts
     ///
     /// * `authority` - The signer executing the batch swap (must sign)
+    /// * `config` - Program `Config` PDA (fee exemptions)
+    /// * `stats` - Program `Stats` PDA (updated per leg)
     /// * `fee_recipient` - Optional fee recipient account
     /// * `token_program` - SPL Token program
     /// * `system_program` - System program for account management
@@ -215,39 +351,70 @@ pub mod batch_swap_router {
     ///
     /// # Errors
     ///
+    /// * `ErrorCode::UnsupportedInstructionVersion` - `version` outside the supported range
+    /// * `ErrorCode::ProgramPaused` - The admin has paused the program
+    /// * `ErrorCode::AuthorityPayerMismatch` - Another account signs while
+    ///   `require_authority_is_payer` is set
     /// * `ErrorCode::EmptySwaps` - No swaps provided
     /// * `ErrorCode::TooManySwaps` - More than MAX_BATCH_SIZE swaps provided
     /// * `ErrorCode::InvalidAmount` - Invalid swap amount (zero or below minimum)
     /// * `ErrorCode::InvalidSwapPair` - Input and output mints are the same
     /// * `ErrorCode::InvalidMinOutput` - Invalid minimum output amount
+    /// * `ErrorCode::InvalidMaxFee` - A swap sets `max_fee` to zero
+    /// * `ErrorCode::SlippageExceeded` - A swap sets `max_slippage_bps` above
+    ///   MAX_SLIPPAGE_BPS
+    /// * `ErrorCode::InsufficientOutput` - A leg has nothing left to swap after its fee
+    /// * `ErrorCode::CallbackProgramNotAllowed` - `callback_program` is not allowlisted
+    /// * `ErrorCode::InvalidAccount` - The callback program account is missing,
+    ///   or a leg's input mint is not the shared input account's mint
+    /// * `ErrorCode::RouteAccountsMismatch` - The leg output accounts are
+    ///   missing, left over, or don't match their legs' output mints, or
+    ///   `global_max_slippage_bps` is set without them
+    /// * `ErrorCode::BatchSlippageExceeded` - A leg slipped more than its own
+    ///   `max_slippage_bps` or `global_max_slippage_bps`
+    /// * `ErrorCode::InsufficientFunds` - The shared input account doesn't
+    ///   cover the legs' summed amounts
+    /// * `ErrorCode::InvalidAuthority` - The authority doesn't own the shared
+    ///   input account
+    /// * `ErrorCode::BatchFeeCapExceeded` - The batch's fees exceed `max_total_fee`
     ///
     /// # Events
     ///
-    /// Emits `BatchSwapEvent` on successful execution with:
+    /// Unless `emit_events` is `false`, emits `BatchSwapEvent` on successful execution with:
     /// - Authority public key
     /// - Number of swaps executed
     /// - Total input amount
     /// - Total protocol fees
+    /// - Base mint and total (minimum) output in the base mint
+    /// - Volume-weighted average price across the legs (`vwap_bps`)
+    /// - Input-weighted slippage tolerance of the legs
+    ///   (`weighted_max_slippage_bps`)
     /// - Timestamp of execution
     ///
     /// # Example
     ///
     /// ```rust,ignore
     /// // Execute a batch of 3 swaps
-    /// batch_swap(ctx, vec![
+    /// batch_swap(ctx, INSTRUCTION_VERSION, vec![
     ///     SwapParams {
     ///         input_mint: sol_mint,
     ///         output_mint: usdc_mint,
     ///         amount: 1_000_000_000, // 1 SOL
     ///         min_output_amount: 90_000_000, // 90 USDC (10% slippage)
+    ///         max_fee: None,
+    ///         max_slippage_bps: None,
+    ///         tag: 0,
     ///     },
     ///     SwapParams {
     ///         input_mint: usdc_mint,
     ///         output_mint: btc_mint,
     ///         amount: 50_000_000, // 50 USDC
     ///         min_output_amount: 0_001_000_000, // 0.001 BTC
+    ///         max_fee: Some(100_000), // fee capped at 0.1 USDC
+    ///         max_slippage_bps: Some(50), // tighter 0.5% tolerance
+    ///         tag: 42, // strategy id, echoed into SwapLegEvent
     ///     },
-    /// ])?;
+    /// ], usdc_mint, true, [0u8; 32], None, 0, false, 0, false)?;
     /// ```
     ///
     /// # Implementation Notes
@@ -257,8 +424,116 @@ pub mod batch_swap_router {
     ///   for validation and tracking
     /// - For program-side execution (future): Program would call Jupiter program via
     ///   CPI for each swap and validate slippage after execution
-    pub fn batch_swap(ctx: Context<BatchSwap>, swaps: Vec<SwapParams>) -> Result<()> {
-        instructions::batch_swap::handler(ctx, swaps)
+    #[allow(clippy::too_many_arguments)]
+    pub fn batch_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchSwap<'info>>,
+        version: u8,
+        swaps: Vec<SwapParams>,
+        base_mint: Pubkey,
+        emit_events: bool,
+        memo_hash: [u8; 32],
+        callback_program: Option<Pubkey>,
+        max_total_fee: u64,
+        leg_output_accounts: bool,
+        global_max_slippage_bps: u16,
+        shared_input_account: bool,
+    ) -> Result<()> {
+        instructions::batch_swap::handler(
+            ctx,
+            version,
+            swaps,
+            base_mint,
+            emit_events,
+            memo_hash,
+            callback_program,
+            max_total_fee,
+            leg_output_accounts,
+            global_max_slippage_bps,
+            shared_input_account,
+        )
+    }
+
+    /// Execute a batch of swaps and token transfers atomically
+    ///
+    /// Generalizes `batch_swap` to heterogeneous operations: each [`BatchOp`]
+    /// is either a swap (validated and charged like a `batch_swap` leg) or a
+    /// plain transfer from one of the authority's token accounts. Ops run in
+    /// index order and share the MAX_BATCH_SIZE cap.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing account information (same accounts as
+    ///   `batch_swap`)
+    /// * `version` - Instruction format version; clients send `INSTRUCTION_VERSION`
+    /// * `ops` - The operations (max 10 per batch, swaps and transfers combined)
+    /// * `emit_events` - Emit the per-op events (pass `true` by default)
+    ///
+    /// # Accounts
+    ///
+    /// Same as `batch_swap`, plus two writable remaining accounts per transfer
+    /// op, in op order: the source token account (owned by the authority)
+    /// and the destination token account `to`.
+    ///
+    /// # Errors
+    ///
+    /// Same as `batch_swap` for swap ops, plus `ErrorCode::InvalidAccount` or
+    /// `ErrorCode::InvalidAuthority` for transfer accounts that don't match
+    /// their op, `ErrorCode::TokenProgramMismatch` for transfer accounts of
+    /// another token program, and `ErrorCode::TransferFailed` if a transfer
+    /// fails.
+    ///
+    /// # Events
+    ///
+    /// Unless `emit_events` is `false`, emits one event per op in op order:
+    /// `SwapLegEvent` (with `leg_index` = op index) for swaps and
+    /// `TransferOpEvent` for transfers.
+    pub fn batch_ops<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchSwap<'info>>,
+        version: u8,
+        ops: Vec<BatchOp>,
+        emit_events: bool,
+    ) -> Result<()> {
+        instructions::batch_ops::handler(ctx, version, ops, emit_events)
+    }
+
+    /// Execute multiple token swaps with `u128` amounts
+    ///
+    /// Batch-wide validation and fee accounting of `batch_swap`, but amounts,
+    /// fees and totals are `u128` for tokens with 18+ decimals and a large
+    /// supply. Legs have no `max_fee`, `max_slippage_bps` or `tag`, and none of
+    /// `batch_swap`'s optional arguments are taken (see the
+    /// `batch_swap_large` module docs for the exact checks). Kept as a
+    /// separate instruction so the `u64` ABI of `batch_swap` does not change.
+    ///
+    /// Only available with the `large-amounts` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing account information (same accounts as `batch_swap`)
+    /// * `version` - Instruction format version; clients send `INSTRUCTION_VERSION`
+    /// * `swaps` - Vector of `u128` swap parameters (max 10 swaps per batch)
+    ///
+    /// # Errors
+    ///
+    /// `batch_swap`'s errors for the checks above, plus
+    /// `ErrorCode::MathOverflow` if a fee or total overflows `u128`.
+    ///
+    /// # Truncation Rules
+    ///
+    /// SPL token amounts are `u64`. Amounts are never truncated: converting an
+    /// amount above `u64::MAX` for a token transfer fails with
+    /// `ErrorCode::MathOverflow` (see `utils::to_token_amount`).
+    ///
+    /// # Events
+    ///
+    /// Emits `LargeBatchSwapEvent` on successful execution.
+    #[cfg(feature = "large-amounts")]
+    pub fn batch_swap_large(
+        ctx: Context<BatchSwap>,
+        version: u8,
+        swaps: Vec<LargeSwapParams>,
+    ) -> Result<()> {
+        instructions::batch_swap_large::handler(ctx, version, swaps)
     }
 
     /// Execute a single token swap
@@ -273,64 +548,149 @@ pub mod batch_swap_router {
     /// 2. Validate account mints differ (actual swap)
     /// 3. Verify authority owns the input account
     /// 4. Validate fee recipient (if provided)
-    /// 5. Calculate protocol fees
-    /// 6. Execute swap (client-side via Jupiter or program-side)
-    /// 7. Validate slippage tolerance
-    /// 8. Distribute fees (if fee recipient provided)
+    /// 5. Calculate protocol fees (0 if the authority is fee-exempt)
+    /// 6. Distribute fees from the input (if fee recipient provided), paying
+    ///    the `Config::lp_fee_bps` part to the LP fee account and burning the
+    ///    `Config::burn_bps` share
+    /// 7. Execute swap of the amount after fee (client-side via Jupiter or program-side)
+    /// 8. Validate slippage tolerance
     /// 9. Emit event for tracking
+    /// 10. Return the output, fee and slippage as return data
     ///
     /// # Arguments
     ///
     /// * `ctx` - Context containing token accounts, mints, and authority
+    /// * `version` - Instruction format version; clients send `INSTRUCTION_VERSION`
     /// * `amount` - Amount of input tokens to swap (in token's smallest unit)
+    ///   (fee included), or `USE_FULL_BALANCE` to swap the input account's
+    ///   whole balance
     /// * `min_output_amount` - Minimum output amount (slippage protection)
-    /// * `expected_output` - Expected output amount (from Jupiter quote, client-provided)
+    /// * `expected_output` - Expected output amount (from Jupiter quote, client-provided);
+    ///   0 means no quote: the relative slippage check is skipped, so
+    ///   `min_output_amount` must be above 1, and `Config::require_expected_output`
+    ///   rejects it
+    /// * `emit_events` - Emit `SwapExecutedEvent` (pass `true` by default);
+    ///   `false` saves compute units, stats are still updated
+    /// * `memo_hash` - SHA-256 hash of the SPL memo attached to the transaction
+    ///   (all zeroes if none), recorded in `SwapExecutedEvent`
+    /// * `tag` - Caller-defined label such as a strategy id (0 if untagged),
+    ///   recorded in `SwapExecutedEvent` so swaps can be grouped off-chain
+    /// * `recipient` - Wallet to deliver the output to (e.g. a cold wallet), or
+    ///   `None` for the authority
+    /// * `simulate` - Dry run for `simulateTransaction`: all validation and
+    ///   fee computation, the event and return data, but no token transfers
+    ///   and no stats; the output is taken from the quote. Pass `false` for
+    ///   real swaps.
+    /// * `slippage_basis` - `SlippageBasis::Output` (the default) checks the
+    ///   share of the expected output lost; `SlippageBasis::Input` checks the
+    ///   extra input consumed for the output received. Events and return data
+    ///   report output-side slippage either way.
+    /// * `quote_timestamp` - Unix timestamp of the quote `expected_output` came from
+    /// * `max_quote_age` - Oldest accepted quote, in seconds against the
+    ///   cluster clock; older quotes fail with `ErrorCode::QuoteExpired`, so a
+    ///   transaction that lands late cannot use a stale price. 0 disables the
+    ///   check.
+    /// * `fee_denomination` - `FeeDenomination::Token` (the default) takes the
+    ///   fee from the input; `FeeDenomination::Lamports` has a WSOL swap pay
+    ///   it in SOL from the authority's wallet to `fee_treasury`, and swaps
+    ///   the whole `amount`
+    /// * `slippage_failure_mode` - `SlippageFailureMode::Revert` (the
+    ///   default) fails a swap whose slippage from `expected_output` exceeds
+    ///   the tolerance; `SlippageFailureMode::AcceptMin` accepts it as long
+    ///   as `min_output_amount` was met. Only matters when `expected_output`
+    ///   is optimistic relative to the minimum.
     ///
     /// # Accounts
     ///
-    /// * `authority` - The signer executing the swap (must sign, must own input account)
+    /// * `authority` - The signer executing the swap (must sign, must own both token accounts)
     /// * `input_token_account` - Input token account (tokens swapped from)
-    /// * `output_token_account` - Output token account (tokens received)
+    /// * `output_token_account` - Output token account (tokens received, owned by
+    ///   authority; unused as destination with a `recipient`)
     /// * `input_mint` - Input token mint
     /// * `output_mint` - Output token mint
+    /// * `config` - Program `Config` PDA (fee exemptions)
+    /// * `stats` - Program `Stats` PDA
+    /// * `pair_stats` - `PairStats` PDA for the pair (created on first use)
+    /// * `last_price` - `LastPrice` PDA for the pair (created on first use)
     /// * `fee_recipient` - Optional fee recipient account
+    /// * `lp_fee_account` - Optional LP fee account (required while
+    ///   `Config::lp_fee_bps` is set)
+    /// * `recipient_token_account` - Optional recipient's ATA of the output
+    ///   mint (only with `recipient`)
+    /// * `fee_treasury` - Optional treasury wallet receiving lamport fees
     /// * `token_program` - SPL Token program
-    /// * `system_program` - System program
+    /// * `system_program` - System program (lamport fee transfers)
     ///
     /// # Validation
     ///
     /// - Amount must be >= MIN_SWAP_AMOUNT (1)
     /// - Input and output accounts must have different mints
     /// - Authority must be the owner of the input token account
+    /// - Output goes to an account the authority owns, or to the recipient's
+    ///   ATA of the output mint
     /// - Slippage must be within tolerance (MAX_SLIPPAGE_BPS)
     /// - Output must meet minimum requirement
     ///
     /// # Errors
     ///
-    /// * `ErrorCode::InvalidAmount` - Amount is zero or below minimum
+    /// * `ErrorCode::UnsupportedInstructionVersion` - `version` outside the supported range
+    /// * `ErrorCode::ProgramPaused` - The admin has paused the program
+    /// * `ErrorCode::InvalidAmount` - Amount (after resolving `USE_FULL_BALANCE`)
+    ///   is zero or below minimum
+    /// * `ErrorCode::QuoteExpired` - The quote is older than a non-zero `max_quote_age`
+    /// * `ErrorCode::InvalidAccount` - The input and output token accounts are
+    ///   the same account
     /// * `ErrorCode::InvalidSwapPair` - Input and output mints are the same
-    /// * `ErrorCode::InvalidAuthority` - Authority doesn't own input account
-    /// * `ErrorCode::SlippageExceeded` - Actual output < min_output_amount
+    /// * `ErrorCode::InvalidAuthority` - Authority doesn't own the input account, or
+    ///   the output account without a `recipient`
+    /// * `ErrorCode::InvalidRecipient` - `recipient_token_account` is missing, or
+    ///   is not the recipient's ATA of the output mint
+    /// * `ErrorCode::AuthorityPayerMismatch` - Another account signs while
+    ///   `require_authority_is_payer` is set
+    /// * `ErrorCode::InvalidLpFeeAccount` - The LP fee account is missing or
+    ///   does not match the config
+    /// * `ErrorCode::FeeExceedsAmount` - The input balance does not cover the fee
+    /// * `ErrorCode::InvalidFeeDenomination` - Lamport fees for a non-WSOL
+    ///   input, or with a burn or LP share configured, or token fees owed to
+    ///   a SOL treasury (see "Treasury Kind" on `Config`)
+    /// * `ErrorCode::InsufficientLamports` - The authority cannot pay a lamport
+    ///   fee and stay rent-exempt
+    /// * `ErrorCode::InsufficientOutput` - No output tokens arrived
+    /// * `ErrorCode::SlippageExceeded` - Some output arrived but < min_output_amount
     /// * `ErrorCode::SwapExecutionFailed` - Swap execution failed
-    /// * `ErrorCode::InvalidFeeRecipient` - Invalid fee recipient account
+    /// * `ErrorCode::InvalidFeeRecipient` - Invalid fee recipient account, or
+    ///   the swap's own input or output token account, or a fee treasury that
+    ///   is the authority or not `Config::fee_recipient`, or a fee account of
+    ///   the wrong treasury kind
     ///
     /// # Events
     ///
-    /// Emits `SwapExecutedEvent` on successful execution with:
-    /// - Authority public key
-    /// - Input and output amounts
+    /// Unless `emit_events` is `false`, emits `SwapExecutedEvent` on successful execution with:
+    /// - Authority public key and output recipient
+    /// - Input and output amounts, and the net output kept after all fees
     /// - Input and output mints
-    /// - Protocol fee
+    /// - Protocol fee, split into treasury and burned amounts, and the asset
+    ///   it was paid in
     /// - Slippage in basis points
+    /// - Memo hash and `tag`
+    /// - Whether the swap was simulated
     /// - Timestamp of execution
     ///
+    /// # Return Data
+    ///
+    /// Returns [`SwapReturnData`] (output amount, protocol fee and slippage),
+    /// which Anchor sets as the transaction's return data. CPI callers read it
+    /// with `get_return_data` to react to the realized slippage; it is set
+    /// even when `emit_events` is `false`.
+    ///
     /// # Example
     ///
     /// ```rust,ignore
     /// // Swap 1000 tokens from mint A to mint B
     /// // Expected output: 950 tokens (from Jupiter quote)
     /// // Minimum output: 900 tokens (5% slippage tolerance)
-    /// execute_swap(ctx, 1000, 900, 950)?;
+    /// // Tagged with strategy id 42 in SwapExecutedEvent
+    /// execute_swap(ctx, INSTRUCTION_VERSION, 1000, 900, 950, true, [0u8; 32], 42, None, false, SlippageBasis::Output, 0, 0, FeeDenomination::Token, SlippageFailureMode::Revert)?;
     /// ```
     ///
     /// # Security Notes
@@ -347,12 +707,390 @@ pub mod batch_swap_router {
     ///   same transaction, and this instruction validates the results
     /// - For program-side execution (future): Program would call Jupiter program via
     ///   CPI to execute the swap
+    #[allow(clippy::too_many_arguments)]
     pub fn execute_swap(
         ctx: Context<ExecuteSwap>,
+        version: u8,
         amount: u64,
         min_output_amount: u64,
         expected_output: u64,
+        emit_events: bool,
+        memo_hash: [u8; 32],
+        tag: u64,
+        recipient: Option<Pubkey>,
+        simulate: bool,
+        slippage_basis: SlippageBasis,
+        quote_timestamp: i64,
+        max_quote_age: i64,
+        fee_denomination: FeeDenomination,
+        slippage_failure_mode: SlippageFailureMode,
+    ) -> Result<SwapReturnData> {
+        instructions::execute_swap::handler(
+            ctx,
+            version,
+            amount,
+            min_output_amount,
+            expected_output,
+            emit_events,
+            memo_hash,
+            tag,
+            recipient,
+            simulate,
+            slippage_basis,
+            quote_timestamp,
+            max_quote_age,
+            fee_denomination,
+            slippage_failure_mode,
+        )
+    }
+
+    /// Create the program configuration and stats accounts
+    ///
+    /// Must be called once after deployment, before any swap instruction.
+    /// The signer becomes the config admin.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing the admin and the config PDA
+    ///
+    /// # Accounts
+    ///
+    /// * `admin` - The signer paying for the config (becomes admin)
+    /// * `config` - The `Config` PDA to create (seeds: `[b"config"]`)
+    /// * `stats` - The `Stats` PDA to create (seeds: `[b"stats"]`)
+    /// * `system_program` - System program
+    ///
+    /// # Errors
+    ///
+    /// Fails if the config has already been initialized.
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        instructions::initialize_config::handler(ctx)
+    }
+
+    /// Create the signer's user stats account
+    ///
+    /// Optional. Once created, passing it to `execute_swap` records the
+    /// signer's volume, which earns a loyalty discount on the protocol fee
+    /// (`FEE_DISCOUNT_TIERS`).
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing the authority and the user stats PDA
+    ///
+    /// # Accounts
+    ///
+    /// * `authority` - The signer paying for the account
+    /// * `user_stats` - The `UserStats` PDA to create (seeds:
+    ///   `[b"user_stats", authority]`)
+    /// * `system_program` - System program
+    ///
+    /// # Errors
+    ///
+    /// Fails if the account already exists.
+    pub fn initialize_user_stats(ctx: Context<InitializeUserStats>) -> Result<()> {
+        instructions::initialize_user_stats::handler(ctx)
+    }
+
+    /// Add or remove a protocol fee exemption
+    ///
+    /// Exempt authorities swap fee-free: `batch_swap` and `execute_swap`
+    /// compute a protocol fee of 0, skip the fee transfer and emit
+    /// `protocol_fee = 0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing the admin and the config PDA
+    /// * `authority` - The swap authority to update
+    /// * `exempt` - `true` to add the exemption, `false` to remove it
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+    /// * `ErrorCode::FeeExemptionListFull` - MAX_FEE_EXEMPTIONS already reached
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Exempt the DAO treasury from protocol fees
+    /// set_fee_exemption(ctx, dao_authority, true)?;
+    /// ```
+    pub fn set_fee_exemption(
+        ctx: Context<SetFeeExemption>,
+        authority: Pubkey,
+        exempt: bool,
     ) -> Result<()> {
-        instructions::execute_swap::handler(ctx, amount, min_output_amount, expected_output)
+        instructions::set_fee_exemption::handler(ctx, authority, exempt)
+    }
+
+    /// Update program-wide policy and fee settings
+    ///
+    /// Emits a `ConfigUpdatedEvent` when the protocol fee rate, fee recipient,
+    /// burn share or LP fee settings change.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing the admin and the config PDA
+    /// * `require_authority_is_payer` - When `true`, swap transactions signed
+    ///   by any account other than the swap authority are rejected with
+    ///   `ErrorCode::AuthorityPayerMismatch` (`None` keeps the current policy)
+    /// * `fee_bps` - New protocol fee rate in basis points (`None` keeps the
+    ///   current rate)
+    /// * `fee_recipient` - New owner of the fee recipient token accounts
+    ///   (`None` keeps the current one, `Pubkey::default()` accepts any owner)
+    /// * `burn_bps` - Share of each protocol fee to burn instead of sending
+    ///   to the treasury, in basis points of the fee (`None` keeps the
+    ///   current share)
+    /// * `lp_fee_bps` - Part of the fee rate paid to liquidity providers, in
+    ///   basis points of the swap amount (`None` keeps the current part)
+    /// * `lp_fee_recipient` - New owner of the LP fee token accounts (`None`
+    ///   keeps the current one, `Pubkey::default()` accepts any owner)
+    /// * `require_expected_output` - When `true`, `execute_swap` calls without
+    ///   an expected output are rejected with `ErrorCode::InvalidAmount`
+    ///   (`None` keeps the current setting)
+    /// * `max_distinct_mints` - Most distinct mints a `batch_swap` or
+    ///   `batch_ops` batch may touch, 0 for no cap (`None` keeps the current cap)
+    /// * `expected_fee_rate_bps` - Realized fee rate, in basis points of
+    ///   volume, that `execute_swap` checks and emits `FeeAnomalyEvent` for
+    ///   when it drifts, 0 for no check (`None` keeps the current rate)
+    /// * `max_price_jump_bps` - Largest move of a pair's execution price
+    ///   within `PRICE_JUMP_WINDOW_SECS` before `execute_swap` emits
+    ///   `PriceJumpEvent` (or rejects the swap in `strict_sanity` builds), 0
+    ///   for no check (`None` keeps the current limit)
+    /// * `strict_ata` - When `true`, `execute_swap` rejects input and output
+    ///   token accounts that are not the associated token account of their
+    ///   owner and mint with `ErrorCode::NonCanonicalTokenAccount` (`None`
+    ///   keeps the current setting)
+    /// * `treasury_is_token_account` - `true` sends `execute_swap` fees as
+    ///   token transfers to token accounts; `false` treats the treasury as a
+    ///   SOL wallet that only receives lamport fees (`None` keeps the current
+    ///   setting)
+    /// * `min_output_floor` - Smallest `min_output_amount` any swap leg may
+    ///   ask for, 0 for the `MIN_OUTPUT_AMOUNT` default (`None` keeps the
+    ///   current floor)
+    /// * `paused` - When `true`, every swap instruction fails with
+    ///   `ErrorCode::ProgramPaused` (`None` keeps the current state)
+    /// * `loyalty_mint` - Mint whose swaps count towards `UserStats` volume
+    ///   and the loyalty discount tiers, `Pubkey::default()` to count none
+    ///   (`None` keeps the current mint)
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+    /// * `ErrorCode::InvalidFeeBps` - `fee_bps` or `expected_fee_rate_bps`
+    ///   exceeds `MAX_PROTOCOL_FEE_BPS`
+    /// * `ErrorCode::InvalidBurnBps` - `burn_bps` exceeds `MAX_BURN_BPS` (10000)
+    /// * `ErrorCode::InvalidLpFeeBps` - `lp_fee_bps` exceeds the (new) `fee_bps`
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Disallow relayer-paid swap transactions, keep the fee settings
+    /// update_config(ctx, Some(true), None, None, None, None, None, None, None, None, None, None, None, None, None, None)?;
+    ///
+    /// // Lower the protocol fee to 0.2% and pay it to the treasury
+    /// update_config(ctx, None, Some(20), Some(treasury), None, None, None, None, None, None, None, None, None, None, None, None)?;
+    ///
+    /// // Burn a quarter of every fee
+    /// update_config(ctx, None, None, None, Some(2_500), None, None, None, None, None, None, None, None, None, None, None)?;
+    ///
+    /// // Pay 0.1% of the 0.3% fee to the liquidity pool
+    /// update_config(ctx, None, Some(30), None, None, Some(10), Some(pool), None, None, None, None, None, None, None, None, None)?;
+    ///
+    /// // Only accept quoted swaps
+    /// update_config(ctx, None, None, None, None, None, None, Some(true), None, None, None, None, None, None, None, None)?;
+    ///
+    /// // Reject batches over more than 8 distinct mints
+    /// update_config(ctx, None, None, None, None, None, None, None, Some(8), None, None, None, None, None, None, None)?;
+    ///
+    /// // Flag realized fee rates away from 0.3%
+    /// update_config(ctx, None, None, None, None, None, None, None, None, Some(30), None, None, None, None, None, None)?;
+    ///
+    /// // Flag price moves over 5% within the jump window
+    /// update_config(ctx, None, None, None, None, None, None, None, None, None, Some(500), None, None, None, None, None)?;
+    ///
+    /// // Only accept canonical associated token accounts
+    /// update_config(ctx, None, None, None, None, None, None, None, None, None, None, Some(true), None, None, None, None)?;
+    ///
+    /// // Keep the treasury as a SOL wallet (lamport fees only)
+    /// update_config(ctx, None, None, None, None, None, None, None, None, None, None, None, Some(false), None, None, None)?;
+    ///
+    /// // Reject swaps asking for less than 1000 output units
+    /// update_config(ctx, None, None, None, None, None, None, None, None, None, None, None, None, Some(1_000), None, None)?;
+    ///
+    /// // Halt all swaps during an incident
+    /// update_config(ctx, None, None, None, None, None, None, None, None, None, None, None, None, None, Some(true), None)?;
+    ///
+    /// // Count loyalty volume in USDC
+    /// update_config(ctx, None, None, None, None, None, None, None, None, None, None, None, None, None, None, Some(usdc_mint))?;
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        require_authority_is_payer: Option<bool>,
+        fee_bps: Option<u16>,
+        fee_recipient: Option<Pubkey>,
+        burn_bps: Option<u16>,
+        lp_fee_bps: Option<u16>,
+        lp_fee_recipient: Option<Pubkey>,
+        require_expected_output: Option<bool>,
+        max_distinct_mints: Option<u8>,
+        expected_fee_rate_bps: Option<u16>,
+        max_price_jump_bps: Option<u16>,
+        strict_ata: Option<bool>,
+        treasury_is_token_account: Option<bool>,
+        min_output_floor: Option<u64>,
+        paused: Option<bool>,
+        loyalty_mint: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::update_config::handler(
+            ctx,
+            require_authority_is_payer,
+            fee_bps,
+            fee_recipient,
+            burn_bps,
+            lp_fee_bps,
+            lp_fee_recipient,
+            require_expected_output,
+            max_distinct_mints,
+            expected_fee_rate_bps,
+            max_price_jump_bps,
+            strict_ata,
+            treasury_is_token_account,
+            min_output_floor,
+            paused,
+            loyalty_mint,
+        )
+    }
+    
+    /// Upgrade the `Config` account to the current layout
+    ///
+    /// Reads a config stored on an older layout (`version < CONFIG_VERSION`),
+    /// reallocates it to the current size (the admin pays any extra rent),
+    /// writes it back with defaults for new fields and sets its `version` to
+    /// `CONFIG_VERSION`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing the admin and the config PDA
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+    /// * `ErrorCode::ConfigVersionMismatch` - Config is already on
+    ///   `CONFIG_VERSION` (e.g. it was already migrated)
+    /// * `ErrorCode::InvalidAccount` - Account is not a `Config`
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Run once after upgrading the program
+    /// migrate_config(ctx)?;
+    /// ```
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        instructions::migrate_config::handler(ctx)
+    }
+    
+    /// Propose a new config admin (step 1 of 2)
+    ///
+    /// Records `new_admin` as `Config::pending_admin`. The current admin
+    /// keeps full control until the proposed key calls `accept_admin`, and
+    /// can replace the proposal or cancel it by proposing `Pubkey::default()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing the current admin and the config PDA
+    /// * `new_admin` - The key to hand the config over to
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Hand the config over to the DAO multisig
+    /// propose_admin(ctx, dao_multisig)?;
+    /// ```
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::admin_transfer::propose_handler(ctx, new_admin)
+    }
+    
+    /// Accept a pending admin proposal (step 2 of 2)
+    ///
+    /// Makes the signer the config admin and clears `Config::pending_admin`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing the pending admin and the config PDA
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::InvalidAuthority` - Signer is not `Config::pending_admin`
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Signed by the proposed admin
+    /// accept_admin(ctx)?;
+    /// ```
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::admin_transfer::accept_handler(ctx)
+    }
+    
+    /// Add or remove a per-leg callback program
+    ///
+    /// Only allowlisted programs can be passed as `batch_swap`'s
+    /// `callback_program`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context containing the admin and the config PDA
+    /// * `program_id` - The callback program to update
+    /// * `allowed` - `true` to allow the program, `false` to remove it
+    ///
+    /// # Errors
+    ///
+    /// * `ErrorCode::InvalidAuthority` - Signer is not the config admin
+    /// * `ErrorCode::CallbackProgramListFull` - MAX_CALLBACK_PROGRAMS already reached
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Let a partner's bookkeeping program observe batch legs
+    /// set_callback_program(ctx, partner_program, true)?;
+    /// ```
+    pub fn set_callback_program(
+        ctx: Context<SetCallbackProgram>,
+        program_id: Pubkey,
+        allowed: bool,
+    ) -> Result<()> {
+        instructions::set_callback_program::handler(ctx, program_id, allowed)
+    }
+    
+    /// Report the program version and build features
+    ///
+    /// Takes no accounts and changes nothing; clients simulate it to learn
+    /// what the deployed program supports before sending other instructions.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Empty context
+    ///
+    /// # Return Data
+    ///
+    /// Returns [`VersionReturnData`] (program version, accepted instruction
+    /// versions and `ENABLED_FEATURES`), which Anchor sets as the
+    /// transaction's return data.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let version = get_version(ctx)?;
+    /// if version.features & FEATURE_LARGE_AMOUNTS != 0 {
+    ///     // batch_swap_large is available
+    /// }
+    /// ```
+    pub fn get_version(ctx: Context<GetVersion>) -> Result<VersionReturnData> {
+        instructions::get_version::handler(ctx)
     }
 }
@@ -22,6 +22,17 @@
 //! - Dust attacks
 //! - DoS attacks
 //!
+//! ## Constant-Time Comparisons
+//!
+//! Account keys, mints and owners are public, so `==` (as in
+//! [`assert_keys_equal`]) leaks nothing and stays the right comparison for
+//! them. Values that are only known to their holder until used (order ids,
+//! idempotency keys, the nonce of a challenge-based admin handover, or any
+//! commitment preimage) would need a comparison whose running time does not
+//! depend on where the inputs first differ. The program compares none:
+//! `accept_admin` checks the pending admin's signature, not a nonce, and
+//! there are no order ids or idempotency keys.
+//!
 //! ## Usage
 //!
 //! ```rust,ignore
@@ -38,8 +49,10 @@
 //! ```
 
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::TokenAccount;
 
+use crate::constants::{INSTRUCTION_VERSION, MIN_SUPPORTED_INSTRUCTION_VERSION};
 use crate::errors::ErrorCode;
 
 // ============================================================================
@@ -173,6 +186,21 @@ pub fn assert_keys_equal(key1: &Pubkey, key2: &Pubkey) -> Result<()> {
     Ok(())
 }
 
+/// Assert that two public keys differ
+///
+/// # Arguments
+///
+/// * `key1` - First public key
+/// * `key2` - Second public key
+///
+/// # Errors
+///
+/// Returns `ErrorCode::InvalidAccount` if the keys are the same
+pub fn assert_keys_differ(key1: &Pubkey, key2: &Pubkey) -> Result<()> {
+    require!(key1 != key2, ErrorCode::InvalidAccount);
+    Ok(())
+}
+
 /// Assert that a token account has the expected mint
 ///
 /// # Arguments
@@ -215,6 +243,100 @@ pub fn assert_token_account_owner(
     Ok(())
 }
 
+/// Assert that a token account is the recipient's associated token account
+///
+/// Used when `execute_swap` delivers output to an explicit recipient instead
+/// of the authority. Requiring the canonical ATA (rather than any account the
+/// recipient owns) means the recipient can always find the output.
+///
+/// # Arguments
+///
+/// * `address` - Address of the token account
+/// * `account_owner` - Owner recorded in the token account
+/// * `account_mint` - Mint recorded in the token account
+/// * `recipient` - The intended recipient
+/// * `output_mint` - The swap's output mint
+///
+/// # Errors
+///
+/// Returns `ErrorCode::InvalidRecipient` if the account is not the
+/// recipient's ATA of `output_mint`
+pub fn assert_recipient_token_account(
+    address: &Pubkey,
+    account_owner: &Pubkey,
+    account_mint: &Pubkey,
+    recipient: &Pubkey,
+    output_mint: &Pubkey,
+) -> Result<()> {
+    require!(
+        *address == get_associated_token_address(recipient, output_mint)
+            && account_owner == recipient
+            && account_mint == output_mint,
+        ErrorCode::InvalidRecipient
+    );
+    Ok(())
+}
+
+/// Assert that a token account is the associated token account of its owner
+///
+/// Backs the `strict_ata` config policy. The ATA is derived from the owner
+/// and mint recorded in the token account, so any account the owner keeps
+/// at another address is rejected.
+///
+/// # Arguments
+///
+/// * `address` - Address of the token account
+/// * `account_owner` - Owner recorded in the token account
+/// * `account_mint` - Mint recorded in the token account
+///
+/// # Errors
+///
+/// Returns `ErrorCode::NonCanonicalTokenAccount` if `address` is not the
+/// owner's ATA of the mint
+pub fn assert_canonical_ata(
+    address: &Pubkey,
+    account_owner: &Pubkey,
+    account_mint: &Pubkey,
+) -> Result<()> {
+    require!(
+        *address == get_associated_token_address(account_owner, account_mint),
+        ErrorCode::NonCanonicalTokenAccount
+    );
+    Ok(())
+}
+
+/// Assert that an account belongs to the token program the instruction was given
+///
+/// SPL Token and Token-2022 accounts share their base layout, so an account
+/// of one passed next to the other program fails deep inside a CPI (or as
+/// Anchor's generic `AccountOwnedByWrongProgram`). Checking the owner first
+/// names the actual mistake, typically a Token-2022 mint or account during
+/// the token interface migration.
+///
+/// Only raw accounts (e.g. remaining accounts) need it: Anchor already
+/// rejects a typed `Account<TokenAccount>` of the other program.
+///
+/// # Arguments
+///
+/// * `token_program` - The `token_program` account's key
+/// * `account_owner` - Owner program of the token account or mint
+///
+/// # Errors
+///
+/// Returns `ErrorCode::TokenProgramMismatch` if the account is not owned by
+/// `token_program`
+pub fn assert_token_program_matches(token_program: &Pubkey, account_owner: &Pubkey) -> Result<()> {
+    if account_owner != token_program {
+        msg!(
+            "Account is owned by {}, but token program {} was passed",
+            account_owner,
+            token_program
+        );
+        return err!(ErrorCode::TokenProgramMismatch);
+    }
+    Ok(())
+}
+
 /// Assert that a public key is not the default/null key
 ///
 /// # Arguments
@@ -268,6 +390,147 @@ pub fn assert_sufficient_token_balance(
     Ok(())
 }
 
+/// Assert that an input balance covers the protocol fee about to be collected
+///
+/// Checked before the fee CPIs so a short balance fails with a program error
+/// instead of the token program's `InsufficientFunds`.
+///
+/// # Arguments
+///
+/// * `balance` - Current (reloaded) balance of the input token account
+/// * `fee` - The protocol fee taken from it (treasury and burned shares)
+///
+/// # Errors
+///
+/// Returns `ErrorCode::FeeExceedsAmount` if the balance is below the fee
+pub fn assert_fee_covered(balance: u64, fee: u64) -> Result<()> {
+    require!(balance >= fee, ErrorCode::FeeExceedsAmount);
+    Ok(())
+}
+
+/// Assert that a wallet can pay a lamport fee and stay rent-exempt
+///
+/// Checked before the system transfer so a short wallet fails with a
+/// program error instead of the system program's generic failure.
+///
+/// # Arguments
+///
+/// * `lamports` - Current lamport balance of the paying wallet
+/// * `fee` - The fee, in lamports
+/// * `rent_exempt_minimum` - Balance the wallet must keep afterwards
+///
+/// # Errors
+///
+/// Returns `ErrorCode::InsufficientLamports` if the balance is below the fee
+/// plus the rent-exempt minimum
+pub fn assert_lamports_cover_fee(lamports: u64, fee: u64, rent_exempt_minimum: u64) -> Result<()> {
+    let required = fee
+        .checked_add(rent_exempt_minimum)
+        .ok_or(ErrorCode::InsufficientLamports)?;
+    require!(lamports >= required, ErrorCode::InsufficientLamports);
+    Ok(())
+}
+
+/// Assert that a fee account is of the kind the treasury is configured as
+///
+/// See "Treasury Kind" on `Config`. A token treasury must not be paid into a
+/// SOL wallet and a SOL treasury must not be paid into a token account.
+///
+/// # Arguments
+///
+/// * `treasury_is_token_account` - `Config::treasury_is_token_account`
+/// * `fee_account_owner` - Owner program of the account the fee goes to
+///
+/// # Errors
+///
+/// Returns `ErrorCode::InvalidFeeRecipient` if the account is owned by the
+/// System Program while the treasury is a token account, or by the SPL Token
+/// program while it is a SOL wallet
+pub fn assert_treasury_kind(treasury_is_token_account: bool, fee_account_owner: &Pubkey) -> Result<()> {
+    let other_kind = if treasury_is_token_account {
+        anchor_lang::system_program::ID
+    } else {
+        anchor_spl::token::ID
+    };
+    require!(fee_account_owner != &other_kind, ErrorCode::InvalidFeeRecipient);
+    Ok(())
+}
+
+/// Assert that the fee recipient is not one of the swap's own token accounts
+///
+/// A fee "transferred" to the input account is a self-transfer: it costs
+/// compute units, moves nothing, and events still report it as collected.
+///
+/// # Arguments
+///
+/// * `fee_recipient` - The fee recipient account
+/// * `input_token_account` - The swap's input token account
+/// * `output_token_account` - The swap's output token account
+///
+/// # Errors
+///
+/// Returns `ErrorCode::InvalidFeeRecipient` if the fee recipient is the input
+/// or output token account
+pub fn assert_fee_recipient_distinct(
+    fee_recipient: &Pubkey,
+    input_token_account: &Pubkey,
+    output_token_account: &Pubkey,
+) -> Result<()> {
+    require!(
+        fee_recipient != input_token_account && fee_recipient != output_token_account,
+        ErrorCode::InvalidFeeRecipient
+    );
+    Ok(())
+}
+
+/// Assert that a batch touches at most `max_distinct_mints` distinct mints
+///
+/// # Arguments
+///
+/// * `mints` - Every mint the batch touches (duplicates allowed)
+/// * `max_distinct_mints` - The cap (`Config::max_distinct_mints`, 0 = no cap)
+///
+/// # Errors
+///
+/// Returns `ErrorCode::TooManyMints` if the distinct mints exceed a non-zero cap
+pub fn assert_distinct_mints_within(
+    mints: impl IntoIterator<Item = Pubkey>,
+    max_distinct_mints: u8,
+) -> Result<()> {
+    if max_distinct_mints == 0 {
+        return Ok(());
+    }
+    
+    // At most 2 mints per leg, so sorting is cheap
+    let mut mints: Vec<Pubkey> = mints.into_iter().collect();
+    mints.sort_unstable();
+    mints.dedup();
+    
+    if mints.len() > usize::from(max_distinct_mints) {
+        msg!("Batch touches {} distinct mints, cap is {}", mints.len(), max_distinct_mints);
+        return err!(ErrorCode::TooManyMints);
+    }
+    Ok(())
+}
+
+/// Assert that a batch's protocol fees stay within its fee cap
+///
+/// # Arguments
+///
+/// * `total_protocol_fees` - Summed protocol fees of the batch's legs
+/// * `max_total_fee` - The cap (`batch_swap`'s `max_total_fee`, 0 = no cap)
+///
+/// # Errors
+///
+/// Returns `ErrorCode::BatchFeeCapExceeded` if the fees exceed a non-zero cap
+pub fn assert_batch_fee_within(total_protocol_fees: u64, max_total_fee: u64) -> Result<()> {
+    if max_total_fee != 0 && total_protocol_fees > max_total_fee {
+        msg!("Batch fees {} exceed the fee cap {}", total_protocol_fees, max_total_fee);
+        return err!(ErrorCode::BatchFeeCapExceeded);
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Input Validation
 // ============================================================================
@@ -423,3 +686,365 @@ pub fn validate_amount_after_fee(amount: u64, fee: u64, min_amount: u64) -> Resu
     Ok(())
 }
 
+/// Reject minimum outputs that are implausible for the input amount
+///
+/// This is a **heuristic**, opt-in sanity check (enabled by the
+/// `strict_sanity` feature). It flags swaps whose `min_output_amount` is
+/// at least twice the input amount, or at most a thousandth of it, as likely
+/// misconfigured (e.g. a decimals mix-up or a copy-pasted amount).
+///
+/// Amounts are compared in raw token units, so legitimate swaps between
+/// tokens with very different decimals or prices can trip this check.
+/// Deployments that route such pairs should leave `strict_sanity` off.
+///
+/// # Arguments
+///
+/// * `amount` - The input amount of the swap
+/// * `min_output_amount` - The minimum output amount of the swap
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns Ok if the minimum output is plausible
+///
+/// # Errors
+///
+/// Returns `ErrorCode::ImplausibleMinOutput` if
+/// `min_output_amount >= amount * 2` or `min_output_amount <= amount / 1000`
+pub fn assert_plausible_min_output(amount: u64, min_output_amount: u64) -> Result<()> {
+    // u128 so `amount * 2` cannot overflow
+    let amount = amount as u128;
+    let min_output_amount = min_output_amount as u128;
+    
+    require!(
+        min_output_amount < amount * 2 && min_output_amount > amount / 1000,
+        ErrorCode::ImplausibleMinOutput
+    );
+    Ok(())
+}
+
+/// Assert that a swap asks for at least the configured output floor
+///
+/// `MIN_SWAP_AMOUNT` only keeps dust off the input side; this keeps swaps
+/// from asking for next to nothing in return.
+///
+/// # Arguments
+///
+/// * `min_output_amount` - The minimum output amount of the swap
+/// * `floor` - The floor (`Config::effective_min_output`)
+///
+/// # Errors
+///
+/// Returns `ErrorCode::InvalidMinOutput` if `min_output_amount < floor`
+pub fn assert_min_output_at_least(min_output_amount: u64, floor: u64) -> Result<()> {
+    if min_output_amount < floor {
+        msg!("min_output_amount {} is below the output floor {}", min_output_amount, floor);
+        return err!(ErrorCode::InvalidMinOutput);
+    }
+    Ok(())
+}
+
+/// Assert that a `u128` swap asks for at least the configured output floor
+///
+/// `u128` counterpart of [`assert_min_output_at_least`] for `batch_swap_large`.
+///
+/// # Arguments
+///
+/// * `min_output_amount` - The minimum output amount of the swap
+/// * `floor` - The floor (`Config::effective_min_output`)
+///
+/// # Errors
+///
+/// Returns `ErrorCode::InvalidMinOutput` if `min_output_amount < floor`
+#[cfg(feature = "large-amounts")]
+pub fn assert_min_output_at_least_u128(min_output_amount: u128, floor: u64) -> Result<()> {
+    if min_output_amount < u128::from(floor) {
+        msg!("min_output_amount {} is below the output floor {}", min_output_amount, floor);
+        return err!(ErrorCode::InvalidMinOutput);
+    }
+    Ok(())
+}
+
+/// Validate the instruction format version sent by the client
+///
+/// # Arguments
+///
+/// * `version` - The leading `version` argument of the instruction
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns Ok if the version is supported
+///
+/// # Errors
+///
+/// Returns `ErrorCode::UnsupportedInstructionVersion` if `version` is outside
+/// `MIN_SUPPORTED_INSTRUCTION_VERSION..=INSTRUCTION_VERSION`
+pub fn assert_supported_version(version: u8) -> Result<()> {
+    require!(
+        (MIN_SUPPORTED_INSTRUCTION_VERSION..=INSTRUCTION_VERSION).contains(&version),
+        ErrorCode::UnsupportedInstructionVersion
+    );
+    Ok(())
+}
+
+/// Assert that no account other than `authority` signs any instruction
+///
+/// Backs the `require_authority_is_payer` config policy. Programs cannot read
+/// the transaction fee payer, so this walks every top-level instruction in the
+/// instructions sysvar and rejects signer accounts other than the authority.
+///
+/// # Limitations
+///
+/// The fee payer is the message's first account key, which the instructions
+/// sysvar does not expose: it lists each instruction's account metas only.
+/// A separate fee payer that no instruction references as a signer is
+/// therefore never seen, and a relayer can still pay for the transaction
+/// without tripping this check. The policy only stops other accounts from
+/// co-signing instructions.
+///
+/// # Arguments
+///
+/// * `instructions_sysvar` - The instructions sysvar account
+/// * `authority` - The swap authority
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns Ok if the authority is the only signer
+///
+/// # Errors
+///
+/// Returns `ErrorCode::AuthorityPayerMismatch` if another account signs an
+/// instruction, or the sysvar's error if it cannot be read.
+pub fn assert_authority_is_payer(instructions_sysvar: &AccountInfo, authority: &Pubkey) -> Result<()> {
+    let mut index = 0;
+    while let Ok(instruction) =
+        solana_instructions_sysvar::load_instruction_at_checked(index, instructions_sysvar)
+    {
+        let foreign_signer = instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.is_signer && meta.pubkey != *authority);
+        require!(!foreign_signer, ErrorCode::AuthorityPayerMismatch);
+        index += 1;
+    }
+    
+    // Index 0 always exists (this instruction), so an unreadable sysvar
+    // surfaces as an error instead of silently passing
+    require!(index > 0, ErrorCode::InvalidAccount);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::MIN_OUTPUT_AMOUNT;
+
+    #[test]
+    fn test_distinct_mints_cap_boundary() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        // Two legs over three mints: a -> b, b -> c
+        let mints = [a, b, b, c];
+
+        assert!(assert_distinct_mints_within(mints, 3).is_ok());
+        assert_eq!(
+            assert_distinct_mints_within(mints, 2).unwrap_err(),
+            ErrorCode::TooManyMints.into()
+        );
+        assert!(assert_distinct_mints_within(mints, 0).is_ok()); // No cap
+    }
+
+    #[test]
+    fn test_lamports_cover_fee_boundary() {
+        assert!(assert_lamports_cover_fee(1_890_880, 1_000, 890_880 + 999_000).is_ok());
+        assert_eq!(
+            assert_lamports_cover_fee(1_890_879, 1_000, 890_880 + 999_000).unwrap_err(),
+            ErrorCode::InsufficientLamports.into()
+        );
+        assert!(assert_lamports_cover_fee(u64::MAX, u64::MAX, 1).is_err()); // Overflow
+    }
+
+    #[test]
+    fn test_treasury_kind_matches_flag() {
+        let (system, token) = (anchor_lang::system_program::ID, anchor_spl::token::ID);
+
+        assert!(assert_treasury_kind(true, &token).is_ok());
+        assert!(assert_treasury_kind(false, &system).is_ok());
+        for (treasury_is_token_account, owner) in [(true, system), (false, token)] {
+            assert_eq!(
+                assert_treasury_kind(treasury_is_token_account, &owner).unwrap_err(),
+                ErrorCode::InvalidFeeRecipient.into()
+            );
+        }
+    }
+
+    #[test]
+    fn test_token_program_matches_account_owner() {
+        let (token, token_2022) = (anchor_spl::token::ID, anchor_spl::token_2022::ID);
+
+        assert!(assert_token_program_matches(&token, &token).is_ok());
+        assert!(assert_token_program_matches(&token_2022, &token_2022).is_ok());
+        // Swapped programs, and an account of neither
+        for (program, owner) in [(token, token_2022), (token_2022, token), (token, anchor_lang::system_program::ID)] {
+            assert_eq!(
+                assert_token_program_matches(&program, &owner).unwrap_err(),
+                ErrorCode::TokenProgramMismatch.into()
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_output_floor_boundary() {
+        assert!(assert_min_output_at_least(MIN_OUTPUT_AMOUNT, MIN_OUTPUT_AMOUNT).is_ok());
+        assert_eq!(
+            assert_min_output_at_least(MIN_OUTPUT_AMOUNT - 1, MIN_OUTPUT_AMOUNT).unwrap_err(),
+            ErrorCode::InvalidMinOutput.into()
+        );
+        // A raised floor
+        assert!(assert_min_output_at_least(1_000, 1_000).is_ok());
+        assert_eq!(
+            assert_min_output_at_least(999, 1_000).unwrap_err(),
+            ErrorCode::InvalidMinOutput.into()
+        );
+    }
+
+    #[cfg(feature = "large-amounts")]
+    #[test]
+    fn test_min_output_floor_boundary_u128() {
+        assert!(assert_min_output_at_least_u128(MIN_OUTPUT_AMOUNT as u128, MIN_OUTPUT_AMOUNT).is_ok());
+        assert_eq!(
+            assert_min_output_at_least_u128(MIN_OUTPUT_AMOUNT as u128 - 1, MIN_OUTPUT_AMOUNT).unwrap_err(),
+            ErrorCode::InvalidMinOutput.into()
+        );
+        // A raised floor, and amounts only a u128 can hold
+        assert_eq!(
+            assert_min_output_at_least_u128(999, 1_000).unwrap_err(),
+            ErrorCode::InvalidMinOutput.into()
+        );
+        assert!(assert_min_output_at_least_u128(u64::MAX as u128 + 1, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_batch_fee_cap_boundary() {
+        assert!(assert_batch_fee_within(300, 300).is_ok());
+        assert_eq!(
+            assert_batch_fee_within(301, 300).unwrap_err(),
+            ErrorCode::BatchFeeCapExceeded.into()
+        );
+        assert!(assert_batch_fee_within(u64::MAX, 0).is_ok()); // No cap
+    }
+
+    #[test]
+    fn test_assert_plausible_min_output_bounds() {
+        assert!(assert_plausible_min_output(10_000, 9_970).is_ok());
+        assert!(assert_plausible_min_output(10_000, 19_999).is_ok());
+        assert!(assert_plausible_min_output(10_000, 11).is_ok());
+
+        assert!(assert_plausible_min_output(10_000, 20_000).is_err());
+        assert!(assert_plausible_min_output(10_000, 10).is_err());
+        assert!(assert_plausible_min_output(u64::MAX, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_assert_recipient_token_account() {
+        let (recipient, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ata = get_associated_token_address(&recipient, &mint);
+
+        assert!(assert_recipient_token_account(&ata, &recipient, &mint, &recipient, &mint).is_ok());
+
+        // Another account owned by the recipient is not its ATA
+        let other = Pubkey::new_unique();
+        assert!(assert_recipient_token_account(&other, &recipient, &mint, &recipient, &mint).is_err());
+
+        // The ATA address alone is not enough: owner and mint must match too
+        let stranger = Pubkey::new_unique();
+        assert!(assert_recipient_token_account(&ata, &stranger, &mint, &recipient, &mint).is_err());
+        assert!(assert_recipient_token_account(&ata, &recipient, &other, &recipient, &mint).is_err());
+    }
+
+    #[test]
+    fn test_assert_canonical_ata() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ata = get_associated_token_address(&owner, &mint);
+
+        assert!(assert_canonical_ata(&ata, &owner, &mint).is_ok());
+        assert_eq!(
+            assert_canonical_ata(&Pubkey::new_unique(), &owner, &mint).unwrap_err(),
+            ErrorCode::NonCanonicalTokenAccount.into()
+        );
+        // The ATA of another mint is not canonical for this one
+        assert!(assert_canonical_ata(&ata, &owner, &Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_assert_fee_covered() {
+        assert!(assert_fee_covered(3_000, 3_000).is_ok());
+        assert!(assert_fee_covered(3_000, 0).is_ok());
+        assert_eq!(
+            assert_fee_covered(2_999, 3_000).err(),
+            Some(ErrorCode::FeeExceedsAmount.into())
+        );
+    }
+
+    #[test]
+    fn test_assert_keys_differ() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert!(assert_keys_differ(&a, &b).is_ok());
+        assert_eq!(assert_keys_differ(&a, &a).unwrap_err(), ErrorCode::InvalidAccount.into());
+    }
+
+    #[test]
+    fn test_assert_fee_recipient_distinct() {
+        let (input, output, treasury) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert!(assert_fee_recipient_distinct(&treasury, &input, &output).is_ok());
+        for recipient in [input, output] {
+            assert_eq!(
+                assert_fee_recipient_distinct(&recipient, &input, &output).err(),
+                Some(ErrorCode::InvalidFeeRecipient.into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_assert_supported_version() {
+        assert!(assert_supported_version(INSTRUCTION_VERSION).is_ok());
+        assert!(assert_supported_version(MIN_SUPPORTED_INSTRUCTION_VERSION).is_ok());
+        assert!(assert_supported_version(0).is_err());
+        assert!(assert_supported_version(INSTRUCTION_VERSION + 1).is_err());
+    }
+
+    fn signer_check(signers: &[Pubkey], authority: &Pubkey) -> Result<()> {
+        use anchor_lang::solana_program::sysvar::instructions::{
+            BorrowedAccountMeta, BorrowedInstruction,
+        };
+
+        let program_id = crate::ID;
+        let instruction = BorrowedInstruction {
+            program_id: &program_id,
+            accounts: signers
+                .iter()
+                .map(|pubkey| BorrowedAccountMeta { pubkey, is_signer: true, is_writable: true })
+                .collect(),
+            data: &[],
+        };
+        let mut data = solana_instructions_sysvar::construct_instructions_data(&[instruction]);
+        let mut lamports = 0;
+        let key = solana_instructions_sysvar::ID;
+        let owner = Pubkey::default();
+        let sysvar = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+
+        assert_authority_is_payer(&sysvar, authority)
+    }
+
+    #[test]
+    fn test_assert_authority_is_payer() {
+        let authority = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+
+        assert!(signer_check(&[authority], &authority).is_ok());
+        assert_eq!(
+            signer_check(&[relayer, authority], &authority).unwrap_err(),
+            ErrorCode::AuthorityPayerMismatch.into()
+        );
+    }
+}
@@ -8,14 +8,596 @@
 //!
 //! - `BatchSwap`: Accounts required for batch swap instruction
 //! - `ExecuteSwap`: Accounts required for execute swap instruction
+//! - `InitializeConfig`: Accounts required to create the `Config` account
+//! - `SetFeeExemption`: Accounts required to add/remove a fee exemption
+//! - `ProposeAdmin` / `AcceptAdmin`: Accounts required for the two-step
+//!   admin transfer
+//! - `SetCallbackProgram`: Accounts required to add/remove a callback program
+//! - `GetVersion`: Accounts required to read the program version (none)
+//!
+//! ## Program Accounts
+//!
+//! - `Config`: Program-wide configuration (admin, fee exemptions)
+//! - `Stats`: Program-wide swap analytics
+//! - `PairStats`: Per-pair (directional) swap analytics
+//! - `LastPrice`: Per-pair (directional) last execution price
+//!
+//! Each program account stores its canonical PDA bump (`bump`) when it is
+//! created. Instructions that read `Config` or `Stats` check the address
+//! with `bump = config.bump` / `bump = stats.bump`, which Anchor re-derives
+//! with a single `create_program_address` instead of a `find_program_address`
+//! search. `PairStats` and `LastPrice` are created on first use with
+//! `init_if_needed`, for which Anchor always searches for the canonical bump.
 //!
 //! ## Data Types
 //!
 //! - `SwapParams`: Parameters for a single swap operation
+//! - `BatchOp`: One operation (swap or transfer) of a `batch_ops` batch
+//! - `LargeSwapParams`: `u128` variant of `SwapParams` (`large-amounts` feature)
 
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
 
+use crate::constants::{
+    CONFIG_SEED, LAST_PRICE_SCALE, LAST_PRICE_SEED, MAX_CALLBACK_PROGRAMS, MAX_FEE_EXEMPTIONS,
+    MAX_SLIPPAGE_BPS, MIN_OUTPUT_AMOUNT, PAIR_STATS_SEED, PRICE_JUMP_WINDOW_SECS, STATS_SEED,
+    USER_STATS_SEED,
+};
+use crate::errors::ErrorCode;
+use crate::security::SafeMath;
+
+/// Program-wide configuration account
+///
+/// A single PDA (seeds: `[CONFIG_SEED]`) created by `initialize_config`. The
+/// swap instructions read it to decide whether the signing authority pays
+/// protocol fees.
+///
+/// # Fields
+///
+/// * `admin` - The only key allowed to change the config
+/// * `fee_exemptions` - Authorities that swap fee-free (max `MAX_FEE_EXEMPTIONS`)
+/// * `require_authority_is_payer` - Reject transactions with a separate payer
+/// * `bump` - PDA bump seed
+/// * `version` - Layout version (`CONFIG_VERSION` for new accounts)
+/// * `fee_bps` - Protocol fee rate in basis points (max `MAX_PROTOCOL_FEE_BPS`)
+/// * `fee_recipient` - Owner fee recipient token accounts must belong to
+///   (`Pubkey::default()` accepts any owner)
+/// * `pending_admin` - Proposed next admin (`Pubkey::default()` if none)
+/// * `callback_programs` - Programs `batch_swap` may call after each leg
+///   (max `MAX_CALLBACK_PROGRAMS`)
+/// * `burn_bps` - Share of each protocol fee burned instead of sent to the
+///   treasury, in basis points of the fee (max `MAX_BURN_BPS`)
+/// * `lp_fee_bps` - Part of `fee_bps` paid to liquidity providers, in basis
+///   points of the swap amount (at most `fee_bps`)
+/// * `lp_fee_recipient` - Owner LP fee token accounts must belong to
+///   (`Pubkey::default()` accepts any owner)
+/// * `require_expected_output` - Reject `execute_swap` calls without a quote
+///   (`expected_output == 0`)
+/// * `max_distinct_mints` - Most distinct mints a batch may touch (0 = no cap)
+/// * `expected_fee_rate_bps` - Fee rate the realized fees are checked
+///   against, in basis points of volume (0 = no monitoring)
+/// * `max_price_jump_bps` - Largest move of a pair's execution price within
+///   `PRICE_JUMP_WINDOW_SECS` before it is flagged (0 = no check)
+/// * `strict_ata` - Require `execute_swap` input and output token accounts
+///   to be associated token accounts
+/// * `treasury_is_token_account` - Whether the treasury receives fees in
+///   token accounts (`true`) or as a plain SOL wallet (`false`)
+/// * `min_output_floor` - Smallest `min_output_amount` a swap may ask for
+///   (0 = `MIN_OUTPUT_AMOUNT`)
+/// * `paused` - Reject every swap instruction (see "Pause")
+/// * `loyalty_mint` - Mint loyalty volume is counted in (see "Loyalty
+///   Volume"; `Pubkey::default()` = none)
+///
+/// # Fee Exemptions
+///
+/// DAOs and internal accounts can be exempted from protocol fees. For an
+/// exempt authority, `calculate_protocol_fee` returns 0, no fee transfer is
+/// made, and events report `protocol_fee = 0`.
+///
+/// # Fee Settings
+///
+/// `fee_bps` starts at `PROTOCOL_FEE_BPS` and can be changed by the admin
+/// through `update_config`, which emits a `ConfigUpdatedEvent` whenever
+/// `fee_bps`, `fee_recipient` or `burn_bps` changes so indexers can track
+/// fee changes.
+///
+/// Token projects with deflationary economics can set `burn_bps` to burn
+/// part of every collected fee: `execute_swap` splits the fee into a
+/// treasury transfer and an SPL `burn` of the input token, and reports both
+/// amounts in `SwapExecutedEvent`.
+///
+/// # LP Fee Share
+///
+/// For shared liquidity, `lp_fee_bps` carves the liquidity providers' part
+/// out of the fee rate: of a `fee_bps` fee, `lp_fee_bps` goes to the LP fee
+/// account and the remaining `fee_bps - lp_fee_bps` to the protocol (which
+/// `burn_bps` then splits between treasury and burn). The split is
+/// proportional, so capped and exempt fees are split at the same ratio.
+///
+/// # Swaps Without a Quote
+///
+/// `execute_swap` only enforces the relative slippage limit against a
+/// non-zero `expected_output`. Without one, `min_output_amount` is the only
+/// protection, so it must be more than the 1-unit floor and a warning is
+/// logged. Deployments that always quote set `require_expected_output` to
+/// reject such swaps outright.
+///
+/// # Distinct Mints per Batch
+///
+/// Every distinct mint a batch touches brings its own token accounts and
+/// route accounts, so a batch of few legs over many mints can outgrow the
+/// transaction's account and size limits. `max_distinct_mints` is a soft cap
+/// on the distinct input and output mints (and transfer mints, for
+/// `batch_ops`) of one batch; batches above it fail early with
+/// `ErrorCode::TooManyMints` and should be split.
+///
+/// # Fee Monitoring
+///
+/// A wrong fee setting or a fee bypass shows up as a realized fee rate (all
+/// fees over all volume in `Stats`) away from the intended one. With a
+/// non-zero `expected_fee_rate_bps`, `execute_swap` compares the two after
+/// every swap and emits a `FeeAnomalyEvent` when they differ by more than
+/// `FEE_ANOMALY_TOLERANCE_BPS`. Swaps are never blocked. Fee-exempt and
+/// capped swaps lower the realized rate, so the admin sets the blended rate
+/// the deployment expects, not necessarily `fee_bps`.
+///
+/// # Price Jumps
+///
+/// `execute_swap` keeps each pair's last execution price in a `LastPrice`
+/// account. With a non-zero `max_price_jump_bps`, a swap whose price moved
+/// by more than that from a price at most `PRICE_JUMP_WINDOW_SECS` old
+/// emits a `PriceJumpEvent`, a sign of a sandwich or a manipulated pool.
+/// Builds with the `strict_sanity` feature reject such swaps with
+/// `ErrorCode::PriceJumpExceeded` instead.
+///
+/// # Canonical Token Accounts
+///
+/// Any token account with the right owner and mint is accepted by default,
+/// since some users keep tokens in accounts other than their associated
+/// token account (ATA) on purpose. Such accounts can confuse downstream
+/// tooling that only looks at ATAs. Deployments that want only canonical
+/// accounts set `strict_ata`: `execute_swap` then derives the ATA of each
+/// token account's owner and mint and rejects input and output accounts at
+/// any other address with `ErrorCode::NonCanonicalTokenAccount`.
+///
+/// # Treasury Kind
+///
+/// Some deployments keep the treasury in per-mint token accounts, others as
+/// a plain SOL wallet that converts what it receives. `treasury_is_token_account`
+/// (the default, and what migrated configs get) makes `execute_swap` send
+/// fees as token transfers to a `fee_recipient` token account. With it off,
+/// fees must be converted to SOL: they are only collected as lamports
+/// (`FeeDenomination::Lamports`, WSOL input) to the `fee_treasury` wallet,
+/// and a swap owing a token fee fails with `ErrorCode::InvalidFeeDenomination`.
+/// A fee account of the other kind fails with `ErrorCode::InvalidFeeRecipient`
+/// instead of silently collecting nothing.
+///
+/// # Output Floor
+///
+/// Every swap leg must ask for at least `min_output_floor` output tokens
+/// (`MIN_OUTPUT_AMOUNT` while it is 0), or it fails with
+/// `ErrorCode::InvalidMinOutput`. The floor is in raw units of whatever the
+/// output mint is, so deployments that route only a few pairs can raise it
+/// to turn away swaps that are economically meaningless on the output side.
+///
+/// # Pause
+///
+/// The admin can halt trading with `update_config` (e.g. during an incident
+/// or an upgrade) by setting `paused`. While it is set, `batch_swap`,
+/// `batch_swap_large`, `batch_ops` and `execute_swap` fail with
+/// `ErrorCode::ProgramPaused` before touching any account, simulated swaps
+/// included. Admin instructions keep working, so the admin can unpause.
+///
+/// # Loyalty Volume
+///
+/// `UserStats::total_volume` selects the loyalty discount tier, so it has to
+/// be in a single unit: raw amounts of different mints are not comparable,
+/// and a worthless 18-decimal token would reach every tier. Only swaps with
+/// `loyalty_mint` on one side add volume, counted as their amount in that
+/// mint (the input amount when it is spent, the output when it is received).
+/// Other swaps still count towards `UserStats::swap_count`. While
+/// `loyalty_mint` is unset no volume accrues and nobody earns a discount.
+///
+/// # Authority and Payer
+///
+/// By default the swap authority and the transaction fee payer may differ, so
+/// relayers can pay fees for their users. The authority is always the
+/// account whose tokens move and who pays the protocol fee; the fee payer
+/// only pays network fees.
+///
+/// Deployments that want the simpler "authority signs and pays" model set
+/// `require_authority_is_payer`. Programs cannot read the fee payer directly,
+/// so the swap instructions inspect the instructions sysvar and reject the
+/// transaction (`ErrorCode::AuthorityPayerMismatch`) if any instruction in it
+/// is signed by an account other than the authority. A fee payer that signs
+/// no instruction is not visible to the program and can't be rejected.
+///
+/// # Admin Transfer
+///
+/// The admin changes in two steps so a mistyped or uncontrolled key can
+/// never take over the config: the current admin records the new key in
+/// `pending_admin` with `propose_admin`, and the change only takes effect
+/// when that key signs `accept_admin`. Until then the current admin stays in
+/// control and can replace or cancel the proposal.
+///
+/// # Versioning
+///
+/// New fields are only ever appended after `version`, so older accounts
+/// keep their byte layout. `migrate_config` upgrades an account from the
+/// older version: it reallocates to the current size, fills new fields
+/// with defaults and sets `version` to `CONFIG_VERSION`.
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    /// Admin allowed to update the config
+    pub admin: Pubkey,
+    
+    /// Authorities exempt from protocol fees
+    #[max_len(MAX_FEE_EXEMPTIONS)]
+    pub fee_exemptions: Vec<Pubkey>,
+    
+    /// Reject transactions signed by anyone other than the swap authority
+    pub require_authority_is_payer: bool,
+    
+    /// PDA bump seed
+    pub bump: u8,
+    
+    /// Layout version of this account (see `CONFIG_VERSION`)
+    pub version: u8,
+    
+    /// Protocol fee rate in basis points
+    pub fee_bps: u16,
+    
+    /// Owner of the accounts protocol fees are paid to (default: any owner)
+    pub fee_recipient: Pubkey,
+    
+    /// Admin proposed by `propose_admin`, awaiting `accept_admin`
+    pub pending_admin: Pubkey,
+    
+    /// Programs allowed as `batch_swap` per-leg callbacks
+    #[max_len(MAX_CALLBACK_PROGRAMS)]
+    pub callback_programs: Vec<Pubkey>,
+    
+    /// Share of each protocol fee burned, in basis points of the fee
+    pub burn_bps: u16,
+    
+    /// Part of `fee_bps` paid to liquidity providers, in basis points
+    pub lp_fee_bps: u16,
+    
+    /// Owner of the accounts LP fees are paid to (default: any owner)
+    pub lp_fee_recipient: Pubkey,
+    
+    /// Reject `execute_swap` without an expected output (quote)
+    pub require_expected_output: bool,
+    
+    /// Most distinct mints one batch may touch (0 = no cap)
+    pub max_distinct_mints: u8,
+    
+    /// Expected realized fee rate, in basis points of volume (0 = off)
+    pub expected_fee_rate_bps: u16,
+    
+    /// Largest price move of a pair within the jump window before it is
+    /// flagged, in basis points (0 = off)
+    pub max_price_jump_bps: u16,
+    
+    /// Reject `execute_swap` token accounts that are not associated token accounts
+    pub strict_ata: bool,
+    
+    /// Treasury receives fees in token accounts (`false` = a SOL wallet)
+    pub treasury_is_token_account: bool,
+    
+    /// Smallest allowed `min_output_amount` (0 = `MIN_OUTPUT_AMOUNT`)
+    pub min_output_floor: u64,
+    
+    /// Reject every swap instruction until the admin unpauses
+    pub paused: bool,
+    
+    /// Mint loyalty volume is counted in (`Pubkey::default()` = none)
+    pub loyalty_mint: Pubkey,
+}
+
+/// `Config` on layout version 0
+///
+/// Version 0 ends after `bump`; version 1 appends the `version` byte and the
+/// settings after it. Only used by `migrate_config` to read legacy accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ConfigV0 {
+    /// Admin allowed to update the config
+    pub admin: Pubkey,
+    
+    /// Authorities exempt from protocol fees
+    pub fee_exemptions: Vec<Pubkey>,
+    
+    /// Reject transactions signed by anyone other than the swap authority
+    pub require_authority_is_payer: bool,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Config {
+    /// Check whether an authority is exempt from protocol fees
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - The swap authority to check
+    ///
+    /// # Returns
+    ///
+    /// `true` if the authority is in the fee exemption list
+    pub fn is_fee_exempt(&self, authority: &Pubkey) -> bool {
+        self.fee_exemptions.contains(authority)
+    }
+    
+    /// Check whether a program may be used as a per-leg callback
+    ///
+    /// # Arguments
+    ///
+    /// * `program_id` - The callback program to check
+    ///
+    /// # Returns
+    ///
+    /// `true` if the program is on the callback allowlist
+    pub fn is_callback_allowed(&self, program_id: &Pubkey) -> bool {
+        self.callback_programs.contains(program_id)
+    }
+    
+    /// Smallest `min_output_amount` a swap may ask for
+    ///
+    /// # Returns
+    ///
+    /// `min_output_floor`, or `MIN_OUTPUT_AMOUNT` if the admin left it at 0
+    pub fn effective_min_output(&self) -> u64 {
+        if self.min_output_floor == 0 {
+            MIN_OUTPUT_AMOUNT
+        } else {
+            self.min_output_floor
+        }
+    }
+}
+
+/// Program-wide swap analytics account
+///
+/// A single PDA (seeds: `[STATS_SEED]`) created by `initialize_config` and
+/// updated by every successful `batch_swap` and `execute_swap`.
+///
+/// Volumes and fees are summed across all mints in raw token units, so they
+/// are only meaningful as activity counters, not as a USD figure. Totals are
+/// `u128` so they cannot realistically overflow.
+#[account]
+#[derive(InitSpace)]
+pub struct Stats {
+    /// Number of swaps recorded (each batch leg counts as one swap)
+    pub total_swaps: u64,
+    
+    /// Sum of input amounts, in raw token units
+    pub total_volume: u128,
+    
+    /// Sum of protocol fees, in raw token units
+    pub total_fees: u128,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Stats {
+    /// Record one swap
+    ///
+    /// # Arguments
+    ///
+    /// * `volume` - Input amount of the swap
+    /// * `fee` - Protocol fee charged on the swap
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::MathOverflow` if a counter overflows.
+    pub fn record(&mut self, volume: u64, fee: u64) -> Result<()> {
+        self.total_swaps = self.total_swaps.safe_add(1)?;
+        self.total_volume = self.total_volume.safe_add(volume as u128)?;
+        self.total_fees = self.total_fees.safe_add(fee as u128)?;
+        Ok(())
+    }
+    
+    /// Check the realized fee rate against an expected one
+    ///
+    /// The realized rate is `total_fees * 10000 / total_volume`, computed in
+    /// `u128`.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected_fee_rate_bps` - Expected rate in basis points (0 skips the check)
+    /// * `tolerance_bps` - Allowed difference in basis points
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(rate))` - The realized rate, if it is off by more than `tolerance_bps`
+    /// * `Ok(None)` - If the rate is within tolerance, or nothing was swapped yet
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::MathOverflow` if the rate computation overflows.
+    pub fn fee_rate_anomaly(&self, expected_fee_rate_bps: u16, tolerance_bps: u64) -> Result<Option<u64>> {
+        if expected_fee_rate_bps == 0 || self.total_volume == 0 {
+            return Ok(None);
+        }
+        
+        let realized = self.total_fees.safe_mul(10_000)?.safe_div(self.total_volume)?;
+        let realized = u64::try_from(realized).map_err(|_| ErrorCode::MathOverflow)?;
+        let deviation = realized.abs_diff(expected_fee_rate_bps as u64);
+        
+        Ok((deviation > tolerance_bps).then_some(realized))
+    }
+}
+
+/// Per-pair swap analytics account
+///
+/// One PDA per directional pair (seeds: `[PAIR_STATS_SEED, input_mint,
+/// output_mint]`), created on the pair's first `execute_swap`.
+#[account]
+#[derive(InitSpace)]
+pub struct PairStats {
+    /// Input token mint of the pair
+    pub input_mint: Pubkey,
+    
+    /// Output token mint of the pair
+    pub output_mint: Pubkey,
+    
+    /// Number of swaps recorded for the pair
+    pub swap_count: u64,
+    
+    /// Sum of input amounts, in input token units
+    pub total_input: u128,
+    
+    /// Sum of output amounts, in output token units
+    pub total_output: u128,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl PairStats {
+    /// Record one swap of the pair
+    ///
+    /// # Arguments
+    ///
+    /// * `input_amount` - Input amount of the swap
+    /// * `output_amount` - Output amount received
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::MathOverflow` if a counter overflows.
+    pub fn record(&mut self, input_amount: u64, output_amount: u64) -> Result<()> {
+        self.swap_count = self.swap_count.safe_add(1)?;
+        self.total_input = self.total_input.safe_add(input_amount as u128)?;
+        self.total_output = self.total_output.safe_add(output_amount as u128)?;
+        Ok(())
+    }
+}
+
+/// Per-authority swap analytics account
+///
+/// One PDA per authority (seeds: `[USER_STATS_SEED, authority]`), created by
+/// `initialize_user_stats` and updated by every `execute_swap` that passes
+/// it. Its volume selects the authority's loyalty discount on the protocol
+/// fee (see `FEE_DISCOUNT_TIERS`). Unlike `Stats`, volume is only counted in
+/// `Config::loyalty_mint` (see "Loyalty Volume" on `Config`).
+#[account]
+#[derive(InitSpace)]
+pub struct UserStats {
+    /// Authority whose swaps are recorded
+    pub authority: Pubkey,
+    
+    /// Number of swaps recorded
+    pub swap_count: u64,
+    
+    /// Sum of swap volumes, in raw units of `Config::loyalty_mint`
+    pub total_volume: u128,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl UserStats {
+    /// Record one swap of the authority
+    ///
+    /// # Arguments
+    ///
+    /// * `volume` - Loyalty volume of the swap (see `loyalty_volume`)
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::MathOverflow` if a counter overflows.
+    pub fn record(&mut self, volume: u64) -> Result<()> {
+        self.swap_count = self.swap_count.safe_add(1)?;
+        self.total_volume = self.total_volume.safe_add(volume as u128)?;
+        Ok(())
+    }
+}
+
+/// Per-pair last execution price account
+///
+/// One PDA per directional pair (seeds: `[LAST_PRICE_SEED, input_mint,
+/// output_mint]`), created on the pair's first `execute_swap` and updated
+/// by every swap of the pair that moves tokens. `execute_swap` compares each
+/// new price with the stored one to detect price jumps (see "Price Jumps" on
+/// [`Config`]).
+#[account]
+#[derive(InitSpace)]
+pub struct LastPrice {
+    /// Input token mint of the pair
+    pub input_mint: Pubkey,
+    
+    /// Output token mint of the pair
+    pub output_mint: Pubkey,
+    
+    /// Output units per input unit of the last swap, scaled by `LAST_PRICE_SCALE`
+    pub price: u64,
+    
+    /// Unix timestamp of the last swap (0 before the first)
+    pub timestamp: i64,
+    
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl LastPrice {
+    /// Compute the execution price of a swap
+    ///
+    /// # Arguments
+    ///
+    /// * `input_amount` - Input amount actually swapped (after the fee)
+    /// * `output_amount` - Output amount received
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u64>` - Output per input unit scaled by `LAST_PRICE_SCALE`,
+    ///   saturating at `u64::MAX`
+    ///
+    /// # Errors
+    ///
+    /// Returns `ErrorCode::MathOverflow` if `input_amount` is 0.
+    pub fn execution_price(input_amount: u64, output_amount: u64) -> Result<u64> {
+        let price = (output_amount as u128)
+            .safe_mul(LAST_PRICE_SCALE)?
+            .safe_div(input_amount as u128)?;
+        
+        Ok(u64::try_from(price).unwrap_or(u64::MAX))
+    }
+    
+    /// Measure how far a new price moved from the stored one
+    ///
+    /// Uses [`calculate_price_impact`](crate::swap_execution::calculate_price_impact)
+    /// with the stored price as the reference, so moves in either direction count.
+    ///
+    /// # Arguments
+    ///
+    /// * `price` - The new execution price (see [`Self::execution_price`])
+    /// * `now` - Current Unix timestamp
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The move in basis points, or `None` if no price is
+    ///   stored yet or it is older than `PRICE_JUMP_WINDOW_SECS`
+    pub fn jump_bps(&self, price: u64, now: i64) -> Option<u64> {
+        if self.timestamp == 0 || now.saturating_sub(self.timestamp) > PRICE_JUMP_WINDOW_SECS {
+            return None;
+        }
+        
+        crate::swap_execution::calculate_price_impact(self.price, price)
+    }
+    
+    /// Store the price of a swap
+    ///
+    /// # Arguments
+    ///
+    /// * `price` - The swap's execution price
+    /// * `timestamp` - Unix timestamp of the swap
+    pub fn record(&mut self, price: u64, timestamp: i64) {
+        self.price = price;
+        self.timestamp = timestamp;
+    }
+}
+
 /// Account structure for batch swap instruction
 ///
 /// This structure defines all accounts required to execute a batch swap.
@@ -28,6 +610,11 @@ use anchor_spl::token::{Token, TokenAccount};
 ///   - Must sign the transaction
 ///   - Must own all input token accounts
 ///
+/// * `config` - Program `Config` PDA
+///   - Determines whether the authority is fee-exempt
+///
+/// * `stats` - Program `Stats` PDA (updated per leg)
+///
 /// * `fee_recipient` - Optional fee recipient account
 ///   - Receives protocol fees from swaps
 ///   - If not provided, fees are not collected
@@ -38,6 +625,10 @@ use anchor_spl::token::{Token, TokenAccount};
 /// * `system_program` - System program for account management
 ///   - Required for any account operations
 ///
+/// * `instructions_sysvar` - Instructions sysvar
+///   - Read when `config.require_authority_is_payer` is set, and for the
+///     priority fee reported in `BatchSwapEvent`
+///
 /// # Security
 ///
 /// - Authority must sign (enforced by `Signer` constraint)
@@ -56,6 +647,18 @@ pub struct BatchSwap<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     
+    /// Program configuration
+    ///
+    /// Read to determine whether the authority is exempt from protocol fees.
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    /// Program-wide swap analytics
+    ///
+    /// Updated with every leg of the batch.
+    #[account(mut, seeds = [STATS_SEED], bump = stats.bump)]
+    pub stats: Account<'info, Stats>,
+    
     /// Fee recipient account
     ///
     /// This account receives protocol fees from swaps.
@@ -73,6 +676,14 @@ pub struct BatchSwap<'info> {
     /// Required for any account operations. This is the standard Solana
     /// system program that handles account creation, transfers, etc.
     pub system_program: Program<'info, System>,
+    
+    /// Instructions sysvar
+    ///
+    /// Inspected for foreign signers when `config.require_authority_is_payer`
+    /// is set (see [`Config`]), and for the transaction's compute unit price.
+    /// CHECK: Address is constrained to the instructions sysvar
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
 }
 
 /// Account structure for execute swap instruction
@@ -104,16 +715,39 @@ pub struct BatchSwap<'info> {
 ///   - Mint of the output token
 ///   - Must differ from input_mint
 ///
+/// * `config` - Program `Config` PDA
+///   - Determines whether the authority is fee-exempt
+///
+/// * `stats` - Program `Stats` PDA
+///
+/// * `pair_stats` - `PairStats` PDA for the input/output pair
+///   - Created on the pair's first swap (authority pays rent)
+///
+/// * `last_price` - `LastPrice` PDA for the input/output pair
+///   - Created on the pair's first swap (authority pays rent)
+///
 /// * `fee_recipient` - Optional fee recipient account
 ///   - Receives protocol fees
 ///
+/// * `lp_fee_account` - Optional LP fee account
+///   - Receives the `Config::lp_fee_bps` part of the fee; required while
+///     that part is non-zero
+///
+/// * `recipient_token_account` - Optional explicit output destination
+///   - Only with the `recipient` argument; must be the recipient's ATA of the
+///     output mint
+///
 /// * `token_program` - SPL Token program
 ///   - Required for token operations
 ///
+/// * `instructions_sysvar` - Instructions sysvar
+///   - Read when `config.require_authority_is_payer` is set
+///
 /// # Security
 ///
 /// - Authority must sign (enforced by `Signer` constraint)
-/// - Input account ownership is validated
+/// - Input account ownership is validated; the output goes to an account the
+///   authority owns, or to the ATA of an explicit recipient
 /// - Mint validation ensures different tokens
 /// - Slippage protection via min_output_amount parameter
 #[derive(Accounts)]
@@ -122,7 +756,8 @@ pub struct ExecuteSwap<'info> {
     ///
     /// This account must:
     /// - Sign the transaction
-    /// - Own the input token account
+    /// - Own the input token account, and the output token account unless a
+    ///   `recipient` is given
     /// - Have sufficient SOL to pay transaction fees
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -141,15 +776,18 @@ pub struct ExecuteSwap<'info> {
     /// This account:
     /// - Must be a valid SPL token account
     /// - Must have a different mint than input account
-    /// - Will receive the swapped tokens
+    /// - Must be owned by the authority unless a `recipient` is given
+    /// - Will receive the swapped tokens (unless a `recipient` is given)
     #[account(mut)]
     pub output_token_account: Account<'info, TokenAccount>,
     
     /// Input token mint
     ///
     /// Mint of the input token being swapped.
-    /// Used for validation and routing.
+    /// Used for validation and routing, and writable so the burned share of
+    /// the protocol fee (`Config::burn_bps`) can reduce its supply.
     /// CHECK: Validated in instruction
+    #[account(mut)]
     pub input_mint: AccountInfo<'info>,
     
     /// Output token mint
@@ -159,6 +797,40 @@ pub struct ExecuteSwap<'info> {
     /// CHECK: Validated in instruction
     pub output_mint: AccountInfo<'info>,
     
+    /// Program configuration
+    ///
+    /// Read to determine whether the authority is exempt from protocol fees.
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    /// Program-wide swap analytics
+    #[account(mut, seeds = [STATS_SEED], bump = stats.bump)]
+    pub stats: Account<'info, Stats>,
+    
+    /// Analytics for this swap's (directional) pair
+    ///
+    /// Created on the pair's first swap; the authority pays the rent.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PairStats::INIT_SPACE,
+        seeds = [PAIR_STATS_SEED, input_mint.key().as_ref(), output_mint.key().as_ref()],
+        bump
+    )]
+    pub pair_stats: Account<'info, PairStats>,
+    
+    /// Last execution price of this swap's (directional) pair
+    ///
+    /// Created on the pair's first swap; the authority pays the rent.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + LastPrice::INIT_SPACE,
+        seeds = [LAST_PRICE_SEED, input_mint.key().as_ref(), output_mint.key().as_ref()],
+        bump
+    )]
+    pub last_price: Account<'info, LastPrice>,
+    
     /// Fee recipient account
     ///
     /// Receives protocol fees from the swap.
@@ -166,6 +838,44 @@ pub struct ExecuteSwap<'info> {
     #[account(mut)]
     pub fee_recipient: UncheckedAccount<'info>,
     
+    /// LP fee account
+    ///
+    /// Receives the liquidity providers' part of the fee
+    /// (`Config::lp_fee_bps`). Required while that part is non-zero and fees
+    /// are collected; must hold the input mint and, once the admin pins
+    /// `Config::lp_fee_recipient`, belong to it.
+    #[account(mut)]
+    pub lp_fee_account: Option<Account<'info, TokenAccount>>,
+    
+    /// Recipient token account (explicit output destination)
+    ///
+    /// Passed together with the `recipient` argument to deliver output to
+    /// another wallet (e.g. a cold wallet). Must be the recipient's
+    /// associated token account of the output mint.
+    #[account(mut)]
+    pub recipient_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// Treasury wallet (lamport fees)
+    ///
+    /// Receives the fee when it is paid in lamports
+    /// (`FeeDenomination::Lamports`). Must be `Config::fee_recipient` once
+    /// the admin pins it, and can't be the authority.
+    #[account(mut)]
+    pub fee_treasury: Option<SystemAccount<'info>>,
+    
+    /// The authority's swap analytics (loyalty discount)
+    ///
+    /// When passed, the swap's volume in `Config::loyalty_mint` is recorded
+    /// and the authority's cumulative volume selects a discount off the
+    /// protocol fee rate (`FEE_DISCOUNT_TIERS`). Created by
+    /// `initialize_user_stats`.
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED, authority.key().as_ref()],
+        bump = user_stats.bump
+    )]
+    pub user_stats: Option<Account<'info, UserStats>>,
+    
     /// SPL Token program
     ///
     /// Required for token operations during the swap.
@@ -173,10 +883,274 @@ pub struct ExecuteSwap<'info> {
     
     /// System program
     ///
-    /// Required for account operations.
+    /// Required for account operations and lamport fee transfers.
+    pub system_program: Program<'info, System>,
+    
+    /// Instructions sysvar
+    ///
+    /// Inspected for foreign signers when `config.require_authority_is_payer`
+    /// is set (see [`Config`]).
+    /// CHECK: Address is constrained to the instructions sysvar
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+}
+
+/// Account structure for the initialize config instruction
+///
+/// Creates the `Config` and `Stats` PDAs. The signer becomes the config admin.
+///
+/// # Accounts
+///
+/// * `admin` - The signer paying for and administering the config
+/// * `config` - The `Config` PDA to create (seeds: `[CONFIG_SEED]`)
+/// * `stats` - The `Stats` PDA to create (seeds: `[STATS_SEED]`)
+/// * `system_program` - System program for account creation
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    /// The admin (signer) creating the config
+    ///
+    /// Pays the rent for the config account and becomes its admin.
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    /// Program configuration account to create
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    /// Program-wide analytics account to create
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Stats::INIT_SPACE,
+        seeds = [STATS_SEED],
+        bump
+    )]
+    pub stats: Account<'info, Stats>,
+    
+    /// System program
+    ///
+    /// Required to create the config and stats accounts.
+    pub system_program: Program<'info, System>,
+}
+
+/// Account structure for the initialize user stats instruction
+///
+/// Creates the signer's `UserStats` PDA.
+///
+/// # Accounts
+///
+/// * `authority` - The signer whose swaps will be recorded (pays the rent)
+/// * `user_stats` - The `UserStats` PDA to create (seeds:
+///   `[USER_STATS_SEED, authority]`)
+/// * `system_program` - System program for account creation
+#[derive(Accounts)]
+pub struct InitializeUserStats<'info> {
+    /// The authority (signer) the stats account is for
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Per-authority analytics account to create
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [USER_STATS_SEED, authority.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    
+    /// System program
+    ///
+    /// Required to create the stats account.
+    pub system_program: Program<'info, System>,
+}
+
+/// Account structure for the set fee exemption instruction
+///
+/// # Accounts
+///
+/// * `admin` - The config admin (must sign)
+/// * `config` - The `Config` PDA to update
+///
+/// # Security
+///
+/// - Only `config.admin` may add or remove exemptions
+///   (`ErrorCode::InvalidAuthority` otherwise)
+#[derive(Accounts)]
+pub struct SetFeeExemption<'info> {
+    /// The config admin (signer)
+    pub admin: Signer<'info>,
+    
+    /// Program configuration account to update
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAuthority
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Account structure for the update config instruction
+///
+/// # Accounts
+///
+/// * `admin` - The config admin (must sign)
+/// * `config` - The `Config` PDA to update
+///
+/// # Security
+///
+/// - Only `config.admin` may update the config
+///   (`ErrorCode::InvalidAuthority` otherwise)
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    /// The config admin (signer)
+    pub admin: Signer<'info>,
+    
+    /// Program configuration account to update
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAuthority
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Account structure for the propose admin instruction
+///
+/// # Accounts
+///
+/// * `admin` - The current config admin (must sign)
+/// * `config` - The `Config` PDA to update
+///
+/// # Security
+///
+/// - Only `config.admin` may propose a new admin
+///   (`ErrorCode::InvalidAuthority` otherwise)
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    /// The current config admin (signer)
+    pub admin: Signer<'info>,
+    
+    /// Program configuration account to update
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAuthority
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Account structure for the accept admin instruction
+///
+/// # Accounts
+///
+/// * `pending_admin` - The proposed admin (must sign)
+/// * `config` - The `Config` PDA to update
+///
+/// # Security
+///
+/// - Only `config.pending_admin` may accept
+///   (`ErrorCode::InvalidAuthority` otherwise)
+/// - With no proposal pending, `config.pending_admin` is `Pubkey::default()`,
+///   which cannot sign
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    /// The proposed admin (signer)
+    pub pending_admin: Signer<'info>,
+    
+    /// Program configuration account to update
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = pending_admin @ ErrorCode::InvalidAuthority
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Account structure for the set callback program instruction
+///
+/// # Accounts
+///
+/// * `admin` - The config admin (must sign)
+/// * `config` - The `Config` PDA to update
+///
+/// # Security
+///
+/// - Only `config.admin` may change the callback allowlist
+///   (`ErrorCode::InvalidAuthority` otherwise)
+#[derive(Accounts)]
+pub struct SetCallbackProgram<'info> {
+    /// The config admin (signer)
+    pub admin: Signer<'info>,
+    
+    /// Program configuration account to update
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::InvalidAuthority
+    )]
+    pub config: Account<'info, Config>,
+}
+
+/// Account structure for the migrate config instruction
+///
+/// # Accounts
+///
+/// * `admin` - The config admin (must sign)
+///   - Pays the rent for any added space
+/// * `config` - The `Config` PDA to migrate
+///   - Resized to the current `Config` size by the handler
+/// * `system_program` - System program for the rent top-up
+///
+/// # Security
+///
+/// - Only `config.admin` may migrate the config
+///   (`ErrorCode::InvalidAuthority` otherwise, checked in the handler)
+/// - The account must be owned by this program and live at the config PDA
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// The config admin (signer)
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    /// Program configuration account to migrate
+    ///
+    /// CHECK: Accounts on an older layout may not deserialize as the current
+    /// `Config` (and Anchor's `realloc` constraint needs a typed account), so
+    /// the handler reads the old layout and resizes the account itself. The
+    /// address is checked by `seeds` and the owner by `owner`.
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump,
+        owner = crate::ID @ ErrorCode::InvalidAccount
+    )]
+    pub config: UncheckedAccount<'info>,
+    
+    /// System program
+    ///
+    /// Required to transfer rent when the config grows.
     pub system_program: Program<'info, System>,
 }
 
+/// Account structure for the get version instruction
+///
+/// The instruction reads no accounts: it only reports constants compiled
+/// into the program.
+#[derive(Accounts)]
+pub struct GetVersion {}
+
 /// Parameters for a single swap operation
 ///
 /// This structure contains all parameters needed to execute a single swap
@@ -205,6 +1179,10 @@ pub struct ExecuteSwap<'info> {
 ///   - Must be > 0 (validated in instruction)
 ///   - Should account for slippage (e.g., 5% slippage tolerance)
 ///
+/// * `tag` - Caller-defined label echoed into the leg's events
+///   - E.g. a strategy id, so off-chain tools can group swaps
+///   - 0 means untagged
+///
 /// # Example
 ///
 /// ```rust,ignore
@@ -213,6 +1191,9 @@ pub struct ExecuteSwap<'info> {
 ///     output_mint: usdc_mint,      // USDC mint address
 ///     amount: 1_000_000_000,       // 1 SOL (in lamports)
 ///     min_output_amount: 90_000_000, // 90 USDC (10% slippage tolerance)
+///     max_fee: None,               // no absolute fee cap
+///     max_slippage_bps: Some(100), // 1% tolerance for this leg
+///     tag: 42,                     // strategy id, echoed into SwapLegEvent
 /// }
 /// ```
 ///
@@ -222,6 +1203,8 @@ pub struct ExecuteSwap<'info> {
 /// - `amount` >= MIN_SWAP_AMOUNT
 /// - `min_output_amount` > 0
 /// - `input_mint` != `output_mint`
+/// - `max_fee` > 0 when set
+/// - `max_slippage_bps` <= MAX_SLIPPAGE_BPS when set
 ///
 /// # Security Considerations
 ///
@@ -308,6 +1291,353 @@ pub struct SwapParams {
     /// - Protects against price manipulation
     /// - Protects against liquidity issues
     pub min_output_amount: u64,
+    
+    /// Optional absolute cap on the protocol fee for this swap
+    ///
+    /// Percentage fees on very large swaps can be large, so some partners
+    /// negotiate an absolute cap. When set, the fee charged is
+    /// `min(amount * PROTOCOL_FEE_BPS / 10000, max_fee)`; the fee actually
+    /// charged is reported in `SwapLegEvent::protocol_fee`.
+    ///
+    /// # Constraints
+    ///
+    /// - Must be > 0 when set (`None` means no cap)
+    /// - Expressed in input token's smallest unit
+    pub max_fee: Option<u64>,
+    
+    /// Optional slippage tolerance for this leg, in basis points
+    ///
+    /// Lets a batch mix tight tolerances on volatile legs with looser ones
+    /// elsewhere. The tolerance applied to the leg is
+    /// `min(max_slippage_bps, MAX_SLIPPAGE_BPS)`, so a leg can only tighten
+    /// the global limit (see [`SwapParams::effective_max_slippage_bps`]).
+    ///
+    /// # Constraints
+    ///
+    /// - Must be <= MAX_SLIPPAGE_BPS when set (`None` uses the global limit)
+    pub max_slippage_bps: Option<u16>,
+    
+    /// Caller-defined label for this swap (0 = untagged)
+    ///
+    /// Not interpreted by the program; echoed into `SwapLegEvent::tag` (and
+    /// the per-leg callback) so portfolio tools can group swaps by strategy
+    /// without separate bookkeeping.
+    pub tag: u64,
+}
+
+impl SwapParams {
+    /// Slippage tolerance applied to this leg, in basis points
+    ///
+    /// Returns the leg's `max_slippage_bps` when set, capped at the global
+    /// `MAX_SLIPPAGE_BPS`; otherwise the global limit.
+    pub fn effective_max_slippage_bps(&self) -> u64 {
+        self.max_slippage_bps
+            .map_or(MAX_SLIPPAGE_BPS, |bps| u64::from(bps).min(MAX_SLIPPAGE_BPS))
+    }
+}
+
+/// Side of the swap `execute_swap` measures slippage on
+///
+/// Both bases compare the realized output with the quoted `expected_output`
+/// and are checked against the same `MAX_SLIPPAGE_BPS` limit; they differ
+/// in the reference amount the shortfall is divided by.
+///
+/// # Variants
+///
+/// * `Output` - Output given up: `(expected - actual) / expected`, the
+///   default and what `SwapExecutedEvent::slippage_bps` reports
+/// * `Input` - Extra input consumed for the output received, compared with
+///   the input the quote would have needed for it:
+///   `(amount - amount * actual / expected) / (amount * actual / expected)`,
+///   which reduces to `(expected - actual) / actual`
+///
+/// For the same swap the input basis is always at least the output basis,
+/// so callers whose tolerances are stated on the input side are never
+/// validated more loosely than they expect.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SlippageBasis {
+    /// Shortfall relative to the expected output
+    #[default]
+    Output,
+    
+    /// Extra input relative to the input the quote needed for the output
+    Input,
+}
+
+/// What `execute_swap` does when the relative slippage check fails
+///
+/// The relative check compares the realized output with the quoted
+/// `expected_output`; the absolute check compares it with
+/// `min_output_amount`. A swap below its minimum always fails.
+///
+/// # Variants
+///
+/// * `Revert` - Fail with `SlippageExceeded` (the default)
+/// * `AcceptMin` - Accept the swap as long as it delivered at least
+///   `min_output_amount`, logging the excess slippage
+///
+/// `AcceptMin` only makes a difference when `expected_output` is more
+/// optimistic than `min_output_amount` implies, i.e. when the minimum sits
+/// below the quote minus `MAX_SLIPPAGE_BPS`. With a minimum derived from the
+/// quote and the tolerance, every output that passes the minimum also
+/// passes the relative check and both modes behave the same.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SlippageFailureMode {
+    /// Revert the swap
+    #[default]
+    Revert,
+    
+    /// Accept any output at or above the minimum
+    AcceptMin,
+}
+
+/// How `execute_swap` collects the protocol fee
+///
+/// # Variants
+///
+/// * `Token` - The fee is taken from the input token account and sent to
+///   the `fee_recipient` token account (the default)
+/// * `Lamports` - The fee is paid in SOL: a system transfer from the
+///   authority to the treasury wallet (`Config::fee_recipient`). Only valid
+///   for WSOL input, where lamports and input units are 1:1, and only while
+///   `Config::burn_bps` and `Config::lp_fee_bps` are 0 (lamports can't be
+///   burned, and the LP share is paid in input tokens). The whole input
+///   `amount` is then swapped.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeeDenomination {
+    /// Fee paid in input tokens
+    #[default]
+    Token,
+    
+    /// Fee paid in lamports from the authority's wallet
+    Lamports,
+}
+
+/// One operation of a `batch_ops` batch
+///
+/// Lets a batch mix plain token transfers with swaps so both happen
+/// atomically. Ops run in index order and share the `MAX_BATCH_SIZE` cap.
+///
+/// # Variants
+///
+/// * `Swap` - A swap, validated and charged exactly like a `batch_swap` leg
+/// * `Transfer` - A transfer of `amount` tokens of `mint` from the
+///   authority's token account to the token account `to`
+///
+/// # Transfer Accounts
+///
+/// Each `Transfer` op takes the next two remaining accounts, in op order:
+/// the source token account (owned by the authority, of `mint`) and the
+/// destination token account (address `to`, of `mint`). Both must be
+/// writable.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum BatchOp {
+    /// Swap between two mints (see [`SwapParams`])
+    Swap(SwapParams),
+    
+    /// Transfer tokens from the authority to another token account
+    Transfer {
+        /// Mint of the transferred tokens
+        mint: Pubkey,
+        
+        /// Destination token account
+        to: Pubkey,
+        
+        /// Amount to transfer (must be >= MIN_SWAP_AMOUNT)
+        amount: u64,
+    },
+}
+
+/// Outcome of `execute_swap`, returned to the caller as return data
+///
+/// Anchor serializes the instruction's return value with `set_return_data`,
+/// so a program calling `execute_swap` via CPI can read the realized outcome
+/// with `get_return_data` (or `Return::get` on the Anchor CPI result) and
+/// react, e.g. abort a larger flow when slippage is too high. Off-chain
+/// callers find it in the `returnData` of `simulateTransaction`.
+///
+/// Available whether or not `emit_events` is set; the values match
+/// `SwapExecutedEvent`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SwapReturnData {
+    /// Output tokens received by the delivery account
+    pub output_amount: u64,
+    
+    /// Protocol fee charged, in input token units
+    pub protocol_fee: u64,
+    
+    /// Realized slippage against `expected_output`, in basis points
+    pub slippage_bps: u64,
 }
 
+/// Program version and features, returned by `get_version` as return data
+///
+/// Clients simulate `get_version` once and gate their behavior on it: send
+/// only instruction versions in
+/// `min_supported_instruction_version..=instruction_version`, and only rely
+/// on build features whose bit is set in `features` (see `FEATURE_*` in
+/// [`constants`](crate::constants)).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VersionReturnData {
+    /// Program version (`PROGRAM_VERSION`, e.g. "0.1.0")
+    pub program_version: String,
+    
+    /// Newest accepted instruction format version (`INSTRUCTION_VERSION`)
+    pub instruction_version: u8,
+    
+    /// Oldest accepted instruction format version
+    /// (`MIN_SUPPORTED_INSTRUCTION_VERSION`)
+    pub min_supported_instruction_version: u8,
+    
+    /// Build feature bits (`ENABLED_FEATURES`)
+    pub features: u64,
+}
 
+/// Parameters for a single swap operation with `u128` amounts
+///
+/// Identical to [`SwapParams`] except that `amount` and `min_output_amount`
+/// are `u128`. Tokens with 18+ decimals and a large supply can need amounts
+/// beyond `u64::MAX` for meaningful trades; this type lets the
+/// `batch_swap_large` instruction validate and account for them without
+/// changing the `u64` ABI of `batch_swap`.
+///
+/// Only available with the `large-amounts` feature.
+///
+/// # Truncation Rules
+///
+/// SPL token balances and transfers are `u64`, so any amount that actually
+/// moves tokens must fit in a `u64`:
+///
+/// - Amounts are **never** truncated or wrapped. Converting an amount above
+///   `u64::MAX` to an SPL amount fails with `ErrorCode::MathOverflow`
+///   (see [`crate::utils::to_token_amount`]).
+/// - Fees are computed in `u128` and rounded down, exactly like the `u64` path.
+/// - Batch totals are accumulated in `u128` and may legitimately exceed
+///   `u64::MAX` even when every leg fits.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// LargeSwapParams {
+///     input_mint: eighteen_decimal_mint,
+///     output_mint: usdc_mint,
+///     amount: 50_000_000_000_000_000_000_000, // 50,000 tokens at 18 decimals
+///     min_output_amount: 45_000_000_000,      // 45,000 USDC
+/// }
+/// ```
+#[cfg(feature = "large-amounts")]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LargeSwapParams {
+    /// Input token mint (source token)
+    pub input_mint: Pubkey,
+    
+    /// Output token mint (destination token)
+    ///
+    /// Must differ from `input_mint`.
+    pub output_mint: Pubkey,
+    
+    /// Amount of input tokens to swap, in the token's smallest unit
+    ///
+    /// Must be >= MIN_SWAP_AMOUNT (1).
+    pub amount: u128,
+    
+    /// Minimum output amount (for slippage protection), in the output
+    /// token's smallest unit
+    ///
+    /// Must be >= `Config::effective_min_output`.
+    pub min_output_amount: u128,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_execution::{fee_discount_bps_for_user, loyalty_volume};
+
+    #[test]
+    fn test_stats_record_accumulates() {
+        let mut stats = Stats { total_swaps: 0, total_volume: 0, total_fees: 0, bump: 0 };
+
+        stats.record(u64::MAX, 3).unwrap();
+        stats.record(u64::MAX, 3).unwrap();
+
+        assert_eq!(stats.total_swaps, 2);
+        assert_eq!(stats.total_volume, 2 * u64::MAX as u128);
+        assert_eq!(stats.total_fees, 6);
+    }
+
+    #[test]
+    fn test_user_stats_volume_reaches_a_discount_tier() {
+        let mut user_stats = UserStats { authority: Pubkey::default(), swap_count: 0, total_volume: 0, bump: 0 };
+        let (tier_volume, tier_discount_bps) = crate::constants::FEE_DISCOUNT_TIERS[0];
+
+        user_stats.record(tier_volume as u64 - 1).unwrap();
+        assert_eq!(fee_discount_bps_for_user(user_stats.total_volume), 0);
+
+        user_stats.record(1).unwrap();
+        assert_eq!(fee_discount_bps_for_user(user_stats.total_volume), tier_discount_bps);
+        assert_eq!(user_stats.swap_count, 2);
+    }
+
+    #[test]
+    fn test_dust_mint_volume_earns_no_discount() {
+        let mut user_stats = UserStats { authority: Pubkey::default(), swap_count: 0, total_volume: 0, bump: 0 };
+        let (usdc, dust, sol) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (top_tier_volume, _) = crate::constants::FEE_DISCOUNT_TIERS[2];
+
+        // u64::MAX raw units (~18 tokens of an 18-decimal dust mint) is above
+        // every tier, but neither side of the swap is the loyalty mint
+        assert!(u128::from(u64::MAX) > top_tier_volume);
+        user_stats.record(loyalty_volume(&usdc, &dust, &sol, u64::MAX, u64::MAX)).unwrap();
+        assert_eq!(user_stats.total_volume, 0);
+        assert_eq!(fee_discount_bps_for_user(user_stats.total_volume), 0);
+        assert_eq!(user_stats.swap_count, 1);
+
+        // Without a loyalty mint nothing counts
+        assert_eq!(loyalty_volume(&Pubkey::default(), &dust, &sol, u64::MAX, 1), 0);
+
+        // Loyalty mint volume counts on either side of the swap
+        assert_eq!(loyalty_volume(&usdc, &usdc, &dust, 7, u64::MAX), 7);
+        assert_eq!(loyalty_volume(&usdc, &dust, &usdc, u64::MAX, 9), 9);
+    }
+
+    #[test]
+    fn test_last_price_jump_within_window() {
+        let mut last_price = LastPrice {
+            input_mint: Pubkey::default(),
+            output_mint: Pubkey::default(),
+            price: 0,
+            timestamp: 0,
+            bump: 0,
+        };
+        let price = LastPrice::execution_price(1_000, 2_000).unwrap();
+        assert_eq!(price, 2 * LAST_PRICE_SCALE as u64);
+
+        // Nothing stored yet
+        assert_eq!(last_price.jump_bps(price, 1_000), None);
+
+        last_price.record(price, 1_000);
+        assert_eq!(last_price.jump_bps(price / 2, 1_030), Some(5_000));
+        assert_eq!(last_price.jump_bps(price * 2, 1_030), Some(10_000));
+        // A stored price older than the window is not compared
+        assert_eq!(last_price.jump_bps(price / 2, 1_000 + PRICE_JUMP_WINDOW_SECS + 1), None);
+
+        assert_eq!(LastPrice::execution_price(1, u64::MAX).unwrap(), u64::MAX);
+        assert!(LastPrice::execution_price(0, 1).is_err());
+    }
+
+    #[test]
+    fn test_fee_rate_anomaly() {
+        let stats = Stats { total_swaps: 2, total_volume: 2_000_000, total_fees: 6_000, bump: 0 };
+
+        // 30 bps realized
+        assert_eq!(stats.fee_rate_anomaly(30, 5).unwrap(), None);
+        assert_eq!(stats.fee_rate_anomaly(35, 5).unwrap(), None);
+        assert_eq!(stats.fee_rate_anomaly(300, 5).unwrap(), Some(30));
+        assert_eq!(stats.fee_rate_anomaly(20, 5).unwrap(), Some(30));
+
+        // Monitoring off, or nothing swapped yet
+        assert_eq!(stats.fee_rate_anomaly(0, 5).unwrap(), None);
+        let empty = Stats { total_swaps: 0, total_volume: 0, total_fees: 0, bump: 0 };
+        assert_eq!(empty.fee_rate_anomaly(300, 5).unwrap(), None);
+    }
+}
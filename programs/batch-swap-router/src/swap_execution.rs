@@ -14,8 +14,10 @@
 
 use anchor_lang::prelude::*;
 
-use crate::constants::PROTOCOL_FEE_BPS;
-use crate::security::calculate_fee_safe;
+use crate::constants::{FEE_DISCOUNT_TIERS, MIN_SWAP_AMOUNT, USE_FULL_BALANCE};
+use crate::errors::ErrorCode;
+use crate::security::{calculate_fee_safe, SafeMath};
+use crate::state::{SlippageBasis, SlippageFailureMode};
 use crate::utils;
 
 /// Result of a swap execution
@@ -61,20 +63,240 @@ fn execute_swap_with_validation_placeholder() {
 /// # Arguments
 ///
 /// * `amount` - Amount to calculate fee for
+/// * `fee_bps` - Protocol fee rate in basis points (`Config::fee_bps`)
+/// * `fee_exempt` - Whether the swap authority is fee-exempt (see `Config::is_fee_exempt`)
+/// * `max_fee` - Optional absolute cap on the fee (negotiated partner caps)
 ///
 /// # Returns
 ///
-/// * `Result<u64>` - Protocol fee amount
+/// * `Result<u64>` - Protocol fee amount (0 for exempt authorities, and
+///   always 0 with the `no-fees` feature)
 ///
 /// # Formula
 ///
-/// Fee = (amount * PROTOCOL_FEE_BPS) / 10000
+/// Fee = min((amount * fee_bps) / 10000, max_fee)
 ///
 /// # Security
 ///
 /// This function uses safe math operations to prevent integer overflow.
-pub fn calculate_protocol_fee(amount: u64) -> Result<u64> {
-    calculate_fee_safe(amount, PROTOCOL_FEE_BPS)
+pub fn calculate_protocol_fee(
+    amount: u64,
+    fee_bps: u16,
+    fee_exempt: bool,
+    max_fee: Option<u64>,
+) -> Result<u64> {
+    if fee_exempt || cfg!(feature = "no-fees") {
+        return Ok(0);
+    }
+    let fee = calculate_fee_safe(amount, u64::from(fee_bps))?;
+    Ok(max_fee.map_or(fee, |max_fee| fee.min(max_fee)))
+}
+
+/// Loyalty discount on the protocol fee rate for a user's volume
+///
+/// Looks up the highest [`FEE_DISCOUNT_TIERS`] tier the volume reaches.
+/// `execute_swap` subtracts the result from `Config::fee_bps`, clamped at 0
+/// (`fee_bps.saturating_sub(discount)`), before [`calculate_protocol_fee`]
+/// when the authority passes its `UserStats`.
+///
+/// # Arguments
+///
+/// * `volume` - The user's cumulative swap volume, in raw units of
+///   `Config::loyalty_mint` (see [`loyalty_volume`])
+///
+/// # Returns
+///
+/// * `u64` - The discount in basis points (0 below the first tier)
+pub fn fee_discount_bps_for_user(volume: u128) -> u64 {
+    FEE_DISCOUNT_TIERS
+        .iter()
+        .rev()
+        .find(|(min_volume, _)| volume >= *min_volume)
+        .map_or(0, |(_, discount_bps)| *discount_bps)
+}
+
+/// Loyalty volume of a swap, in raw units of the loyalty mint
+///
+/// Only swaps with `loyalty_mint` on one side have a comparable volume: the
+/// input amount when they spend it, the output amount when they receive it.
+/// Any other swap counts 0, so huge raw amounts of an unrelated (e.g.
+/// worthless 18-decimal) mint never reach a [`FEE_DISCOUNT_TIERS`] tier.
+///
+/// # Arguments
+///
+/// * `loyalty_mint` - `Config::loyalty_mint` (`Pubkey::default()` = none)
+/// * `input_mint` - The swap's input mint
+/// * `output_mint` - The swap's output mint
+/// * `input_amount` - Input amount of the swap
+/// * `output_amount` - Output amount received
+///
+/// # Returns
+///
+/// * `u64` - The volume to record in `UserStats` (0 without the loyalty mint)
+pub fn loyalty_volume(
+    loyalty_mint: &Pubkey,
+    input_mint: &Pubkey,
+    output_mint: &Pubkey,
+    input_amount: u64,
+    output_amount: u64,
+) -> u64 {
+    if *loyalty_mint == Pubkey::default() {
+        0
+    } else if input_mint == loyalty_mint {
+        input_amount
+    } else if output_mint == loyalty_mint {
+        output_amount
+    } else {
+        0
+    }
+}
+
+/// Calculate protocol fee for a `u128` swap amount
+///
+/// `u128` counterpart of [`calculate_protocol_fee`] used by the
+/// `batch_swap_large` instruction.
+///
+/// # Arguments
+///
+/// * `amount` - Amount to calculate fee for
+/// * `fee_bps` - Protocol fee rate in basis points (`Config::fee_bps`)
+/// * `fee_exempt` - Whether the swap authority is fee-exempt
+///
+/// # Returns
+///
+/// * `Result<u128>` - Protocol fee amount (rounded down, 0 for exempt
+///   authorities and with the `no-fees` feature)
+///
+/// # Security
+///
+/// `amount * fee_bps` is checked, so amounts close to `u128::MAX`
+/// return `ErrorCode::MathOverflow` instead of wrapping.
+#[cfg(feature = "large-amounts")]
+pub fn calculate_protocol_fee_u128(amount: u128, fee_bps: u16, fee_exempt: bool) -> Result<u128> {
+    if fee_exempt || cfg!(feature = "no-fees") {
+        return Ok(0);
+    }
+    amount
+        .safe_mul(u128::from(fee_bps))?
+        .safe_div(10000u128)
+}
+
+/// Split a protocol fee into its treasury and burn shares
+///
+/// # Arguments
+///
+/// * `protocol_fee` - The protocol fee charged on a swap
+/// * `burn_bps` - Share of the fee to burn, in basis points of the fee
+///   (`Config::burn_bps`, at most 10000)
+///
+/// # Returns
+///
+/// * `Result<(u64, u64)>` - `(treasury_fee, burned_fee)`, which always add
+///   up to `protocol_fee`
+///
+/// # Formula
+///
+/// burned = (protocol_fee * burn_bps) / 10000 (rounded down), and the
+/// treasury receives the rest, so rounding never burns more than configured.
+pub fn split_protocol_fee(protocol_fee: u64, burn_bps: u16) -> Result<(u64, u64)> {
+    let burned_fee = calculate_fee_safe(protocol_fee, u64::from(burn_bps))?;
+    Ok((protocol_fee.safe_sub(burned_fee)?, burned_fee))
+}
+
+/// Split a fee into its protocol and liquidity-provider parts
+///
+/// # Arguments
+///
+/// * `fee` - The fee charged on a swap
+/// * `fee_bps` - The fee rate it was charged at (`Config::fee_bps`)
+/// * `lp_fee_bps` - Part of the rate paid to liquidity providers
+///   (`Config::lp_fee_bps`, at most `fee_bps`)
+///
+/// # Returns
+///
+/// * `Result<(u64, u64)>` - `(protocol_fee, lp_fee)`, which always add up
+///   to `fee`
+///
+/// # Formula
+///
+/// lp = (fee * lp_fee_bps) / fee_bps (rounded down), and the protocol keeps
+/// the rest. Splitting the charged fee rather than the swap amount keeps the
+/// ratio for fees reduced by `max_fee` or exemptions.
+///
+/// # Errors
+///
+/// Returns `ErrorCode::InvalidLpFeeBps` if `lp_fee_bps` exceeds `fee_bps`.
+pub fn split_lp_fee(fee: u64, fee_bps: u16, lp_fee_bps: u16) -> Result<(u64, u64)> {
+    require!(lp_fee_bps <= fee_bps, ErrorCode::InvalidLpFeeBps);
+    if lp_fee_bps == 0 {
+        return Ok((fee, 0));
+    }
+    
+    let lp_fee = u128::from(fee)
+        .safe_mul(u128::from(lp_fee_bps))?
+        .safe_div(u128::from(fee_bps))?;
+    let lp_fee = u64::try_from(lp_fee).map_err(|_| ErrorCode::MathOverflow)?;
+    Ok((fee.safe_sub(lp_fee)?, lp_fee))
+}
+
+/// Compute the output a swap actually delivered
+///
+/// This is the first of the two output checks in `execute_swap` and answers
+/// "did the swap deliver anything at all?". Whether enough arrived is left to
+/// [`validate_slippage`]:
+///
+/// | Outcome                         | Error                | Log line                          |
+/// |---------------------------------|----------------------|-----------------------------------|
+/// | balance unchanged or decreased  | `InsufficientOutput` | `No output received: ...`         |
+/// | `0 < actual < min_output`       | `SlippageExceeded`   | `Slippage exceeded: received ...` |
+///
+/// # Arguments
+///
+/// * `balance_before` - Output token account balance before the swap
+/// * `balance_after` - Output token account balance after the swap
+///
+/// # Returns
+///
+/// * `Result<u64>` - The (non-zero) amount of output tokens received
+///
+/// # Errors
+///
+/// Returns `ErrorCode::InsufficientOutput` if no output tokens arrived.
+pub fn received_output(balance_before: u64, balance_after: u64) -> Result<u64> {
+    match balance_after.checked_sub(balance_before) {
+        Some(actual_output) if actual_output > 0 => Ok(actual_output),
+        _ => {
+            msg!(
+                "No output received: balance before {}, after {}",
+                balance_before,
+                balance_after
+            );
+            err!(ErrorCode::InsufficientOutput)
+        }
+    }
+}
+
+/// Resolve the `execute_swap` amount against the input balance
+///
+/// [`USE_FULL_BALANCE`] stands for the input token account's current
+/// balance; any other amount is used as given. The caller validates the
+/// resolved amount against `MIN_SWAP_AMOUNT` like an explicit one.
+///
+/// # Arguments
+///
+/// * `amount` - The `amount` argument of `execute_swap`
+/// * `input_balance` - The input token account balance when the swap runs
+///
+/// # Returns
+///
+/// * `u64` - The amount to swap (fee included)
+pub fn resolve_swap_amount(amount: u64, input_balance: u64) -> u64 {
+    if amount == USE_FULL_BALANCE {
+        msg!("Swapping the full input balance of {}", input_balance);
+        input_balance
+    } else {
+        amount
+    }
 }
 
 /// Validate slippage tolerance
@@ -88,35 +310,156 @@ pub fn calculate_protocol_fee(amount: u64) -> Result<u64> {
 /// * `actual_output` - Actual output amount received
 /// * `min_output_amount` - Minimum acceptable output amount
 /// * `max_slippage_bps` - Maximum acceptable slippage in basis points
+/// * `basis` - Side the relative slippage is measured on (see [`SlippageBasis`])
+/// * `failure_mode` - Whether a failed relative check reverts or is accepted
+///   at the minimum (see [`SlippageFailureMode`])
 ///
 /// # Returns
 ///
 /// * `Result<()>` - Returns Ok if slippage is acceptable, error otherwise
 ///
+/// # Errors
+///
+/// Returns `ErrorCode::SlippageExceeded` (logging `Slippage exceeded:
+/// received <actual> < minimum <min>`) if `actual_output` is below
+/// `min_output_amount`, or, in `SlippageFailureMode::Revert`, if the
+/// relative slippage exceeds `max_slippage_bps`.
+///
 /// # Security
 ///
 /// This function validates both absolute minimum and relative slippage tolerance
-/// to prevent slippage attacks.
+/// to prevent slippage attacks. Relative slippage is rounded up (see
+/// [`utils::calculate_slippage_ceil`] and
+/// [`utils::calculate_input_slippage_ceil`]) so it is never under-estimated.
 pub fn validate_slippage(
     expected_output: u64,
     actual_output: u64,
     min_output_amount: u64,
     max_slippage_bps: u64,
+    basis: SlippageBasis,
+    failure_mode: SlippageFailureMode,
 ) -> Result<()> {
     // Validate minimum output (absolute check)
+    if actual_output < min_output_amount {
+        msg!(
+            "Slippage exceeded: received {} < minimum {}",
+            actual_output,
+            min_output_amount
+        );
+    }
     crate::security::validate_min_output(actual_output, min_output_amount)?;
     
     // Validate slippage tolerance (relative check)
+    // Rounds up so a fractional basis point never lets a borderline swap through
     if expected_output > 0 && actual_output < expected_output {
-        if let Some(slippage_bps) = utils::calculate_slippage(expected_output, actual_output) {
-            // Validate slippage is within tolerance
-            crate::security::assert_valid_slippage(slippage_bps, max_slippage_bps)?;
+        let slippage_bps = match basis {
+            SlippageBasis::Output => utils::calculate_slippage_ceil(expected_output, actual_output),
+            SlippageBasis::Input => utils::calculate_input_slippage_ceil(expected_output, actual_output),
+        };
+        // No output at all is unbounded input-side slippage
+        let slippage_bps = slippage_bps.ok_or(ErrorCode::SlippageExceeded)?;
+        
+        // Validate slippage is within tolerance, unless the caller accepts
+        // any output that met the minimum (checked above)
+        if let Err(error) = crate::security::assert_valid_slippage(slippage_bps, max_slippage_bps) {
+            if failure_mode == SlippageFailureMode::Revert {
+                return Err(error);
+            }
+            msg!(
+                "Slippage of {} bps exceeds {} bps; accepted at the minimum output",
+                slippage_bps,
+                max_slippage_bps
+            );
         }
     }
     
     Ok(())
 }
 
+/// Check how a swap without a quote is handled
+///
+/// [`validate_slippage`] skips its relative check when `expected_output` is
+/// 0, leaving `min_output_amount` as the swap's only protection. Such swaps
+/// are only accepted with a minimum above the 1-unit floor (which protects
+/// nothing), and log a warning so the missing quote shows up in the
+/// transaction logs.
+///
+/// # Arguments
+///
+/// * `expected_output` - Expected output amount (0 = no quote)
+/// * `min_output_amount` - Minimum acceptable output amount
+/// * `require_expected_output` - Reject swaps without a quote
+///   (`Config::require_expected_output`)
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns Ok if the swap may proceed
+///
+/// # Errors
+///
+/// * `ErrorCode::InvalidAmount` - No quote while `require_expected_output` is set
+/// * `ErrorCode::InvalidMinOutput` - No quote and `min_output_amount` is at
+///   the `MIN_SWAP_AMOUNT` floor
+pub fn check_expected_output(
+    expected_output: u64,
+    min_output_amount: u64,
+    require_expected_output: bool,
+) -> Result<()> {
+    if expected_output > 0 {
+        return Ok(());
+    }
+    
+    if require_expected_output {
+        msg!("Rejected: expected_output is 0 and the config requires a quote");
+        return err!(ErrorCode::InvalidAmount);
+    }
+    
+    require!(
+        min_output_amount > MIN_SWAP_AMOUNT,
+        ErrorCode::InvalidMinOutput
+    );
+    
+    msg!(
+        "Warning: expected_output is 0, relative slippage is not enforced (only min_output_amount {})",
+        min_output_amount
+    );
+    Ok(())
+}
+
+/// Check that the quote behind `expected_output` is fresh enough
+///
+/// The age is measured with the cluster clock, so the client's clock only
+/// matters through `quote_timestamp`. A timestamp ahead of the cluster clock
+/// (clock drift) counts as age 0.
+///
+/// # Arguments
+///
+/// * `quote_timestamp` - Unix timestamp of the quote, set by the client
+/// * `max_quote_age` - Oldest accepted quote, in seconds (0 or less disables
+///   the check)
+/// * `now` - The current Unix timestamp (`Clock::unix_timestamp`)
+///
+/// # Returns
+///
+/// * `Result<()>` - Returns Ok if the quote may be used
+///
+/// # Errors
+///
+/// Returns `ErrorCode::QuoteExpired` (logging the quote's age) if the check
+/// is enabled and the quote is more than `max_quote_age` seconds old.
+pub fn check_quote_age(quote_timestamp: i64, max_quote_age: i64, now: i64) -> Result<()> {
+    if max_quote_age <= 0 {
+        return Ok(());
+    }
+    
+    let age = now.saturating_sub(quote_timestamp);
+    if age > max_quote_age {
+        msg!("Quote expired: {}s old, max age {}s", age, max_quote_age);
+        return err!(ErrorCode::QuoteExpired);
+    }
+    Ok(())
+}
+
 /// Calculate price impact for a swap
 ///
 /// Price impact measures how much the swap affects the market price.
@@ -159,6 +502,76 @@ pub fn calculate_price_impact(
     u64::try_from(impact).ok()
 }
 
+/// Calculate the volume-weighted average price of a batch's legs
+///
+/// Every leg is weighted by its input amount, so the result is the batch's
+/// total output over its total input. This is the single execution-quality
+/// number reported as `BatchSwapEvent::vwap_bps`.
+///
+/// # Arguments
+///
+/// * `legs` - `(input_amount, output_amount)` of each leg
+///
+/// # Returns
+///
+/// * `Result<u64>` - Output per unit of input in basis points (10000 = 1:1),
+///   0 for an empty batch, saturating at `u64::MAX`
+///
+/// # Formula
+///
+/// VWAP = (sum(output_amount) * 10000) / sum(input_amount)
+///
+/// # Security
+///
+/// Sums and the product are taken in `u128`, so a full batch of `u64::MAX`
+/// amounts cannot overflow.
+pub fn calculate_vwap_bps<I>(legs: I) -> Result<u64>
+where
+    I: IntoIterator<Item = (u64, u64)>,
+{
+    let mut total_input: u128 = 0;
+    let mut total_output: u128 = 0;
+    for (input_amount, output_amount) in legs {
+        total_input = total_input.safe_add(u128::from(input_amount))?;
+        total_output = total_output.safe_add(u128::from(output_amount))?;
+    }
+    
+    if total_input == 0 {
+        return Ok(0);
+    }
+    
+    let vwap = total_output.safe_mul(10000)?.safe_div(total_input)?;
+    Ok(u64::try_from(vwap).unwrap_or(u64::MAX))
+}
+
+/// Calculate the input-weighted average slippage tolerance of a batch's legs
+///
+/// The execution loop accumulates `sum(max_slippage_bps * input_amount)` in
+/// `u128` as it processes legs; this turns that sum into the average
+/// reported as `BatchSwapEvent::weighted_max_slippage_bps`.
+///
+/// # Arguments
+///
+/// * `weighted_max_slippage` - Sum over legs of `max_slippage_bps * input_amount`
+/// * `total_input` - Sum over legs of `input_amount`
+///
+/// # Returns
+///
+/// * `Result<u64>` - Average tolerance in basis points (rounded down), 0 for
+///   an empty batch, saturating at `u64::MAX`
+///
+/// # Formula
+///
+/// weighted_max_slippage_bps = sum(max_slippage_bps * input_amount) / sum(input_amount)
+pub fn calculate_weighted_max_slippage_bps(weighted_max_slippage: u128, total_input: u128) -> Result<u64> {
+    if total_input == 0 {
+        return Ok(0);
+    }
+    
+    let average = weighted_max_slippage.safe_div(total_input)?;
+    Ok(u64::try_from(average).unwrap_or(u64::MAX))
+}
+
 /// Get swap quote (placeholder for Jupiter integration)
 ///
 /// In production, this would:
@@ -193,3 +606,337 @@ pub fn get_swap_quote(
     Ok(_input_amount)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::amount_after_fee;
+
+    /// Default protocol fee rate (`PROTOCOL_FEE_BPS`)
+    const FEE_BPS: u16 = crate::constants::PROTOCOL_FEE_BPS as u16;
+
+    #[test]
+    fn test_split_protocol_fee() {
+        assert_eq!(split_protocol_fee(300, 0).unwrap(), (300, 0));
+        assert_eq!(split_protocol_fee(300, 2_500).unwrap(), (225, 75));
+        assert_eq!(split_protocol_fee(300, 10_000).unwrap(), (0, 300));
+        // Rounding favours the treasury
+        assert_eq!(split_protocol_fee(3, 5_000).unwrap(), (2, 1));
+        assert_eq!(split_protocol_fee(0, 5_000).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_split_lp_fee() {
+        // 10 of 30 bps to LPs
+        assert_eq!(split_lp_fee(300, 30, 10).unwrap(), (200, 100));
+        assert_eq!(split_lp_fee(300, 30, 0).unwrap(), (300, 0));
+        assert_eq!(split_lp_fee(300, 30, 30).unwrap(), (0, 300));
+        // Rounding favours the protocol
+        assert_eq!(split_lp_fee(2, 30, 10).unwrap(), (2, 0));
+        assert_eq!(split_lp_fee(0, 0, 0).unwrap(), (0, 0));
+        assert_eq!(split_lp_fee(300, 10, 30).unwrap_err(), ErrorCode::InvalidLpFeeBps.into());
+    }
+
+    #[test]
+    fn test_calculate_vwap_bps_weights_by_input() {
+        // 1:1 on 100 input and 1:2 on 300 input -> 700 / 400 = 1.75
+        assert_eq!(calculate_vwap_bps([(100, 100), (300, 600)]).unwrap(), 17_500);
+        assert_eq!(calculate_vwap_bps([(1_000, 995)]).unwrap(), 9_950);
+        assert_eq!(calculate_vwap_bps(std::iter::empty()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_calculate_weighted_max_slippage_bps() {
+        // 100 bps on 100 input and 500 bps on 300 input -> 160_000 / 400 = 400
+        let weighted = 100u128 * 100 + 500 * 300;
+        assert_eq!(calculate_weighted_max_slippage_bps(weighted, 400).unwrap(), 400);
+        assert_eq!(calculate_weighted_max_slippage_bps(0, 0).unwrap(), 0);
+        // A full batch of u64::MAX legs at the maximum tolerance fits in u128
+        let legs = u128::from(u64::MAX) * 10;
+        assert_eq!(calculate_weighted_max_slippage_bps(legs * 500, legs).unwrap(), 500);
+    }
+
+    #[test]
+    fn test_calculate_vwap_bps_does_not_overflow() {
+        let legs = vec![(u64::MAX, u64::MAX); 10];
+        assert_eq!(calculate_vwap_bps(legs).unwrap(), 10_000);
+        // Prices beyond u64 saturate instead of failing the batch
+        assert_eq!(calculate_vwap_bps([(1, u64::MAX)]).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_zero_output_is_insufficient_output() {
+        assert_eq!(received_output(500, 500).unwrap_err(), ErrorCode::InsufficientOutput.into());
+        assert_eq!(received_output(500, 499).unwrap_err(), ErrorCode::InsufficientOutput.into());
+        assert_eq!(received_output(500, 501).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_full_balance_sentinel_resolves_to_balance() {
+        assert_eq!(resolve_swap_amount(USE_FULL_BALANCE, 5_000), 5_000);
+        assert_eq!(resolve_swap_amount(1_000, 5_000), 1_000);
+        // An empty account resolves to 0, which the amount check rejects
+        assert_eq!(resolve_swap_amount(USE_FULL_BALANCE, 0), 0);
+    }
+
+    #[test]
+    fn test_quote_age_boundary() {
+        let now = 1_700_000_000;
+
+        assert!(check_quote_age(now - 30, 30, now).is_ok());
+        assert_eq!(check_quote_age(now - 31, 30, now).unwrap_err(), ErrorCode::QuoteExpired.into());
+        // Clock drift: a quote from the future is fresh
+        assert!(check_quote_age(now + 5, 30, now).is_ok());
+        // 0 disables the check, even without a timestamp
+        assert!(check_quote_age(0, 0, now).is_ok());
+    }
+
+    #[test]
+    fn test_fee_discount_tiers() {
+        assert_eq!(fee_discount_bps_for_user(0), 0);
+        assert_eq!(fee_discount_bps_for_user(999_999_999_999), 0);
+        assert_eq!(fee_discount_bps_for_user(1_000_000_000_000), 5);
+        assert_eq!(fee_discount_bps_for_user(50_000_000_000_000), 10);
+        assert_eq!(fee_discount_bps_for_user(u128::MAX), 15);
+        // The discount never exceeds the default fee rate
+        assert!(fee_discount_bps_for_user(u128::MAX) <= u64::from(FEE_BPS));
+    }
+
+    #[test]
+    fn test_partial_output_is_slippage_exceeded() {
+        let actual_output = received_output(0, 94).unwrap();
+
+        assert_eq!(
+            validate_slippage(100, actual_output, 95, 10_000, SlippageBasis::Output, SlippageFailureMode::Revert).unwrap_err(),
+            ErrorCode::SlippageExceeded.into()
+        );
+        assert!(validate_slippage(100, 95, 95, 10_000, SlippageBasis::Output, SlippageFailureMode::Revert).is_ok());
+    }
+
+    #[test]
+    fn test_swap_without_quote_relies_on_min_output() {
+        // Relative check skipped: any output at or above the minimum passes
+        assert!(validate_slippage(0, 1_000, 1_000, 0, SlippageBasis::Output, SlippageFailureMode::Revert).is_ok());
+
+        assert!(check_expected_output(0, 1_000, false).is_ok());
+        assert_eq!(
+            check_expected_output(0, MIN_SWAP_AMOUNT, false).unwrap_err(),
+            ErrorCode::InvalidMinOutput.into()
+        );
+        // A quote makes the floor minimum acceptable again
+        assert!(check_expected_output(1_000, MIN_SWAP_AMOUNT, false).is_ok());
+    }
+
+    #[test]
+    fn test_strict_config_requires_quote() {
+        assert_eq!(
+            check_expected_output(0, 1_000, true).unwrap_err(),
+            ErrorCode::InvalidAmount.into()
+        );
+        assert!(check_expected_output(1_000, 950, true).is_ok());
+    }
+
+    #[test]
+    fn test_validate_slippage_rejects_fractional_overshoot() {
+        // 1 short of 30_000 is 0.33 bps, which floors to 0 but must count as 1
+        assert!(validate_slippage(30_000, 29_999, 1, 0, SlippageBasis::Output, SlippageFailureMode::Revert).is_err());
+        assert!(validate_slippage(30_000, 29_999, 1, 1, SlippageBasis::Output, SlippageFailureMode::Revert).is_ok());
+    }
+
+    #[test]
+    fn test_validate_slippage_on_input_basis() {
+        // 80 of 100: 2000 bps of output given up, 2500 bps of extra input
+        assert!(validate_slippage(100, 80, 1, 2_000, SlippageBasis::Output, SlippageFailureMode::Revert).is_ok());
+        assert_eq!(
+            validate_slippage(100, 80, 1, 2_000, SlippageBasis::Input, SlippageFailureMode::Revert).unwrap_err(),
+            ErrorCode::SlippageExceeded.into()
+        );
+        assert!(validate_slippage(100, 80, 1, 2_500, SlippageBasis::Input, SlippageFailureMode::Revert).is_ok());
+        assert!(validate_slippage(100, 100, 1, 0, SlippageBasis::Input, SlippageFailureMode::Revert).is_ok());
+    }
+
+    #[test]
+    fn test_slippage_failure_modes_at_the_boundary() {
+        use SlippageFailureMode::{AcceptMin, Revert};
+
+        // An optimistic quote of 1_000 with a minimum of 900: 950 is 500 bps
+        // short of the quote, 1 bps over a 499 bps tolerance
+        for mode in [Revert, AcceptMin] {
+            assert!(validate_slippage(1_000, 950, 900, 500, SlippageBasis::Output, mode).is_ok());
+        }
+        assert_eq!(
+            validate_slippage(1_000, 950, 900, 499, SlippageBasis::Output, Revert).unwrap_err(),
+            ErrorCode::SlippageExceeded.into()
+        );
+        assert!(validate_slippage(1_000, 950, 900, 499, SlippageBasis::Output, AcceptMin).is_ok());
+
+        // Exactly the minimum is accepted, one unit below it never is
+        assert!(validate_slippage(1_000, 900, 900, 499, SlippageBasis::Output, AcceptMin).is_ok());
+        for mode in [Revert, AcceptMin] {
+            assert_eq!(
+                validate_slippage(1_000, 899, 900, 499, SlippageBasis::Output, mode).unwrap_err(),
+                ErrorCode::SlippageExceeded.into()
+            );
+        }
+    }
+
+    #[cfg(all(feature = "large-amounts", not(feature = "no-fees")))]
+    #[test]
+    fn test_calculate_protocol_fee_u128_matches_u64_path() {
+        for amount in [1u64, 10_000, u64::MAX] {
+            assert_eq!(
+                calculate_protocol_fee_u128(amount as u128, FEE_BPS, false).unwrap(),
+                calculate_protocol_fee(amount, FEE_BPS, false, None).unwrap() as u128
+            );
+        }
+        assert!(calculate_protocol_fee_u128(u128::MAX, FEE_BPS, false).is_err());
+    }
+
+    #[test]
+    fn test_fee_and_swap_amount_consume_exact_input() {
+        for amount in [1u64, 333, 10_000, 1_000_000_007, u64::MAX / 10_000] {
+            let fee = calculate_protocol_fee(amount, FEE_BPS, false, None).unwrap();
+            let swap_amount = amount_after_fee(amount, fee).unwrap();
+
+            assert_eq!(fee + swap_amount, amount);
+        }
+    }
+
+    /// Run one SPL Token instruction through the token program's processor
+    fn process_token_instruction(
+        instruction: anchor_lang::solana_program::instruction::Instruction,
+        accounts: &[AccountInfo],
+    ) {
+        use anchor_spl::token::spl_token::processor::Processor;
+        Processor::process(&instruction.program_id, accounts, &instruction.data).unwrap();
+    }
+
+    /// Transfer tokens between SPL Token accounts through the token program's processor
+    fn process_token_transfer<'a>(
+        from: &AccountInfo<'a>,
+        to: &AccountInfo<'a>,
+        authority: &AccountInfo<'a>,
+        amount: u64,
+    ) {
+        let instruction = anchor_spl::token::spl_token::instruction::transfer(
+            &anchor_spl::token::ID,
+            from.key,
+            to.key,
+            authority.key,
+            &[],
+            amount,
+        )
+        .unwrap();
+        process_token_instruction(instruction, &[from.clone(), to.clone(), authority.clone()]);
+    }
+
+    /// Token balance of a packed SPL Token account
+    fn token_amount(account: &AccountInfo) -> u64 {
+        use anchor_lang::solana_program::program_pack::Pack;
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data.borrow()).unwrap().amount
+    }
+
+    #[cfg(not(feature = "no-fees"))]
+    #[test]
+    fn test_fee_transfers_and_swap_drain_exact_input() {
+        use anchor_lang::solana_program::program_pack::Pack;
+        use anchor_spl::token::spl_token::{
+            self,
+            state::{Account as TokenAccount, AccountState, Mint},
+        };
+
+        // A full-rate fee split three ways: LP share, burn and treasury
+        let (fee_bps, lp_fee_bps, burn_bps) = (FEE_BPS, 10, 2_000);
+        let amount = 1_000_000_007u64;
+        let protocol_fee = calculate_protocol_fee(amount, fee_bps, false, None).unwrap();
+        let swap_amount = amount_after_fee(amount, protocol_fee).unwrap();
+        let (protocol_share, lp_fee) = split_lp_fee(protocol_fee, fee_bps, lp_fee_bps).unwrap();
+        let (treasury_fee, burned_fee) = split_protocol_fee(protocol_share, burn_bps).unwrap();
+        assert!(lp_fee > 0 && burned_fee > 0 && treasury_fee > 0);
+
+        let (mint_key, authority_key, token) = (Pubkey::new_unique(), Pubkey::new_unique(), spl_token::ID);
+        let account_data = |amount| {
+            let mut data = vec![0u8; TokenAccount::LEN];
+            TokenAccount {
+                mint: mint_key,
+                owner: authority_key,
+                amount,
+                state: AccountState::Initialized,
+                ..Default::default()
+            }
+            .pack_into_slice(&mut data);
+            data
+        };
+        let mut mint_data = vec![0u8; Mint::LEN];
+        Mint { supply: amount, decimals: 6, is_initialized: true, ..Default::default() }
+            .pack_into_slice(&mut mint_data);
+
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let (mut lamports, mut mint_lamports, mut authority_lamports) = ([0u64; 4], 0, 0);
+        let mut datas = [account_data(amount), account_data(0), account_data(0), account_data(0)];
+        let mut authority_data = vec![];
+        let [input_lamports, treasury_lamports, lp_lamports, route_lamports] = &mut lamports;
+        let [input_data, treasury_data, lp_data, route_data] = &mut datas;
+        let input = AccountInfo::new(&keys[0], false, true, input_lamports, input_data, &token, false, 0);
+        let treasury = AccountInfo::new(&keys[1], false, true, treasury_lamports, treasury_data, &token, false, 0);
+        let lp = AccountInfo::new(&keys[2], false, true, lp_lamports, lp_data, &token, false, 0);
+        // Stands in for the route's pool account the swap pays into
+        let route = AccountInfo::new(&keys[3], false, true, route_lamports, route_data, &token, false, 0);
+        let mint = AccountInfo::new(&mint_key, false, true, &mut mint_lamports, &mut mint_data, &token, false, 0);
+        let system = anchor_lang::system_program::ID;
+        let authority = AccountInfo::new(
+            &authority_key,
+            true,
+            false,
+            &mut authority_lamports,
+            &mut authority_data,
+            &system,
+            false,
+            0,
+        );
+
+        // The handler's STEP 7 sequence, then the swap of `swap_amount`
+        process_token_transfer(&input, &treasury, &authority, treasury_fee);
+        process_token_transfer(&input, &lp, &authority, lp_fee);
+        process_token_instruction(
+            spl_token::instruction::burn(&token, input.key, &mint_key, &authority_key, &[], burned_fee).unwrap(),
+            &[input.clone(), mint.clone(), authority.clone()],
+        );
+        assert_eq!(token_amount(&input), swap_amount);
+        process_token_transfer(&input, &route, &authority, swap_amount);
+
+        // Fees and swap together consume exactly `amount`, and each share
+        // lands where the event reports it
+        assert_eq!(token_amount(&input), 0);
+        assert_eq!(token_amount(&treasury), treasury_fee);
+        assert_eq!(token_amount(&lp), lp_fee);
+        assert_eq!(token_amount(&route), swap_amount);
+        assert_eq!(Mint::unpack(&mint.data.borrow()).unwrap().supply, amount - burned_fee);
+    }
+
+    #[cfg(not(feature = "no-fees"))]
+    #[test]
+    fn test_exempt_authority_pays_no_fee() {
+        for amount in [1u64, 10_000, u64::MAX] {
+            assert_eq!(calculate_protocol_fee(amount, FEE_BPS, true, None).unwrap(), 0);
+        }
+        assert_eq!(calculate_protocol_fee(10_000, FEE_BPS, false, None).unwrap(), 30);
+    }
+
+    #[cfg(not(feature = "no-fees"))]
+    #[test]
+    fn test_max_fee_caps_protocol_fee() {
+        // 30 bps of 10_000_000 is 30_000
+        assert_eq!(calculate_protocol_fee(10_000_000, FEE_BPS, false, Some(1_000)).unwrap(), 1_000);
+        assert_eq!(calculate_protocol_fee(10_000_000, FEE_BPS, false, Some(50_000)).unwrap(), 30_000);
+        assert_eq!(calculate_protocol_fee(10_000_000, FEE_BPS, true, Some(1_000)).unwrap(), 0);
+    }
+
+    #[cfg(feature = "no-fees")]
+    #[test]
+    fn test_no_fees_build_charges_nothing() {
+        for amount in [1u64, 10_000, u64::MAX] {
+            assert_eq!(calculate_protocol_fee(amount, FEE_BPS, false, None).unwrap(), 0);
+        }
+    }
+}
@@ -0,0 +1,445 @@
+//! # Test Utilities
+//!
+//! This module contains account fixtures for tests of the program and of
+//! downstream crates: mints, funded associated token accounts, the program's
+//! PDAs and the full account set of `execute_swap`. It replaces the mint,
+//! ATA and funding boilerplate every integration test otherwise repeats.
+//!
+//! Only compiled with the `test-utils` feature (and for the crate's own
+//! tests), so deployments never carry it.
+//!
+//! ## Runtimes
+//!
+//! The fixtures are plain account data (key, owner, lamports, bytes) and
+//! work with any test runtime: borrow them as `AccountInfo`s to test helpers
+//! directly, or load them into an SVM runtime such as litesvm (one
+//! `set_account` per [`TestAccount`]) and send an instruction built from
+//! [`ExecuteSwapFixture::account_metas`]. The crate itself does not depend
+//! on a runtime.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use batch_swap_router::test_utils::*;
+//!
+//! // Authority with 1,000 input tokens, empty output account, live config
+//! let mut fixture = build_execute_swap_ctx(1_000_000_000);
+//!
+//! for account in fixture.accounts() {
+//!     svm.set_account(account.key, Account {
+//!         lamports: account.lamports,
+//!         data: account.data.clone(),
+//!         owner: account.owner,
+//!         executable: account.executable,
+//!         rent_epoch: 0,
+//!     })?;
+//! }
+//! let metas = fixture.account_metas();
+//! ```
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program_pack::Pack, sysvar};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token::state::{
+    Account as SplTokenAccount, AccountState, Mint as SplMint,
+};
+
+use crate::constants::{
+    CONFIG_SEED, CONFIG_VERSION, LAST_PRICE_SEED, PAIR_STATS_SEED, PROTOCOL_FEE_BPS, STATS_SEED,
+};
+use crate::state::{Config, Stats};
+
+/// Owner of the SPL Token program account (the BPF loader)
+const BPF_LOADER_ID: Pubkey = pubkey!("BPFLoader2111111111111111111111111111111111");
+
+/// Owner of the System program account (the native loader)
+const NATIVE_LOADER_ID: Pubkey = pubkey!("NativeLoader1111111111111111111111111111111");
+
+/// Lamports of every fixture account, rent-exempt at any size used here
+pub const FIXTURE_LAMPORTS: u64 = 1_000_000_000;
+
+/// Decimals of the input mint of [`build_execute_swap_ctx`]
+pub const FIXTURE_INPUT_DECIMALS: u8 = 9;
+
+/// Decimals of the output mint of [`build_execute_swap_ctx`]
+pub const FIXTURE_OUTPUT_DECIMALS: u8 = 6;
+
+/// An account owned by the test: its key, owner, lamports, data and flags
+#[derive(Clone, Debug)]
+pub struct TestAccount {
+    /// Address of the account
+    pub key: Pubkey,
+
+    /// Program owning the account
+    pub owner: Pubkey,
+
+    /// Balance in lamports
+    pub lamports: u64,
+
+    /// Raw account data
+    pub data: Vec<u8>,
+
+    /// Whether the account signs the instruction
+    pub is_signer: bool,
+
+    /// Whether the instruction may write the account
+    pub is_writable: bool,
+
+    /// Whether the account is a program
+    pub executable: bool,
+}
+
+impl TestAccount {
+    /// A read-only, non-signing account holding [`FIXTURE_LAMPORTS`]
+    pub fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        Self {
+            key,
+            owner,
+            lamports: FIXTURE_LAMPORTS,
+            data,
+            is_signer: false,
+            is_writable: false,
+            executable: false,
+        }
+    }
+
+    /// Mark the account as a signer
+    #[must_use]
+    pub fn signer(mut self) -> Self {
+        self.is_signer = true;
+        self
+    }
+
+    /// Mark the account as writable
+    #[must_use]
+    pub fn writable(mut self) -> Self {
+        self.is_writable = true;
+        self
+    }
+
+    /// Borrow the account as an `AccountInfo`
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            self.executable,
+            0,
+        )
+    }
+}
+
+/// An initialized SPL Token mint with no supply or authorities
+///
+/// # Arguments
+///
+/// * `decimals` - Decimals of the mint
+pub fn setup_mint(decimals: u8) -> TestAccount {
+    let mut data = vec![0u8; SplMint::LEN];
+    SplMint {
+        decimals,
+        is_initialized: true,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+
+    TestAccount::new(Pubkey::new_unique(), anchor_spl::token::ID, data)
+}
+
+/// The associated token account of `owner` for `mint`, holding `amount`
+///
+/// # Arguments
+///
+/// * `owner` - Wallet owning the token account
+/// * `mint` - Mint of the token account
+/// * `amount` - Token balance, in raw units
+pub fn setup_funded_ata(owner: &Pubkey, mint: &Pubkey, amount: u64) -> TestAccount {
+    let mut data = vec![0u8; SplTokenAccount::LEN];
+    SplTokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+
+    TestAccount::new(get_associated_token_address(owner, mint), anchor_spl::token::ID, data).writable()
+}
+
+/// The `Config` PDA as `initialize_config` leaves it
+///
+/// # Arguments
+///
+/// * `admin` - Admin of the config
+pub fn setup_config(admin: &Pubkey) -> TestAccount {
+    let (key, bump) = Pubkey::find_program_address(&[CONFIG_SEED], &crate::ID);
+    let config = Config {
+        admin: *admin,
+        fee_exemptions: Vec::new(),
+        require_authority_is_payer: false,
+        bump,
+        version: CONFIG_VERSION,
+        fee_bps: PROTOCOL_FEE_BPS as u16,
+        fee_recipient: Pubkey::default(),
+        pending_admin: Pubkey::default(),
+        callback_programs: Vec::new(),
+        burn_bps: 0,
+        lp_fee_bps: 0,
+        lp_fee_recipient: Pubkey::default(),
+        require_expected_output: false,
+        max_distinct_mints: 0,
+        expected_fee_rate_bps: 0,
+        max_price_jump_bps: 0,
+        strict_ata: false,
+        treasury_is_token_account: true,
+        min_output_floor: 0,
+        paused: false,
+        loyalty_mint: Pubkey::default(),
+    };
+
+    TestAccount::new(key, crate::ID, program_account_data(&config, 8 + Config::INIT_SPACE))
+}
+
+/// The `Stats` PDA with no swaps recorded
+pub fn setup_stats() -> TestAccount {
+    let (key, bump) = Pubkey::find_program_address(&[STATS_SEED], &crate::ID);
+    let stats = Stats {
+        total_swaps: 0,
+        total_volume: 0,
+        total_fees: 0,
+        bump,
+    };
+
+    TestAccount::new(key, crate::ID, program_account_data(&stats, 8 + Stats::INIT_SPACE)).writable()
+}
+
+/// Accounts of an `execute_swap` call, in `ExecuteSwap` order
+///
+/// Built by [`build_execute_swap_ctx`]. The optional accounts
+/// (`lp_fee_account`, `recipient_token_account`, `fee_treasury`,
+/// `user_stats`) are unset.
+#[derive(Clone, Debug)]
+pub struct ExecuteSwapFixture {
+    /// The swap authority (signer, pays for the pair PDAs)
+    pub authority: TestAccount,
+
+    /// The authority's input token account, funded with the swap amount
+    pub input_token_account: TestAccount,
+
+    /// The authority's (empty) output token account
+    pub output_token_account: TestAccount,
+
+    /// The input mint
+    pub input_mint: TestAccount,
+
+    /// The output mint
+    pub output_mint: TestAccount,
+
+    /// The `Config` PDA (see [`setup_config`])
+    pub config: TestAccount,
+
+    /// The `Stats` PDA (see [`setup_stats`])
+    pub stats: TestAccount,
+
+    /// The pair's `PairStats` PDA, not yet created
+    pub pair_stats: TestAccount,
+
+    /// The pair's `LastPrice` PDA, not yet created
+    pub last_price: TestAccount,
+
+    /// An empty input-mint token account of a treasury wallet
+    pub fee_recipient: TestAccount,
+
+    /// The SPL Token program
+    pub token_program: TestAccount,
+
+    /// The System program
+    pub system_program: TestAccount,
+
+    /// The instructions sysvar
+    pub instructions_sysvar: TestAccount,
+}
+
+impl ExecuteSwapFixture {
+    /// Every account, in `ExecuteSwap` order
+    pub fn accounts(&self) -> [&TestAccount; 13] {
+        [
+            &self.authority,
+            &self.input_token_account,
+            &self.output_token_account,
+            &self.input_mint,
+            &self.output_mint,
+            &self.config,
+            &self.stats,
+            &self.pair_stats,
+            &self.last_price,
+            &self.fee_recipient,
+            &self.token_program,
+            &self.system_program,
+            &self.instructions_sysvar,
+        ]
+    }
+
+    /// Every account borrowed as an `AccountInfo`, in `ExecuteSwap` order
+    pub fn account_infos(&mut self) -> Vec<AccountInfo<'_>> {
+        [
+            &mut self.authority,
+            &mut self.input_token_account,
+            &mut self.output_token_account,
+            &mut self.input_mint,
+            &mut self.output_mint,
+            &mut self.config,
+            &mut self.stats,
+            &mut self.pair_stats,
+            &mut self.last_price,
+            &mut self.fee_recipient,
+            &mut self.token_program,
+            &mut self.system_program,
+            &mut self.instructions_sysvar,
+        ]
+        .into_iter()
+        .map(TestAccount::info)
+        .collect()
+    }
+
+    /// Account metas of the `execute_swap` instruction
+    ///
+    /// Unset optional accounts are passed as the program id, as Anchor
+    /// clients do.
+    pub fn account_metas(&self) -> Vec<AccountMeta> {
+        crate::accounts::ExecuteSwap {
+            authority: self.authority.key,
+            input_token_account: self.input_token_account.key,
+            output_token_account: self.output_token_account.key,
+            input_mint: self.input_mint.key,
+            output_mint: self.output_mint.key,
+            config: self.config.key,
+            stats: self.stats.key,
+            pair_stats: self.pair_stats.key,
+            last_price: self.last_price.key,
+            fee_recipient: self.fee_recipient.key,
+            lp_fee_account: None,
+            recipient_token_account: None,
+            fee_treasury: None,
+            user_stats: None,
+            token_program: self.token_program.key,
+            system_program: self.system_program.key,
+            instructions_sysvar: self.instructions_sysvar.key,
+        }
+        .to_account_metas(None)
+    }
+}
+
+/// Accounts for an `execute_swap` of `amount` input tokens
+///
+/// A fresh authority holds `amount` of a new input mint
+/// ([`FIXTURE_INPUT_DECIMALS`]) and an empty account of a new output mint
+/// ([`FIXTURE_OUTPUT_DECIMALS`]); the config is as initialized and the fee
+/// recipient is an empty input-mint account of another wallet.
+///
+/// # Arguments
+///
+/// * `amount` - Input balance of the authority, in raw units
+pub fn build_execute_swap_ctx(amount: u64) -> ExecuteSwapFixture {
+    let authority = TestAccount::new(Pubkey::new_unique(), system_program::ID, Vec::new())
+        .signer()
+        .writable();
+    let input_mint = setup_mint(FIXTURE_INPUT_DECIMALS).writable();
+    let output_mint = setup_mint(FIXTURE_OUTPUT_DECIMALS);
+    let pair_seeds = |seed: &[u8]| {
+        let (key, _) = Pubkey::find_program_address(
+            &[seed, input_mint.key.as_ref(), output_mint.key.as_ref()],
+            &crate::ID,
+        );
+        // Created by the swap itself (`init_if_needed`)
+        let mut account = TestAccount::new(key, system_program::ID, Vec::new()).writable();
+        account.lamports = 0;
+        account
+    };
+
+    ExecuteSwapFixture {
+        input_token_account: setup_funded_ata(&authority.key, &input_mint.key, amount),
+        output_token_account: setup_funded_ata(&authority.key, &output_mint.key, 0),
+        fee_recipient: setup_funded_ata(&Pubkey::new_unique(), &input_mint.key, 0),
+        config: setup_config(&Pubkey::new_unique()),
+        stats: setup_stats(),
+        pair_stats: pair_seeds(PAIR_STATS_SEED),
+        last_price: pair_seeds(LAST_PRICE_SEED),
+        token_program: program_account(anchor_spl::token::ID, BPF_LOADER_ID),
+        system_program: program_account(system_program::ID, NATIVE_LOADER_ID),
+        instructions_sysvar: TestAccount::new(sysvar::instructions::ID, sysvar::ID, Vec::new()),
+        authority,
+        input_mint,
+        output_mint,
+    }
+}
+
+/// An executable account for a program
+fn program_account(program_id: Pubkey, loader: Pubkey) -> TestAccount {
+    let mut account = TestAccount::new(program_id, loader, Vec::new());
+    account.executable = true;
+    account
+}
+
+/// Data of a program-owned account: discriminator, then the serialized state
+fn program_account_data<T: AccountSerialize>(state: &T, space: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(space);
+    state
+        .try_serialize(&mut data)
+        .expect("fixture state serializes");
+    data.resize(space, 0);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token::TokenAccount;
+
+    use crate::leg_accounts::LegOutputAccounts;
+    use crate::security::assert_canonical_ata;
+    use crate::state::SwapParams;
+
+    #[test]
+    fn test_execute_swap_fixture_is_consistent() {
+        let fixture = build_execute_swap_ctx(5_000);
+
+        let input = TokenAccount::try_deserialize(&mut &fixture.input_token_account.data[..]).unwrap();
+        assert_eq!((input.owner, input.mint, input.amount), (fixture.authority.key, fixture.input_mint.key, 5_000));
+        assert!(assert_canonical_ata(&fixture.input_token_account.key, &input.owner, &input.mint).is_ok());
+
+        let config = Config::try_deserialize(&mut &fixture.config.data[..]).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.fee_bps, PROTOCOL_FEE_BPS as u16);
+
+        // Thirteen accounts plus the four unset optional ones
+        let metas = fixture.account_metas();
+        assert_eq!(metas.len(), 17);
+        assert!(metas[0].is_signer && metas[0].is_writable);
+        assert_eq!(metas[5].pubkey, fixture.config.key);
+    }
+
+    #[test]
+    fn test_fixture_accounts_as_leg_outputs() {
+        let mut fixture = build_execute_swap_ctx(5_000);
+        let swap = SwapParams {
+            input_mint: fixture.input_mint.key,
+            output_mint: fixture.output_mint.key,
+            amount: 5_000,
+            min_output_amount: 4_000,
+            max_fee: None,
+            max_slippage_bps: None,
+            tag: 0,
+        };
+
+        // The output ATA is a writable token account of the leg's output mint
+        let accounts = fixture.account_infos();
+        let (outputs, rest) = LegOutputAccounts::resolve(&[swap], &accounts[2..3], false).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(outputs.balances().unwrap(), vec![0]);
+    }
+}
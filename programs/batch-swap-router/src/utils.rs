@@ -11,10 +11,11 @@
 //!
 //! - Address validation
 //! - Amount calculations
-//! - Slippage calculations
+//! - Slippage calculations (floor for display, ceil for validation)
 //! - Fee calculations
 //! - Price calculations
 //! - Format conversions
+//! - Priority fee lookup (instructions sysvar)
 //!
 //! ## Future Enhancements
 //!
@@ -26,6 +27,8 @@
 
 use anchor_lang::prelude::*;
 
+use crate::constants::{COMPUTE_BUDGET_PROGRAM_ID, SET_COMPUTE_UNIT_PRICE_TAG};
+
 // This module is currently a placeholder for future utility functions.
 // As the program evolves, utility functions can be added here.
 
@@ -121,6 +124,159 @@ pub fn calculate_slippage(expected: u64, actual: u64) -> Option<u64> {
     u64::try_from(slippage_bps).ok()
 }
 
+/// Calculate slippage percentage, rounding up
+///
+/// Same as [`calculate_slippage`], but rounds the basis points up instead of
+/// down. Flooring can under-report slippage by up to one basis point, which
+/// lets a borderline swap pass a tolerance check it should fail. On-chain
+/// validation uses this conservative variant; [`calculate_slippage`] is kept
+/// for display and events.
+///
+/// # Arguments
+///
+/// * `expected` - The expected amount
+/// * `actual` - The actual amount received
+///
+/// # Returns
+///
+/// * `Option<u64>` - Returns the slippage (in basis points, rounded up), or `None` if calculation fails
+///
+/// # Edge Cases
+///
+/// - Returns `None` if expected is 0 (division by zero)
+/// - Returns 0 if actual >= expected (no slippage)
+///
+/// # Example
+///
+/// ```rust,ignore
+/// // Expected 3, got 2 -> 33.33% slippage
+/// assert_eq!(calculate_slippage(3, 2), Some(3333));
+/// assert_eq!(calculate_slippage_ceil(3, 2), Some(3334));
+/// ```
+pub fn calculate_slippage_ceil(expected: u64, actual: u64) -> Option<u64> {
+    if expected == 0 {
+        return None;
+    }
+    
+    if actual >= expected {
+        return Some(0);
+    }
+    
+    let difference = expected.checked_sub(actual)?;
+    
+    // ceil(a / b) = (a + b - 1) / b, computed in u128 so it cannot overflow
+    let expected = expected as u128;
+    let slippage_bps = (difference as u128)
+        .checked_mul(10000)?
+        .checked_add(expected - 1)?
+        .checked_div(expected)?;
+    
+    u64::try_from(slippage_bps).ok()
+}
+
+/// Calculate input-denominated slippage, rounding up
+///
+/// Measures how much more input the swap consumed than the quote needed for
+/// the output actually received. At the quoted price, `actual` output costs
+/// `input * actual / expected`, so the extra input is
+/// `(expected - actual) / actual` of it, independent of the input amount.
+/// See `SlippageBasis::Input`.
+///
+/// # Arguments
+///
+/// * `expected` - The expected (quoted) output amount
+/// * `actual` - The actual output amount received
+///
+/// # Returns
+///
+/// * `Option<u64>` - The slippage (in basis points, rounded up), or `None` if calculation fails
+///
+/// # Edge Cases
+///
+/// - Returns `None` if expected or actual is 0 (no reference amount)
+/// - Returns 0 if actual >= expected (no slippage)
+///
+/// # Example
+///
+/// ```rust,ignore
+/// // Expected 100, got 80 -> 20% less output, but 25% more input per output
+/// assert_eq!(calculate_slippage_ceil(100, 80), Some(2000));
+/// assert_eq!(calculate_input_slippage_ceil(100, 80), Some(2500));
+/// ```
+pub fn calculate_input_slippage_ceil(expected: u64, actual: u64) -> Option<u64> {
+    if expected == 0 || actual == 0 {
+        return None;
+    }
+    
+    if actual >= expected {
+        return Some(0);
+    }
+    
+    let difference = expected.checked_sub(actual)?;
+    
+    // Same ceiling division as `calculate_slippage_ceil`, over the actual output
+    let actual = actual as u128;
+    let slippage_bps = (difference as u128)
+        .checked_mul(10000)?
+        .checked_add(actual - 1)?
+        .checked_div(actual)?;
+    
+    u64::try_from(slippage_bps).ok()
+}
+
+/// Convert a `u128` amount to an SPL token amount
+///
+/// SPL token balances and transfers are `u64`. This conversion never
+/// truncates: amounts above `u64::MAX` are rejected.
+///
+/// # Arguments
+///
+/// * `amount` - The `u128` amount to convert
+///
+/// # Returns
+///
+/// * `Result<u64>` - The amount as a `u64`
+///
+/// # Errors
+///
+/// Returns `ErrorCode::MathOverflow` if `amount` exceeds `u64::MAX`
+#[cfg(feature = "large-amounts")]
+pub fn to_token_amount(amount: u128) -> Result<u64> {
+    u64::try_from(amount).map_err(|_| crate::errors::ErrorCode::MathOverflow.into())
+}
+
+/// Calculate slippage percentage for `u128` amounts, rounding up
+///
+/// `u128` counterpart of [`calculate_slippage_ceil`].
+///
+/// # Arguments
+///
+/// * `expected` - The expected amount
+/// * `actual` - The actual amount received
+///
+/// # Returns
+///
+/// * `Option<u64>` - The slippage (in basis points, rounded up), or `None` if
+///   `expected` is 0 or the calculation overflows
+#[cfg(feature = "large-amounts")]
+pub fn calculate_slippage_ceil_u128(expected: u128, actual: u128) -> Option<u64> {
+    if expected == 0 {
+        return None;
+    }
+    
+    if actual >= expected {
+        return Some(0);
+    }
+    
+    let difference = expected.checked_sub(actual)?;
+    let slippage_bps = difference
+        .checked_mul(10000)?
+        .checked_add(expected - 1)?
+        .checked_div(expected)?;
+    
+    u64::try_from(slippage_bps).ok()
+}
+
 /// Validate slippage tolerance
 ///
 /// This function validates that the actual amount received is within the
@@ -197,3 +353,136 @@ pub fn is_slippage_acceptable(expected: u64, actual: u64, tolerance_bps: u64) ->
 }
 
 
+
+/// Read the transaction's compute unit price from the instructions sysvar
+///
+/// Walks the top-level instructions for a Compute Budget
+/// `SetComputeUnitPrice` instruction. The runtime rejects transactions with
+/// two of them, so the first one found is the price the transaction paid.
+///
+/// The lookup is informational and never fails: a transaction without the
+/// instruction (no priority fee), malformed instruction data or an
+/// unreadable sysvar all read as 0.
+///
+/// # Arguments
+///
+/// * `instructions_sysvar` - The instructions sysvar account
+///
+/// # Returns
+///
+/// * `u64` - The priority fee in micro-lamports per compute unit, or 0 if none
+///   was set
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let priority_fee_microlamports = read_compute_unit_price(&ctx.accounts.instructions_sysvar);
+/// ```
+pub fn read_compute_unit_price(instructions_sysvar: &AccountInfo) -> u64 {
+    let mut index = 0;
+    while let Ok(instruction) =
+        solana_instructions_sysvar::load_instruction_at_checked(index, instructions_sysvar)
+    {
+        if instruction.program_id == COMPUTE_BUDGET_PROGRAM_ID
+            && instruction.data.first() == Some(&SET_COMPUTE_UNIT_PRICE_TAG)
+        {
+            return instruction
+                .data
+                .get(1..9)
+                .and_then(|price| price.try_into().ok())
+                .map_or(0, u64::from_le_bytes);
+        }
+        index += 1;
+    }
+    
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_slippage_floors() {
+        assert_eq!(calculate_slippage(3, 2), Some(3333));
+        assert_eq!(calculate_slippage(100, 95), Some(500));
+        assert_eq!(calculate_slippage(0, 1), None);
+    }
+
+    #[test]
+    fn test_calculate_slippage_ceil_rounds_up_fractions() {
+        assert_eq!(calculate_slippage_ceil(3, 2), Some(3334));
+        assert_eq!(calculate_slippage_ceil(30_001, 30_000), Some(1));
+        assert_eq!(calculate_slippage_ceil(7, 1), Some(8572));
+    }
+
+    #[test]
+    fn test_calculate_slippage_ceil_exact_values() {
+        assert_eq!(calculate_slippage_ceil(100, 95), Some(500));
+        assert_eq!(calculate_slippage_ceil(100, 100), Some(0));
+        assert_eq!(calculate_slippage_ceil(100, 105), Some(0));
+        assert_eq!(calculate_slippage_ceil(100, 0), Some(10000));
+        assert_eq!(calculate_slippage_ceil(0, 1), None);
+    }
+
+    #[test]
+    fn test_calculate_input_slippage_ceil() {
+        assert_eq!(calculate_input_slippage_ceil(100, 80), Some(2500));
+        assert_eq!(calculate_input_slippage_ceil(3, 2), Some(5000));
+        assert_eq!(calculate_input_slippage_ceil(30_001, 30_000), Some(1));
+        assert_eq!(calculate_input_slippage_ceil(100, 100), Some(0));
+        assert_eq!(calculate_input_slippage_ceil(100, 0), None);
+        assert_eq!(calculate_input_slippage_ceil(0, 1), None);
+    }
+
+    #[cfg(feature = "large-amounts")]
+    #[test]
+    fn test_u128_helpers() {
+        assert_eq!(to_token_amount(u64::MAX as u128).unwrap(), u64::MAX);
+        assert!(to_token_amount(u64::MAX as u128 + 1).is_err());
+        assert_eq!(calculate_slippage_ceil_u128(3, 2), Some(3334));
+        assert_eq!(calculate_slippage_ceil_u128(u64::MAX as u128 * 4, u64::MAX as u128 * 3), Some(2500));
+    }
+
+    #[test]
+    fn test_calculate_slippage_ceil_large_amounts() {
+        assert_eq!(calculate_slippage_ceil(u64::MAX, u64::MAX - 1), Some(1));
+        assert_eq!(calculate_slippage_ceil(u64::MAX, 0), Some(10000));
+    }
+
+    fn sysvar_price(instructions: &[(Pubkey, Vec<u8>)]) -> u64 {
+        use anchor_lang::solana_program::sysvar::instructions::BorrowedInstruction;
+
+        let instructions: Vec<_> = instructions
+            .iter()
+            .map(|(program_id, data)| BorrowedInstruction { program_id, accounts: vec![], data })
+            .collect();
+        let mut data = solana_instructions_sysvar::construct_instructions_data(&instructions);
+        let mut lamports = 0;
+        let key = solana_instructions_sysvar::ID;
+        let owner = Pubkey::default();
+        let sysvar = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+
+        read_compute_unit_price(&sysvar)
+    }
+
+    #[test]
+    fn test_read_compute_unit_price() {
+        let mut set_price = vec![SET_COMPUTE_UNIT_PRICE_TAG];
+        set_price.extend(25_000u64.to_le_bytes());
+        // SetComputeUnitLimit (tag 2) carries a u32 and is not the price
+        let set_limit = vec![2, 0x40, 0x0d, 0x03, 0x00];
+
+        assert_eq!(
+            sysvar_price(&[
+                (COMPUTE_BUDGET_PROGRAM_ID, set_limit.clone()),
+                (COMPUTE_BUDGET_PROGRAM_ID, set_price),
+                (crate::ID, vec![]),
+            ]),
+            25_000
+        );
+        assert_eq!(sysvar_price(&[(COMPUTE_BUDGET_PROGRAM_ID, set_limit), (crate::ID, vec![])]), 0);
+        // Truncated price data reads as no priority fee
+        assert_eq!(sysvar_price(&[(COMPUTE_BUDGET_PROGRAM_ID, vec![SET_COMPUTE_UNIT_PRICE_TAG, 1])]), 0);
+    }
+}
@@ -5,11 +5,14 @@ import {
   PublicKey, 
   Keypair, 
   SystemProgram,
+  ComputeBudgetProgram,
   LAMPORTS_PER_SOL 
 } from "@solana/web3.js";
 import { 
   TOKEN_PROGRAM_ID, 
+  TOKEN_2022_PROGRAM_ID,
   getOrCreateAssociatedTokenAccount,
+  createAccount,
   createMint,
   mintTo,
   getAccount,
@@ -22,6 +25,63 @@ describe("batch-swap-router", () => {
   const provider = anchor.getProvider() as anchor.AnchorProvider;
   const program = anchor.workspace.BatchSwapRouter as Program<BatchSwapRouter>;
 
+  // `memo_hash` argument for transactions without an SPL memo
+  const NO_MEMO = Array(32).fill(0);
+
+  // Instruction format version (matches the program's INSTRUCTION_VERSION)
+  const VERSION = 1;
+
+  // `base_mint` argument when batch totals aren't needed (matches no leg)
+  const NO_BASE_MINT = PublicKey.default;
+
+  // `callback_program` argument for batches without per-leg callbacks
+  const NO_CALLBACK = null;
+
+  // `max_total_fee` argument of batch_swap without a batch fee cap
+  const NO_FEE_CAP = new anchor.BN(0);
+
+  // `leg_output_accounts` argument of batch_swap when no leg output accounts are passed
+  const NO_LEG_OUTPUTS = false;
+
+  // `global_max_slippage_bps` argument of batch_swap when no batch-level limit is set
+  const NO_BATCH_SLIPPAGE_LIMIT = 0;
+
+  // `shared_input_account` argument of batch_swap when no shared input account is passed
+  const NO_SHARED_INPUT = false;
+
+  // `tag` value for untagged swaps
+  const NO_TAG = new anchor.BN(0);
+
+  // `recipient` argument of execute_swap when output goes to the authority
+  const NO_RECIPIENT = null;
+
+  // `amount` of execute_swap that swaps the full input balance (u64::MAX)
+  const USE_FULL_BALANCE = new anchor.BN("18446744073709551615");
+
+  // `slippage_basis` argument of execute_swap (default output-side check)
+  const OUTPUT_BASIS = { output: {} };
+
+  // `quote_timestamp` / `max_quote_age` arguments of execute_swap (no expiry)
+  const NO_QUOTE_TIMESTAMP = new anchor.BN(0);
+  const NO_MAX_QUOTE_AGE = new anchor.BN(0);
+
+  // `fee_denomination` argument of execute_swap
+  const TOKEN_FEE = { token: {} };
+  const LAMPORT_FEE = { lamports: {} };
+
+  // `slippage_failure_mode` argument of execute_swap (default: revert)
+  const REVERT_ON_SLIPPAGE = { revert: {} };
+
+  // PDA seeds (match the program's constants.rs and the client's pda module)
+  const CONFIG_SEED = Buffer.from("config");
+  const STATS_SEED = Buffer.from("stats");
+  const USER_STATS_SEED = Buffer.from("user_stats");
+
+  const configPda = () => PublicKey.findProgramAddressSync([CONFIG_SEED], program.programId);
+  const statsPda = () => PublicKey.findProgramAddressSync([STATS_SEED], program.programId);
+  const userStatsPda = (owner: PublicKey) =>
+    PublicKey.findProgramAddressSync([USER_STATS_SEED, owner.toBuffer()], program.programId);
+
   // Test accounts
   let authority: Keypair;
   let user: Keypair;
@@ -115,6 +175,14 @@ describe("batch-swap-router", () => {
       authority,
       1000 * 10 ** 6 // 1000 tokens with 6 decimals
     );
+
+    // Create the program config; the provider wallet becomes admin
+    await program.methods
+      .initializeConfig()
+      .accounts({
+        admin: provider.wallet.publicKey,
+      })
+      .rpc();
   });
 
   describe("batch_swap", () => {
@@ -125,11 +193,14 @@ describe("batch-swap-router", () => {
           outputMint: mintB,
           amount: new anchor.BN(100 * 10 ** 9), // 100 tokens
           minOutputAmount: new anchor.BN(90 * 10 ** 6), // 90 tokens minimum
+          maxFee: null, // no absolute fee cap
+          maxSlippageBps: null, // global MAX_SLIPPAGE_BPS
+          tag: NO_TAG, // untagged
         },
       ];
 
       const tx = await program.methods
-        .batchSwap(swaps)
+        .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
         .accounts({
           authority: authority.publicKey,
           systemProgram: SystemProgram.programId,
@@ -146,10 +217,118 @@ describe("batch-swap-router", () => {
       expect(transaction?.meta?.err).to.be.null;
     });
 
+    it("Checks leg output accounts against the legs' output mints", async () => {
+      const swaps = [
+        {
+          inputMint: mintA,
+          outputMint: mintB,
+          amount: new anchor.BN(10 * 10 ** 9),
+          minOutputAmount: new anchor.BN(9 * 10 ** 6),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
+        },
+      ];
+      const submit = (outputAccount: PublicKey, globalMaxSlippageBps = NO_BATCH_SLIPPAGE_LIMIT) =>
+        program.methods
+          .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, true, globalMaxSlippageBps, NO_SHARED_INPUT)
+          .accounts({
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts([{ pubkey: outputAccount, isSigner: false, isWritable: true }])
+          .signers([authority])
+          .rpc();
+
+      // Routed client-side, the leg received nothing inside the instruction,
+      // which counts as a total loss against the leg's own tolerance, with
+      // or without a batch slippage limit
+      for (const globalMaxSlippageBps of [NO_BATCH_SLIPPAGE_LIMIT, 1]) {
+        try {
+          await submit(tokenAccountB, globalMaxSlippageBps);
+          expect.fail("Should have thrown an error");
+        } catch (err) {
+          expect(err.error?.errorCode?.code).to.equal("BatchSlippageExceeded");
+        }
+      }
+
+      try {
+        await submit(tokenAccountA);
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("RouteAccountsMismatch");
+      }
+    });
+
+    it("Requires leg output accounts for a batch slippage limit", async () => {
+      // Slippage is measured on the leg output accounts
+      const swaps = [
+        {
+          inputMint: mintA,
+          outputMint: mintB,
+          amount: new anchor.BN(10 * 10 ** 9),
+          minOutputAmount: new anchor.BN(9 * 10 ** 6),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
+        },
+      ];
+
+      try {
+        await program.methods
+          .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, 50, NO_SHARED_INPUT)
+          .accounts({
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("RouteAccountsMismatch");
+      }
+    });
+
+    it("Checks a shared input account covers every leg up front", async () => {
+      const { amount: balance } = await getAccount(provider.connection, tokenAccountA);
+      const legOf = (amount: bigint) => ({
+        inputMint: mintA,
+        outputMint: mintB,
+        amount: new anchor.BN(amount.toString()),
+        minOutputAmount: new anchor.BN(10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
+      });
+      const submit = (swaps) =>
+        program.methods
+          .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, true)
+          .accounts({
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts([{ pubkey: tokenAccountA, isSigner: false, isWritable: true }])
+          .signers([authority])
+          .rpc();
+
+      // One mint A account fans out into three legs it can cover together
+      const third = balance / BigInt(3);
+      await submit([legOf(third), legOf(third), legOf(third)]);
+
+      // Each leg fits on its own, but not all of them together
+      const half = balance / BigInt(2) + BigInt(1);
+      try {
+        await submit([legOf(half), legOf(half)]);
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InsufficientFunds");
+      }
+    });
+
     it("Fails with empty swaps array", async () => {
       try {
         await program.methods
-          .batchSwap([])
+          .batchSwap(VERSION, [], NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
           .accounts({
             authority: authority.publicKey,
             systemProgram: SystemProgram.programId,
@@ -163,6 +342,74 @@ describe("batch-swap-router", () => {
       }
     });
 
+    it("Fails with an unsupported instruction version", async () => {
+      const swaps = [
+        {
+          inputMint: mintA,
+          outputMint: mintB,
+          amount: new anchor.BN(10 * 10 ** 9),
+          minOutputAmount: new anchor.BN(9 * 10 ** 6),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
+        },
+      ];
+
+      try {
+        await program.methods
+          .batchSwap(99, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+          .accounts({
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("UnsupportedInstructionVersion");
+      }
+    });
+
+    it("Rejects a batch over the config's distinct mint cap", async () => {
+      // Two legs over three mints: A -> B, B -> C
+      const mintC = Keypair.generate().publicKey;
+      const leg = (inputMint: PublicKey, outputMint: PublicKey) => ({
+        inputMint,
+        outputMint,
+        amount: new anchor.BN(10 * 10 ** 9),
+        minOutputAmount: new anchor.BN(9 * 10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
+      });
+
+      await program.methods
+        .updateConfig(null, null, null, null, null, null, null, 2, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await program.methods
+          .batchSwap(VERSION, [leg(mintA, mintB), leg(mintB, mintC)], NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+          .accounts({
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("TooManyMints");
+      } finally {
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, null, 0, null, null, null, null, null, null, null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
     it("Fails with too many swaps", async () => {
       // Create 11 swaps (max is 10)
       const swaps = Array.from({ length: 11 }, () => ({
@@ -170,11 +417,14 @@ describe("batch-swap-router", () => {
         outputMint: mintB,
         amount: new anchor.BN(100 * 10 ** 9),
         minOutputAmount: new anchor.BN(90 * 10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
       }));
 
       try {
         await program.methods
-          .batchSwap(swaps)
+          .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
           .accounts({
             authority: authority.publicKey,
             systemProgram: SystemProgram.programId,
@@ -197,12 +447,15 @@ describe("batch-swap-router", () => {
           outputMint: mintB,
           amount: new anchor.BN(0),
           minOutputAmount: new anchor.BN(90 * 10 ** 6),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
         },
       ];
 
       try {
         await program.methods
-          .batchSwap(swaps)
+          .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
           .accounts({
             authority: authority.publicKey,
             systemProgram: SystemProgram.programId,
@@ -225,12 +478,15 @@ describe("batch-swap-router", () => {
           outputMint: mintA, // Same mint
           amount: new anchor.BN(100 * 10 ** 9),
           minOutputAmount: new anchor.BN(90 * 10 ** 6),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
         },
       ];
 
       try {
         await program.methods
-          .batchSwap(swaps)
+          .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
           .accounts({
             authority: authority.publicKey,
             systemProgram: SystemProgram.programId,
@@ -253,12 +509,15 @@ describe("batch-swap-router", () => {
           outputMint: mintB,
           amount: new anchor.BN(100 * 10 ** 9),
           minOutputAmount: new anchor.BN(0),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
         },
       ];
 
       try {
         await program.methods
-          .batchSwap(swaps)
+          .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
           .accounts({
             authority: authority.publicKey,
             systemProgram: SystemProgram.programId,
@@ -279,17 +538,23 @@ describe("batch-swap-router", () => {
           outputMint: mintB,
           amount: new anchor.BN(50 * 10 ** 9),
           minOutputAmount: new anchor.BN(45 * 10 ** 6),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
         },
         {
           inputMint: mintB,
           outputMint: mintA,
           amount: new anchor.BN(30 * 10 ** 6),
           minOutputAmount: new anchor.BN(25 * 10 ** 9),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
         },
       ];
 
       const tx = await program.methods
-        .batchSwap(swaps)
+        .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
         .accounts({
           authority: authority.publicKey,
           systemProgram: SystemProgram.programId,
@@ -304,121 +569,1777 @@ describe("batch-swap-router", () => {
       });
       expect(transaction?.meta?.err).to.be.null;
     });
-  });
-
-  describe("execute_swap", () => {
-    it("Executes swap with valid parameters", async () => {
-      const amount = new anchor.BN(100 * 10 ** 9); // 100 tokens
 
-      // Get initial balances
-      const fromAccountBefore = await getAccount(
-        provider.connection,
-        tokenAccountAUser
-      );
-      const toAccountBefore = await getAccount(
+    it("Processes dependent legs in index order", async () => {
+      // Leg 0 swaps A -> B into the intermediate B account, leg 1 spends
+      // that same B account for B -> C
+      const mintC = await createMint(
         provider.connection,
-        tokenAccountA
+        authority,
+        authority.publicKey,
+        null,
+        6
       );
 
+      const swaps = [
+        {
+          inputMint: mintA,
+          outputMint: mintB,
+          amount: new anchor.BN(50 * 10 ** 9),
+          minOutputAmount: new anchor.BN(45 * 10 ** 6),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
+        },
+        {
+          inputMint: mintB,
+          outputMint: mintC,
+          amount: new anchor.BN(45 * 10 ** 6),
+          minOutputAmount: new anchor.BN(40 * 10 ** 6),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
+        },
+      ];
+
       const tx = await program.methods
-        .executeSwap(amount)
+        .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
         .accounts({
-          authority: user.publicKey,
-          from: tokenAccountAUser,
-          to: tokenAccountA,
-          tokenProgram: TOKEN_PROGRAM_ID,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
         })
-        .signers([user])
+        .signers([authority])
         .rpc();
 
-      console.log("Execute swap transaction:", tx);
-
-      // Verify transaction was successful
       const transaction = await provider.connection.getTransaction(tx, {
         commitment: "confirmed",
       });
       expect(transaction?.meta?.err).to.be.null;
 
-      // Verify balances changed
-      const fromAccountAfter = await getAccount(
-        provider.connection,
-        tokenAccountAUser
-      );
-      const toAccountAfter = await getAccount(
-        provider.connection,
-        tokenAccountA
-      );
+      // Per-leg events are emitted in leg order with their index
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const events = Array.from(parser.parseLogs(transaction.meta.logMessages));
+      const legs = events
+        .filter((event) => event.name === "swapLegEvent")
+        .map((event) => event.data);
 
-      expect(fromAccountAfter.amount.toString()).to.equal(
-        (BigInt(fromAccountBefore.amount.toString()) - BigInt(amount.toString())).toString()
-      );
-      expect(toAccountAfter.amount.toString()).to.equal(
-        (BigInt(toAccountBefore.amount.toString()) + BigInt(amount.toString())).toString()
-      );
+      expect(legs.map((leg) => leg.legIndex)).to.deep.equal([0, 1]);
+      expect(legs[0].outputMint.toBase58()).to.equal(mintB.toBase58());
+      expect(legs[1].inputMint.toBase58()).to.equal(mintB.toBase58());
+      expect(legs[1].outputMint.toBase58()).to.equal(mintC.toBase58());
+
+      // Each leg also reports through the single-swap schema, marked as a batch leg
+      const executed = events
+        .filter((event) => event.name === "swapExecutedEvent")
+        .map((event) => event.data);
+      expect(executed.map((swap) => swap.batch)).to.deep.equal([true, true]);
+      expect(executed.map((swap) => swap.legIndex)).to.deep.equal([0, 1]);
     });
+  });
 
-    it("Fails with zero amount", async () => {
-      try {
-        await program.methods
-          .executeSwap(new anchor.BN(0))
-          .accounts({
-            authority: user.publicKey,
-            from: tokenAccountAUser,
-            to: tokenAccountA,
-            tokenProgram: TOKEN_PROGRAM_ID,
-          })
-          .signers([user])
-          .rpc();
+  describe("malformed batches", () => {
+    const U64_MAX = new anchor.BN("18446744073709551615");
+
+    const submit = (swaps) =>
+      program.methods
+        .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+        .accounts({
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
 
+    const expectError = async (swaps, code: string) => {
+      try {
+        await submit(swaps);
         expect.fail("Should have thrown an error");
       } catch (err) {
-        expect(err.error?.errorMessage).to.equal(
-          "Invalid swap amount (must be > 0)"
-        );
+        expect(err.error?.errorCode?.code).to.equal(code);
       }
+    };
+
+    it("Accepts a batch of exactly MAX_BATCH_SIZE legs", async () => {
+      const swaps = Array.from({ length: 10 }, () => ({
+        inputMint: mintA,
+        outputMint: mintB,
+        amount: new anchor.BN(10 ** 9),
+        minOutputAmount: new anchor.BN(10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
+      }));
+
+      await submit(swaps);
     });
 
-    it("Fails with mismatched account mints", async () => {
-      try {
-        await program.methods
-          .executeSwap(new anchor.BN(100 * 10 ** 9))
-          .accounts({
-            authority: user.publicKey,
-            from: tokenAccountAUser, // Mint A
-            to: tokenAccountB, // Mint B - mismatch!
-            tokenProgram: TOKEN_PROGRAM_ID,
-          })
-          .signers([user])
-          .rpc();
+    it("Rejects a default mint anywhere in the batch", async () => {
+      const swaps = Array.from({ length: 10 }, () => ({
+        inputMint: mintA,
+        outputMint: mintB,
+        amount: new anchor.BN(10 ** 9),
+        minOutputAmount: new anchor.BN(10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
+      }));
+      swaps[9].outputMint = PublicKey.default;
 
-        expect.fail("Should have thrown an error");
-      } catch (err) {
-        // This will fail at the Anchor account validation level
-        expect(err).to.exist;
-      }
+      await expectError(swaps, "InvalidAccount");
     });
 
-    it("Fails when authority doesn't own source account", async () => {
-      // Try to use authority's account but sign with user
-      try {
-        await program.methods
-          .executeSwap(new anchor.BN(100 * 10 ** 9))
-          .accounts({
-            authority: user.publicKey, // User is signing
-            from: tokenAccountA, // But this is authority's account
-            to: tokenAccountAUser,
-            tokenProgram: TOKEN_PROGRAM_ID,
-          })
-          .signers([user])
-          .rpc();
+    it("Rejects a zero min output on a u64::MAX leg", async () => {
+      await expectError(
+        [{ inputMint: mintA, outputMint: mintB, amount: U64_MAX, minOutputAmount: new anchor.BN(0), maxFee: null, maxSlippageBps: null, tag: NO_TAG }],
+        "InvalidMinOutput"
+      );
+    });
 
-        expect.fail("Should have thrown an error");
-      } catch (err) {
-        expect(err.error?.errorMessage).to.equal(
-          "Invalid authority (must be token account owner)"
-        );
-      }
+    it("Reports MathOverflow when u64::MAX legs overflow the batch total", async () => {
+      const leg = { inputMint: mintA, outputMint: mintB, amount: U64_MAX, minOutputAmount: U64_MAX, maxFee: null, maxSlippageBps: null, tag: NO_TAG };
+
+      await expectError([leg, leg], "MathOverflow");
     });
   });
-});
 
+  describe("stats", () => {
+    it("Counts every batch leg in the program-wide stats", async () => {
+      const [statsAddress] = statsPda();
+      const before = await program.account.stats.fetch(statsAddress);
+
+      await program.methods
+        .batchSwap(VERSION, [
+          {
+            inputMint: mintA,
+            outputMint: mintB,
+            amount: new anchor.BN(10 * 10 ** 9),
+            minOutputAmount: new anchor.BN(9 * 10 ** 6),
+            maxFee: null,
+            maxSlippageBps: null,
+            tag: NO_TAG,
+          },
+          {
+            inputMint: mintB,
+            outputMint: mintA,
+            amount: new anchor.BN(10 * 10 ** 6),
+            minOutputAmount: new anchor.BN(9 * 10 ** 9),
+            maxFee: null,
+            maxSlippageBps: null,
+            tag: NO_TAG,
+          },
+        ], NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .signers([authority])
+        .rpc();
+
+      const after = await program.account.stats.fetch(statsAddress);
+      expect(after.totalSwaps.sub(before.totalSwaps).toNumber()).to.equal(2);
+    });
+  });
+
+  describe("batch_ops", () => {
+    // Built lazily: the mints are created in the top-level `before`
+    const swapOp = () => ({
+      swap: {
+        0: {
+          inputMint: mintA,
+          outputMint: mintB,
+          amount: new anchor.BN(10 ** 9),
+          minOutputAmount: new anchor.BN(10 ** 6),
+          maxFee: null,
+          maxSlippageBps: null,
+          tag: NO_TAG,
+        },
+      },
+    });
+
+    const submit = (ops, remainingAccounts = []) =>
+      program.methods
+        .batchOps(VERSION, ops, true)
+        .accounts({
+          authority: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([user])
+        .rpc();
+
+    const transferAccounts = () => [
+      { pubkey: tokenAccountAUser, isSigner: false, isWritable: true },
+      { pubkey: tokenAccountA, isSigner: false, isWritable: true },
+    ];
+
+    it("Runs a transfer and a swap atomically", async () => {
+      const amount = 5 * 10 ** 9;
+      const before = await getAccount(provider.connection, tokenAccountA);
+
+      await submit(
+        [{ transfer: { mint: mintA, to: tokenAccountA, amount: new anchor.BN(amount) } }, swapOp()],
+        transferAccounts()
+      );
+
+      const after = await getAccount(provider.connection, tokenAccountA);
+      expect(Number(after.amount - before.amount)).to.equal(amount);
+    });
+
+    it("Rejects a transfer without its token accounts", async () => {
+      try {
+        await submit([{ transfer: { mint: mintA, to: tokenAccountA, amount: new anchor.BN(10 ** 9) } }]);
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAccount");
+      }
+    });
+
+    it("Rejects Token-2022 transfer accounts with the SPL Token program", async () => {
+      const mint2022 = await createMint(
+        provider.connection, authority, authority.publicKey, null, 9, undefined, undefined, TOKEN_2022_PROGRAM_ID
+      );
+      const [source, destination] = await Promise.all(
+        [user.publicKey, authority.publicKey].map((owner) =>
+          createAccount(provider.connection, authority, mint2022, owner, undefined, undefined, TOKEN_2022_PROGRAM_ID)
+        )
+      );
+
+      try {
+        await submit(
+          [{ transfer: { mint: mint2022, to: destination, amount: new anchor.BN(1) } }],
+          [
+            { pubkey: source, isSigner: false, isWritable: true },
+            { pubkey: destination, isSigner: false, isWritable: true },
+          ]
+        );
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("TokenProgramMismatch");
+      }
+    });
+
+    it("Caps swaps and transfers together at MAX_BATCH_SIZE", async () => {
+      const transfer = { transfer: { mint: mintA, to: tokenAccountA, amount: new anchor.BN(10 ** 9) } };
+      const ops = [...Array(6).fill(swapOp()), ...Array(5).fill(transfer)];
+
+      try {
+        await submit(ops, Array(5).fill(transferAccounts()).flat());
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("TooManySwaps");
+      }
+    });
+  });
+
+  describe("emit_events", () => {
+    it("Skips events but still updates stats when disabled", async () => {
+      const [statsAddress] = statsPda();
+      const before = await program.account.stats.fetch(statsAddress);
+
+      const tx = await program.methods
+        .batchSwap(
+          VERSION,
+          [
+            {
+              inputMint: mintA,
+              outputMint: mintB,
+              amount: new anchor.BN(10 * 10 ** 9),
+              minOutputAmount: new anchor.BN(9 * 10 ** 6),
+              maxFee: null,
+              maxSlippageBps: null,
+              tag: NO_TAG,
+            },
+          ],
+          NO_BASE_MINT,
+          false,
+          NO_MEMO,
+          NO_CALLBACK,
+          NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT
+        )
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      expect(Array.from(parser.parseLogs(transaction.meta.logMessages))).to.be.empty;
+
+      const after = await program.account.stats.fetch(statsAddress);
+      expect(after.totalSwaps.sub(before.totalSwaps).toNumber()).to.equal(1);
+    });
+  });
+
+  describe("base mint accounting", () => {
+    it("Totals outputs only over legs paying out in the base mint", async () => {
+      const tx = await program.methods
+        .batchSwap(
+          VERSION,
+          [
+            {
+              inputMint: mintA,
+              outputMint: mintB,
+              amount: new anchor.BN(10 * 10 ** 9),
+              minOutputAmount: new anchor.BN(9 * 10 ** 6),
+              maxFee: null,
+              maxSlippageBps: null,
+              tag: NO_TAG,
+            },
+            {
+              inputMint: mintB,
+              outputMint: mintA,
+              amount: new anchor.BN(10 * 10 ** 6),
+              minOutputAmount: new anchor.BN(9 * 10 ** 9),
+              maxFee: null,
+              maxSlippageBps: null,
+              tag: NO_TAG,
+            },
+          ],
+          mintB,
+          true,
+          NO_MEMO,
+          NO_CALLBACK,
+          NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT
+        )
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const event = Array.from(parser.parseLogs(transaction.meta.logMessages)).find(
+        (e) => e.name === "batchSwapEvent"
+      );
+
+      expect(event.data.baseMint.toBase58()).to.equal(mintB.toBase58());
+      // Only the A -> B leg pays out in the base mint
+      expect(event.data.totalOutputAmount.toString()).to.equal((9 * 10 ** 6).toString());
+      // Both legs count towards the blended price: 9.009e9 out / 1.001e10 in
+      expect(event.data.vwapBps.toNumber()).to.equal(9_000);
+      // Neither leg tightens its tolerance, so both weigh in at MAX_SLIPPAGE_BPS
+      expect(event.data.weightedMaxSlippageBps.toNumber()).to.equal(500);
+      // No compute unit price was set
+      expect(event.data.priorityFeeMicrolamports.toNumber()).to.equal(0);
+      // Matches the program's EVENT_SCHEMA_VERSION
+      expect(event.data.schemaVersion).to.equal(5);
+    });
+  });
+
+  describe("priority fee reporting", () => {
+    it("Records the compute unit price the transaction paid", async () => {
+      const tx = await program.methods
+        .batchSwap(
+          VERSION,
+          [
+            {
+              inputMint: mintA,
+              outputMint: mintB,
+              amount: new anchor.BN(10 * 10 ** 9),
+              minOutputAmount: new anchor.BN(9 * 10 ** 6),
+              maxFee: null,
+              maxSlippageBps: null,
+              tag: NO_TAG,
+            },
+          ],
+          NO_BASE_MINT,
+          true,
+          NO_MEMO,
+          NO_CALLBACK,
+          NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT
+        )
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitPrice({ microLamports: 25_000 }),
+        ])
+        .signers([authority])
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const event = Array.from(parser.parseLogs(transaction.meta.logMessages)).find(
+        (e) => e.name === "batchSwapEvent"
+      );
+
+      expect(event.data.priorityFeeMicrolamports.toNumber()).to.equal(25_000);
+    });
+  });
+
+  describe("authority/payer policy", () => {
+    const swaps = () => [
+      {
+        inputMint: mintA,
+        outputMint: mintB,
+        amount: new anchor.BN(10 * 10 ** 9),
+        minOutputAmount: new anchor.BN(9 * 10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
+      },
+    ];
+
+    // An instruction signed by the provider wallet alongside the authority
+    const foreignSignerIx = () =>
+      SystemProgram.transfer({
+        fromPubkey: provider.wallet.publicKey,
+        toPubkey: authority.publicKey,
+        lamports: 1,
+      });
+
+    after(async () => {
+      await program.methods
+        .updateConfig(false, null, null, null, null, null, null, null, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+    });
+
+    it("Allows foreign signers by default", async () => {
+      await program.methods
+        .batchSwap(VERSION, swaps(), NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .preInstructions([foreignSignerIx()])
+        .signers([authority])
+        .rpc();
+    });
+
+    it("Rejects foreign signers when require_authority_is_payer is set", async () => {
+      await program.methods
+        .updateConfig(true, null, null, null, null, null, null, null, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await program.methods
+          .batchSwap(VERSION, swaps(), NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+          .accounts({
+            authority: authority.publicKey,
+            feeRecipient: tokenAccountA,
+          })
+          .preInstructions([foreignSignerIx()])
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("AuthorityPayerMismatch");
+      }
+    });
+
+    it("Keeps the payer policy on updates that leave it unset", async () => {
+      await program.methods
+        .updateConfig(null, 30, null, null, null, null, null, null, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      const config = await program.account.config.fetch(configPda()[0]);
+      expect(config.requireAuthorityIsPayer).to.equal(true);
+    });
+
+    it("Rejects config updates from non-admins", async () => {
+      try {
+        await program.methods
+          .updateConfig(true, null, null, null, null, null, null, null, null, null, null, null, null, null, null)
+          .accounts({ admin: user.publicKey })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAuthority");
+      }
+    });
+  });
+
+  describe("fee caps", () => {
+    const legFee = async (maxFee: anchor.BN | null): Promise<string> => {
+      const tx = await program.methods
+        .batchSwap(
+          VERSION,
+          [
+            {
+              inputMint: mintA,
+              outputMint: mintB,
+              amount: new anchor.BN(100 * 10 ** 9),
+              minOutputAmount: new anchor.BN(90 * 10 ** 6),
+              maxFee,
+              maxSlippageBps: null,
+              tag: NO_TAG,
+            },
+          ],
+          NO_BASE_MINT,
+          true,
+          NO_MEMO,
+          NO_CALLBACK,
+          NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT
+        )
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const event = Array.from(parser.parseLogs(transaction.meta.logMessages)).find(
+        (e) => e.name === "swapLegEvent"
+      );
+      return event.data.protocolFee.toString();
+    };
+
+    it("Charges the capped fee when max_fee is below the bps fee", async () => {
+      // 0.3% of 100 tokens is 0.3 tokens; the cap is 0.1 tokens
+      expect(await legFee(new anchor.BN(10 ** 8))).to.equal((10 ** 8).toString());
+    });
+
+    it("Charges the bps fee when it is below max_fee", async () => {
+      expect(await legFee(new anchor.BN(10 ** 9))).to.equal((3 * 10 ** 8).toString());
+    });
+
+    it("Rejects a zero max_fee", async () => {
+      try {
+        await legFee(new anchor.BN(0));
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidMaxFee");
+      }
+    });
+
+    it("Enforces the batch-wide max_total_fee", async () => {
+      // Two legs of 100 tokens pay 0.3 tokens each: 0.6 tokens in total
+      const leg = {
+        inputMint: mintA,
+        outputMint: mintB,
+        amount: new anchor.BN(100 * 10 ** 9),
+        minOutputAmount: new anchor.BN(90 * 10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
+      };
+      const batchWithFeeCap = (maxTotalFee: anchor.BN) =>
+        program.methods
+          .batchSwap(VERSION, [leg, leg], NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, maxTotalFee, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+          .accounts({
+            authority: authority.publicKey,
+            feeRecipient: tokenAccountA,
+          })
+          .signers([authority])
+          .rpc();
+
+      await batchWithFeeCap(new anchor.BN(6 * 10 ** 8));
+
+      try {
+        await batchWithFeeCap(new anchor.BN(6 * 10 ** 8 - 1));
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("BatchFeeCapExceeded");
+      }
+    });
+  });
+
+  describe("per-leg slippage", () => {
+    const legWithSlippage = (maxSlippageBps: number | null) => ({
+      inputMint: mintA,
+      outputMint: mintB,
+      amount: new anchor.BN(10 * 10 ** 9),
+      minOutputAmount: new anchor.BN(9 * 10 ** 6),
+      maxFee: null,
+      maxSlippageBps,
+      tag: NO_TAG,
+    });
+
+    it("Accepts a batch mixing tight and default tolerances", async () => {
+      await program.methods
+        .batchSwap(
+          VERSION,
+          [legWithSlippage(50), legWithSlippage(null), legWithSlippage(500)],
+          NO_BASE_MINT,
+          true,
+          NO_MEMO,
+          NO_CALLBACK,
+          NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT
+        )
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .signers([authority])
+        .rpc();
+    });
+
+    it("Rejects a leg tolerance above MAX_SLIPPAGE_BPS", async () => {
+      try {
+        await program.methods
+          .batchSwap(VERSION, [legWithSlippage(501)], NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+          .accounts({
+            authority: authority.publicKey,
+            feeRecipient: tokenAccountA,
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("SlippageExceeded");
+      }
+    });
+  });
+
+  describe("swap tags", () => {
+    it("Echoes each leg's tag into its SwapLegEvent", async () => {
+      const leg = (tag: number) => ({
+        inputMint: mintA,
+        outputMint: mintB,
+        amount: new anchor.BN(10 * 10 ** 9),
+        minOutputAmount: new anchor.BN(9 * 10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: new anchor.BN(tag),
+      });
+
+      const tx = await program.methods
+        .batchSwap(VERSION, [leg(42), leg(0)], NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const tags = Array.from(parser.parseLogs(transaction.meta.logMessages))
+        .filter((e) => e.name === "swapLegEvent")
+        .map((e) => e.data.tag.toString());
+
+      expect(tags).to.deep.equal(["42", "0"]);
+    });
+  });
+
+  describe("fee exemptions", () => {
+    const swaps = () => [
+      {
+        inputMint: mintA,
+        outputMint: mintB,
+        amount: new anchor.BN(100 * 10 ** 9),
+        minOutputAmount: new anchor.BN(90 * 10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
+      },
+    ];
+
+    const batchFees = async (): Promise<string> => {
+      const tx = await program.methods
+        .batchSwap(VERSION, swaps(), NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const event = Array.from(parser.parseLogs(transaction.meta.logMessages)).find(
+        (e) => e.name === "batchSwapEvent"
+      );
+      return event.data.totalProtocolFees.toString();
+    };
+
+    it("Charges fees to non-exempt authorities", async () => {
+      // 0.3% of 100 tokens
+      expect(await batchFees()).to.equal((3 * 10 ** 8).toString());
+    });
+
+    it("Skips fees for exempt authorities", async () => {
+      await program.methods
+        .setFeeExemption(authority.publicKey, true)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      expect(await batchFees()).to.equal("0");
+    });
+
+    it("Charges fees again after the exemption is removed", async () => {
+      await program.methods
+        .setFeeExemption(authority.publicKey, false)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      expect(await batchFees()).to.equal((3 * 10 ** 8).toString());
+    });
+
+    it("Rejects exemption changes from non-admins", async () => {
+      try {
+        await program.methods
+          .setFeeExemption(user.publicKey, true)
+          .accounts({ admin: user.publicKey })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAuthority");
+      }
+    });
+  });
+
+  describe("fee settings", () => {
+    const DEFAULT_FEE_BPS = 30;
+
+    after(async () => {
+      await program.methods
+        .updateConfig(false, DEFAULT_FEE_BPS, PublicKey.default, 0, 0, PublicKey.default, false, 0, 0, 0, false, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+    });
+
+    it("Emits ConfigUpdatedEvent when the fee changes", async () => {
+      const tx = await program.methods
+        .updateConfig(null, 20, null, null, null, null, null, null, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const event = Array.from(parser.parseLogs(transaction.meta.logMessages)).find(
+        (e) => e.name === "configUpdatedEvent"
+      );
+
+      expect(event.data.admin.toBase58()).to.equal(provider.wallet.publicKey.toBase58());
+      expect(event.data.oldFeeBps).to.equal(DEFAULT_FEE_BPS);
+      expect(event.data.newFeeBps).to.equal(20);
+      expect(event.data.newRecipient.toBase58()).to.equal(PublicKey.default.toBase58());
+    });
+
+    it("Does not emit ConfigUpdatedEvent for policy-only updates", async () => {
+      const tx = await program.methods
+        .updateConfig(null, null, null, null, null, null, null, null, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const events = Array.from(parser.parseLogs(transaction.meta.logMessages));
+
+      expect(events.find((e) => e.name === "configUpdatedEvent")).to.be.undefined;
+    });
+
+    it("Reports burn share changes in ConfigUpdatedEvent", async () => {
+      const tx = await program.methods
+        .updateConfig(null, null, null, 2_500, null, null, null, null, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const event = Array.from(parser.parseLogs(transaction.meta.logMessages)).find(
+        (e) => e.name === "configUpdatedEvent"
+      );
+
+      expect(event.data.oldBurnBps).to.equal(0);
+      expect(event.data.newBurnBps).to.equal(2_500);
+    });
+
+    it("Rejects burning more than the whole fee", async () => {
+      try {
+        await program.methods
+          .updateConfig(null, null, null, 10_001, null, null, null, null, null, null, null, null, null, null, null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidBurnBps");
+      }
+    });
+
+    it("Reports LP fee changes in ConfigUpdatedEvent", async () => {
+      const pool = Keypair.generate().publicKey;
+      const tx = await program.methods
+        .updateConfig(null, null, null, null, 10, pool, null, null, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const event = Array.from(parser.parseLogs(transaction.meta.logMessages)).find(
+        (e) => e.name === "configUpdatedEvent"
+      );
+
+      expect(event.data.oldLpFeeBps).to.equal(0);
+      expect(event.data.newLpFeeBps).to.equal(10);
+      expect(event.data.newLpRecipient.toBase58()).to.equal(pool.toBase58());
+    });
+
+    it("Rejects an LP share above the fee rate", async () => {
+      try {
+        // The LP part is carved out of the fee rate, so it can't exceed it
+        await program.methods
+          .updateConfig(null, 20, null, null, 21, null, null, null, null, null, null, null, null, null, null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidLpFeeBps");
+      }
+    });
+
+    it("Rejects fees above MAX_PROTOCOL_FEE_BPS", async () => {
+      try {
+        await program.methods
+          .updateConfig(null, 101, null, null, null, null, null, null, null, null, null, null, null, null, null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidFeeBps");
+      }
+    });
+
+    it("Sets and bounds the monitored fee rate", async () => {
+      await program.methods
+        .updateConfig(null, null, null, null, null, null, null, null, 30, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      const config = await program.account.config.fetch(configPda()[0]);
+      expect(config.expectedFeeRateBps).to.equal(30);
+
+      try {
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, null, null, 101, null, null, null, null, null, null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidFeeBps");
+      }
+    });
+  });
+
+  describe("price jump detection", () => {
+    after(async () => {
+      await program.methods
+        .updateConfig(null, null, null, null, null, null, null, null, null, 0, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+    });
+
+    it("Stores the price jump limit", async () => {
+      await program.methods
+        .updateConfig(null, null, null, null, null, null, null, null, null, 500, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      const config = await program.account.config.fetch(configPda()[0]);
+      expect(config.maxPriceJumpBps).to.equal(500);
+    });
+  });
+
+  describe("protocol pause", () => {
+    const swaps = () => [
+      {
+        inputMint: mintA,
+        outputMint: mintB,
+        amount: new anchor.BN(10 * 10 ** 9),
+        minOutputAmount: new anchor.BN(9 * 10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
+      },
+    ];
+
+    const batchSwap = () =>
+      program.methods
+        .batchSwap(VERSION, swaps(), NO_BASE_MINT, true, NO_MEMO, NO_CALLBACK, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+        .accounts({
+          authority: authority.publicKey,
+          feeRecipient: tokenAccountA,
+        })
+        .signers([authority]);
+
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .updateConfig(null, null, null, null, null, null, null, null, null, null, null, null, null, paused, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+    after(async () => {
+      await setPaused(false);
+    });
+
+    it("Rejects swaps while paused and accepts them again once unpaused", async () => {
+      await setPaused(true);
+      expect((await program.account.config.fetch(configPda()[0])).paused).to.be.true;
+
+      try {
+        await batchSwap().rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("ProgramPaused");
+      }
+
+      await setPaused(false);
+      await batchSwap().rpc();
+    });
+  });
+
+  describe("config migration", () => {
+    it("Rejects migrating a config already on the current layout", async () => {
+      try {
+        await program.methods
+          .migrateConfig()
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("ConfigVersionMismatch");
+      }
+    });
+  });
+
+  describe("per-leg callbacks", () => {
+    const swaps = [
+      {
+        inputMint: mintA,
+        outputMint: mintB,
+        amount: new anchor.BN(10 * 10 ** 9),
+        minOutputAmount: new anchor.BN(9 * 10 ** 6),
+        maxFee: null,
+        maxSlippageBps: null,
+        tag: NO_TAG,
+      },
+    ];
+
+    it("Rejects callback programs that are not allowlisted", async () => {
+      try {
+        await program.methods
+          .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, TOKEN_PROGRAM_ID, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+          .accounts({
+            authority: authority.publicKey,
+            feeRecipient: tokenAccountA,
+          })
+          .remainingAccounts([
+            { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          ])
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("CallbackProgramNotAllowed");
+      }
+    });
+
+    it("Rejects allowlist changes from non-admins", async () => {
+      try {
+        await program.methods
+          .setCallbackProgram(TOKEN_PROGRAM_ID, true)
+          .accounts({ admin: user.publicKey })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAuthority");
+      }
+    });
+
+    it("Requires the callback program as the first remaining account", async () => {
+      await program.methods
+        .setCallbackProgram(TOKEN_PROGRAM_ID, true)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await program.methods
+          .batchSwap(VERSION, swaps, NO_BASE_MINT, true, NO_MEMO, TOKEN_PROGRAM_ID, NO_FEE_CAP, NO_LEG_OUTPUTS, NO_BATCH_SLIPPAGE_LIMIT, NO_SHARED_INPUT)
+          .accounts({
+            authority: authority.publicKey,
+            feeRecipient: tokenAccountA,
+          })
+          .signers([authority])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAccount");
+      } finally {
+        await program.methods
+          .setCallbackProgram(TOKEN_PROGRAM_ID, false)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+  });
+
+  describe("admin transfer", () => {
+    const newAdmin = Keypair.generate();
+
+    after(async () => {
+      // Hand the config back to the provider wallet for later tests
+      await program.methods
+        .proposeAdmin(provider.wallet.publicKey)
+        .accounts({ admin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+      await program.methods
+        .acceptAdmin()
+        .accounts({ pendingAdmin: provider.wallet.publicKey })
+        .rpc();
+    });
+
+    it("Rejects proposals from non-admins", async () => {
+      try {
+        await program.methods
+          .proposeAdmin(user.publicKey)
+          .accounts({ admin: user.publicKey })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAuthority");
+      }
+    });
+
+    it("Rejects accept from anyone but the pending admin", async () => {
+      await program.methods
+        .proposeAdmin(newAdmin.publicKey)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await program.methods
+          .acceptAdmin()
+          .accounts({ pendingAdmin: user.publicKey })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAuthority");
+      }
+    });
+
+    it("Transfers the admin role once the pending admin accepts", async () => {
+      await program.methods
+        .acceptAdmin()
+        .accounts({ pendingAdmin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+
+      const [configAddress] = configPda();
+      const config = await program.account.config.fetch(configAddress);
+      expect(config.admin.toBase58()).to.equal(newAdmin.publicKey.toBase58());
+      expect(config.pendingAdmin.toBase58()).to.equal(PublicKey.default.toBase58());
+    });
+  });
+
+  describe("execute_swap", () => {
+    it("Executes swap with valid parameters", async () => {
+      const amount = new anchor.BN(100 * 10 ** 9); // 100 tokens
+
+      // Get initial balances
+      const fromAccountBefore = await getAccount(
+        provider.connection,
+        tokenAccountAUser
+      );
+      const toAccountBefore = await getAccount(
+        provider.connection,
+        tokenAccountA
+      );
+
+      const tx = await program.methods
+        .executeSwap(amount)
+        .accounts({
+          authority: user.publicKey,
+          from: tokenAccountAUser,
+          to: tokenAccountA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      console.log("Execute swap transaction:", tx);
+
+      // Verify transaction was successful
+      const transaction = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+      });
+      expect(transaction?.meta?.err).to.be.null;
+
+      // Verify balances changed
+      const fromAccountAfter = await getAccount(
+        provider.connection,
+        tokenAccountAUser
+      );
+      const toAccountAfter = await getAccount(
+        provider.connection,
+        tokenAccountA
+      );
+
+      expect(fromAccountAfter.amount.toString()).to.equal(
+        (BigInt(fromAccountBefore.amount.toString()) - BigInt(amount.toString())).toString()
+      );
+      expect(toAccountAfter.amount.toString()).to.equal(
+        (BigInt(toAccountBefore.amount.toString()) + BigInt(amount.toString())).toString()
+      );
+    });
+
+    it("Fails with zero amount", async () => {
+      try {
+        await program.methods
+          .executeSwap(new anchor.BN(0))
+          .accounts({
+            authority: user.publicKey,
+            from: tokenAccountAUser,
+            to: tokenAccountA,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorMessage).to.equal(
+          "Invalid swap amount (must be > 0)"
+        );
+      }
+    });
+
+    it("Fails with mismatched account mints", async () => {
+      try {
+        await program.methods
+          .executeSwap(new anchor.BN(100 * 10 ** 9))
+          .accounts({
+            authority: user.publicKey,
+            from: tokenAccountAUser, // Mint A
+            to: tokenAccountB, // Mint B - mismatch!
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        // This will fail at the Anchor account validation level
+        expect(err).to.exist;
+      }
+    });
+
+    it("Fails when authority doesn't own source account", async () => {
+      // Try to use authority's account but sign with user
+      try {
+        await program.methods
+          .executeSwap(new anchor.BN(100 * 10 ** 9))
+          .accounts({
+            authority: user.publicKey, // User is signing
+            from: tokenAccountA, // But this is authority's account
+            to: tokenAccountAUser,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc();
+
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorMessage).to.equal(
+          "Invalid authority (must be token account owner)"
+        );
+      }
+    });
+  });
+
+  describe("execute_swap end-to-end", () => {
+    // Fresh mints and accounts so balances are not affected by other suites
+    let trader: Keypair;
+    let inputMint: PublicKey;
+    let outputMint: PublicKey;
+    let inputAta: PublicKey;
+    let outputAta: PublicKey;
+    let feeAta: PublicKey;
+
+    const FUNDED = 1_000 * 10 ** 9;
+    const PROTOCOL_FEE_BPS = 30;
+
+    const balance = async (address: PublicKey) =>
+      BigInt((await getAccount(provider.connection, address)).amount.toString());
+
+    const executeSwap = (
+      amount: number | anchor.BN,
+      minOutput: number,
+      expectedOutput: number,
+      outputTokenAccount = outputAta,
+      recipient: PublicKey | null = NO_RECIPIENT,
+      recipientTokenAccount: PublicKey | null = null,
+      lpFeeAccount: PublicKey | null = null,
+      simulate = false,
+      slippageBasis = OUTPUT_BASIS,
+      quoteTimestamp = NO_QUOTE_TIMESTAMP,
+      maxQuoteAge = NO_MAX_QUOTE_AGE,
+      feeDenomination = TOKEN_FEE,
+      feeTreasury: PublicKey | null = null,
+      slippageFailureMode = REVERT_ON_SLIPPAGE,
+      userStats: PublicKey | null = null
+    ) =>
+      program.methods
+        .executeSwap(
+          VERSION,
+          new anchor.BN(amount),
+          new anchor.BN(minOutput),
+          new anchor.BN(expectedOutput),
+          true,
+          NO_MEMO,
+          NO_TAG,
+          recipient,
+          simulate,
+          slippageBasis,
+          quoteTimestamp,
+          maxQuoteAge,
+          feeDenomination,
+          slippageFailureMode
+        )
+        .accounts({
+          authority: trader.publicKey,
+          inputTokenAccount: inputAta,
+          outputTokenAccount,
+          inputMint,
+          outputMint,
+          feeRecipient: feeAta,
+          lpFeeAccount,
+          recipientTokenAccount,
+          feeTreasury,
+          userStats,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([trader]);
+
+    before(async () => {
+      trader = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(
+        trader.publicKey,
+        2 * LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig, "confirmed");
+
+      inputMint = await createMint(provider.connection, authority, authority.publicKey, null, 9);
+      outputMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+
+      inputAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, trader, inputMint, trader.publicKey)
+      ).address;
+      outputAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, trader, outputMint, trader.publicKey)
+      ).address;
+      // Fees are paid in the input mint
+      feeAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, authority, inputMint, authority.publicKey)
+      ).address;
+
+      await mintTo(provider.connection, authority, inputMint, inputAta, authority, FUNDED);
+    });
+
+    it("Reverts atomically when no output arrives", async () => {
+      const inputBefore = await balance(inputAta);
+      const feeBefore = await balance(feeAta);
+
+      // No swap route precedes the instruction, so the output balance does not
+      // increase: no output at all is InsufficientOutput, not SlippageExceeded
+      try {
+        await executeSwap(100 * 10 ** 9, 90 * 10 ** 6, 95 * 10 ** 6).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InsufficientOutput");
+      }
+
+      // The fee transfer ran before the output check but was rolled back
+      expect(await balance(inputAta)).to.equal(inputBefore);
+      expect(await balance(feeAta)).to.equal(feeBefore);
+      expect(await balance(outputAta)).to.equal(BigInt(0));
+    });
+
+    it("Rejects a fee the input balance cannot cover", async () => {
+      // The fee on 1000x the balance is larger than the balance itself
+      try {
+        await executeSwap(1_000 * FUNDED, 90 * 10 ** 6, 95 * 10 ** 6).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("FeeExceedsAmount");
+      }
+    });
+
+    it("Requires the LP fee account while an LP share is set", async () => {
+      await program.methods
+        .updateConfig(null, null, null, null, 10, PublicKey.default, null, null, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await executeSwap(100 * 10 ** 9, 90 * 10 ** 6, 95 * 10 ** 6).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidLpFeeAccount");
+      } finally {
+        await program.methods
+          .updateConfig(null, null, null, null, 0, null, null, null, null, null, null, null, null, null, null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
+    it("Requires a real minimum output without a quote", async () => {
+      // expected_output 0 skips the relative check, so a 1-unit minimum
+      // would leave the swap unprotected
+      try {
+        await executeSwap(100 * 10 ** 9, 1, 0).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidMinOutput");
+      }
+
+      // With a real minimum the swap gets past validation (and reverts on
+      // the missing route)
+      try {
+        await executeSwap(100 * 10 ** 9, 90 * 10 ** 6, 0).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InsufficientOutput");
+      }
+    });
+
+    it("Enforces the configured output floor at the boundary", async () => {
+      const floor = 90 * 10 ** 6;
+      await program.methods
+        .updateConfig(null, null, null, null, null, null, null, null, null, null, null, null, new anchor.BN(floor), null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await executeSwap(100 * 10 ** 9, floor - 1, 95 * 10 ** 6).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidMinOutput");
+      }
+
+      // At the floor the swap gets past validation (and reverts on the
+      // missing route)
+      try {
+        await executeSwap(100 * 10 ** 9, floor, 95 * 10 ** 6).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InsufficientOutput");
+      } finally {
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, null, null, null, null, null, null, new anchor.BN(0), null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
+    it("Rejects swaps without a quote in strict mode", async () => {
+      await program.methods
+        .updateConfig(null, null, null, null, null, null, true, null, null, null, null, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await executeSwap(100 * 10 ** 9, 90 * 10 ** 6, 0).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAmount");
+      } finally {
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, false, null, null, null, null, null, null, null, null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
+    it("Rejects a non-ATA output account in strict ATA mode", async () => {
+      // Owned by the trader and holding the output mint, but not its ATA
+      const auxiliaryAccount = await createAccount(
+        provider.connection,
+        trader,
+        outputMint,
+        trader.publicKey,
+        Keypair.generate()
+      );
+
+      await program.methods
+        .updateConfig(null, null, null, null, null, null, null, null, null, null, true, null, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await executeSwap(100 * 10 ** 9, 90 * 10 ** 6, 95 * 10 ** 6, auxiliaryAccount).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("NonCanonicalTokenAccount");
+      } finally {
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, null, null, null, null, false, null, null, null, null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
+    it("Rejects a quote older than max_quote_age", async () => {
+      const now = Math.floor(Date.now() / 1000);
+      const quotedAt = (age: number) => new anchor.BN(now - age);
+      const swap = (quoteTimestamp: anchor.BN) =>
+        executeSwap(
+          100 * 10 ** 9,
+          90 * 10 ** 6,
+          95 * 10 ** 6,
+          outputAta,
+          NO_RECIPIENT,
+          null,
+          null,
+          true,
+          OUTPUT_BASIS,
+          quoteTimestamp,
+          new anchor.BN(60)
+        ).rpc();
+
+      // A fresh quote passes (simulated, so no tokens move)
+      await swap(quotedAt(0));
+
+      try {
+        await swap(quotedAt(600));
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("QuoteExpired");
+      }
+    });
+
+    it("Rejects lamport fees for a non-WSOL input", async () => {
+      // Lamports only price the fee 1:1 for native SOL input
+      try {
+        await executeSwap(
+          100 * 10 ** 9,
+          90 * 10 ** 6,
+          95 * 10 ** 6,
+          outputAta,
+          NO_RECIPIENT,
+          null,
+          null,
+          true,
+          OUTPUT_BASIS,
+          NO_QUOTE_TIMESTAMP,
+          NO_MAX_QUOTE_AGE,
+          LAMPORT_FEE,
+          authority.publicKey
+        ).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidFeeDenomination");
+      }
+    });
+
+    it("Rejects a token fee account for a SOL treasury", async () => {
+      await program.methods
+        .updateConfig(null, null, null, null, null, null, null, null, null, null, null, false, null, null, null)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await executeSwap(100 * 10 ** 9, 90 * 10 ** 6, 95 * 10 ** 6).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidFeeRecipient");
+      } finally {
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, null, null, null, null, null, true, null, null, null)
+          .accounts({ admin: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
+    it("Rejects the same account as input and output", async () => {
+      // Rejected as InvalidAccount before the mints are compared
+      try {
+        await executeSwap(100 * 10 ** 9, 90 * 10 ** 6, 95 * 10 ** 6, inputAta).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAccount");
+      }
+    });
+
+    it("Rejects the input account as its own fee recipient", async () => {
+      // Same mint and token-program owned, but the fee would be a self-transfer
+      try {
+        await program.methods
+          .executeSwap(
+            VERSION,
+            new anchor.BN(100 * 10 ** 9),
+            new anchor.BN(90 * 10 ** 6),
+            new anchor.BN(95 * 10 ** 6),
+            true,
+            NO_MEMO,
+            NO_TAG,
+            NO_RECIPIENT,
+            false,
+            OUTPUT_BASIS,
+            NO_QUOTE_TIMESTAMP,
+            NO_MAX_QUOTE_AGE,
+            TOKEN_FEE,
+            REVERT_ON_SLIPPAGE
+          )
+          .accounts({
+            authority: trader.publicKey,
+            inputTokenAccount: inputAta,
+            outputTokenAccount: outputAta,
+            inputMint,
+            outputMint,
+            feeRecipient: inputAta,
+            lpFeeAccount: null,
+            recipientTokenAccount: null,
+            feeTreasury: null,
+            userStats: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([trader])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidFeeRecipient");
+      }
+    });
+
+    it("Rejects an output account owned by a third party", async () => {
+      const thirdParty = Keypair.generate();
+      const thirdPartyAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, authority, outputMint, thirdParty.publicKey)
+      ).address;
+
+      try {
+        await executeSwap(100 * 10 ** 9, 90 * 10 ** 6, 95 * 10 ** 6, thirdPartyAta).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidAuthority");
+      }
+    });
+
+    it("Accepts a third-party output when it is the explicit recipient's ATA", async () => {
+      const coldWallet = Keypair.generate();
+      const coldAta = (
+        await getOrCreateAssociatedTokenAccount(provider.connection, authority, outputMint, coldWallet.publicKey)
+      ).address;
+
+      // Ownership passes; the swap then fails only because no route ran
+      try {
+        await executeSwap(
+          100 * 10 ** 9,
+          90 * 10 ** 6,
+          95 * 10 ** 6,
+          outputAta,
+          coldWallet.publicKey,
+          coldAta
+        ).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InsufficientOutput");
+      }
+    });
+
+    it("Rejects a recipient account that is not the recipient's ATA", async () => {
+      const coldWallet = Keypair.generate();
+      // The trader's own account is not the cold wallet's ATA
+      try {
+        await executeSwap(
+          100 * 10 ** 9,
+          90 * 10 ** 6,
+          95 * 10 ** 6,
+          outputAta,
+          coldWallet.publicKey,
+          outputAta
+        ).rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err) {
+        expect(err.error?.errorCode?.code).to.equal("InvalidRecipient");
+      }
+    });
+
+    it("Moves no tokens when simulating", async () => {
+      const inputBefore = await balance(inputAta);
+      const outputBefore = await balance(outputAta);
+      const feeBefore = await balance(feeAta);
+
+      let event;
+      const listener = program.addEventListener("swapExecutedEvent", (e) => {
+        event = e;
+      });
+
+      // Even sent for real, a simulated swap only validates and reports: the
+      // output is the quote and no fee is transferred
+      await executeSwap(100 * 10 ** 9, 90 * 10 ** 6, 95 * 10 ** 6, outputAta, NO_RECIPIENT, null, null, true).rpc({
+        commitment: "confirmed",
+      });
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      expect(await balance(inputAta)).to.equal(inputBefore);
+      expect(await balance(outputAta)).to.equal(outputBefore);
+      expect(await balance(feeAta)).to.equal(feeBefore);
+
+      expect(event.schemaVersion).to.equal(5);
+      expect(event.simulated).to.be.true;
+      expect(event.feeCollected.toString()).to.equal("0");
+      expect(event.batch).to.be.false;
+      expect(event.legIndex).to.equal(0);
+      expect(event.outputAmount.toString()).to.equal((95 * 10 ** 6).toString());
+    });
+
+    it("Resolves USE_FULL_BALANCE to the input balance", async () => {
+      const inputBefore = await balance(inputAta);
+
+      let event;
+      const listener = program.addEventListener("swapExecutedEvent", (e) => {
+        event = e;
+      });
+
+      await executeSwap(USE_FULL_BALANCE, 90 * 10 ** 6, 95 * 10 ** 6, outputAta, NO_RECIPIENT, null, null, true).rpc({
+        commitment: "confirmed",
+      });
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      // The event reports the balance read on-chain, not the sentinel
+      expect(event.inputAmount.toString()).to.equal(inputBefore.toString());
+    });
+
+    it("Takes user stats and reports the loyalty discount", async () => {
+      const [userStats] = userStatsPda(trader.publicKey);
+      await program.methods
+        .initializeUserStats()
+        .accounts({ authority: trader.publicKey })
+        .signers([trader])
+        .rpc();
+
+      let event;
+      const listener = program.addEventListener("swapExecutedEvent", (e) => {
+        event = e;
+      });
+
+      await executeSwap(
+        100 * 10 ** 9,
+        90 * 10 ** 6,
+        95 * 10 ** 6,
+        outputAta,
+        NO_RECIPIENT,
+        null,
+        null,
+        true,
+        OUTPUT_BASIS,
+        NO_QUOTE_TIMESTAMP,
+        NO_MAX_QUOTE_AGE,
+        TOKEN_FEE,
+        null,
+        REVERT_ON_SLIPPAGE,
+        userStats
+      ).rpc({ commitment: "confirmed" });
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      // A new account has no volume yet, so no tier applies
+      expect(event.feeDiscountBps).to.equal(0);
+      // Simulated swaps are not recorded
+      const stats = await program.account.userStats.fetch(userStats);
+      expect(stats.authority.toBase58()).to.equal(trader.publicKey.toBase58());
+      expect(stats.swapCount.toString()).to.equal("0");
+      expect(stats.totalVolume.toString()).to.equal("0");
+    });
+
+    // The output balance is read before and after STEP 8 of the handler, so it
+    // can only increase once the program-side swap CPI lands; route
+    // instructions earlier in the transaction are already reflected in the
+    // "before" balance. Un-skip this test when the CPI is in place.
+    it.skip("Transfers the fee and emits SwapExecutedEvent", async () => {
+      const amount = 100 * 10 ** 9;
+      const expectedFee = BigInt(Math.floor((amount * PROTOCOL_FEE_BPS) / 10_000));
+      const inputBefore = await balance(inputAta);
+      const feeBefore = await balance(feeAta);
+
+      let event;
+      const listener = program.addEventListener("swapExecutedEvent", (e) => {
+        event = e;
+      });
+
+      await executeSwap(amount, 90 * 10 ** 6, 95 * 10 ** 6).rpc({ commitment: "confirmed" });
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+      await program.removeEventListener(listener);
+
+      expect(await balance(feeAta)).to.equal(feeBefore + expectedFee);
+      expect(await balance(inputAta)).to.equal(inputBefore - BigInt(amount));
+
+      expect(event.authority.toBase58()).to.equal(trader.publicKey.toBase58());
+      expect(event.recipient.toBase58()).to.equal(trader.publicKey.toBase58());
+      expect(event.inputAmount.toString()).to.equal(amount.toString());
+      expect(event.inputMint.toBase58()).to.equal(inputMint.toBase58());
+      expect(event.outputMint.toBase58()).to.equal(outputMint.toBase58());
+      expect(event.protocolFee.toString()).to.equal(expectedFee.toString());
+      // Nothing is burned with the default burn_bps of 0
+      expect(event.treasuryFee.toString()).to.equal(expectedFee.toString());
+      expect(event.burnedFee.toString()).to.equal("0");
+      expect(event.lpFee.toString()).to.equal("0");
+      // The fee recipient's measured balance change matches the treasury share
+      expect(event.feeCollected.toString()).to.equal(expectedFee.toString());
+      expect(event.outputAmount.gte(new anchor.BN(90 * 10 ** 6))).to.be.true;
+      // No output-side fee: the recipient keeps the full output
+      expect(event.netOutputAmount.toString()).to.equal(event.outputAmount.toString());
+      expect(event.simulated).to.be.false;
+    });
+  });
+
+  describe("get_version", () => {
+    it("Reports the version and accepted instruction versions", async () => {
+      // Read-only: the return data comes from a simulation
+      const version = await program.methods.getVersion().view();
+
+      expect(version.programVersion).to.equal("0.1.0");
+      expect(version.minSupportedInstructionVersion).to.be.at.most(VERSION);
+      expect(version.instructionVersion).to.be.at.least(VERSION);
+      // The default build enables no optional features
+      expect(version.features.toString()).to.equal("0");
+    });
+  });
+});